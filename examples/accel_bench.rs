@@ -0,0 +1,116 @@
+//! Compare acceleration structures by tracing the same random rays through each.
+//!
+//! Run with `cargo run --release --example accel_bench`, and add `--features simd`
+//! to see the effect of testing BVH nodes with SIMD instructions.
+
+use std::{sync::Arc, time::Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rtweekend::{
+    hit::{KdTree, UniformGrid, BVH},
+    material::Lambertian,
+    object::Block,
+    Color, Hit, Interval, Point3, Ray, Sphere,
+};
+
+const RAYS: usize = 200_000;
+
+type Scene = fn() -> Vec<Box<dyn Hit>>;
+
+/// Many heavily overlapping spheres.
+fn sphere_cluster() -> Vec<Box<dyn Hit>> {
+    let mut rng = StdRng::seed_from_u64(1);
+    let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+    (0..2000)
+        .map(|_| {
+            let center = Point3::new(
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+            );
+            let radius = rng.gen_range(0.5..2.0);
+            Box::new(Sphere::new(center, radius, material.clone())) as Box<dyn Hit>
+        })
+        .collect()
+}
+
+/// A regular field of boxes, like the floor of the final scene.
+fn block_field() -> Vec<Box<dyn Hit>> {
+    let mut rng = StdRng::seed_from_u64(2);
+    let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+    let mut objects: Vec<Box<dyn Hit>> = Vec::new();
+    for i in 0..30 {
+        for j in 0..30 {
+            let min = Point3::new(i as f64 - 15.0, -1.0, j as f64 - 15.0);
+            let max = Point3::new(min.x() + 1.0, rng.gen_range(0.0..3.0), min.z() + 1.0);
+            objects.push(Box::new(Block::new(min, max, material.clone())));
+        }
+    }
+    objects
+}
+
+fn rays() -> Vec<Ray> {
+    let mut rng = StdRng::seed_from_u64(3);
+    (0..RAYS)
+        .map(|_| {
+            let origin = Point3::new(
+                rng.gen_range(-20.0..20.0),
+                rng.gen_range(5.0..20.0),
+                rng.gen_range(-20.0..20.0),
+            );
+            let target = Point3::new(
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+            );
+            Ray::new(origin, target - origin, 0.0)
+        })
+        .collect()
+}
+
+fn measure(name: &str, world: &dyn Hit, rays: &[Ray]) {
+    let start = Instant::now();
+    let hits = rays
+        .iter()
+        .filter(|ray| {
+            world
+                .hit((*ray).clone(), Interval::from_min(1e-10))
+                .is_some()
+        })
+        .count();
+    let elapsed = start.elapsed();
+    println!(
+        "  {name:<8} {:>8.1} ms, {:>6.2} Mrays/s, {hits} hits",
+        elapsed.as_secs_f64() * 1e3,
+        rays.len() as f64 / elapsed.as_secs_f64() / 1e6,
+    );
+}
+
+fn build<H: Hit>(name: &str, build: impl FnOnce() -> H) -> H {
+    let start = Instant::now();
+    let world = build();
+    println!(
+        "  {name:<8} built in {:.1} ms",
+        start.elapsed().as_secs_f64() * 1e3
+    );
+    world
+}
+
+fn main() {
+    let rays = rays();
+    let scenes: [(&str, Scene); 2] = [
+        ("sphere cluster", sphere_cluster),
+        ("block field", block_field),
+    ];
+
+    for (name, scene) in scenes {
+        println!("{name}:");
+        let bvh = build("BVH", || BVH::new(scene(), 0.0..1.0));
+        let kdtree = build("KD-tree", || KdTree::new(scene(), 0.0..1.0));
+        let grid = build("grid", || UniformGrid::new(scene(), 0.0..1.0));
+
+        measure("BVH", &bvh, &rays);
+        measure("KD-tree", &kdtree, &rays);
+        measure("grid", &grid, &rays);
+    }
+}
@@ -0,0 +1,173 @@
+//! What rays see when they hit nothing.
+
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{object::sphere::to_sphere_uv, texture::Texture, Color, Vec3};
+
+/// Color of the light coming from infinitely far away, seen by the rays that
+/// hit nothing in the scene.
+pub trait Background: Sync + Send + Debug {
+    /// Color seen along `direction`, which does not need to be normalized.
+    fn color(&self, direction: Vec3<f64>) -> Color;
+}
+
+/// The same color in every direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolidBackground {
+    pub color: Color,
+}
+
+impl SolidBackground {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Background for SolidBackground {
+    fn color(&self, _direction: Vec3<f64>) -> Color {
+        self.color
+    }
+}
+
+/// A color is seen in every direction, like a [`SolidBackground`], so that
+/// `&color` can be given where a background is expected.
+impl Background for Color {
+    fn color(&self, _direction: Vec3<f64>) -> Color {
+        *self
+    }
+}
+
+impl From<Color> for Arc<dyn Background> {
+    fn from(color: Color) -> Self {
+        Arc::new(SolidBackground::new(color))
+    }
+}
+
+/// Sky of the first book, blending linearly from `bottom` straight down to
+/// `top` straight up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientSky {
+    pub bottom: Color,
+    pub top: Color,
+}
+
+impl Default for GradientSky {
+    /// White below, and light blue above.
+    fn default() -> Self {
+        Self {
+            bottom: Color::WHITE,
+            top: Color::new(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+impl Background for GradientSky {
+    fn color(&self, direction: Vec3<f64>) -> Color {
+        let height = 0.5 * (direction.normalized().y() + 1.0);
+        self.bottom.lerp(self.top, height)
+    }
+}
+
+/// Environment map, a texture wrapped around the scene at infinity and looked
+/// up by ray direction, with the same (u, v) mapping as [`crate::Sphere`].
+///
+/// With an equirectangular [`crate::texture::Image`], e.g. an HDR photo of
+/// the sky, it lights the scene like the place the photo was taken in.
+#[derive(Debug, Clone)]
+pub struct EnvironmentMap<T: Texture> {
+    texture: T,
+    /// Rotation of the map about the world Y axis, in degrees.
+    pub rotation_degrees: f64,
+    /// Flip the map upside down, for images stored with the sky at the bottom.
+    pub flip_vertical: bool,
+}
+
+impl<T: Texture> EnvironmentMap<T> {
+    pub fn new(texture: T) -> Self {
+        Self {
+            texture,
+            rotation_degrees: 0.0,
+            flip_vertical: false,
+        }
+    }
+
+    pub fn with_rotation_degrees(mut self, rotation_degrees: f64) -> Self {
+        self.rotation_degrees = rotation_degrees;
+        self
+    }
+
+    pub fn with_flip_vertical(mut self, flip_vertical: bool) -> Self {
+        self.flip_vertical = flip_vertical;
+        self
+    }
+
+    /// Direction in map space, i.e. `direction` rotated back by the map rotation.
+    fn to_map(&self, direction: Vec3<f64>) -> Vec3<f64> {
+        let (sin, cos) = self.rotation_degrees.to_radians().sin_cos();
+        Vec3::new(
+            cos * direction.x() - sin * direction.z(),
+            direction.y(),
+            sin * direction.x() + cos * direction.z(),
+        )
+    }
+
+    /// Color seen along `direction`, which does not need to be normalized.
+    pub fn color(&self, direction: Vec3<f64>) -> Color {
+        let direction = self.to_map(direction);
+        let (u, v) = to_sphere_uv(&direction);
+        let v = if self.flip_vertical { 1.0 - v } else { v };
+        self.texture.color(direction, u, v)
+    }
+}
+
+impl<T: Texture> Background for EnvironmentMap<T> {
+    fn color(&self, direction: Vec3<f64>) -> Color {
+        EnvironmentMap::color(self, direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{texture::Image, Point3, Ray, World, T_RANGE};
+
+    #[test]
+    fn rotation_swaps_opposite_directions() {
+        // -X sits on the seam at u = 0, so both edge columns get the same color
+        let image = image::ImageBuffer::from_fn(3, 2, |x, y| match (x, y) {
+            (1, _) => image::Rgb([255, 0, 0]),
+            (_, 0) => image::Rgb([0, 0, 255]),
+            _ => image::Rgb([0, 255, 0]),
+        });
+        let map = EnvironmentMap::new(Image::new(image));
+        let rotated = map.clone().with_rotation_degrees(180.0);
+
+        let (pos_x, neg_x) = (Vec3::new(1.0, 0.1, 0.0), Vec3::new(-1.0, 0.1, 0.0));
+        assert_ne!(map.color(pos_x), map.color(neg_x));
+        assert_eq!(rotated.color(pos_x), map.color(neg_x));
+        assert_eq!(rotated.color(neg_x), map.color(pos_x));
+
+        let flipped = map.clone().with_flip_vertical(true);
+        let (up, down) = (Vec3::new(-1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0));
+        assert_ne!(map.color(up), map.color(down));
+        assert_eq!(flipped.color(up), map.color(down));
+    }
+
+    #[test]
+    fn gradient_from_bottom_to_top() {
+        let sky = GradientSky::default();
+        assert_eq!(sky.color(Vec3::new(0.0, -2.0, 0.0)), Color::WHITE);
+        assert_eq!(sky.color(Vec3::unit_y()), Color::new(0.5, 0.7, 1.0));
+        assert_eq!(sky.color(Vec3::unit_x()), Color::new(0.75, 0.85, 1.0));
+
+        let solid: Arc<dyn Background> = Color::constant(0.3).into();
+        assert_eq!(solid.color(Vec3::unit_z()), Color::constant(0.3));
+    }
+
+    #[test]
+    fn colors_are_backgrounds() {
+        let ray = Ray::new(Point3::zeros(), Vec3::unit_z(), 0.0);
+        let color = crate::ray_color(ray, &Color::constant(0.3), &World::new(), 4, T_RANGE);
+        assert_eq!(color, Color::constant(0.3));
+    }
+}
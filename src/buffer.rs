@@ -0,0 +1,699 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use image::{
+    error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind},
+    ImageError,
+};
+
+use crate::{
+    hit::{AABB, BVH},
+    output::{
+        BufferSizeError, EncodedImage, OutputError, OutputFormat, OutputSettings, OutputSpec,
+    },
+    Camera, Color,
+};
+
+/// An image produced by the ray tracer.
+///
+/// Pixels are stored row by row, starting from the top-left corner, and hold the
+/// linear radiance computed by the tracer, before tone mapping and gamma
+/// correction. Outputs never modify the buffer: display outputs are encoded
+/// into an [`EncodedImage`], and [`RenderBuffer::save_linear`] writes the
+/// radiance itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderBuffer {
+    width: u64,
+    height: u64,
+    pixels: Vec<Color>,
+}
+
+impl RenderBuffer {
+    /// Create a black buffer of the given size.
+    pub fn new(width: u64, height: u64) -> Self {
+        Self::from_pixels(width, height, vec![Color::BLACK; (width * height) as usize])
+    }
+
+    /// Create a buffer from row-major pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of pixels is not `width * height`.
+    pub fn from_pixels(width: u64, height: u64, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len() as u64, width * height, "pixel count mismatch");
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u64 {
+        self.width
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<Color> {
+        self.pixels
+    }
+
+    /// Pixels as contiguous `f32` RGB triples, row by row, which is the
+    /// layout external denoisers expect.
+    pub fn to_f32_rgb(&self) -> Vec<f32> {
+        self.pixels
+            .iter()
+            .flat_map(|color| color.into_array().map(|c| c as f32))
+            .collect()
+    }
+
+    /// Create a buffer from contiguous `f32` RGB triples, the inverse of
+    /// [`RenderBuffer::to_f32_rgb`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values is not `3 * width * height`.
+    pub fn from_f32_rgb(width: u64, height: u64, values: &[f32]) -> Self {
+        assert_eq!(
+            values.len() as u64,
+            3 * width * height,
+            "value count mismatch"
+        );
+        let pixels = values
+            .chunks_exact(3)
+            .map(|rgb| Color::new(rgb[0] as f64, rgb[1] as f64, rgb[2] as f64))
+            .collect();
+        Self::from_pixels(width, height, pixels)
+    }
+
+    fn index(&self, x: u64, y: u64) -> usize {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        (y * self.width + x) as usize
+    }
+
+    /// Color of the pixel at column `x` and row `y`, counted from the top.
+    pub fn get(&self, x: u64, y: u64) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u64, y: u64, color: Color) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    /// Returns a copy of the buffer with fireflies removed.
+    ///
+    /// A pixel whose luminance is more than `threshold` times the median
+    /// luminance of its 3x3 neighborhood is replaced by the median pixel,
+    /// unless one of its eight neighbors is brighter than its luminance over
+    /// `threshold`. So only isolated pixels count as speckles, and bright
+    /// features of two or more adjacent pixels are left alone.
+    /// The neighborhood of border pixels repeats the pixels on the border.
+    pub fn despeckle(&self, threshold: f64) -> Self {
+        let mut buffer = self.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut neighborhood = [Color::BLACK; 9];
+                for (index, color) in neighborhood.iter_mut().enumerate() {
+                    let (dx, dy) = (index as u64 % 3, index as u64 / 3);
+                    let nx = (x + dx).saturating_sub(1).min(self.width - 1);
+                    let ny = (y + dy).saturating_sub(1).min(self.height - 1);
+                    *color = self.get(nx, ny);
+                }
+                neighborhood.sort_by(|lhs, rhs| lhs.luminance().total_cmp(&rhs.luminance()));
+
+                let median = neighborhood[4];
+                let luminance = self.get(x, y).luminance();
+                if luminance > threshold * median.luminance()
+                    && luminance > threshold * self.brightest_neighbor(x, y)
+                {
+                    buffer.set(x, y, median);
+                }
+            }
+        }
+        buffer
+    }
+
+    /// Largest luminance of the pixels next to `(x, y)`, not counting the
+    /// pixel itself, or zero if it has no neighbors.
+    fn brightest_neighbor(&self, x: u64, y: u64) -> f64 {
+        let columns = x.saturating_sub(1)..=(x + 1).min(self.width - 1);
+        let rows = y.saturating_sub(1)..=(y + 1).min(self.height - 1);
+        rows.flat_map(|ny| columns.clone().map(move |nx| (nx, ny)))
+            .filter(|&neighbor| neighbor != (x, y))
+            .map(|(nx, ny)| self.get(nx, ny).luminance())
+            .fold(0.0, f64::max)
+    }
+
+    /// Write the buffer as a plain text PPM image.
+    pub fn write_ppm<T: Write>(&self, buffer: &mut T) -> std::io::Result<()> {
+        self.encode_with(Color::to_rgb8).write_ppm(buffer)
+    }
+
+    /// Tone map, gamma correct and quantize the buffer as `spec` asks.
+    pub fn encode(&self, spec: &OutputSpec) -> EncodedImage {
+        self.encode_with(|&color| spec.map_rgb8(color))
+    }
+
+    fn encode_with<F: Fn(&Color) -> [u8; 3]>(&self, to_rgb8: F) -> EncodedImage {
+        let pixels = self.pixels.iter().map(to_rgb8).collect();
+        EncodedImage::new(self.width, self.height, pixels)
+    }
+
+    /// Write the buffer into `dest` as interleaved 8-bit RGBA, mapped with
+    /// `settings`, without allocating. Alpha is always opaque.
+    ///
+    /// Fails if `dest` does not hold exactly `4 * width * height` bytes.
+    pub fn write_rgba8_into(
+        &self,
+        dest: &mut [u8],
+        settings: &OutputSettings,
+    ) -> Result<(), BufferSizeError> {
+        self.check_rgba_len(dest.len())?;
+        for (rgba, color) in dest.chunks_exact_mut(4).zip(&self.pixels) {
+            let [r, g, b] = settings.map_rgb8(*color);
+            rgba.copy_from_slice(&[r, g, b, u8::MAX]);
+        }
+        Ok(())
+    }
+
+    /// Like [`RenderBuffer::write_rgba8_into`], but with `f32` channels that
+    /// are not clamped or quantized. Alpha is always 1.
+    pub fn write_rgba_f32_into(
+        &self,
+        dest: &mut [f32],
+        settings: &OutputSettings,
+    ) -> Result<(), BufferSizeError> {
+        self.check_rgba_len(dest.len())?;
+        for (rgba, color) in dest.chunks_exact_mut(4).zip(&self.pixels) {
+            let [r, g, b] = settings.map(*color).into_array().map(|c| c as f32);
+            rgba.copy_from_slice(&[r, g, b, 1.0]);
+        }
+        Ok(())
+    }
+
+    fn check_rgba_len(&self, actual: usize) -> Result<(), BufferSizeError> {
+        let expected = 4 * self.pixels.len();
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(BufferSizeError { expected, actual })
+        }
+    }
+
+    /// Write the untouched linear radiance to `path`, as OpenEXR or PFM
+    /// depending on its extension.
+    pub fn save_linear<P: AsRef<Path>>(&self, path: P) -> image::ImageResult<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("exr") => {
+                let values = self.to_f32_rgb();
+                image::Rgb32FImage::from_raw(self.width as u32, self.height as u32, values)
+                    .expect("buffer size matches its dimensions")
+                    .save_with_format(path, image::ImageFormat::OpenExr)
+            }
+            Some("pfm") => {
+                let mut file = BufWriter::new(File::create(path)?);
+                self.write_pfm(&mut file)?;
+                file.flush()?;
+                Ok(())
+            }
+            _ => Err(ImageError::Unsupported(
+                UnsupportedError::from_format_and_kind(
+                    ImageFormatHint::PathExtension(path.to_path_buf()),
+                    UnsupportedErrorKind::Format(ImageFormatHint::PathExtension(
+                        path.to_path_buf(),
+                    )),
+                ),
+            )),
+        }
+    }
+
+    /// Write the buffer as a little-endian PFM image, whose rows go from the
+    /// bottom to the top. An empty buffer gives only the header, as in
+    /// [`RenderBuffer::write_ppm`].
+    fn write_pfm<T: Write>(&self, buffer: &mut T) -> std::io::Result<()> {
+        writeln!(buffer, "PF")?;
+        writeln!(buffer, "{} {}", self.width, self.height)?;
+        writeln!(buffer, "-1.0")?;
+
+        // empty buffers have no pixels, and so no rows, whatever the width
+        for row in self.pixels.chunks(self.width.max(1) as usize).rev() {
+            for value in row.iter().flat_map(|color| color.into_array()) {
+                buffer.write_all(&(value as f32).to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the buffer to every output in `specs`, each with its own tone
+    /// mapping and gamma.
+    ///
+    /// A failing output does not stop the others, the errors of all failed
+    /// outputs are returned together.
+    pub fn write_all(&self, specs: &[OutputSpec]) -> Result<(), Vec<OutputError>> {
+        let errors = specs
+            .iter()
+            .filter_map(|spec| {
+                let error = self.write_output(spec).err()?;
+                let path = spec.path.clone();
+                Some(OutputError { path, error })
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn write_output(&self, spec: &OutputSpec) -> image::ImageResult<()> {
+        let (width, height) = (self.width as u32, self.height as u32);
+        let pixel = |x: u32, y: u32| self.get(x as u64, y as u64);
+
+        match spec.format {
+            OutputFormat::Ppm => {
+                let mut file = BufWriter::new(File::create(&spec.path)?);
+                self.encode(spec).write_ppm(&mut file)?;
+                file.flush()?;
+            }
+            OutputFormat::Png => {
+                self.encode(spec)
+                    .to_rgb_image()
+                    .save_with_format(&spec.path, image::ImageFormat::Png)?;
+            }
+            OutputFormat::Exr => {
+                image::Rgb32FImage::from_fn(width, height, |x, y| {
+                    image::Rgb(spec.map(pixel(x, y)).into_array().map(|c| c as f32))
+                })
+                .save_with_format(&spec.path, image::ImageFormat::OpenExr)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Downscale the buffer to `width_chars` terminal columns and draw it with
+    /// ANSI escape sequences, using the colors `colors` supports.
+    ///
+    /// Every character cell shows two pixels stacked vertically, as the
+    /// foreground and background color of an upper half block. Each line ends
+    /// by resetting the colors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width_chars` is zero.
+    pub fn to_ansi_with(&self, width_chars: u64, colors: AnsiColors) -> String {
+        assert!(width_chars > 0, "preview must be at least one column wide");
+        let columns = width_chars.min(self.width);
+        // terminal cells are about twice as high as wide, which the two
+        // pixels per cell make up for
+        let rows = ((self.height * columns) as f64 / self.width as f64 / 2.0)
+            .round()
+            .max(1.0) as u64;
+
+        let mut ansi = String::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let top = self.box_filter(column, 2 * row, columns, 2 * rows);
+                let bottom = self.box_filter(column, 2 * row + 1, columns, 2 * rows);
+                ansi.push_str(&format!(
+                    "\x1b[{};{}m\u{2580}",
+                    colors.escape(38, top),
+                    colors.escape(48, bottom)
+                ));
+            }
+            ansi.push_str("\x1b[0m\n");
+        }
+        ansi
+    }
+
+    /// Same as [`RenderBuffer::to_ansi_with`], with the colors the current
+    /// terminal supports according to [`AnsiColors::detect`].
+    pub fn to_ansi(&self, width_chars: u64) -> String {
+        self.to_ansi_with(width_chars, AnsiColors::detect())
+    }
+
+    /// Average color of the pixels falling into cell `(x, y)` when the buffer
+    /// is divided into `columns * rows` cells.
+    fn box_filter(&self, x: u64, y: u64, columns: u64, rows: u64) -> Color {
+        let span = |i: u64, cells: u64, size: u64| {
+            let from = i * size / cells;
+            let to = ((i + 1) * size / cells).max(from + 1).min(size);
+            from.min(size - 1)..to
+        };
+        let (xs, ys) = (span(x, columns, self.width), span(y, rows, self.height));
+
+        let count = (xs.end - xs.start) * (ys.end - ys.start);
+        let sum = ys
+            .flat_map(|y| xs.clone().map(move |x| (x, y)))
+            .fold(Color::BLACK, |sum, (x, y)| sum + self.get(x, y));
+        sum / count as f64
+    }
+
+    /// Draw a one pixel wide line between two points given in pixel coordinates.
+    /// The parts of the line outside the buffer are clipped.
+    pub fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), color: Color) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let max = ((self.width - 1) as f64, (self.height - 1) as f64);
+        let Some((from, to)) = clip_line(from, to, max) else {
+            return;
+        };
+
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as u64;
+        for step in 0..=steps {
+            let t = step as f64 / steps as f64;
+            let x = (from.0 + t * dx).round();
+            let y = (from.1 + t * dy).round();
+            self.set(x as u64, y as u64, color);
+        }
+    }
+
+    /// Draw the 12 edges of `aabb` as seen through `camera`.
+    ///
+    /// Edges with an end behind the camera are skipped.
+    pub fn draw_box(&mut self, camera: &Camera, aabb: &AABB, color: Color) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let (width, height) = ((self.width - 1) as f64, (self.height - 1) as f64);
+        let to_pixel = |(u, v): (f64, f64)| (u * width, (1.0 - v) * height);
+
+        for from in 0..8 {
+            // corners differing in exactly one bit share an edge
+            for bit in [1, 2, 4] {
+                let to = from | bit;
+                if to == from {
+                    continue;
+                }
+                let from = camera.project(aabb.corner(from));
+                let to = camera.project(aabb.corner(to));
+                if let (Some(from), Some(to)) = (from, to) {
+                    self.draw_line(to_pixel(from), to_pixel(to), color);
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of the buffer with the silhouettes of `boxes` drawn over it.
+    pub fn with_boxes<'a, I>(&self, camera: &Camera, boxes: I, color: Color) -> Self
+    where
+        I: IntoIterator<Item = &'a AABB>,
+    {
+        let mut buffer = self.clone();
+        for aabb in boxes {
+            buffer.draw_box(camera, aabb, color);
+        }
+        buffer
+    }
+
+    /// Returns a copy of the buffer with the bounding boxes of the `bvh` nodes
+    /// up to `max_depth` drawn over it.
+    pub fn with_bvh_boxes(
+        &self,
+        camera: &Camera,
+        bvh: &BVH,
+        max_depth: usize,
+        color: Color,
+    ) -> Self {
+        let mut boxes = Vec::new();
+        bvh.visit_boxes(max_depth, |aabb, _| boxes.push(aabb.clone()));
+        self.with_boxes(camera, &boxes, color)
+    }
+}
+
+/// Colors available in the terminal for [`RenderBuffer::to_ansi_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColors {
+    /// 24-bit colors
+    TrueColor,
+    /// The 6x6x6 color cube of the 256-color palette
+    Palette256,
+}
+
+impl AnsiColors {
+    /// True color if the `COLORTERM` environment variable announces it,
+    /// otherwise the 256-color palette.
+    pub fn detect() -> Self {
+        match std::env::var("COLORTERM").as_deref() {
+            Ok("truecolor" | "24bit") => Self::TrueColor,
+            _ => Self::Palette256,
+        }
+    }
+
+    /// SGR parameters selecting `color`, where `target` is 38 for the
+    /// foreground and 48 for the background.
+    fn escape(self, target: u8, color: Color) -> String {
+        let [r, g, b] = color.to_rgb8();
+        match self {
+            Self::TrueColor => format!("{};2;{};{};{}", target, r, g, b),
+            Self::Palette256 => {
+                let level = |c: u8| (c as u16 * 5 + 127) / 255;
+                let index = 16 + 36 * level(r) + 6 * level(g) + level(b);
+                format!("{};5;{}", target, index)
+            }
+        }
+    }
+}
+
+/// Liang-Barsky clipping of the segment `from -> to` to the rectangle
+/// `[0, max.0] x [0, max.1]`.
+fn clip_line(
+    from: (f64, f64),
+    to: (f64, f64),
+    max: (f64, f64),
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+
+    for (p, q) in [
+        (-dx, from.0),
+        (dx, max.0 - from.0),
+        (-dy, from.1),
+        (dy, max.1 - from.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if p < 0.0 {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+
+    let at = |t: f64| (from.0 + t * dx, from.1 + t * dy);
+    Some((at(t0), at(t1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{output::ToneMap, Point3};
+
+    #[test]
+    fn box_on_view_axis_is_symmetric() {
+        let camera = Camera::builder().aspect_ratio(1.0).build();
+        let aabb = AABB::new(Point3::new(-0.4, -0.3, -3.3), Point3::new(0.4, 0.3, -2.7));
+
+        let mut buffer = RenderBuffer::new(41, 41);
+        buffer.draw_box(&camera, &aabb, Color::WHITE);
+
+        let edges = (0..41)
+            .flat_map(|y| (0..41).map(move |x| (x, y)))
+            .filter(|&(x, y)| buffer.get(x, y) == Color::WHITE)
+            .collect::<Vec<_>>();
+        assert!(!edges.is_empty());
+        for (x, y) in edges {
+            assert_eq!(buffer.get(40 - x, y), Color::WHITE);
+            assert_eq!(buffer.get(x, 40 - y), Color::WHITE);
+        }
+
+        // nothing to draw on
+        for (width, height) in [(0, 0), (0, 41), (41, 0)] {
+            let mut empty = RenderBuffer::new(width, height);
+            empty.draw_box(&camera, &aabb, Color::WHITE);
+            empty.draw_line((0.0, 0.0), (10.0, 10.0), Color::WHITE);
+        }
+    }
+
+    #[test]
+    fn write_all_outputs() {
+        let mut buffer = RenderBuffer::new(4, 2);
+        buffer.set(1, 0, Color::new(0.25, 2.0, 0.0));
+
+        let dir = std::env::temp_dir().join(format!("rtweekend-outputs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = OutputSpec::from_path(dir.join("out.png")).unwrap();
+        let exr = OutputSpec::from_path(dir.join("out.exr"))
+            .unwrap()
+            .with_tone_map(ToneMap::Reinhard);
+        let missing = OutputSpec::from_path(dir.join("missing").join("out.ppm")).unwrap();
+
+        let errors = buffer
+            .write_all(&[png.clone(), missing.clone(), exr.clone()])
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, missing.path);
+
+        let written = image::open(&png.path).unwrap().into_rgb8();
+        assert_eq!(written.get_pixel(1, 0).0, [128, 255, 0]);
+        let written = image::open(&exr.path).unwrap().into_rgb32f();
+        assert_eq!(written.get_pixel(1, 0).0, [0.2, 2.0 / 3.0, 0.0]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn encoding_keeps_linear_radiance() {
+        let mut buffer = RenderBuffer::new(2, 1);
+        buffer.set(0, 0, Color::new(4.0, 2.0, 0.5));
+        let original = buffer.clone();
+
+        let dir = std::env::temp_dir().join(format!("rtweekend-linear-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = OutputSpec::from_path(dir.join("out.png"))
+            .unwrap()
+            .with_tone_map(ToneMap::Aces);
+        let encoded = buffer.encode(&png);
+        assert!(encoded.get(0, 0)[0] < 255);
+        buffer.write_all(&[png]).unwrap();
+        assert_eq!(buffer, original);
+
+        buffer.save_linear(dir.join("linear.exr")).unwrap();
+        let written = image::open(dir.join("linear.exr")).unwrap().into_rgb32f();
+        assert_eq!(written.get_pixel(0, 0).0, [4.0, 2.0, 0.5]);
+
+        buffer.save_linear(dir.join("linear.pfm")).unwrap();
+        let written = std::fs::read(dir.join("linear.pfm")).unwrap();
+        let header = b"PF\n2 1\n-1.0\n";
+        assert_eq!(&written[..header.len()], header);
+        assert_eq!(&written[header.len()..][..4], &4.0f32.to_le_bytes());
+        assert!(buffer.save_linear(dir.join("linear.png")).is_err());
+
+        for (width, height) in [(0, 3), (3, 0)] {
+            let mut written = vec![];
+            RenderBuffer::new(width, height)
+                .write_pfm(&mut written)
+                .unwrap();
+            assert_eq!(written, format!("PF\n{width} {height}\n-1.0\n").as_bytes());
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rgba_into_caller_buffer() {
+        let mut buffer = RenderBuffer::new(3, 2);
+        buffer.set(1, 0, Color::new(0.25, 2.0, 0.0));
+        buffer.set(2, 1, Color::new(0.5, 0.1, 0.9));
+
+        let mut dest = vec![0; 4 * 3 * 2];
+        buffer
+            .write_rgba8_into(&mut dest, &OutputSettings::default())
+            .unwrap();
+        for (rgba, color) in dest.chunks_exact(4).zip(buffer.pixels()) {
+            assert_eq!(rgba[..3], color.to_rgb8());
+            assert_eq!(rgba[3], 255);
+        }
+
+        let settings = OutputSettings {
+            tone_map: ToneMap::Reinhard,
+            gamma: false,
+        };
+        let mut dest = vec![0.0; 4 * 3 * 2];
+        buffer.write_rgba_f32_into(&mut dest, &settings).unwrap();
+        assert_eq!(dest[4..8], [0.2, 2.0 / 3.0, 0.0, 1.0]);
+
+        let error = buffer
+            .write_rgba8_into(&mut [0; 4 * 3], &settings)
+            .unwrap_err();
+        assert_eq!((error.expected, error.actual), (24, 12));
+        assert!(buffer.write_rgba_f32_into(&mut [], &settings).is_err());
+    }
+
+    #[test]
+    fn f32_rgb_is_row_major() {
+        let mut buffer = RenderBuffer::new(3, 2);
+        buffer.set(1, 0, Color::new(0.5, 0.25, 2.0));
+        buffer.set(0, 1, Color::new(1.0, 0.0, 0.125));
+
+        let values = buffer.to_f32_rgb();
+        assert_eq!(values.len(), 3 * 3 * 2);
+        assert_eq!(values[3..6], [0.5, 0.25, 2.0]);
+        assert_eq!(values[9..12], [1.0, 0.0, 0.125]);
+        assert_eq!(RenderBuffer::from_f32_rgb(3, 2, &values), buffer);
+    }
+
+    #[test]
+    fn despeckle_keeps_edges() {
+        let background = Color::constant(0.1);
+        let mut buffer = RenderBuffer::from_pixels(8, 6, vec![background; 8 * 6]);
+        // a two pixel wide bright stripe, with one end on the border
+        for y in 0..6 {
+            buffer.set(5, y, Color::constant(50.0));
+            buffer.set(6, y, Color::constant(50.0));
+        }
+        buffer.set(1, 2, Color::constant(100.0));
+        buffer.set(0, 5, Color::constant(100.0));
+        // two adjacent bright pixels, and a 1000x outlier
+        buffer.set(1, 0, Color::constant(50.0));
+        buffer.set(2, 0, Color::constant(50.0));
+        buffer.set(3, 4, background * 1000.0);
+
+        let despeckled = buffer.despeckle(10.0);
+        assert_eq!(despeckled.get(1, 2), background);
+        assert_eq!(despeckled.get(0, 5), background);
+        assert_eq!(despeckled.get(3, 4), background);
+        assert_eq!(despeckled.get(1, 0), buffer.get(1, 0));
+        assert_eq!(despeckled.get(2, 0), buffer.get(2, 0));
+        for y in 0..6 {
+            assert_eq!(despeckled.get(5, y), buffer.get(5, y));
+            assert_eq!(despeckled.get(6, y), buffer.get(6, y));
+        }
+        assert_eq!(despeckled.get(3, 3), background);
+    }
+
+    #[test]
+    fn ansi_preview() {
+        let mut buffer = RenderBuffer::new(40, 30);
+        buffer.set(0, 0, Color::WHITE);
+
+        for colors in [AnsiColors::TrueColor, AnsiColors::Palette256] {
+            let ansi = buffer.to_ansi_with(20, colors);
+            // 20 columns of 2x2 pixels cover 15 pixel rows, two per line
+            let lines = ansi.lines().collect::<Vec<_>>();
+            assert_eq!(lines.len(), 8);
+            assert!(lines
+                .iter()
+                .all(|line| line.matches('\u{2580}').count() == 20));
+            assert!(ansi.ends_with("\x1b[0m\n"));
+        }
+
+        let ansi = buffer.to_ansi_with(20, AnsiColors::TrueColor);
+        assert!(ansi.starts_with("\x1b[38;2;180;180;180;48;2;0;0;0m"));
+    }
+}
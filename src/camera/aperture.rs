@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::warn;
+use rand::Rng;
+
+use crate::{
+    random,
+    texture::{Image, Texture},
+    Point3, Vec3,
+};
+
+/// Number of rejected samples after which [`Aperture::Masked`] gives up and
+/// samples the whole disk instead.
+const MAX_MASK_TRIES: usize = 64;
+
+static WARNED_DARK_MASK: AtomicBool = AtomicBool::new(false);
+
+/// Shape of the camera aperture, which gives the shape of the bokeh.
+#[derive(Debug, Clone, Default)]
+pub enum Aperture {
+    /// Round aperture
+    #[default]
+    Circle,
+    /// Aperture cut out of a grayscale mask, whose square is mapped onto the
+    /// lens diameter. The brightness of the mask is the probability a lens
+    /// sample is kept, so black parts block the light.
+    ///
+    /// The brightness is the decoded color of the image, so 8-bit masks
+    /// should be [`Image::linear`], as made by [`Aperture::masked`].
+    /// Otherwise a 50% gray is decoded from sRGB and keeps about 22% of the
+    /// samples.
+    Masked(Image),
+}
+
+impl Aperture {
+    /// Aperture cut out of `mask`, whose 8-bit pixels are taken as linear
+    /// brightness.
+    pub fn masked(mask: Image) -> Self {
+        Self::Masked(mask.linear())
+    }
+
+    /// Random point on the lens of radius `radius`, in lens coordinates.
+    pub fn sample(&self, radius: f64) -> Vec3<f64> {
+        match self {
+            Self::Circle => Vec3::random_in_disk(radius),
+            Self::Masked(mask) => Self::sample_mask(mask, radius),
+        }
+    }
+
+    /// Rejection sampling of the square against `mask`.
+    fn sample_mask(mask: &Image, radius: f64) -> Vec3<f64> {
+        for _ in 0..MAX_MASK_TRIES {
+            let (u, v, keep): (f64, f64, f64) =
+                random::with_rng(|rng| (rng.gen(), rng.gen(), rng.gen()));
+            let color = mask.color(Point3::zeros(), u, v);
+            let acceptance = (color.r() + color.g() + color.b()) / 3.0;
+            if keep < acceptance {
+                return Vec3::new(2.0 * u - 1.0, 2.0 * v - 1.0, 0.0) * radius;
+            }
+        }
+
+        if !WARNED_DARK_MASK.swap(true, Ordering::Relaxed) {
+            warn!("aperture mask is almost black, sampling the whole lens instead");
+        }
+        Vec3::random_in_disk(radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_shifts_lens_samples() {
+        let mask = image::ImageBuffer::from_fn(2, 1, |x, _| image::Rgb([255 * (1 - x as u8); 3]));
+        let aperture = Aperture::Masked(Image::new(mask));
+
+        let samples = (0..1000).map(|_| aperture.sample(0.5)).collect::<Vec<_>>();
+        assert!(samples.iter().all(|sample| sample.x() <= 0.0));
+        assert!(samples.iter().all(|sample| sample.y().abs() <= 0.5));
+        let mean = samples.iter().map(|sample| sample.x()).sum::<f64>() / 1000.0;
+        assert!(mean < -0.2, "mean lens offset is {mean}");
+    }
+
+    #[test]
+    fn gray_mask_keeps_half_the_samples() {
+        random::seed(3);
+        // gray on the left half, white on the right
+        let mask = image::ImageBuffer::from_fn(2, 1, |x, _| image::Rgb([128 + 127 * x as u8; 3]));
+        let aperture = Aperture::masked(Image::new(mask));
+
+        const SAMPLES: usize = 20_000;
+        let left = (0..SAMPLES)
+            .filter(|_| aperture.sample(0.5).x() < 0.0)
+            .count() as f64
+            / SAMPLES as f64;
+        // half as likely to be kept on the left, and not 22% as likely
+        let gray = 128.0 / 255.0;
+        assert!((left - gray / (gray + 1.0)).abs() < 0.02, "{left}");
+    }
+}
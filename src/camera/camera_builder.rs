@@ -1,4 +1,8 @@
-use crate::{Camera, Point3, Vec3};
+use crate::{
+    camera::Aperture,
+    hit::{scene_bounds, AABB},
+    Camera, Hit, Point3, Vec3,
+};
 use std::ops::Range;
 
 macro_rules! builder_methods {
@@ -42,6 +46,8 @@ pub struct CameraBuilder {
     aspect_ratio: f64,
     /// The aperture of the camera. This is the diameter of the lens.
     aperture: f64,
+    /// Shape of the aperture, scaled to the `aperture` diameter.
+    aperture_shape: Aperture,
     /// The distance between the projection point and the plane
     /// where everything is in perfect focus.
     focus_distance: Option<f64>,
@@ -58,6 +64,7 @@ impl CameraBuilder {
             vertical_field_of_view: 90.0,
             aspect_ratio: 16.0 / 9.0,
             aperture: 0.0,
+            aperture_shape: Aperture::Circle,
             focus_distance: None,
             time_range: 0.0..1.0,
         }
@@ -70,6 +77,7 @@ impl CameraBuilder {
         vertical_field_of_view: f64,
         aspect_ratio: f64,
         aperture: f64,
+        aperture_shape: Aperture,
         time_range: Range<f64> as range
     }
 
@@ -83,6 +91,51 @@ impl CameraBuilder {
         self
     }
 
+    /// Look at the center of `bounds` from `direction`, far enough that the
+    /// whole box fits into the view with some margin, and focus there.
+    ///
+    /// Set the aspect ratio first, as a narrow image needs more distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `direction` is zero.
+    pub fn frame_bounds(
+        mut self,
+        bounds: &AABB,
+        vertical_field_of_view: f64,
+        direction: Vec3<f64>,
+    ) -> Self {
+        const MARGIN: f64 = 1.1;
+        assert!(direction.norm() > 0.0, "direction must not be zero");
+
+        // fit the bounding sphere into the narrower of the two fields of view
+        let half_height = (vertical_field_of_view.to_radians() / 2.0).tan();
+        let half_width = half_height * self.aspect_ratio;
+        let half_angle = half_height.min(half_width).atan();
+        let radius = bounds.diagonal().norm() / 2.0;
+        let distance = MARGIN * radius / half_angle.sin();
+
+        let center = bounds.center();
+        self.look_at = center;
+        self.look_from = center + direction.normalized() * distance;
+        self.vertical_field_of_view = vertical_field_of_view;
+        self.focus_distance = Some(distance);
+        self
+    }
+
+    /// Same as [`CameraBuilder::frame_bounds`] with the bounds of `world`,
+    /// see [`scene_bounds`]. Fails if the scene is unbounded.
+    pub fn frame_scene<H: Hit + ?Sized>(
+        self,
+        world: &H,
+        vertical_field_of_view: f64,
+        direction: Vec3<f64>,
+    ) -> Result<Self, String> {
+        let bounds = scene_bounds(world, self.time_range.clone())
+            .ok_or("cannot frame a scene without finite bounds")?;
+        Ok(self.frame_bounds(&bounds, vertical_field_of_view, direction))
+    }
+
     pub fn build(self) -> Camera {
         let Self {
             look_from,
@@ -91,6 +144,7 @@ impl CameraBuilder {
             vertical_field_of_view,
             aspect_ratio,
             aperture,
+            aperture_shape,
             focus_distance,
             time_range,
         } = self;
@@ -125,6 +179,7 @@ impl CameraBuilder {
             u: camera_u,
             v: camera_v,
             lens_radius: aperture / 2.0,
+            aperture_shape,
             time_range,
         }
     }
@@ -135,3 +190,28 @@ impl Default for CameraBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn framed_box_is_in_view() {
+        let bounds = AABB::new(Point3::zeros(), Point3::ones());
+        for (aspect_ratio, direction) in [
+            (1.0, Vec3::new(1.0, 1.0, 1.0)),
+            (16.0 / 9.0, Vec3::new(0.0, 0.2, -1.0)),
+            (0.5, Vec3::new(-3.0, 0.5, 0.1)),
+        ] {
+            let camera = CameraBuilder::new()
+                .aspect_ratio(aspect_ratio)
+                .frame_bounds(&bounds, 40.0, direction)
+                .build();
+
+            for corner in bounds.iter_corners() {
+                let (u, v) = camera.project(corner).unwrap();
+                assert!((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v));
+            }
+        }
+    }
+}
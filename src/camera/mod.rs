@@ -1,8 +1,10 @@
+mod aperture;
 mod camera_builder;
+pub use aperture::Aperture;
 pub use camera_builder::CameraBuilder;
 use rand::Rng;
 
-use crate::{Point3, Ray, Vec3};
+use crate::{random, Point3, Ray, Vec3};
 use std::{fmt::Display, ops::Range};
 
 /// Ray-tracing camera
@@ -39,6 +41,8 @@ pub struct Camera {
     u: Vec3<f64>,
     v: Vec3<f64>,
     lens_radius: f64,
+    /// Shape of the lens opening
+    aperture_shape: Aperture,
     /// Shutter open and close times
     time_range: Range<f64>,
 }
@@ -51,9 +55,9 @@ impl Camera {
     /// `u` and `v` are the coordinates of the point on the
     /// viewport, in the range of [0.0, 1.0].
     pub fn cast(&self, u: f64, v: f64) -> Ray {
-        let random = Vec3::random_in_disk(self.lens_radius);
+        let random = self.aperture_shape.sample(self.lens_radius);
         let offset = self.u * random.x() + self.v * random.y();
-        let time = rand::thread_rng().gen_range(self.time_range.clone());
+        let time = random::with_rng(|rng| rng.gen_range(self.time_range.clone()));
 
         let origin = self.origin + offset;
         let destination = self.lower_left_corner + u * self.horizontal + v * self.vertical;
@@ -62,6 +66,30 @@ impl Camera {
         Ray::new(origin, direction, time)
     }
 
+    /// Returns the viewport coordinates `(u, v)` of `point`, i.e. the inverse
+    /// of [`Camera::cast`] for a pinhole camera.
+    ///
+    /// The coordinates are in `[0.0, 1.0]` when the point is inside the field of
+    /// view, and outside of that range otherwise. Returns `None` for points
+    /// that are not in front of the camera.
+    pub fn project(&self, point: Point3) -> Option<(f64, f64)> {
+        // the camera faces -w, and w = u x v
+        let forward = -self.u.cross(self.v);
+        let direction = point - self.origin;
+        let depth = direction.dot(forward);
+        if depth <= 0.0 {
+            return None;
+        }
+
+        // intersect the line with the plane of the viewport
+        let distance = (self.lower_left_corner - self.origin).dot(forward);
+        let relative = self.origin + direction * (distance / depth) - self.lower_left_corner;
+        let u = relative.dot(self.horizontal) / self.horizontal.len_squared();
+        let v = relative.dot(self.vertical) / self.vertical.len_squared();
+
+        Some((u, v))
+    }
+
     pub fn aspect_ratio(&self) -> f64 {
         self.horizontal.norm() / self.vertical.norm()
     }
@@ -0,0 +1,90 @@
+//! Inputs for external denoisers such as Intel Open Image Denoise.
+//!
+//! Denoisers work much better when they are given the albedo and normal of
+//! the first hit besides the noisy image. [`crate::RayTracer::render_with_aovs`]
+//! renders all three, and [`crate::RenderBuffer::to_f32_rgb`] exports each of
+//! them in the layout such libraries expect. With the `oidn` feature,
+//! [`denoise`] runs Open Image Denoise on them directly.
+
+use crate::RenderBuffer;
+
+/// Noisy render with its auxiliary buffers, all of the same size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenoiseInputs {
+    /// Linear color of the render
+    pub color: RenderBuffer,
+    /// Albedo of the first surface hit, or the background if nothing is hit
+    pub albedo: RenderBuffer,
+    /// Normal of the first surface hit facing the camera, zero if nothing is hit
+    pub normal: RenderBuffer,
+}
+
+/// Denoise `inputs` with Open Image Denoise, using its ray tracing filter.
+#[cfg(feature = "oidn")]
+pub fn denoise(inputs: &DenoiseInputs) -> Result<RenderBuffer, String> {
+    let (width, height) = (inputs.color.width(), inputs.color.height());
+    let color = inputs.color.to_f32_rgb();
+    let albedo = inputs.albedo.to_f32_rgb();
+    let normal = inputs.normal.to_f32_rgb();
+    let mut output = vec![0.0; color.len()];
+
+    let device = oidn::Device::new();
+    oidn::RayTracing::new(&device)
+        .hdr(true)
+        .image_dimensions(width as usize, height as usize)
+        .albedo_normal(&albedo, &normal)
+        .filter(&color, &mut output)
+        .map_err(|error| format!("invalid denoiser input: {:?}", error))?;
+    if let Err((_, message)) = device.get_error() {
+        return Err(format!("denoising failed: {}", message));
+    }
+
+    Ok(RenderBuffer::from_f32_rgb(width, height, &output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenes;
+
+    /// The Cornell box at 64 by 64 pixels, rendered with its auxiliary buffers.
+    fn cornell_box(samples_per_pixel: u64, seed: u64) -> DenoiseInputs {
+        let mut tracer = scenes::cornell_box().build_tracer(8);
+        tracer.image_height = 64;
+        tracer.samples_per_pixel = samples_per_pixel;
+        tracer.seed = Some(seed);
+        tracer.render_with_aovs()
+    }
+
+    fn mean_squared_error(lhs: &RenderBuffer, rhs: &RenderBuffer) -> f64 {
+        let sum = (lhs.pixels().iter().zip(rhs.pixels()))
+            .map(|(lhs, rhs)| (*lhs - *rhs).len_squared())
+            .sum::<f64>();
+        sum / lhs.pixels().len() as f64
+    }
+
+    #[test]
+    fn cornell_guides_are_smooth() {
+        // two noisy renders differ in color much more than in the albedo and
+        // normal that guide the denoiser
+        let (first, second) = (cornell_box(2, 1), cornell_box(2, 2));
+        let color = mean_squared_error(&first.color, &second.color);
+        let albedo = mean_squared_error(&first.albedo, &second.albedo);
+        let normal = mean_squared_error(&first.normal, &second.normal);
+        assert!(albedo < color / 4.0, "{albedo} vs {color}");
+        assert!(normal < color / 4.0, "{normal} vs {color}");
+    }
+
+    #[cfg(feature = "oidn")]
+    #[test]
+    fn denoising_reduces_cornell_variance() {
+        let reference = cornell_box(256, 3).color;
+        let inputs = cornell_box(4, 4);
+        let denoised = denoise(&inputs).unwrap();
+        let (noisy, denoised) = (
+            mean_squared_error(&inputs.color, &reference),
+            mean_squared_error(&denoised, &reference),
+        );
+        assert!(denoised < noisy / 2.0, "{denoised} vs {noisy}");
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{Point3, Ray, Vec3};
+use std::ops::Range;
+
+use crate::{Interval, Point3, Ray, Vec3};
 
 /// Axis aligned bounding box (AABB).
 /// AABBs are used to determine whether two objects are colliding.
@@ -22,6 +24,11 @@ impl AABB {
         max: Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
     };
 
+    /// Thickness that flat objects, such as quads facing along an axis, pad
+    /// their boxes to with [`AABB::padded`], so that they can still be put
+    /// into a BVH.
+    pub const MIN_THICKNESS: f64 = 1e-6;
+
     pub fn new(min: Point3, max: Point3) -> Self {
         assert!(min.x() <= max.x());
         assert!(min.y() <= max.y());
@@ -29,36 +36,39 @@ impl AABB {
         Self { min, max }
     }
 
-    pub fn is_hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
-        // t_min and t_max are the intersection points of the ray with the AABB
-        let mut t_min = t_min;
-        let mut t_max = t_max;
-
-        // iterate over all three axes
-        // when t_min is greater than t_max, the ray misses the AABB
-        for i in 0..self.min.len() {
-            let origin = ray.origin()[i];
-            let direction = ray.direction()[i];
-            let min = self.min[i];
-            let max = self.max[i];
-
-            // t0 and t1 are the intersection points of the ray with the slab
-            // line: point = origin + t * direction
-            let t0 = (min - origin) / direction;
-            let t1 = (max - origin) / direction;
-
-            // t0 and t1 are swapped if the ray is pointing in the opposite direction
-            let (t0, t1) = if direction < 0.0 { (t1, t0) } else { (t0, t1) };
-            t_min = t0.max(t_min);
-            t_max = t1.min(t_max);
-
-            // if t_max < t_min, then the slab is missed
-            if t_max <= t_min {
-                return false;
+    /// Whether the ray enters the AABB within `t_range`, see [`AABB::hit_interval`].
+    pub fn is_hit(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.hit_interval(ray, t_range).is_some()
+    }
+
+    /// Parametric interval in which the ray is inside the AABB, restricted to
+    /// `t_range`, or `None` if it misses.
+    ///
+    /// This is the slab method with the inverse of the direction. A zero
+    /// direction component, of either sign, makes the distances to both slabs
+    /// infinite, so the ray is inside that slab for all `t` or never. A ray
+    /// lying exactly on a face counts as inside, and so does a ray touching
+    /// the AABB or passing through a flat one.
+    pub fn hit_interval(&self, ray: &Ray, t_range: Interval) -> Option<Interval> {
+        let (mut t_min, mut t_max) = (t_range.min, t_range.max);
+        let (origin, inverse, sign) = (ray.origin(), ray.inverse_direction(), ray.sign());
+        let bounds = [&self.min, &self.max];
+        for axis in 0..3 {
+            // the near and far slab, swapped if the ray is pointing in the
+            // opposite direction, which includes -0.0 as its inverse is
+            // negative infinity
+            let near = (bounds[sign[axis]][axis] - origin[axis]) * inverse[axis];
+            let far = (bounds[1 - sign[axis]][axis] - origin[axis]) * inverse[axis];
+
+            // a ray on a face gives 0 * inf = NaN, which `max` and `min` ignore
+            t_min = near.max(t_min);
+            t_max = far.min(t_max);
+            if t_max < t_min {
+                return None;
             }
         }
 
-        true
+        Some(Interval::new(t_min, t_max))
     }
 
     /// Combines two AABBs into a single AABB that contains both.
@@ -85,8 +95,42 @@ impl AABB {
         self.max
     }
 
+    /// Center point of the AABB.
+    pub fn center(&self) -> Point3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Vector from the minimum to the maximum point.
+    pub fn diagonal(&self) -> Vec3<f64> {
+        self.max - self.min
+    }
+
+    /// Total area of the six faces, zero for a flat or empty box.
+    pub fn surface_area(&self) -> f64 {
+        let diagonal = self.diagonal().max(&Vec3::zeros());
+        2.0 * (diagonal.x() * diagonal.y()
+            + diagonal.y() * diagonal.z()
+            + diagonal.z() * diagonal.x())
+    }
+
+    /// Grow the AABB by `delta` on every side.
+    pub fn expand(self, delta: f64) -> AABB {
+        let delta = Vec3::constant(delta);
+        Self::new(self.min - delta, self.max + delta)
+    }
+
+    /// Grow the AABB around its center along the axes on which it is thinner
+    /// than `min_thickness`, to exactly that thickness. Other axes are kept.
+    pub fn padded(self, min_thickness: f64) -> AABB {
+        let padding = (Vec3::constant(min_thickness) - self.diagonal()).max(&Vec3::zeros()) / 2.0;
+        Self::new(self.min - padding, self.max + padding)
+    }
+
     pub fn move_by(self, offset: Vec3<f64>) -> AABB {
-        Self { min: self.min + offset, max: self.max + offset }
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
     }
 
     /// Returns the point at the corner of the AABB with the given index.
@@ -110,3 +154,170 @@ impl AABB {
         (0..(1 << 3)).map(move |i| self.corner(i))
     }
 }
+
+/// Bounding box of something moving over `time_range`, ends included, whose box at
+/// time `t` is `bounds_at(t)`.
+///
+/// The box is sampled at `samples` evenly spaced times including both ends,
+/// and the samples are merged, then grown by `padding`. BVH culling skips
+/// everything outside of this box, so it must be conservative: anything the
+/// object can occupy between the samples must be covered by `padding`. If the
+/// box moves by at most `speed` per unit time, a padding of
+/// `speed * (time_range.end - time_range.start) / (samples - 1) / 2` is enough. For linear
+/// motion, the two end points with no padding are exact.
+///
+/// # Panics
+///
+/// Panics if `samples` is less than 2.
+pub fn swept_bounds(
+    time_range: Range<f64>,
+    samples: usize,
+    padding: f64,
+    bounds_at: impl Fn(f64) -> AABB,
+) -> AABB {
+    assert!(samples >= 2, "at least the two end points must be sampled");
+    let step = (time_range.end - time_range.start) / (samples - 1) as f64;
+    let merged = (1..samples)
+        .map(|i| bounds_at(time_range.start + i as f64 * step))
+        .fold(bounds_at(time_range.start), |merged, aabb| {
+            merged.merge(&aabb)
+        });
+    merged.expand(padding)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn unit() -> AABB {
+        AABB::new(Point3::zeros(), Point3::ones())
+    }
+
+    #[test]
+    fn zero_direction_components() {
+        for zero in [0.0, -0.0] {
+            // inside the x and y slabs
+            let ray = Ray::new(Point3::new(0.5, 0.5, -1.0), Vec3::new(zero, zero, 1.0), 0.0);
+            let interval = unit().hit_interval(&ray, Interval::from_min(0.0));
+            assert_eq!(interval, Some(Interval::new(1.0, 2.0)));
+
+            // outside the x slab, on either side
+            for x in [-0.5, 1.5] {
+                let ray = Ray::new(Point3::new(x, 0.5, -1.0), Vec3::new(zero, 0.0, 1.0), 0.0);
+                assert_eq!(unit().hit_interval(&ray, Interval::from_min(0.0)), None);
+            }
+        }
+    }
+
+    #[test]
+    fn rays_on_a_face() {
+        for (x, zero) in [(0.0, 0.0), (0.0, -0.0), (1.0, 0.0), (1.0, -0.0)] {
+            let ray = Ray::new(Point3::new(x, 0.5, -1.0), Vec3::new(zero, 0.0, 1.0), 0.0);
+            let interval = unit().hit_interval(&ray, Interval::from_min(0.0));
+            assert_eq!(interval, Some(Interval::new(1.0, 2.0)));
+        }
+        // along an edge, and touching a corner
+        let ray = Ray::new(Point3::new(1.0, 1.0, -1.0), Vec3::unit_z(), 0.0);
+        assert!(unit().is_hit(&ray, Interval::from_min(0.0)));
+        let ray = Ray::new(Point3::new(2.0, 0.0, 0.0), Vec3::new(-1.0, 1.0, 0.0), 0.0);
+        assert_eq!(
+            unit().hit_interval(&ray, Interval::from_min(0.0)),
+            Some(Interval::new(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn degenerate_boxes() {
+        // flat in z, hit straight on and along the plane
+        let flat = AABB::new(Point3::zeros(), Point3::new(1.0, 1.0, 0.0));
+        let ray = Ray::new(Point3::new(0.5, 0.5, -1.0), Vec3::unit_z(), 0.0);
+        assert_eq!(
+            flat.hit_interval(&ray, Interval::from_min(0.0)),
+            Some(Interval::new(1.0, 1.0))
+        );
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.0), Vec3::unit_x(), 0.0);
+        assert_eq!(
+            flat.hit_interval(&ray, Interval::from_min(0.0)),
+            Some(Interval::new(1.0, 2.0))
+        );
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.1), Vec3::unit_x(), 0.0);
+        assert!(!flat.is_hit(&ray, Interval::from_min(0.0)));
+
+        // a single point
+        let point = AABB::new(Point3::ones(), Point3::ones());
+        let ray = Ray::new(Point3::zeros(), Vec3::ones(), 0.0);
+        assert_eq!(
+            point.hit_interval(&ray, Interval::from_min(0.0)),
+            Some(Interval::new(1.0, 1.0))
+        );
+
+        // the empty box is never hit
+        for direction in [Vec3::ones(), -Vec3::ones(), Vec3::unit_x()] {
+            let ray = Ray::new(Point3::zeros(), direction, 0.0);
+            assert!(!AABB::EMPTY.is_hit(&ray, Interval::UNIVERSE));
+        }
+    }
+
+    #[test]
+    fn padding_flat_boxes() {
+        let flat = AABB::new(Point3::new(0.0, 2.0, 0.0), Point3::new(1.0, 2.0, 3.0));
+        let padded = flat.padded(0.5);
+        assert_eq!(padded.min(), Point3::new(0.0, 1.75, 0.0));
+        assert_eq!(padded.max(), Point3::new(1.0, 2.25, 3.0));
+        // thicker axes are kept, and thin ones grow around their center
+        let thin = AABB::new(Point3::zeros(), Point3::new(0.25, 1.0, 1.0));
+        assert_eq!(thin.padded(0.5).min(), Point3::new(-0.125, 0.0, 0.0));
+        assert_eq!(unit().padded(0.5).max(), unit().max());
+    }
+
+    /// Slab test dividing by the direction, with zero components handled
+    /// separately.
+    fn divided_interval(aabb: &AABB, ray: &Ray, t_range: Interval) -> Option<Interval> {
+        let (mut t_min, mut t_max) = (t_range.min, t_range.max);
+        for axis in 0..3 {
+            let (origin, direction) = (ray.origin()[axis], ray.direction()[axis]);
+            if direction == 0.0 {
+                if origin < aabb.min[axis] || aabb.max[axis] < origin {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (aabb.min[axis] - origin) / direction;
+            let t1 = (aabb.max[axis] - origin) / direction;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+        (t_min <= t_max).then_some(Interval::new(t_min, t_max))
+    }
+
+    #[test]
+    fn inverse_direction_agrees_with_division() {
+        let mut rng = StdRng::seed_from_u64(30);
+        // on a lattice, so that rays often lie on faces or have zero components
+        let mut lattice = |range: std::ops::RangeInclusive<i32>| {
+            let sign = if rng.gen() { 1.0 } else { -1.0 };
+            f64::from(rng.gen_range(range)) * sign
+        };
+        for _ in 0..20000 {
+            let (a, b) = (
+                Point3::new(lattice(0..=2), lattice(0..=2), lattice(0..=2)),
+                Point3::new(lattice(0..=2), lattice(0..=2), lattice(0..=2)),
+            );
+            let aabb = AABB::new(a.min(&b), a.max(&b));
+            let origin = Point3::new(lattice(0..=3), lattice(0..=3), lattice(0..=3));
+            let direction = Vec3::new(lattice(0..=2), lattice(0..=2), lattice(0..=2));
+            if direction.len_squared() == 0.0 {
+                continue;
+            }
+            let ray = Ray::new(origin, direction, 0.0);
+            let t_range = Interval::from_min(1e-10);
+            assert_eq!(
+                aabb.hit_interval(&ray, t_range),
+                divided_interval(&aabb, &ray, t_range),
+                "{ray:?} {aabb:?}"
+            );
+        }
+    }
+}
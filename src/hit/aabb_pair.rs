@@ -0,0 +1,189 @@
+use crate::{hit::AABB, Interval, Ray};
+
+/// Two bounding boxes stored side by side so that they can be tested against a
+/// ray at the same time.
+///
+/// For every axis, the four lanes hold the near and far slabs of both boxes:
+/// `[min_0, min_1, max_0, max_1]` for rays going in the positive direction, and
+/// `[max_0, max_1, min_0, min_1]` for rays going in the negative direction. A
+/// single 4-wide operation then computes the entry and exit distances of both
+/// boxes along one axis.
+///
+/// With the `simd` feature the lanes are processed with `wide::f64x4`, otherwise
+/// one at a time. Both give exactly the same result as [`AABB::hit_interval`].
+#[derive(Debug, Clone)]
+pub(crate) struct AABBPair {
+    positive: [[f64; 4]; 3],
+    negative: [[f64; 4]; 3],
+}
+
+impl AABBPair {
+    /// Pack two boxes, a missing box is never hit.
+    pub fn new(first: Option<&AABB>, second: Option<&AABB>) -> Self {
+        let first = first.unwrap_or(&AABB::EMPTY);
+        let second = second.unwrap_or(&AABB::EMPTY);
+
+        let mut positive = [[0.0; 4]; 3];
+        let mut negative = [[0.0; 4]; 3];
+        for axis in 0..3 {
+            let (min0, min1) = (first.min[axis], second.min[axis]);
+            let (max0, max1) = (first.max[axis], second.max[axis]);
+            positive[axis] = [min0, min1, max0, max1];
+            negative[axis] = [max0, max1, min0, min1];
+        }
+
+        Self { positive, negative }
+    }
+
+    fn lanes(&self, ray: &Ray, axis: usize) -> &[f64; 4] {
+        // same condition as the swap in `AABB::hit_interval`
+        if ray.sign()[axis] == 1 {
+            &self.negative[axis]
+        } else {
+            &self.positive[axis]
+        }
+    }
+
+    /// Whether the ray hits each of the boxes within `t_range`.
+    pub fn hit_mask(&self, ray: &Ray, t_range: Interval) -> [bool; 2] {
+        #[cfg(feature = "simd")]
+        {
+            self.hit_mask_simd(ray, t_range)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.hit_mask_scalar(ray, t_range)
+        }
+    }
+
+    #[cfg_attr(feature = "simd", allow(dead_code))]
+    pub fn hit_mask_scalar(&self, ray: &Ray, t_range: Interval) -> [bool; 2] {
+        let mut near = [t_range.min; 2];
+        let mut far = [t_range.max; 2];
+
+        for axis in 0..3 {
+            let origin = ray.origin()[axis];
+            let inverse = ray.inverse_direction()[axis];
+            let lanes = self.lanes(ray, axis);
+            for lane in 0..2 {
+                let t0 = (lanes[lane] - origin) * inverse;
+                let t1 = (lanes[lane + 2] - origin) * inverse;
+                near[lane] = t0.max(near[lane]);
+                far[lane] = t1.min(far[lane]);
+            }
+        }
+
+        // the interval only shrinks, so checking once at the end is the same
+        // as returning early like `AABB::hit_interval` does
+        [far[0] >= near[0], far[1] >= near[1]]
+    }
+
+    #[cfg(feature = "simd")]
+    pub fn hit_mask_simd(&self, ray: &Ray, t_range: Interval) -> [bool; 2] {
+        use wide::{f64x4, CmpGt};
+
+        // far distances are negated, so that all four lanes shrink with `max`
+        let sign = f64x4::from([1.0, 1.0, -1.0, -1.0]);
+        let Interval { min, max } = t_range;
+        let mut interval = f64x4::from([min, min, -max, -max]);
+
+        for axis in 0..3 {
+            let origin = f64x4::splat(ray.origin()[axis]);
+            let inverse = ray.inverse_direction()[axis];
+            let lanes = f64x4::from(*self.lanes(ray, axis));
+            let t = (lanes - origin) * f64x4::splat(inverse) * sign;
+
+            // `f64::max` ignores a NaN operand, and a comparison with NaN is false,
+            // so NaN distances keep the current bound here as well
+            interval = t.cmp_gt(interval).blend(t, interval);
+        }
+
+        let [near0, near1, far0, far1] = interval.to_array();
+        [-far0 >= near0, -far1 >= near1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{Point3, Vec3};
+
+    fn random_box(rng: &mut StdRng) -> AABB {
+        let a = Point3::new(
+            rng.gen_range(-2..=2) as f64,
+            rng.gen_range(-2..=2) as f64,
+            0.0,
+        );
+        let b = Point3::new(
+            rng.gen_range(-2..=2) as f64,
+            rng.gen_range(-2..=2) as f64,
+            1.0,
+        );
+        AABB::new(a.min(&b), a.max(&b))
+    }
+
+    /// Rays with coordinates on the integer lattice, so that many of them lie
+    /// exactly on a face or have a zero direction component.
+    fn random_ray(rng: &mut StdRng) -> Ray {
+        let origin = Point3::new(
+            rng.gen_range(-3..=3) as f64,
+            rng.gen_range(-3..=3) as f64,
+            rng.gen_range(-3..=3) as f64,
+        );
+        // zeros of both signs
+        let mut component = || rng.gen_range(-1..=1) as f64 * if rng.gen() { 1.0 } else { -1.0 };
+        loop {
+            let direction = Vec3::new(component(), component(), component());
+            if direction.len_squared() > 0.0 {
+                return Ray::new(origin, direction, 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn pair_matches_aabb() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..10000 {
+            let boxes = [random_box(&mut rng), random_box(&mut rng)];
+            let pair = AABBPair::new(Some(&boxes[0]), Some(&boxes[1]));
+            let ray = random_ray(&mut rng);
+            let expected = [
+                boxes[0].is_hit(&ray, Interval::from_min(1e-10)),
+                boxes[1].is_hit(&ray, Interval::from_min(1e-10)),
+            ];
+            assert_eq!(
+                pair.hit_mask(&ray, Interval::from_min(1e-10)),
+                expected,
+                "{ray:?} {boxes:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn missing_box_is_never_hit() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let aabb = random_box(&mut rng);
+        let pair = AABBPair::new(Some(&aabb), None);
+        for _ in 0..1000 {
+            let [_, second] = pair.hit_mask(&random_ray(&mut rng), Interval::from_min(1e-10));
+            assert!(!second);
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_matches_scalar() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..10000 {
+            let pair = AABBPair::new(Some(&random_box(&mut rng)), Some(&random_box(&mut rng)));
+            let ray = random_ray(&mut rng);
+            let t_max = rng.gen_range(0.5..4.0);
+            assert_eq!(
+                pair.hit_mask_simd(&ray, Interval::new(1e-10, t_max)),
+                pair.hit_mask_scalar(&ray, Interval::new(1e-10, t_max)),
+            );
+        }
+    }
+}
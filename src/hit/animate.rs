@@ -0,0 +1,155 @@
+use std::{fmt::Debug, ops::Range};
+
+use crate::{Hit, Interval, Point3, Ray, Vec3};
+
+use super::{swept_bounds, Motion, OutwardHitRecord, AABB};
+
+/// Number of times the path is sampled for the bounding box by default.
+const DEFAULT_BOUNDS_SAMPLES: usize = 16;
+
+/// Instance moving along an arbitrary path over time
+///
+/// At time `t`, the object is moved by `path.offset(t)`, where the path is a
+/// closure or a [`super::MotionPath`]. Like [`super::translation::Translate`],
+/// the ray is moved in the opposite direction instead.
+///
+/// The bounding box uses [`Motion::offset_bounds`] when the path has them.
+/// Otherwise it is found with [`swept_bounds`], and for the box to be
+/// conservative, give the maximum speed of the path with
+/// [`Animate::with_max_speed`].
+#[derive(Clone)]
+pub struct Animate<H: Hit, F> {
+    object: H,
+    path: F,
+    bounds_samples: usize,
+    max_speed: f64,
+}
+
+impl<H: Hit, F: Motion> Animate<H, F> {
+    pub fn new(object: H, path: F) -> Self {
+        Self {
+            object,
+            path,
+            bounds_samples: DEFAULT_BOUNDS_SAMPLES,
+            max_speed: 0.0,
+        }
+    }
+
+    /// Sample the path `samples` times for the bounding box.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` is less than 2.
+    pub fn with_bounds_samples(mut self, samples: usize) -> Self {
+        assert!(samples >= 2, "at least the two end points must be sampled");
+        self.bounds_samples = samples;
+        self
+    }
+
+    /// Upper bound of `|path'(t)|`, used to pad the bounding box between samples.
+    pub fn with_max_speed(mut self, max_speed: f64) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+}
+
+impl<H: Hit, F> Debug for Animate<H, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Animate")
+            .field("object", &self.object)
+            .field("bounds_samples", &self.bounds_samples)
+            .field("max_speed", &self.max_speed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H: Hit, F: Motion> Hit for Animate<H, F> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let offset = self.path.offset(ray.time());
+        let ray = ray.move_origin_by(-offset);
+        ray.hit(&self.object, t_range).map(|mut hit| {
+            hit.point += offset;
+            hit
+        })
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        let aabb = self.object.bounding_box(time_range.clone())?;
+        if let Some(offsets) = self.path.offset_bounds(time_range.clone()) {
+            return Some(AABB::new(aabb.min + offsets.min, aabb.max + offsets.max));
+        }
+        let step = (time_range.end - time_range.start) / (self.bounds_samples - 1) as f64;
+        let padding = self.max_speed * step.abs() / 2.0;
+        Some(swept_bounds(
+            time_range,
+            self.bounds_samples,
+            padding,
+            |time| aabb.clone().move_by(self.path.offset(time)),
+        ))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        let ray = ray.clone().move_origin_by(-self.path.offset(ray.time()));
+        self.object.hit_any(&ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let origin = origin - self.path.offset(time);
+        self.object.pdf_value(origin, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        let origin = origin - self.path.offset(time);
+        self.object.random_point_towards(origin, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::TAU, sync::Arc};
+
+    use super::*;
+    use crate::{hit::MotionPath, material::Lambertian, random, Color, Point3, Sphere, Vec3};
+
+    #[test]
+    fn swept_box_contains_every_time() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let sphere = Sphere::new(Point3::zeros(), 0.5, material);
+        let path = |time: f64| Vec3::new((TAU * time).sin(), 0.0, 0.0);
+        let animated = Animate::new(sphere, path)
+            .with_bounds_samples(6)
+            .with_max_speed(TAU);
+
+        let swept = animated.bounding_box(0.0..1.0).unwrap();
+        for _ in 0..20 {
+            let center = path(random::random::<f64>());
+            for corner in [center - Vec3::constant(0.5), center + Vec3::constant(0.5)] {
+                assert!(
+                    (0..3).all(|i| swept.min[i] <= corner[i] && corner[i] <= swept.max[i]),
+                    "{corner} outside {swept:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn follows_a_motion_path() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let sphere = Sphere::new(Point3::zeros(), 0.5, material);
+        let path = MotionPath::linear(vec![
+            (0.0, Point3::zeros()),
+            (0.5, Point3::new(0.0, 2.0, 0.0)),
+            (1.0, Point3::new(2.0, 2.0, 0.0)),
+        ]);
+        let animated = Animate::new(sphere, path);
+
+        let aabb = animated.bounding_box(0.25..1.0).unwrap();
+        assert_eq!(aabb.min, Point3::new(-0.5, 0.5, -0.5));
+        assert_eq!(aabb.max, Point3::new(2.5, 2.5, 0.5));
+
+        let ray = Ray::new(Point3::new(1.0, 2.0, 5.0), -Vec3::unit_z(), 0.75);
+        let hit = animated.hit(ray, Interval::from_min(0.0)).unwrap();
+        assert_eq!(hit.t, 4.5);
+        assert_eq!(hit.point, Point3::new(1.0, 2.0, 0.5));
+    }
+}
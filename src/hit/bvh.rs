@@ -1,8 +1,12 @@
-use std::ops::Range;
+use std::{cell::Cell, fmt, ops::Range};
 
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
 
-use crate::{Hit, hit::{AABB, OutwardHitRecord}, Ray};
+use crate::{
+    hit::{AABBPair, OutwardHitRecord, AABB},
+    random, Hit, Interval, Point3, Ray, Vec3,
+};
 
 /// Bounding volume hierarchy (BVH) tree node.
 ///
@@ -12,24 +16,227 @@ pub struct BVH {
     /// Bounding box of the node
     bounding_box: AABB,
     /// Left child
-    left: Option<Box<dyn Hit>>,
+    left: Option<Child>,
     /// Right child
-    right: Option<Box<dyn Hit>>,
+    right: Option<Child>,
+    /// Bounding boxes of the left and right child, tested together
+    child_boxes: AABBPair,
 }
 
-fn sort_objects_by_axis(objects: &mut [Box<dyn Hit>], axis: usize, time_from: f64, time_to: f64) {
-    objects.sort_unstable_by(|lhs, rhs| {
-        let lhs = lhs
-            .bounding_box(time_from, time_to)
-            .expect("No bounding box in BVHNode constructor")
-            .min()[axis];
-        let rhs = rhs
-            .bounding_box(time_from, time_to)
-            .expect("No bounding box in BVHNode constructor")
-            .min()[axis];
+/// Child of a BVH node, either another node or one of the objects.
+#[derive(Debug)]
+enum Child {
+    Node(Box<BVH>),
+    Object(Box<dyn Hit>),
+}
+
+impl Child {
+    fn as_hit(&self) -> &dyn Hit {
+        match self {
+            Child::Node(node) => node.as_ref(),
+            Child::Object(object) => object.as_ref(),
+        }
+    }
+
+    /// Like [`Hit::hit`], but the bounding box of the child is known to be hit.
+    fn hit_inside(&self, ray: &Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        match self {
+            Child::Node(node) => {
+                NODE_VISITS.with(|visits| visits.set(visits.get() + 1));
+                node.hit_children(ray, t_range)
+            }
+            Child::Object(object) => object.hit(ray.clone(), t_range),
+        }
+    }
+
+    /// Like [`Hit::hit_any`], but the bounding box of the child is known to be hit.
+    fn hit_any_inside(&self, ray: &Ray, t_range: Interval) -> bool {
+        match self {
+            Child::Node(node) => {
+                NODE_VISITS.with(|visits| visits.set(visits.get() + 1));
+                node.hit_any_children(ray, t_range)
+            }
+            Child::Object(object) => object.hit_any(ray, t_range),
+        }
+    }
+}
+
+thread_local! {
+    /// Number of BVH nodes visited by the current thread since the last reset.
+    static NODE_VISITS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Why a [`BVH`] cannot be built from a list of objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BvhError {
+    /// The list of objects is empty
+    Empty,
+    /// The object at `index` has no bounding box
+    MissingBoundingBox { index: usize },
+    /// The bounding box of the object at `index` has a NaN component
+    NaNBounds { index: usize },
+}
 
-        lhs.partial_cmp(&rhs).expect("NaN in BVHNode constructor")
-    })
+impl fmt::Display for BvhError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BvhError::Empty => write!(f, "no objects to build a BVH from"),
+            BvhError::MissingBoundingBox { index } => {
+                write!(f, "object {} has no bounding box", index)
+            }
+            BvhError::NaNBounds { index } => {
+                write!(f, "bounding box of object {} has a NaN component", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BvhError {}
+
+/// How the objects of a [`BVH`] node are split between its two children.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BvhSplit {
+    /// Sort along a random axis, and split at the median
+    #[default]
+    Median,
+    /// Split where the surface area heuristic (SAH) estimates the cheapest
+    /// traversal, found by binning the centers of the bounding boxes.
+    ///
+    /// The median split is used when all the centers are at the same point.
+    Sah,
+}
+
+/// Number of bins per axis for [`BvhSplit::Sah`].
+const SAH_BINS: usize = 12;
+
+/// Objects are checked by [`BVH::try_new`] before they are sorted, so every
+/// bounding box exists and compares.
+fn sort_objects_by_axis(objects: &mut [Box<dyn Hit>], axis: usize, options: &BuildOptions) {
+    let key = |object: &dyn Hit| {
+        object
+            .bounding_box(options.time_range.clone())
+            .expect("bounding box checked by BVH::try_new")
+            .min()[axis]
+    };
+    let compare = |lhs: &dyn Hit, rhs: &dyn Hit| {
+        key(lhs)
+            .partial_cmp(&key(rhs))
+            .expect("NaN checked by BVH::try_new")
+    };
+
+    if options.parallel && objects.len() >= PARALLEL_BUILD_THRESHOLD {
+        objects.par_sort_unstable_by(|lhs, rhs| compare(lhs.as_ref(), rhs.as_ref()));
+    } else {
+        objects.sort_unstable_by(|lhs, rhs| compare(lhs.as_ref(), rhs.as_ref()));
+    }
+}
+
+/// Check that `objects` is not empty, and every object has a bounding box
+/// without NaN over `time_range`.
+pub(crate) fn check_objects(
+    objects: &[Box<dyn Hit>],
+    time_range: &Range<f64>,
+) -> Result<(), BvhError> {
+    if objects.is_empty() {
+        return Err(BvhError::Empty);
+    }
+    for (index, object) in objects.iter().enumerate() {
+        let bounding_box = object
+            .bounding_box(time_range.clone())
+            .ok_or(BvhError::MissingBoundingBox { index })?;
+        let (min, max) = (bounding_box.min(), bounding_box.max());
+        if (0..3).any(|axis| min[axis].is_nan() || max[axis].is_nan()) {
+            return Err(BvhError::NaNBounds { index });
+        }
+    }
+    Ok(())
+}
+
+/// Nodes with at least this many objects build their children in parallel.
+const PARALLEL_BUILD_THRESHOLD: usize = 256;
+
+/// What stays the same while a [`BVH`] is built.
+struct BuildOptions {
+    time_range: Range<f64>,
+    split: BvhSplit,
+    /// Whether large nodes are built with rayon
+    parallel: bool,
+}
+
+/// Split `objects` into two non-empty halves with the surface area heuristic,
+/// or give them back if their centers can not be told apart.
+///
+/// The centers are put into [`SAH_BINS`] bins along each axis, and the cost of
+/// splitting between two bins is estimated as the surface area of each half
+/// times its number of objects.
+#[allow(clippy::type_complexity)]
+fn sah_split(
+    objects: Vec<Box<dyn Hit>>,
+    time_range: &Range<f64>,
+) -> Result<(Vec<Box<dyn Hit>>, Vec<Box<dyn Hit>>), Vec<Box<dyn Hit>>> {
+    let boxes: Vec<_> = objects
+        .iter()
+        .map(|object| {
+            object
+                .bounding_box(time_range.clone())
+                .expect("bounding box checked by BVH::try_new")
+        })
+        .collect();
+    let centers = boxes
+        .iter()
+        .fold(AABB::EMPTY, |centers, aabb| centers.include(&aabb.center()));
+    let extent = centers.diagonal();
+    let bin = |aabb: &AABB, axis: usize| {
+        let ratio = (aabb.center()[axis] - centers.min[axis]) / extent[axis];
+        ((ratio * SAH_BINS as f64) as usize).min(SAH_BINS - 1)
+    };
+
+    // cheapest (cost, axis, first bin of the right half)
+    let mut best: Option<(f64, usize, usize)> = None;
+    for axis in (0..3).filter(|&axis| extent[axis] > 0.0) {
+        let mut bins = vec![(0, AABB::EMPTY); SAH_BINS];
+        for aabb in &boxes {
+            let (count, bounds) = &mut bins[bin(aabb, axis)];
+            *count += 1;
+            *bounds = bounds.merge(aabb);
+        }
+
+        // count and area of the bins from each index to the end
+        let mut right = [(0, 0.0); SAH_BINS];
+        let (mut count, mut bounds) = (0, AABB::EMPTY);
+        for index in (1..SAH_BINS).rev() {
+            count += bins[index].0;
+            bounds = bounds.merge(&bins[index].1);
+            right[index] = (count, bounds.surface_area());
+        }
+
+        let (mut count, mut bounds) = (0, AABB::EMPTY);
+        for index in 1..SAH_BINS {
+            count += bins[index - 1].0;
+            bounds = bounds.merge(&bins[index - 1].1);
+            let (right_count, right_area) = right[index];
+            if count == 0 || right_count == 0 {
+                continue;
+            }
+            let cost = count as f64 * bounds.surface_area() + right_count as f64 * right_area;
+            if best.is_none_or(|(best, _, _)| cost < best) {
+                best = Some((cost, axis, index));
+            }
+        }
+    }
+
+    let Some((_, axis, split)) = best else {
+        return Err(objects);
+    };
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    for (object, aabb) in objects.into_iter().zip(&boxes) {
+        if bin(aabb, axis) < split {
+            left.push(object);
+        } else {
+            right.push(object);
+        }
+    }
+    Ok((left, right))
 }
 
 impl BVH {
@@ -38,95 +245,254 @@ impl BVH {
     /// # Arguments
     ///
     /// * `objects` - List of objects
-    /// * `time_from` - Start time of the animation
-    /// * `time_to` - End time of the animation
+    /// * `time_range` - Time range of the animation
     ///
     /// # Panics
     ///
     /// * If the list of objects is empty
     /// * If any object does not have a bounding box
     /// * If any bounding box has a NaN component
-    pub fn new(mut objects: Vec<Box<dyn Hit>>, time_range: Range<f64>) -> Self {
-        let time_from = time_range.start;
-        let time_to = time_range.end;
+    ///
+    /// See [`BVH::try_new`] for a version that returns these as errors.
+    pub fn new(objects: Vec<Box<dyn Hit>>, time_range: Range<f64>) -> Self {
+        Self::try_new(objects, time_range).expect("invalid objects in BVH constructor")
+    }
+
+    /// Like [`BVH::new`], but split by the surface area heuristic, which
+    /// takes longer to build and is faster to hit, see [`BvhSplit::Sah`].
+    pub fn new_sah(objects: Vec<Box<dyn Hit>>, time_range: Range<f64>) -> Self {
+        Self::try_new_with(objects, time_range, BvhSplit::Sah)
+            .expect("invalid objects in BVH constructor")
+    }
+
+    /// Create a new BVH tree from a list of objects, or tell why it cannot be
+    /// built. The objects are all checked before the tree is built.
+    pub fn try_new(objects: Vec<Box<dyn Hit>>, time_range: Range<f64>) -> Result<Self, BvhError> {
+        Self::try_new_with(objects, time_range, BvhSplit::default())
+    }
+
+    /// Like [`BVH::try_new`], with the objects split by `split`.
+    pub fn try_new_with(
+        objects: Vec<Box<dyn Hit>>,
+        time_range: Range<f64>,
+        split: BvhSplit,
+    ) -> Result<Self, BvhError> {
+        check_objects(&objects, &time_range)?;
+
+        let options = BuildOptions {
+            time_range,
+            split,
+            parallel: true,
+        };
+        Ok(Self::build(objects, &options, random::random()))
+    }
 
+    /// Build the tree from a non-empty list of objects checked by [`BVH::try_new`].
+    ///
+    /// The random axes of the median split come from `seed`, so the same seed
+    /// builds the same tree, in parallel or not.
+    fn build(mut objects: Vec<Box<dyn Hit>>, options: &BuildOptions, seed: u64) -> Self {
+        let time_range = &options.time_range;
+        let mut rng = StdRng::seed_from_u64(seed);
         match objects.len() {
-            0 => panic!("No objects in BVHNode constructor"),
-            1 => Self {
-                bounding_box: objects[0]
-                    .bounding_box(time_from, time_to)
-                    .expect("No bounding box in BVHNode constructor"),
-                left: Some(objects.remove(0)),
-                right: None,
-            },
+            0 => unreachable!("objects checked by BVH::try_new"),
+            1 => {
+                let bounding_box = objects[0]
+                    .bounding_box(time_range.clone())
+                    .expect("bounding box checked by BVH::try_new");
+
+                Self {
+                    child_boxes: AABBPair::new(Some(&bounding_box), None),
+                    bounding_box,
+                    left: Some(Child::Object(objects.remove(0))),
+                    right: None,
+                }
+            }
             2 => {
-                let axis = rand::thread_rng().gen_range(0..3);
-                sort_objects_by_axis(&mut objects, axis, time_from, time_to);
+                let axis = rng.gen_range(0..3);
+                sort_objects_by_axis(&mut objects, axis, options);
 
                 let left = objects.remove(0);
                 let right = objects.remove(0);
                 let left_bounding_box = left
-                    .bounding_box(time_from, time_to)
-                    .expect("No bounding box in BVHNode constructor");
+                    .bounding_box(time_range.clone())
+                    .expect("bounding box checked by BVH::try_new");
                 let right_bounding_box = right
-                    .bounding_box(time_from, time_to)
-                    .expect("No bounding box in BVHNode constructor");
+                    .bounding_box(time_range.clone())
+                    .expect("bounding box checked by BVH::try_new");
                 let bounding_box = left_bounding_box.merge(&right_bounding_box);
 
                 Self {
                     bounding_box,
-                    left: Some(left),
-                    right: Some(right),
+                    child_boxes: AABBPair::new(Some(&left_bounding_box), Some(&right_bounding_box)),
+                    left: Some(Child::Object(left)),
+                    right: Some(Child::Object(right)),
                 }
             }
             len => {
-                let axis = rand::thread_rng().gen_range(0..3);
-                sort_objects_by_axis(&mut objects, axis, time_from, time_to);
-
-                // right comes first because we want to split the list in half
-                let right = objects.split_off(len / 2);
-                let left = objects;
-                let left = Box::new(Self::new(left, time_range.clone()));
-                let right = Box::new(Self::new(right, time_range));
+                let (axis, left_seed, right_seed) = (rng.gen_range(0..3), rng.gen(), rng.gen());
+                let halves = match options.split {
+                    BvhSplit::Median => Err(objects),
+                    BvhSplit::Sah => sah_split(objects, time_range),
+                };
+                let (left, right) = match halves {
+                    Ok(halves) => halves,
+                    Err(mut objects) => {
+                        sort_objects_by_axis(&mut objects, axis, options);
+
+                        // right comes first because we want to split the list in half
+                        let right = objects.split_off(len / 2);
+                        (objects, right)
+                    }
+                };
+                let build_left = || Box::new(Self::build(left, options, left_seed));
+                let build_right = || Box::new(Self::build(right, options, right_seed));
+                let (left, right) = if options.parallel && len >= PARALLEL_BUILD_THRESHOLD {
+                    rayon::join(build_left, build_right)
+                } else {
+                    (build_left(), build_right())
+                };
                 let bounding_box = left.bounding_box.merge(&right.bounding_box);
 
                 Self {
                     bounding_box,
-                    left: Some(left),
-                    right: Some(right),
+                    child_boxes: AABBPair::new(Some(&left.bounding_box), Some(&right.bounding_box)),
+                    left: Some(Child::Node(left)),
+                    right: Some(Child::Node(right)),
                 }
             }
         }
     }
-}
 
-impl Hit for BVH {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        if !self.bounding_box.is_hit(&ray, t_min, t_max) {
-            return None;
+    /// Call `callback` with the bounding box and depth of every node up to
+    /// `max_depth`, in depth-first order. The root node has depth 0.
+    pub fn visit_boxes<F: FnMut(&AABB, usize)>(&self, max_depth: usize, mut callback: F) {
+        self.visit_boxes_from(0, max_depth, &mut callback);
+    }
+
+    fn visit_boxes_from(
+        &self,
+        depth: usize,
+        max_depth: usize,
+        callback: &mut dyn FnMut(&AABB, usize),
+    ) {
+        if depth > max_depth {
+            return;
         }
 
-        let mut t_max = t_max;
+        callback(&self.bounding_box, depth);
+        for child in [&self.left, &self.right].into_iter().flatten() {
+            if let Child::Node(node) = child {
+                node.visit_boxes_from(depth + 1, max_depth, callback);
+            }
+        }
+    }
+}
+
+impl BVH {
+    /// Reset the node visit counter of the current thread.
+    ///
+    /// The node [`BVH::hit`] is called on counts as one visit, even if its
+    /// bounding box is missed, and so does every node below it whose bounding
+    /// box is hit. The counter is thread-local, so it
+    /// can be reset before tracing a single ray and read back afterwards.
+    pub fn reset_visit_count() {
+        NODE_VISITS.with(|visits| visits.set(0));
+    }
 
-        let left = self
-            .left
-            .as_ref()
-            .and_then(|left| left.hit(ray.clone(), t_min, t_max));
+    /// Number of nodes visited by the current thread since the last reset.
+    pub fn visit_count() -> u64 {
+        NODE_VISITS.with(|visits| visits.get())
+    }
+}
+
+impl BVH {
+    /// Test both children of a node whose bounding box is hit.
+    fn hit_children(&self, ray: &Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let [hit_left, hit_right] = self.child_boxes.hit_mask(ray, t_range);
+        let mut t_range = t_range;
+
+        let left = match &self.left {
+            Some(left) if hit_left => left.hit_inside(ray, t_range),
+            _ => None,
+        };
         if let Some(left) = &left {
-            t_max = t_max.min(left.t);
+            t_range = t_range.with_max(left.t);
         }
 
-        let right = self
-            .right
-            .as_ref()
-            .and_then(|right| right.hit(ray, t_min, t_max));
+        let right = match &self.right {
+            Some(right) if hit_right => right.hit_inside(ray, t_range),
+            _ => None,
+        };
 
         right.or(left)
     }
 
-    fn bounding_box(&self, _: f64, _: f64) -> Option<AABB> {
+    /// Test both children of a node whose bounding box is hit, stopping at the first hit.
+    fn hit_any_children(&self, ray: &Ray, t_range: Interval) -> bool {
+        let [hit_left, hit_right] = self.child_boxes.hit_mask(ray, t_range);
+        let hit_child = |child: &Option<Child>, is_hit: bool| {
+            is_hit
+                && child
+                    .as_ref()
+                    .is_some_and(|child| child.hit_any_inside(ray, t_range))
+        };
+
+        hit_child(&self.left, hit_left) || hit_child(&self.right, hit_right)
+    }
+}
+
+impl Hit for BVH {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        NODE_VISITS.with(|visits| visits.set(visits.get() + 1));
+
+        if !self.bounding_box.is_hit(&ray, t_range) {
+            return None;
+        }
+
+        self.hit_children(&ray, t_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        NODE_VISITS.with(|visits| visits.set(visits.get() + 1));
+
+        self.bounding_box.is_hit(ray, t_range) && self.hit_any_children(ray, t_range)
+    }
+
+    fn bounding_box(&self, _: Range<f64>) -> Option<AABB> {
         Some(self.bounding_box.clone())
     }
+
+    /// Average of the densities of the children, since each child is picked
+    /// as often.
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let children = [&self.left, &self.right].into_iter().flatten();
+        let (sum, count) = children.fold((0.0, 0), |(sum, count), child| {
+            let value = child.as_hit().pdf_value(origin, direction, time);
+            (sum + value, count + 1)
+        });
+        if count == 0 {
+            0.0
+        } else {
+            sum / count as f64
+        }
+    }
+
+    /// Direction towards one of the children, picked uniformly.
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        let child = match (&self.left, &self.right) {
+            (Some(left), Some(right)) => {
+                if random::random::<f64>() < 0.5 {
+                    left
+                } else {
+                    right
+                }
+            }
+            (Some(child), None) | (None, Some(child)) => child,
+            (None, None) => return None,
+        };
+        child.as_hit().random_point_towards(origin, time)
+    }
 }
 
 #[cfg(test)]
@@ -134,7 +500,12 @@ mod tests {
     use std::sync::Arc;
 
     use super::*;
-    use crate::{material::{Lambertian, Dielectric}, Point3, Sphere, texture::SolidColor};
+    use crate::{
+        material::{Dielectric, Lambertian},
+        object::Block,
+        texture::SolidColor,
+        HitExt, Point3, Sphere, Vec3,
+    };
 
     #[test]
     fn test_bvh_create() -> Result<(), Box<dyn std::error::Error>> {
@@ -159,4 +530,139 @@ mod tests {
         let _ = BVH::new(objects, 0.0..1.0);
         Ok(())
     }
+
+    /// Object with a fixed, possibly missing, bounding box.
+    #[derive(Debug)]
+    struct Bounded(Option<AABB>);
+
+    impl Hit for Bounded {
+        fn hit(&self, _ray: Ray, _t_range: Interval) -> Option<OutwardHitRecord> {
+            None
+        }
+
+        fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn try_new_reports_invalid_objects() {
+        let unit = || AABB::new(Point3::zeros(), Point3::ones());
+        let bounded = |aabb: Option<AABB>| -> Box<dyn Hit> { Box::new(Bounded(aabb)) };
+
+        let objects = vec![bounded(Some(unit())), bounded(Some(unit()))];
+        let bvh = BVH::try_new(objects, 0.0..1.0).unwrap();
+        assert_eq!(bvh.bounding_box(0.0..1.0).unwrap().max(), Point3::ones());
+
+        assert_eq!(
+            BVH::try_new(Vec::new(), 0.0..1.0).unwrap_err(),
+            BvhError::Empty
+        );
+
+        let objects = vec![bounded(Some(unit())), bounded(None), bounded(Some(unit()))];
+        assert_eq!(
+            BVH::try_new(objects, 0.0..1.0).unwrap_err(),
+            BvhError::MissingBoundingBox { index: 1 }
+        );
+
+        let nan = AABB {
+            min: Point3::new(0.0, f64::NAN, 0.0),
+            max: Point3::ones(),
+        };
+        let objects = vec![
+            bounded(Some(unit())),
+            bounded(Some(unit())),
+            bounded(Some(nan)),
+        ];
+        assert_eq!(
+            BVH::try_new(objects, 0.0..1.0).unwrap_err(),
+            BvhError::NaNBounds { index: 2 }
+        );
+    }
+
+    /// Ground blocks and sphere cluster of the final scene.
+    fn final_scene_objects() -> Vec<Box<dyn Hit>> {
+        let mut rng = StdRng::seed_from_u64(17);
+        let material = Arc::new(Lambertian::new(SolidColor::new_rgb(0.5, 0.5, 0.5)));
+        let mut objects: Vec<Box<dyn Hit>> = Vec::new();
+        for i in 0..20 {
+            for j in 0..20 {
+                let min = Point3::new(-1000.0 + i as f64 * 100.0, 0.0, -1000.0 + j as f64 * 100.0);
+                let max = min + Vec3::new(100.0, rng.gen_range(1.0..101.0), 100.0);
+                objects.push(Block::new(min, max, material.clone()).boxed());
+            }
+        }
+        for _ in 0..1000 {
+            let center = Point3::new(
+                rng.gen_range(-100.0..65.0),
+                rng.gen_range(270.0..435.0),
+                rng.gen_range(395.0..560.0),
+            );
+            objects.push(Sphere::new(center, 10.0, material.clone()).boxed());
+        }
+        objects
+    }
+
+    #[test]
+    fn sah_visits_fewer_nodes() {
+        let median = BVH::new(final_scene_objects(), 0.0..1.0);
+        let sah = BVH::new_sah(final_scene_objects(), 0.0..1.0);
+
+        let mut rng = StdRng::seed_from_u64(18);
+        let origin = Point3::new(478.0, 278.0, -600.0);
+        let (mut median_visits, mut sah_visits) = (0, 0);
+        for _ in 0..2000 {
+            let target = Point3::new(
+                rng.gen_range(-1000.0..1000.0),
+                rng.gen_range(0.0..600.0),
+                rng.gen_range(-1000.0..1000.0),
+            );
+            let ray = Ray::new(origin, target - origin, 0.0);
+
+            BVH::reset_visit_count();
+            let expected = median.hit(ray.clone(), Interval::from_min(1e-3));
+            median_visits += BVH::visit_count();
+            BVH::reset_visit_count();
+            let actual = sah.hit(ray, Interval::from_min(1e-3));
+            sah_visits += BVH::visit_count();
+
+            assert_eq!(actual.map(|hit| hit.t), expected.map(|hit| hit.t));
+        }
+        assert!(
+            sah_visits < median_visits,
+            "median: {median_visits} visits, SAH: {sah_visits} visits"
+        );
+    }
+
+    #[test]
+    fn parallel_build_matches_sequential() {
+        let build = |split, parallel| {
+            let options = BuildOptions {
+                time_range: 0.0..1.0,
+                split,
+                parallel,
+            };
+            BVH::build(final_scene_objects(), &options, 21)
+        };
+
+        for split in [BvhSplit::Median, BvhSplit::Sah] {
+            let (parallel, sequential) = (build(split, true), build(split, false));
+            let (lhs, rhs) = (&parallel.bounding_box, &sequential.bounding_box);
+            assert_eq!((lhs.min, lhs.max), (rhs.min, rhs.max));
+
+            let mut rng = StdRng::seed_from_u64(22);
+            let origin = Point3::new(478.0, 278.0, -600.0);
+            for _ in 0..500 {
+                let target = Point3::new(
+                    rng.gen_range(-1000.0..1000.0),
+                    rng.gen_range(0.0..600.0),
+                    rng.gen_range(-1000.0..1000.0),
+                );
+                let ray = Ray::new(origin, target - origin, 0.0);
+                let expected = sequential.hit(ray.clone(), Interval::from_min(1e-3));
+                let actual = parallel.hit(ray, Interval::from_min(1e-3));
+                assert_eq!(actual.map(|hit| hit.t), expected.map(|hit| hit.t));
+            }
+        }
+    }
 }
@@ -1,9 +1,13 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 use log::debug;
 
 use crate::{
-    hit::OutwardHitRecord, material::Isotropic, texture::{Texture, SolidColor}, Hit, Vec3, Color,
+    hit::OutwardHitRecord,
+    material::Isotropic,
+    random,
+    texture::{SolidColor, Texture},
+    Color, Hit, Interval, Ray, Vec3,
 };
 
 /// A volume of constant density.
@@ -20,6 +24,11 @@ use crate::{
 /// In this material, the place where the ray scatters is sampled by
 /// `t = -density * ln(random) + t_min`, where `random` is a random number
 /// in the range `[0, 1)`.
+///
+/// The boundary does not have to be convex. Its crossings along the ray are
+/// taken as entry and exit pairs, and the ray only travels through the medium
+/// between an entry and the following exit. A ray may also start inside the
+/// medium, such as from a camera in fog.
 #[derive(Debug, Clone)]
 pub struct ConstantMedium<H: Hit, T: Texture> {
     /// Object to be filled with the medium.
@@ -55,44 +64,32 @@ impl<H: Hit> ConstantMedium<H, SolidColor> {
 }
 
 impl<H: Hit, T: Texture + 'static> Hit for ConstantMedium<H, T> {
-    fn hit(&self, ray: crate::Ray, t_min: f64, t_max: f64) -> Option<super::OutwardHitRecord> {
-        // ray doesn't hit the boundary -> ray does not hit the medium.
-        // ray does hit the boundary    -> hit point is the first point
-        // NOTE: we should not limit the range of t, because the ray origin
-        // may be inside the medium.
-        let hit_record = ray.clone().hit(&self.boundary, f64::NEG_INFINITY, f64::INFINITY)?;
-
-        // update t_min
-        let t_min = t_min.max(hit_record.t);
-
-        // ray doesn't hit the other side -> does not hit the medium.
-        // ray does hit the other side    -> hit point is the second point
-        // skip a small amount of distance to avoid self-intersection or a tiny plane.
-        let hit_record = ray.clone().hit(&self.boundary, t_min + 1e-5, t_max)?;
-
-        // update t_max
-        let t_max = t_max.min(hit_record.t);
-
-        // if t_min >= t_max, the ray will not hit the medium, because it travels
-        // in the opposite direction.
-        if t_min >= t_max {
-            return None;
-        };
-
-        // t_min should be positive?
-        assert!(t_min >= -f64::EPSILON);
-
-        // find the distance the ray travels through the medium
+    fn hit(&self, ray: crate::Ray, t_range: Interval) -> Option<super::OutwardHitRecord> {
+        // The distance to scatter is sampled once and used up segment by
+        // segment, as if the segments were joined together; since the distance
+        // is exponential, this is the same as testing each segment in turn.
         let ray_length = ray.direction().norm();
-        let distance_traveled = (t_max - t_min) * ray_length;
-        // generate random distance the ray should scatter
-        let distance_to_scatter = self.negative_reciprocal_density * rand::random::<f64>().ln();
-        // if distance to scatter is greater than the distance traveled,
-        // the ray will not scatter.
-        debug!("       distance traveled {} to scatter {}", distance_to_scatter, distance_traveled);
-        if distance_to_scatter > distance_traveled {
-            return None;
-        };
+        let mut distance_to_scatter = None;
+        let (t_min, distance_to_scatter) = segments_inside(&self.boundary, &ray, t_range)
+            .find_map(|(t_min, t_max)| {
+                // find the distance the ray travels through this segment
+                let distance_traveled = (t_max - t_min) * ray_length;
+                // generate random distance the ray should scatter
+                let distance = *distance_to_scatter.get_or_insert_with(|| {
+                    self.negative_reciprocal_density * random::random::<f64>().ln()
+                });
+                debug!(
+                    "       distance traveled {} to scatter {}",
+                    distance_traveled, distance
+                );
+                // if distance to scatter is greater than the distance traveled,
+                // the ray will not scatter in this segment.
+                if distance <= distance_traveled {
+                    return Some((t_min, distance));
+                }
+                distance_to_scatter = Some(distance - distance_traveled);
+                None
+            })?;
 
         // find the point where our ray really scatters
         let t = t_min + distance_to_scatter / ray_length;
@@ -114,7 +111,112 @@ impl<H: Hit, T: Texture + 'static> Hit for ConstantMedium<H, T> {
         ))
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<super::AABB> {
-        self.boundary.bounding_box(time_from, time_to)
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<super::AABB> {
+        self.boundary.bounding_box(time_range)
+    }
+}
+
+/// Parameters of the successive crossings of `ray` with `boundary`, in order
+/// along the whole line of the ray, including behind its origin.
+fn crossings<'a, H: Hit>(boundary: &'a H, ray: &'a Ray) -> impl Iterator<Item = f64> + 'a {
+    // NOTE: we should not limit the range of t when searching for a crossing,
+    // because the ray origin may be inside the medium.
+    let mut search_from = f64::NEG_INFINITY;
+    std::iter::from_fn(move || {
+        let crossing = ray.clone().hit(boundary, Interval::from_min(search_from))?;
+        // skip a small amount of distance to avoid self-intersection or a tiny plane.
+        search_from = crossing.t + 1e-5;
+        Some(crossing.t)
+    })
+}
+
+/// Parts of `ray` inside `boundary` and `t_range`, as pairs of the parameters
+/// where they start and end, in order along the ray.
+///
+/// The boundary crossings along the ray pair up into entry and exit points,
+/// so a non-convex boundary gives several segments inside the medium, and a
+/// ray starting inside the medium starts in the middle of a segment. Segments
+/// are clamped to `t_range`, and those outside it are skipped. An entry
+/// without an exit, as for a boundary that is not closed, is not inside.
+pub(super) fn segments_inside<'a, H: Hit>(
+    boundary: &'a H,
+    ray: &'a Ray,
+    t_range: Interval,
+) -> impl Iterator<Item = (f64, f64)> + 'a {
+    let mut crossings = crossings(boundary, ray);
+    std::iter::from_fn(move || loop {
+        let entry = crossings.next()?;
+        if entry >= t_range.max {
+            return None;
+        }
+        let exit = crossings.next()?;
+
+        // the part of the segment inside t_range, which may be empty
+        let t_min = t_range.min.max(entry);
+        let t_max = t_range.max.min(exit);
+        if t_min < t_max {
+            return Some((t_min, t_max));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{material::Lambertian, object::Block, Point3, Sphere, World};
+
+    #[test]
+    fn gaps_in_the_boundary_are_empty() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let mut boundary = World::new();
+        boundary.add(Block::new(
+            Point3::zeros(),
+            Point3::ones(),
+            material.clone(),
+        ));
+        boundary.add(Block::new(
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(3.0, 1.0, 1.0),
+            material,
+        ));
+        let medium = ConstantMedium::new_solid(boundary, Color::constant(0.5), 0.7);
+
+        random::seed(4);
+        for origin_x in [-1.0, 0.5, 1.5] {
+            let ray = Ray::new(Point3::new(origin_x, 0.5, 0.5), Vec3::unit_x(), 0.0);
+            let scattered: Vec<_> = (0..1000)
+                .filter_map(|_| medium.hit(ray.clone(), Interval::from_min(1e-3)))
+                .map(|hit| hit.point.x())
+                .collect();
+
+            assert!(scattered.iter().all(|x| !(1.0..2.0).contains(x)));
+            assert!(scattered.iter().any(|x| (2.0..=3.0).contains(x)));
+        }
+    }
+
+    #[test]
+    fn ray_starting_inside_scatters_ahead() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let boundary = Sphere::new(Point3::zeros(), 2.0, material);
+        let medium = ConstantMedium::new_solid(boundary, Color::constant(0.5), 0.5);
+
+        random::seed(5);
+        let ray = Ray::new(Point3::zeros(), Vec3::new(0.0, 0.0, 2.0), 0.0);
+        let ts: Vec<_> = (0..2000)
+            .filter_map(|_| medium.hit(ray.clone(), Interval::from_min(0.0)))
+            .map(|hit| hit.t)
+            .collect();
+        assert!(ts.iter().all(|t| (0.0..=1.0).contains(t)));
+        // the ray goes through a radius of 2 in the medium
+        let expected = 1.0 - (-0.5 * 2.0f64).exp();
+        assert!((ts.len() as f64 / 2000.0 - expected).abs() < 0.03);
+        // and no further than t_range allows
+        let ts: Vec<_> = (0..200)
+            .filter_map(|_| medium.hit(ray.clone(), Interval::new(0.25, 0.5)))
+            .map(|hit| hit.t)
+            .collect();
+        assert!(!ts.is_empty() && ts.iter().all(|t| (0.25..=0.5).contains(t)));
     }
 }
@@ -0,0 +1,83 @@
+use std::ops::Range;
+
+use crate::{Hit, Interval, Point3, Ray, Vec3};
+
+use super::{OutwardHitRecord, AABB};
+
+/// Instance with the outward normal reversed, so that the front and the back
+/// of the object are swapped.
+///
+/// The emitted color is computed again for the reversed normal, so that it
+/// matches what the material sees from the flipped side.
+#[derive(Debug, Clone)]
+pub struct FlipFace<H: Hit> {
+    object: H,
+}
+
+impl<H: Hit> FlipFace<H> {
+    pub fn new(object: H) -> Self {
+        Self { object }
+    }
+}
+
+impl<H: Hit> Hit for FlipFace<H> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let hit = self.object.hit(ray.clone(), t_range)?;
+        Some(OutwardHitRecord {
+            tangents: hit.tangents,
+            ..OutwardHitRecord::new(
+                hit.point,
+                &ray,
+                -hit.normal_outward,
+                hit.t,
+                hit.material,
+                (hit.u, hit.v),
+            )
+        })
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.object.bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.object.hit_any(ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.object.pdf_value(origin, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.object.random_point_towards(origin, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::Lambertian, object::rectangle::AxisAlignedRectangle, Color, HitExt, Point3, Vec3,
+    };
+
+    #[test]
+    fn flipped_rectangle_faces_down() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let ceiling = AxisAlignedRectangle::new_xz((0.0, 0.0), (1.0, 1.0), 1.0, material);
+        let down = Ray::new(Point3::new(0.5, 2.0, 0.5), -Vec3::unit_y(), 0.0);
+        let up = Ray::new(Point3::new(0.5, 0.0, 0.5), Vec3::unit_y(), 0.0);
+        assert!(ceiling
+            .hit(down.clone(), Interval::from_min(0.0))
+            .unwrap()
+            .is_front());
+
+        let flipped = ceiling.flip_faces();
+        let back = flipped.hit(down, Interval::from_min(0.0)).unwrap();
+        assert!(!back.is_front());
+        assert_eq!(back.normal_outward, -Vec3::unit_y());
+        assert_eq!(back.t, 1.0);
+        assert!(flipped.hit(up, Interval::from_min(0.0)).unwrap().is_front());
+    }
+}
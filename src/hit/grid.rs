@@ -0,0 +1,395 @@
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    hit::{check_objects, BvhError, OutwardHitRecord, AABB},
+    Hit, Interval, Ray, Vec3,
+};
+
+/// Largest number of cells along one axis when the resolution is chosen automatically.
+const MAX_RESOLUTION: usize = 64;
+
+/// Source of unique ids for mailboxing, one per query.
+static NEXT_RAY_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Uniform grid over a list of objects.
+///
+/// The bounds of the scene are divided into equally sized cells, and every cell
+/// stores the objects whose bounding box overlaps it. Rays walk the cells they
+/// pass through in order (3D-DDA), so only nearby objects are tested. This works
+/// well for dense, evenly distributed scenes, like a field of blocks.
+///
+/// An object overlapping several cells is tested at most once per ray: each
+/// object has a mailbox holding the id of the last query that tested it.
+///
+/// Objects without a bounding box are kept in a separate list that is tested
+/// against every ray.
+#[derive(Debug)]
+pub struct UniformGrid {
+    /// Objects with a bounding box
+    objects: Vec<Box<dyn Hit>>,
+    /// Objects without a bounding box
+    unbounded: Vec<Box<dyn Hit>>,
+    /// Id of the last query that tested each object
+    mailboxes: Vec<AtomicU64>,
+    /// Bounds of the grid
+    bounds: Option<AABB>,
+    /// Number of cells along each axis
+    resolution: [usize; 3],
+    /// Objects of cell `i` are `cell_objects[cell_start[i]..cell_start[i + 1]]`
+    cell_start: Vec<usize>,
+    cell_objects: Vec<usize>,
+}
+
+impl UniformGrid {
+    /// Create a new grid from a list of objects, choosing the resolution from
+    /// the number of objects so that there are a few cells per object.
+    ///
+    /// # Arguments
+    ///
+    /// * `objects` - List of objects
+    /// * `time_range` - Time range for the bounding boxes of moving objects
+    pub fn new(objects: Vec<Box<dyn Hit>>, time_range: Range<f64>) -> Self {
+        Self::build(objects, time_range, None)
+    }
+
+    /// Create a new grid with `resolution` cells along each axis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any component of `resolution` is zero.
+    pub fn with_resolution(
+        objects: Vec<Box<dyn Hit>>,
+        time_range: Range<f64>,
+        resolution: [usize; 3],
+    ) -> Self {
+        assert!(resolution.iter().all(|&n| n > 0));
+        Self::build(objects, time_range, Some(resolution))
+    }
+
+    /// Create a new grid like [`UniformGrid::new`] or, with a `resolution`,
+    /// [`UniformGrid::with_resolution`], but reject the objects with the same
+    /// checks as [`super::BVH::try_new`] instead of keeping unbounded ones aside.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any component of `resolution` is zero.
+    pub fn try_new(
+        objects: Vec<Box<dyn Hit>>,
+        time_range: Range<f64>,
+        resolution: Option<[usize; 3]>,
+    ) -> Result<Self, BvhError> {
+        check_objects(&objects, &time_range)?;
+        Ok(match resolution {
+            Some(resolution) => Self::with_resolution(objects, time_range, resolution),
+            None => Self::new(objects, time_range),
+        })
+    }
+
+    /// Number of cells along each axis.
+    pub fn resolution(&self) -> [usize; 3] {
+        self.resolution
+    }
+
+    fn build(
+        objects: Vec<Box<dyn Hit>>,
+        time_range: Range<f64>,
+        resolution: Option<[usize; 3]>,
+    ) -> Self {
+        let mut bounded = Vec::new();
+        let mut boxes = Vec::new();
+        let mut unbounded = Vec::new();
+        for object in objects {
+            match object.bounding_box(time_range.clone()) {
+                Some(aabb) => {
+                    boxes.push(aabb);
+                    bounded.push(object);
+                }
+                None => unbounded.push(object),
+            }
+        }
+
+        let bounds = boxes.iter().cloned().reduce(|lhs, rhs| lhs.merge(&rhs));
+        let resolution = match (&bounds, resolution) {
+            (_, Some(resolution)) => resolution,
+            (Some(bounds), None) => Self::choose_resolution(bounds, boxes.len()),
+            (None, None) => [1, 1, 1],
+        };
+
+        let mut grid = Self {
+            mailboxes: bounded.iter().map(|_| AtomicU64::new(u64::MAX)).collect(),
+            objects: bounded,
+            unbounded,
+            bounds,
+            resolution,
+            cell_start: Vec::new(),
+            cell_objects: Vec::new(),
+        };
+
+        let mut cells = vec![Vec::new(); resolution.iter().product()];
+        if grid.bounds.is_some() {
+            for (object, aabb) in boxes.iter().enumerate() {
+                let min = grid.cell_of(&aabb.min());
+                let max = grid.cell_of(&aabb.max());
+                for z in min[2]..=max[2] {
+                    for y in min[1]..=max[1] {
+                        for x in min[0]..=max[0] {
+                            cells[grid.cell_index([x, y, z])].push(object);
+                        }
+                    }
+                }
+            }
+        }
+
+        grid.cell_start.push(0);
+        for cell in cells {
+            grid.cell_objects.extend(cell);
+            grid.cell_start.push(grid.cell_objects.len());
+        }
+
+        grid
+    }
+
+    /// About three cells per object, distributed so that cells are roughly cubes.
+    fn choose_resolution(bounds: &AABB, count: usize) -> [usize; 3] {
+        let extent = bounds.max() - bounds.min();
+        let max_extent = extent.x().max(extent.y()).max(extent.z());
+        if max_extent <= 0.0 {
+            return [1, 1, 1];
+        }
+
+        let cells_per_unit = (3.0 * count as f64).cbrt() / max_extent;
+        let mut resolution = [1; 3];
+        for (axis, n) in resolution.iter_mut().enumerate() {
+            *n = ((extent[axis] * cells_per_unit).round() as usize).clamp(1, MAX_RESOLUTION);
+        }
+        resolution
+    }
+
+    fn cell_size(&self) -> Vec3<f64> {
+        let bounds = self.bounds.as_ref().expect("grid has no bounds");
+        let extent = bounds.max() - bounds.min();
+        Vec3::new(
+            extent.x() / self.resolution[0] as f64,
+            extent.y() / self.resolution[1] as f64,
+            extent.z() / self.resolution[2] as f64,
+        )
+    }
+
+    /// Cell containing `point`, clamped to the grid.
+    fn cell_of(&self, point: &Vec3<f64>) -> [usize; 3] {
+        let bounds = self.bounds.as_ref().expect("grid has no bounds");
+        let size = self.cell_size();
+        let mut cell = [0; 3];
+        for (axis, index) in cell.iter_mut().enumerate() {
+            if size[axis] > 0.0 {
+                let offset = (point[axis] - bounds.min()[axis]) / size[axis];
+                *index = (offset.max(0.0) as usize).min(self.resolution[axis] - 1);
+            }
+        }
+        cell
+    }
+
+    fn cell_index(&self, [x, y, z]: [usize; 3]) -> usize {
+        (z * self.resolution[1] + y) * self.resolution[0] + x
+    }
+
+    fn hit_bounded(&self, ray: &Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let bounds = self.bounds.as_ref()?;
+        let Interval {
+            min: t_enter,
+            max: t_exit,
+        } = bounds.hit_interval(ray, t_range)?;
+
+        let ray_id = NEXT_RAY_ID.fetch_add(1, Ordering::Relaxed);
+        let size = self.cell_size();
+        let entry = ray.at(t_enter);
+        let mut cell = self.cell_of(&entry);
+
+        // parameter at which the ray crosses into the next cell along each axis,
+        // and the parameter distance between two crossings
+        let mut next_t = [f64::INFINITY; 3];
+        let mut delta_t = [f64::INFINITY; 3];
+        for axis in 0..3 {
+            let direction = ray.direction()[axis];
+            let cell_min = bounds.min()[axis] + cell[axis] as f64 * size[axis];
+            if direction > 0.0 {
+                next_t[axis] = t_enter + (cell_min + size[axis] - entry[axis]) / direction;
+                delta_t[axis] = size[axis] / direction;
+            } else if direction < 0.0 {
+                next_t[axis] = t_enter + (cell_min - entry[axis]) / direction;
+                delta_t[axis] = -size[axis] / direction;
+            }
+        }
+
+        let mut closest: Option<OutwardHitRecord> = None;
+        loop {
+            let index = self.cell_index(cell);
+            for &object in &self.cell_objects[self.cell_start[index]..self.cell_start[index + 1]] {
+                if self.mailboxes[object].swap(ray_id, Ordering::Relaxed) == ray_id {
+                    continue;
+                }
+
+                let t_range = t_range.with_max(closest.as_ref().map_or(t_range.max, |hit| hit.t));
+                if let Some(hit) = self.objects[object].hit(ray.clone(), t_range) {
+                    closest = Some(hit);
+                }
+            }
+
+            let axis = (0..3)
+                .min_by(|&lhs, &rhs| next_t[lhs].total_cmp(&next_t[rhs]))
+                .unwrap();
+            let closest_t = closest.as_ref().map_or(t_range.max, |hit| hit.t);
+            if closest_t <= next_t[axis] || next_t[axis] > t_exit {
+                break;
+            }
+
+            if ray.direction()[axis] > 0.0 {
+                cell[axis] += 1;
+                if cell[axis] == self.resolution[axis] {
+                    break;
+                }
+            } else {
+                if cell[axis] == 0 {
+                    break;
+                }
+                cell[axis] -= 1;
+            }
+            next_t[axis] += delta_t[axis];
+        }
+
+        closest
+    }
+}
+
+impl Hit for UniformGrid {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let closest = self.hit_bounded(&ray, t_range);
+        let t_range = t_range.with_max(closest.as_ref().map_or(t_range.max, |hit| hit.t));
+        self.unbounded.hit(ray, t_range).or(closest)
+    }
+
+    fn bounding_box(&self, _: Range<f64>) -> Option<AABB> {
+        if self.unbounded.is_empty() {
+            self.bounds.clone()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{hit::BVH, material::Lambertian, object::Block, Color, Point3};
+
+    /// The ground of the final scene.
+    fn block_field() -> Vec<Box<dyn Hit>> {
+        let mut rng = StdRng::seed_from_u64(42);
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let mut objects: Vec<Box<dyn Hit>> = Vec::new();
+        for i in 0..20 {
+            for j in 0..20 {
+                let w = 100.0;
+                let min = Point3::new(-1000.0 + i as f64 * w, 0.0, -1000.0 + j as f64 * w);
+                let max = Point3::new(min.x() + w, rng.gen_range(1.0..101.0), min.z() + w);
+                objects.push(Box::new(Block::new(min, max, material.clone())));
+            }
+        }
+        objects
+    }
+
+    fn assert_same_hit(list: &[Box<dyn Hit>], grid: &UniformGrid, ray: Ray) {
+        let expected = list
+            .hit(ray.clone(), Interval::from_min(1e-10))
+            .map(|hit| hit.t);
+        let actual = grid.hit(ray, Interval::from_min(1e-10)).map(|hit| hit.t);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn grid_matches_list() {
+        let list = block_field();
+        let grid = UniformGrid::new(block_field(), 0.0..1.0);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for _ in 0..2000 {
+            let origin = Point3::new(
+                rng.gen_range(-1500.0..1500.0),
+                rng.gen_range(0.0..500.0),
+                rng.gen_range(-1500.0..1500.0),
+            );
+            let target = Point3::new(
+                rng.gen_range(-1000.0..1000.0),
+                rng.gen_range(0.0..100.0),
+                rng.gen_range(-1000.0..1000.0),
+            );
+            assert_same_hit(&list, &grid, Ray::new(origin, target - origin, 0.0));
+        }
+    }
+
+    #[test]
+    fn grid_ray_starting_inside() {
+        let list = block_field();
+        let grid = UniformGrid::with_resolution(block_field(), 0.0..1.0, [20, 1, 20]);
+        let mut rng = StdRng::seed_from_u64(11);
+
+        for _ in 0..500 {
+            let origin = Point3::new(
+                rng.gen_range(-1000.0..1000.0),
+                rng.gen_range(0.0..100.0),
+                rng.gen_range(-1000.0..1000.0),
+            );
+            let direction = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            assert_same_hit(&list, &grid, Ray::new(origin, direction, 0.0));
+        }
+    }
+
+    #[test]
+    fn grid_matches_bvh() {
+        let bvh = BVH::new(block_field(), 0.0..1.0);
+        let grid = UniformGrid::try_new(block_field(), 0.0..1.0, Some([16, 4, 16])).unwrap();
+        let mut rng = StdRng::seed_from_u64(13);
+
+        for _ in 0..2000 {
+            let origin = Point3::new(
+                rng.gen_range(-1500.0..1500.0),
+                rng.gen_range(0.0..500.0),
+                rng.gen_range(-1500.0..1500.0),
+            );
+            let direction = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..0.5),
+                rng.gen_range(-1.0..1.0),
+            );
+            let ray = Ray::new(origin, direction, 0.0);
+            let expected = bvh.hit(ray.clone(), Interval::from_min(1e-10));
+            let actual = grid.hit(ray, Interval::from_min(1e-10));
+            assert_eq!(
+                actual.map(|hit| (hit.t, hit.point)),
+                expected.map(|hit| (hit.t, hit.point))
+            );
+        }
+
+        let mut objects = block_field();
+        objects.push(Box::new(UniformGrid::new(Vec::new(), 0.0..1.0)));
+        assert_eq!(
+            UniformGrid::try_new(objects, 0.0..1.0, None).unwrap_err(),
+            BvhError::MissingBoundingBox { index: 400 }
+        );
+        assert_eq!(
+            UniformGrid::try_new(Vec::new(), 0.0..1.0, None).unwrap_err(),
+            BvhError::Empty
+        );
+    }
+}
@@ -1,5 +1,11 @@
+use crate::{Color, Material, Point3, Ray, Vec3};
 use std::sync::Arc;
-use crate::{Material, Point3, Ray, Vec3, Color};
+
+/// Whether `ray` hits the front of a surface with outward normal
+/// `normal_outward`, i.e. comes from outside.
+pub fn is_front_face(ray: &Ray, normal_outward: Vec3<f64>) -> bool {
+    ray.direction().dot(normal_outward) < crate::vec3::Float::EPSILON
+}
 
 #[derive(Debug, Clone)]
 pub struct OutwardHitRecord {
@@ -20,6 +26,9 @@ pub struct OutwardHitRecord {
     /// Color of emitted light from the object at hit point.
     /// This may larger than 1.0, which means the object is brighter.
     pub emitted: Color,
+    /// Partial derivatives `(dp/du, dp/dv)` of the point by the surface
+    /// coordinates, for objects which know them
+    pub tangents: Option<(Vec3<f64>, Vec3<f64>)>,
 }
 
 impl OutwardHitRecord {
@@ -32,8 +41,8 @@ impl OutwardHitRecord {
         (u, v): (f64, f64),
     ) -> Self {
         assert!(point.is_valid_point());
-        let emitted = material.emit(point, u, v);
-        let front_face = ray.direction().dot(normal_outward) < crate::vec3::Float::EPSILON;
+        let emitted = material.emit_towards(ray, point, normal_outward, u, v);
+        let front_face = is_front_face(ray, normal_outward);
         Self {
             point,
             normal_outward,
@@ -43,6 +52,46 @@ impl OutwardHitRecord {
             u,
             v,
             emitted,
+            tangents: None,
+        }
+    }
+
+    /// Same hit, with the partial derivatives of the point by the surface
+    /// coordinates.
+    pub fn with_tangents(self, dpdu: Vec3<f64>, dpdv: Vec3<f64>) -> Self {
+        Self {
+            tangents: Some((dpdu, dpdv)),
+            ..self
+        }
+    }
+
+    /// The same hit after the object is transformed, e.g. rotated, seen by
+    /// `ray` at `point` with `normal_outward`, and whose linear part maps
+    /// vectors by `to_world`.
+    ///
+    /// The side and the emitted color are computed again, so that materials
+    /// which emit by the normal see the transformed one. The tangents are
+    /// mapped by `to_world`.
+    pub fn transformed(
+        self,
+        ray: &Ray,
+        point: Point3,
+        normal_outward: Vec3<f64>,
+        to_world: impl Fn(Vec3<f64>) -> Vec3<f64>,
+    ) -> Self {
+        let tangents = self
+            .tangents
+            .map(|(dpdu, dpdv)| (to_world(dpdu), to_world(dpdv)));
+        Self {
+            tangents,
+            ..Self::new(
+                point,
+                ray,
+                normal_outward,
+                self.t,
+                self.material,
+                (self.u, self.v),
+            )
         }
     }
 
@@ -71,6 +120,7 @@ impl OutwardHitRecord {
             u: self.u,
             v: self.v,
             emitted: self.emitted,
+            tangents: self.tangents,
         }
     }
 }
@@ -94,6 +144,9 @@ pub struct AgainstRayHitRecord {
     /// Color of emitted light from the object at hit point.
     /// This may larger than 1.0, which means the object is brighter.
     pub emitted: Color,
+    /// Partial derivatives `(dp/du, dp/dv)` of the point by the surface
+    /// coordinates, for objects which know them
+    pub tangents: Option<(Vec3<f64>, Vec3<f64>)>,
 }
 
 impl AgainstRayHitRecord {
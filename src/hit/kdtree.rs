@@ -0,0 +1,390 @@
+use std::ops::Range;
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    Hit, Interval, Ray, Vec3,
+};
+
+/// Cost of visiting a node, relative to `INTERSECT_COST`.
+const TRAVERSAL_COST: f64 = 1.0;
+/// Cost of testing one object.
+const INTERSECT_COST: f64 = 80.0;
+/// Discount given to splits that leave one side empty.
+const EMPTY_BONUS: f64 = 0.5;
+/// Nodes with this many objects or fewer are not split further.
+const MAX_LEAF_SIZE: usize = 2;
+
+#[derive(Debug, Clone)]
+enum Node {
+    /// Objects are `indices[start..start + count]`
+    Leaf { start: usize, count: usize },
+    /// The child below the plane follows this node, the one above is at `above`.
+    Split {
+        axis: usize,
+        position: f64,
+        above: usize,
+    },
+}
+
+/// KD-tree over a list of objects.
+///
+/// Space is split recursively by axis-aligned planes, chosen with the surface
+/// area heuristic (SAH) among the faces of the bounding boxes. Unlike a [`BVH`],
+/// the nodes do not overlap, but an object straddling a plane is referenced by
+/// both children. This tends to work better than a BVH for heavily overlapping
+/// geometry.
+///
+/// Objects without a bounding box are kept in a separate list that is tested
+/// against every ray.
+///
+/// [`BVH`]: super::BVH
+#[derive(Debug)]
+pub struct KdTree {
+    /// Objects with a bounding box
+    objects: Vec<Box<dyn Hit>>,
+    /// Objects without a bounding box
+    unbounded: Vec<Box<dyn Hit>>,
+    /// Flattened nodes, the root is the first one
+    nodes: Vec<Node>,
+    /// Object indices referenced by the leaves
+    indices: Vec<usize>,
+    /// Bounding box of all bounded objects
+    bounds: Option<AABB>,
+}
+
+/// A face of a bounding box along the split axis.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    position: f64,
+    is_start: bool,
+}
+
+struct Builder<'a> {
+    boxes: &'a [AABB],
+    nodes: Vec<Node>,
+    indices: Vec<usize>,
+}
+
+impl Builder<'_> {
+    fn build(&mut self, objects: Vec<usize>, bounds: AABB, depth: usize) {
+        if objects.len() <= MAX_LEAF_SIZE || depth == 0 {
+            return self.leaf(objects);
+        }
+
+        let Some((axis, position)) = self.find_split(&objects, &bounds) else {
+            return self.leaf(objects);
+        };
+
+        let below = objects
+            .iter()
+            .copied()
+            .filter(|&i| self.boxes[i].min()[axis] < position)
+            .collect::<Vec<_>>();
+        let above = objects
+            .iter()
+            .copied()
+            .filter(|&i| self.boxes[i].max()[axis] > position)
+            .collect::<Vec<_>>();
+
+        let mut below_bounds = bounds.clone();
+        below_bounds.max[axis] = position;
+        let mut above_bounds = bounds;
+        above_bounds.min[axis] = position;
+
+        let node = self.nodes.len();
+        self.nodes.push(Node::Split {
+            axis,
+            position,
+            above: 0,
+        });
+        self.build(below, below_bounds, depth - 1);
+        let above_index = self.nodes.len();
+        if let Node::Split { above, .. } = &mut self.nodes[node] {
+            *above = above_index;
+        }
+        self.build(above, above_bounds, depth - 1);
+    }
+
+    fn leaf(&mut self, objects: Vec<usize>) {
+        self.nodes.push(Node::Leaf {
+            start: self.indices.len(),
+            count: objects.len(),
+        });
+        self.indices.extend(objects);
+    }
+
+    /// Find the cheapest split plane according to the SAH, or `None` if not
+    /// splitting is cheaper.
+    fn find_split(&self, objects: &[usize], bounds: &AABB) -> Option<(usize, f64)> {
+        let extent = bounds.max() - bounds.min();
+        let total_area = surface_area(&extent);
+        let leaf_cost = INTERSECT_COST * objects.len() as f64;
+        let mut best: Option<(f64, usize, f64)> = None;
+
+        for axis in 0..3 {
+            let mut edges = objects
+                .iter()
+                .flat_map(|&object| {
+                    let aabb = &self.boxes[object];
+                    [
+                        Edge {
+                            position: aabb.min()[axis],
+                            is_start: true,
+                        },
+                        Edge {
+                            position: aabb.max()[axis],
+                            is_start: false,
+                        },
+                    ]
+                })
+                .collect::<Vec<_>>();
+            // ends sort before starts at the same position, so that touching
+            // objects end up on different sides
+            edges.sort_by(|lhs, rhs| {
+                lhs.position
+                    .total_cmp(&rhs.position)
+                    .then(lhs.is_start.cmp(&rhs.is_start))
+            });
+
+            let (other0, other1) = ((axis + 1) % 3, (axis + 2) % 3);
+            let (mut below, mut above) = (0, objects.len());
+            for edge in &edges {
+                if !edge.is_start {
+                    above -= 1;
+                }
+
+                let position = edge.position;
+                if position > bounds.min()[axis] && position < bounds.max()[axis] {
+                    let below_length = position - bounds.min()[axis];
+                    let above_length = bounds.max()[axis] - position;
+                    let cap = extent[other0] * extent[other1];
+                    let ring = extent[other0] + extent[other1];
+                    let below_area = 2.0 * (cap + below_length * ring);
+                    let above_area = 2.0 * (cap + above_length * ring);
+
+                    let bonus = if below == 0 || above == 0 {
+                        EMPTY_BONUS
+                    } else {
+                        0.0
+                    };
+                    let cost = TRAVERSAL_COST
+                        + INTERSECT_COST
+                            * (1.0 - bonus)
+                            * (below_area / total_area * below as f64
+                                + above_area / total_area * above as f64);
+
+                    if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                        best = Some((cost, axis, position));
+                    }
+                }
+
+                if edge.is_start {
+                    below += 1;
+                }
+            }
+        }
+
+        best.filter(|&(cost, _, _)| cost < leaf_cost)
+            .map(|(_, axis, position)| (axis, position))
+    }
+}
+
+fn surface_area(extent: &Vec3<f64>) -> f64 {
+    2.0 * (extent.x() * extent.y() + extent.y() * extent.z() + extent.z() * extent.x())
+}
+
+impl KdTree {
+    /// Create a new KD-tree from a list of objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `objects` - List of objects
+    /// * `time_range` - Time range for the bounding boxes of moving objects
+    pub fn new(objects: Vec<Box<dyn Hit>>, time_range: Range<f64>) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = objects
+            .into_iter()
+            .map(|object| (object.bounding_box(time_range.clone()), object))
+            .partition(|(aabb, _)| aabb.is_some());
+        let (boxes, objects): (Vec<_>, Vec<_>) = bounded
+            .into_iter()
+            .map(|(aabb, object)| (aabb.unwrap(), object))
+            .unzip();
+        let unbounded = unbounded.into_iter().map(|(_, object)| object).collect();
+
+        let bounds = boxes.iter().cloned().reduce(|lhs, rhs| lhs.merge(&rhs));
+        let mut builder = Builder {
+            boxes: &boxes,
+            nodes: Vec::new(),
+            indices: Vec::new(),
+        };
+        if let Some(bounds) = &bounds {
+            let max_depth = (8.0 + 1.3 * (boxes.len() as f64).log2()).round() as usize;
+            builder.build((0..objects.len()).collect(), bounds.clone(), max_depth);
+        }
+        let Builder { nodes, indices, .. } = builder;
+
+        Self {
+            objects,
+            unbounded,
+            nodes,
+            indices,
+            bounds,
+        }
+    }
+
+    fn hit_bounded(&self, ray: &Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let bounds = self.bounds.as_ref()?;
+        let Interval {
+            min: near,
+            max: far,
+        } = bounds.hit_interval(ray, t_range)?;
+
+        let mut closest: Option<OutwardHitRecord> = None;
+        let mut stack = Vec::with_capacity(16);
+        stack.push((0, near, far));
+
+        while let Some((mut node, near, mut far)) = stack.pop() {
+            let closest_t = closest.as_ref().map_or(t_range.max, |hit| hit.t);
+            if closest_t < near {
+                // the remaining segments are all further away
+                break;
+            }
+
+            loop {
+                match self.nodes[node] {
+                    Node::Split {
+                        axis,
+                        position,
+                        above,
+                    } => {
+                        let origin = ray.origin()[axis];
+                        let direction = ray.direction()[axis];
+                        let below_first =
+                            origin < position || (origin == position && direction <= 0.0);
+                        let (first, second) = if below_first {
+                            (node + 1, above)
+                        } else {
+                            (above, node + 1)
+                        };
+
+                        let t_plane = if direction == 0.0 {
+                            f64::INFINITY
+                        } else {
+                            (position - origin) / direction
+                        };
+
+                        if t_plane > far || t_plane <= 0.0 {
+                            node = first;
+                        } else if t_plane < near {
+                            node = second;
+                        } else {
+                            stack.push((second, t_plane, far));
+                            node = first;
+                            far = t_plane;
+                        }
+                    }
+                    Node::Leaf { start, count } => {
+                        for &index in &self.indices[start..start + count] {
+                            let closest_t = closest.as_ref().map_or(t_range.max, |hit| hit.t);
+                            if let Some(hit) =
+                                self.objects[index].hit(ray.clone(), t_range.with_max(closest_t))
+                            {
+                                closest = Some(hit);
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        closest
+    }
+}
+
+impl Hit for KdTree {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let closest = self.hit_bounded(&ray, t_range);
+        let t_range = t_range.with_max(closest.as_ref().map_or(t_range.max, |hit| hit.t));
+        self.unbounded.hit(ray, t_range).or(closest)
+    }
+
+    fn bounding_box(&self, _: Range<f64>) -> Option<AABB> {
+        if self.unbounded.is_empty() {
+            self.bounds.clone()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{material::Lambertian, Color, Point3, Sphere};
+
+    fn spheres() -> Vec<Box<dyn Hit>> {
+        let mut rng = StdRng::seed_from_u64(42);
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        (0..300)
+            .map(|_| {
+                let center = Point3::new(
+                    rng.gen_range(-5.0..5.0),
+                    rng.gen_range(-5.0..5.0),
+                    rng.gen_range(-5.0..5.0),
+                );
+                let radius = rng.gen_range(0.1..1.0);
+                Box::new(Sphere::new(center, radius, material.clone())) as Box<dyn Hit>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kdtree_matches_list() {
+        let list = spheres();
+        let tree = KdTree::new(spheres(), 0.0..1.0);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for _ in 0..2000 {
+            let origin = Point3::new(
+                rng.gen_range(-8.0..8.0),
+                rng.gen_range(-8.0..8.0),
+                rng.gen_range(-8.0..8.0),
+            );
+            let direction = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            let ray = Ray::new(origin, direction, 0.0);
+
+            let expected = list
+                .hit(ray.clone(), Interval::from_min(1e-10))
+                .map(|hit| hit.t);
+            let actual = tree.hit(ray, Interval::from_min(1e-10)).map(|hit| hit.t);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn kdtree_axis_aligned_rays() {
+        let list = spheres();
+        let tree = KdTree::new(spheres(), 0.0..1.0);
+
+        for x in -10..=10 {
+            for y in -10..=10 {
+                let origin = Point3::new(x as f64 * 0.5, y as f64 * 0.5, -10.0);
+                let ray = Ray::new(origin, Vec3::new(0.0, 0.0, 1.0), 0.0);
+                let expected = list
+                    .hit(ray.clone(), Interval::from_min(1e-10))
+                    .map(|hit| hit.t);
+                let actual = tree.hit(ray, Interval::from_min(1e-10)).map(|hit| hit.t);
+                assert_eq!(expected, actual);
+            }
+        }
+    }
+}
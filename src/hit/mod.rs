@@ -1,60 +1,257 @@
 mod aabb;
+mod aabb_pair;
+mod animate;
 mod hit_record;
 pub mod translation;
 pub mod rotation;
+pub mod scaling;
+mod transform;
 mod bvh;
+mod kdtree;
 mod constant;
+mod non_uniform;
+mod grid;
+mod flip_face;
+mod motion;
 
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Range, sync::Arc};
 
-pub use aabb::AABB;
-pub use bvh::BVH;
+use rand::Rng;
 
-use crate::Ray;
+pub use aabb::{swept_bounds, AABB};
+use aabb_pair::AABBPair;
+pub use animate::Animate;
+pub use bvh::{BvhError, BvhSplit, BVH};
+pub(crate) use bvh::check_objects;
+pub use flip_face::FlipFace;
+pub use transform::Transform;
+pub use motion::{Interpolation, Motion, MotionPath};
+pub use grid::UniformGrid;
+pub use kdtree::KdTree;
+
+use crate::{random, Interval, Point3, Quat, Ray, Vec3};
+pub use constant::ConstantMedium;
+pub use non_uniform::NonUniformMedium;
 pub use hit_record::AgainstRayHitRecord;
 pub use hit_record::OutwardHitRecord;
-pub use constant::ConstantMedium;
+pub use hit_record::is_front_face;
+use rotation::{Rotate, RotateQuat};
+use scaling::Scale;
+use translation::Translate;
 /// Trait for objects that can be hit by a ray
 pub trait Hit: Sync + Send + Debug {
     /// Returns the hit record for the ray if it hits the object, otherwise None
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord>;
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord>;
 
     /// Returns the bounding box of the object
     ///
     /// This function returns a option because some objects do not have a bounding box,
     /// such as infinite planes. Moving objects will have a bounding box that encloses
-    /// the object at all times.
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB>;
+    /// the object at all times in `time_range`.
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB>;
+
+    /// Returns whether the ray hits the object at any point in `t_range`
+    ///
+    /// This is enough for occlusion queries like shadow rays, which do not need
+    /// the closest hit. Implementations can skip building the hit record, and
+    /// stop at the first object hit.
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.hit(ray.clone(), t_range).is_some()
+    }
+
+    /// Density over solid angle of [`Hit::random_point_towards`] giving
+    /// `direction` from `origin` at `time`, which is how lights are sampled
+    /// directly.
+    ///
+    /// The default is zero, for objects that cannot be sampled.
+    #[allow(unused_variables)] // This is a default implementation, so the arguments may not be used.
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        0.0
+    }
+
+    /// Direction from `origin` towards a random point on the object at `time`,
+    /// or `None` if the object cannot be sampled from there, such as objects
+    /// which are not lights. [`Hit::pdf_value`] is then zero for every
+    /// direction.
+    ///
+    /// Whether a direction is returned may depend on `origin` and `time`, but
+    /// not on chance, so that the renderer can fall back to the density of
+    /// the material instead.
+    #[allow(unused_variables)] // This is a default implementation, so the arguments may not be used.
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        None
+    }
+}
+
+/// Ratio of the density over solid angle of `direction` in the world space to
+/// that of `object_direction` in the object space, for objects placed by a
+/// linear map with `determinant` which takes `object_direction` to
+/// `direction`.
+///
+/// A map stretching space also spreads the directions through it, and the
+/// solid angle around a direction scales by the determinant over the cube of
+/// the length the direction is scaled by.
+fn solid_angle_ratio(direction: Vec3<f64>, object_direction: Vec3<f64>, determinant: f64) -> f64 {
+    (direction.norm() / object_direction.norm()).powi(3) / determinant.abs()
+}
+
+/// Chainable wrappers for any [`Hit`], so that instances read in the order
+/// the transforms are applied, e.g.
+/// `Block::new(...).rotate_y(15.0).translate(offset)`.
+pub trait HitExt: Hit + Sized {
+    /// Rotate by `degree` around the X axis, see [`Rotate::new_x`].
+    fn rotate_x(self, degree: f64) -> Rotate<Self> {
+        Rotate::new_x(self, degree)
+    }
+
+    /// Rotate by `degree` around the Y axis, see [`Rotate::new_y`].
+    fn rotate_y(self, degree: f64) -> Rotate<Self> {
+        Rotate::new_y(self, degree)
+    }
+
+    /// Rotate by `degree` around the Z axis, see [`Rotate::new_z`].
+    fn rotate_z(self, degree: f64) -> Rotate<Self> {
+        Rotate::new_z(self, degree)
+    }
+
+    /// Rotate by the quaternion `rotation`.
+    fn rotate(self, rotation: Quat) -> RotateQuat<Self> {
+        RotateQuat::new(self, rotation)
+    }
+
+    /// Move by `offset`.
+    fn translate(self, offset: Vec3<f64>) -> Translate<Self> {
+        Translate::new(self, offset)
+    }
+
+    /// Scale by `factors` along each axis, see [`Scale`].
+    fn scale(self, factors: Vec3<f64>) -> Scale<Self> {
+        Scale::new(self, factors)
+    }
+
+    /// Swap the front and the back, see [`FlipFace`].
+    fn flip_faces(self) -> FlipFace<Self> {
+        FlipFace::new(self)
+    }
+
+    fn boxed(self) -> Box<dyn Hit>
+    where
+        Self: 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<H: Hit> HitExt for H {}
+
+/// Bounding box of the whole scene over the shutter interval `time_range`.
+///
+/// Returns `None` if the scene is empty, or has no finite bounding box.
+pub fn scene_bounds<H: Hit + ?Sized>(world: &H, time_range: Range<f64>) -> Option<AABB> {
+    let bounds = world.bounding_box(time_range)?;
+    // also rejects the empty box, whose diagonal is negative infinity
+    let finite = bounds.diagonal().iter().all(|x| x.is_finite());
+    finite.then_some(bounds)
 }
 
 impl<H: Hit> Hit for Box<H> {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        self.as_ref().hit(ray, t_min, t_max)
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.as_ref().hit(ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.as_ref().bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.as_ref().hit_any(ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.as_ref().pdf_value(origin, direction, time)
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB> {
-        self.as_ref().bounding_box(time_from, time_to)
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.as_ref().random_point_towards(origin, time)
     }
 }
 
 impl Hit for Box<dyn Hit> {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        self.as_ref().hit(ray, t_min, t_max)
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.as_ref().hit(ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.as_ref().bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.as_ref().hit_any(ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.as_ref().pdf_value(origin, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.as_ref().random_point_towards(origin, time)
+    }
+}
+
+// Shared objects, so that one expensive object, like a BVH of a mesh, can be
+// placed in many instances without cloning it.
+impl<H: Hit> Hit for Arc<H> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.as_ref().hit(ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.as_ref().bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.as_ref().hit_any(ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.as_ref().pdf_value(origin, direction, time)
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB> {
-        self.as_ref().bounding_box(time_from, time_to)
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.as_ref().random_point_towards(origin, time)
+    }
+}
+
+impl Hit for Arc<dyn Hit> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.as_ref().hit(ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.as_ref().bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.as_ref().hit_any(ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.as_ref().pdf_value(origin, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.as_ref().random_point_towards(origin, time)
     }
 }
 
 impl<H: Hit> Hit for [H] {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
         // https://doc.rust-lang.org/std/primitive.slice.html#method.sort_by
-        let mut t_max = t_max;
+        let mut t_range = t_range;
         let mut closest = None;
         for object in self.iter().rev() {
-            if let Some(hit) = object.hit(ray.clone(), t_min, t_max) {
-                t_max = t_max.min(hit.t);
+            if let Some(hit) = object.hit(ray.clone(), t_range) {
+                t_range = t_range.with_max(hit.t);
                 closest = Some(hit);
             }
         }
@@ -62,20 +259,295 @@ impl<H: Hit> Hit for [H] {
         closest
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB> {
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
         self.iter()
             .rev()
-            .filter_map(|obj| obj.bounding_box(time_from, time_to))
+            .filter_map(|obj| obj.bounding_box(time_range.clone()))
             .reduce(|a, b| a.merge(&b))
     }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.iter().any(|object| object.hit_any(ray, t_range))
+    }
+
+    /// Average of the densities, since each object is picked as often.
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let sum: f64 = self
+            .iter()
+            .map(|object| object.pdf_value(origin, direction, time))
+            .sum();
+        if self.is_empty() {
+            0.0
+        } else {
+            sum / self.len() as f64
+        }
+    }
+
+    /// Direction towards one of the objects, picked uniformly. An object which
+    /// cannot be sampled gives `None` when it is picked, so lists of lights
+    /// should only hold objects which can.
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        if self.is_empty() {
+            return None;
+        }
+        let index = random::with_rng(|rng| rng.gen_range(0..self.len()));
+        self[index].random_point_towards(origin, time)
+    }
 }
 
 impl<H: Hit> Hit for Vec<H> {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        self.as_slice().hit(ray, t_min, t_max)
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.as_slice().hit(ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.as_slice().bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.as_slice().hit_any(ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.as_slice().pdf_value(origin, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.as_slice().random_point_towards(origin, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        material::{DiffuseLight, Lambertian},
+        object::{rectangle::AxisAlignedRectangle, Block},
+        Color, Point3, Vec3, World,
+    };
+
+    /// Counts how many times the wrapped object is tested.
+    #[derive(Debug)]
+    struct Counted<H: Hit> {
+        object: H,
+        tests: Arc<AtomicUsize>,
+    }
+
+    impl<H: Hit> Hit for Counted<H> {
+        fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+            self.tests.fetch_add(1, Ordering::Relaxed);
+            self.object.hit(ray, t_range)
+        }
+
+        fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+            self.object.bounding_box(time_range)
+        }
+
+        fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+            self.tests.fetch_add(1, Ordering::Relaxed);
+            self.object.hit_any(ray, t_range)
+        }
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB> {
-        self.as_slice().bounding_box(time_from, time_to)
+    /// Records every time range its bounding box is asked for.
+    #[derive(Debug, Clone)]
+    struct RangeRecorder(Arc<Mutex<Vec<Range<f64>>>>);
+
+    impl Hit for RangeRecorder {
+        fn hit(&self, _ray: Ray, _t_range: Interval) -> Option<OutwardHitRecord> {
+            None
+        }
+
+        fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+            self.0.lock().unwrap().push(time_range);
+            Some(AABB::new(Point3::zeros(), Point3::constant(1.0)))
+        }
+    }
+
+    fn cornell_box(tests: &Arc<AtomicUsize>) -> Vec<Box<dyn Hit>> {
+        let white = Arc::new(Lambertian::new_solid(Color::constant(0.73)));
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(15.0)));
+        let block = |size: f64, angle: f64, offset: Vec3<f64>| {
+            let block = Block::new(
+                Point3::zeros(),
+                Point3::new(165.0, size, 165.0),
+                white.clone(),
+            );
+            Translate::new(Rotate::new_y(block, angle), offset)
+        };
+
+        let objects: Vec<Box<dyn Hit>> = vec![
+            Box::new(AxisAlignedRectangle::new_yz(
+                (0.0, 0.0),
+                (555.0, 555.0),
+                555.0,
+                white.clone(),
+            )),
+            Box::new(AxisAlignedRectangle::new_yz(
+                (0.0, 0.0),
+                (555.0, 555.0),
+                0.0,
+                white.clone(),
+            )),
+            Box::new(AxisAlignedRectangle::new_xz(
+                (213.0, 227.0),
+                (343.0, 332.0),
+                554.0,
+                light,
+            )),
+            Box::new(AxisAlignedRectangle::new_xz(
+                (0.0, 0.0),
+                (555.0, 555.0),
+                0.0,
+                white.clone(),
+            )),
+            Box::new(AxisAlignedRectangle::new_xz(
+                (0.0, 0.0),
+                (555.0, 555.0),
+                555.0,
+                white.clone(),
+            )),
+            Box::new(AxisAlignedRectangle::new_xy(
+                (0.0, 0.0),
+                (555.0, 555.0),
+                555.0,
+                white.clone(),
+            )),
+            Box::new(block(330.0, 15.0, Vec3::new(265.0, 0.0, 295.0))),
+            Box::new(block(165.0, -18.0, Vec3::new(130.0, 0.0, 65.0))),
+        ];
+
+        objects
+            .into_iter()
+            .map(|object| {
+                let tests = tests.clone();
+                Box::new(Counted { object, tests }) as Box<dyn Hit>
+            })
+            .collect()
+    }
+
+    /// Rays between two random points inside the box, reaching the second one at `t = 1`.
+    fn random_rays() -> impl Iterator<Item = Ray> {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut point = move || {
+            Point3::new(
+                rng.gen_range(1.0..554.0),
+                rng.gen_range(1.0..554.0),
+                rng.gen_range(1.0..554.0),
+            )
+        };
+        (0..2000).map(move |_| {
+            let (from, to) = (point(), point());
+            Ray::new(from, to - from, 0.0)
+        })
+    }
+
+    #[test]
+    fn hit_any_agrees_with_hit() {
+        let tests = Arc::new(AtomicUsize::new(0));
+        let world = World::from_vec(cornell_box(&tests));
+        let bvh = BVH::new(cornell_box(&tests), 0.0..1.0);
+
+        // like shadow rays, only the segment between the two points matters
+        for ray in random_rays() {
+            let expected = world.hit(ray.clone(), Interval::new(1e-3, 1.0)).is_some();
+            assert_eq!(world.hit_any(&ray, Interval::new(1e-3, 1.0)), expected);
+            assert_eq!(bvh.hit_any(&ray, Interval::new(1e-3, 1.0)), expected);
+        }
+    }
+
+    #[test]
+    fn wrappers_pass_the_time_range_through() {
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let recorder = RangeRecorder(ranges.clone());
+        let objects: Vec<Box<dyn Hit>> = vec![
+            recorder.clone().rotate_y(30.0).boxed(),
+            recorder
+                .clone()
+                .rotate(Quat::from_euler(10.0, 20.0, 30.0))
+                .boxed(),
+            recorder.clone().translate(Vec3::unit_x()).boxed(),
+            Box::new(Animate::new(recorder.clone(), |time| time * Vec3::unit_y())),
+            Box::new(World::from_vec(vec![recorder.clone().boxed()])),
+        ];
+        // `Rotate` computes its box for the default shutter when it is created
+        ranges.lock().unwrap().clear();
+        let bvh = BVH::new(objects, 0.25..0.75);
+        bvh.bounding_box(0.25..0.75);
+        scene_bounds(&recorder, 0.25..0.75);
+
+        let ranges = ranges.lock().unwrap();
+        assert!(ranges.len() >= 6);
+        assert!(ranges.iter().all(|range| *range == (0.25..0.75)));
+    }
+
+    #[test]
+    fn chained_instances_match_nested() {
+        let white = Arc::new(Lambertian::new_solid(Color::constant(0.73)));
+        let block = Block::new(Point3::zeros(), Point3::new(165.0, 330.0, 165.0), white);
+        let offset = Vec3::new(265.0, 0.0, 295.0);
+        let nested = Translate::new(Rotate::new_y(block.clone(), 15.0), offset);
+        let chained = block.rotate_y(15.0).translate(offset).boxed();
+
+        for ray in random_rays() {
+            let expected = nested.hit(ray.clone(), Interval::from_min(1e-3));
+            let actual = chained.hit(ray, Interval::from_min(1e-3));
+            assert_eq!(actual.map(|hit| hit.t), expected.map(|hit| hit.t));
+        }
+    }
+
+    #[test]
+    fn hit_any_tests_fewer_objects() {
+        let tests = Arc::new(AtomicUsize::new(0));
+        let world = World::from_vec(cornell_box(&tests));
+
+        // unbounded rays always hit a wall
+        let rays = random_rays().collect::<Vec<_>>();
+        for ray in &rays {
+            world.hit(ray.clone(), Interval::from_min(1e-3));
+        }
+        let full = tests.swap(0, Ordering::Relaxed);
+        for ray in &rays {
+            world.hit_any(ray, Interval::from_min(1e-3));
+        }
+        let any = tests.load(Ordering::Relaxed);
+
+        assert!(
+            any < full,
+            "hit_any tested {any} objects, hit tested {full}"
+        );
+    }
+
+    #[test]
+    fn shared_object_in_two_instances() {
+        let white = Arc::new(Lambertian::new_solid(Color::constant(0.73)));
+        let blocks = (0..4)
+            .map(|i| {
+                let min = Point3::new(f64::from(i), 0.0, 0.0);
+                Block::new(min, min + Vec3::constant(0.5), white.clone()).boxed()
+            })
+            .collect();
+        let shared: Arc<dyn Hit> = Arc::new(BVH::new(blocks, 0.0..1.0));
+
+        let mut world = World::new();
+        world.add(shared.clone().translate(Vec3::new(0.0, 10.0, 0.0)));
+        world.add(shared.clone().translate(Vec3::new(0.0, -10.0, 0.0)));
+        assert_eq!(Arc::strong_count(&shared), 3);
+
+        for y in [10.25, -9.75] {
+            let ray = Ray::new(Point3::new(3.25, y, -5.0), Vec3::unit_z(), 0.0);
+            let hit = world.hit(ray, Interval::from_min(1e-3)).unwrap();
+            assert!((hit.point - Point3::new(3.25, y, 0.0)).norm() < 1e-9);
+        }
+        // nothing in between
+        let ray = Ray::new(Point3::new(3.25, 0.25, -5.0), Vec3::unit_z(), 0.0);
+        assert!(world.hit(ray, Interval::from_min(1e-3)).is_none());
     }
 }
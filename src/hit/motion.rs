@@ -0,0 +1,264 @@
+use std::ops::Range;
+
+use crate::{Point3, Vec3};
+
+use super::AABB;
+
+/// Time-dependent offset of an animated object, see [`super::Animate`].
+///
+/// Any `Fn(f64) -> Vec3<f64>` is a motion whose bounds are found by sampling.
+pub trait Motion: Sync + Send {
+    /// Offset at `time`.
+    fn offset(&self, time: f64) -> Vec3<f64>;
+
+    /// Box enclosing every offset over `time_range`, if it can be computed
+    /// exactly. Otherwise `None`, and the offset is sampled instead.
+    fn offset_bounds(&self, _time_range: Range<f64>) -> Option<AABB> {
+        None
+    }
+}
+
+impl<F: Fn(f64) -> Vec3<f64> + Sync + Send> Motion for F {
+    fn offset(&self, time: f64) -> Vec3<f64> {
+        self(time)
+    }
+}
+
+/// How a [`MotionPath`] moves between its keyframes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Straight lines at constant speed between keyframes
+    #[default]
+    Linear,
+    /// Smooth Catmull-Rom spline through the keyframes, with the tangent at a
+    /// keyframe given by its neighbors, so uneven keyframe times are fine
+    CatmullRom,
+}
+
+/// Path through positions at given times.
+///
+/// Before the first keyframe and after the last one, the position stays at
+/// that keyframe. As a [`Motion`], the positions are offsets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotionPath {
+    /// Sorted by time
+    keyframes: Vec<(f64, Point3)>,
+    interpolation: Interpolation,
+}
+
+impl MotionPath {
+    /// Path through `keyframes` of `(time, position)`, which may be in any order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no keyframes, or a time is not finite.
+    pub fn new(mut keyframes: Vec<(f64, Point3)>, interpolation: Interpolation) -> Self {
+        assert!(!keyframes.is_empty(), "motion path needs a keyframe");
+        assert!(
+            keyframes.iter().all(|(time, _)| time.is_finite()),
+            "keyframe times must be finite"
+        );
+        keyframes.sort_by(|(lhs, _), (rhs, _)| lhs.total_cmp(rhs));
+        Self {
+            keyframes,
+            interpolation,
+        }
+    }
+
+    /// Path with straight lines between `keyframes`.
+    pub fn linear(keyframes: Vec<(f64, Point3)>) -> Self {
+        Self::new(keyframes, Interpolation::Linear)
+    }
+
+    /// Path with a smooth curve through `keyframes`.
+    pub fn catmull_rom(keyframes: Vec<(f64, Point3)>) -> Self {
+        Self::new(keyframes, Interpolation::CatmullRom)
+    }
+
+    pub fn keyframes(&self) -> &[(f64, Point3)] {
+        &self.keyframes
+    }
+
+    /// Time range from the first to the last keyframe.
+    pub fn time_range(&self) -> Range<f64> {
+        self.keyframes[0].0..self.keyframes[self.keyframes.len() - 1].0
+    }
+
+    pub fn position(&self, time: f64) -> Point3 {
+        // index of the first keyframe after `time`
+        let next = self.keyframes.partition_point(|(key, _)| *key <= time);
+        if next == 0 {
+            return self.keyframes[0].1;
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1].1;
+        }
+
+        let segment = next - 1;
+        let (from, to) = (self.keyframes[segment].0, self.keyframes[next].0);
+        let s = (time - from) / (to - from);
+        self.segment_position(segment, s)
+    }
+
+    /// Position at fraction `s` of the way through the segment from keyframe
+    /// `index` to the next one.
+    fn segment_position(&self, index: usize, s: f64) -> Point3 {
+        let [a, b, c, d] = self.segment_cubic(index);
+        ((a * s + b) * s + c) * s + d
+    }
+
+    /// Coefficients of the segment from keyframe `index` as a cubic in the
+    /// fraction of the segment, highest power first.
+    fn segment_cubic(&self, index: usize) -> [Vec3<f64>; 4] {
+        let (p0, p1) = (self.keyframes[index].1, self.keyframes[index + 1].1);
+        match self.interpolation {
+            Interpolation::Linear => [Vec3::zeros(), Vec3::zeros(), p1 - p0, p0],
+            Interpolation::CatmullRom => {
+                // Hermite form, with tangents scaled to the segment length in time
+                let duration = self.keyframes[index + 1].0 - self.keyframes[index].0;
+                let m0 = self.velocity(index) * duration;
+                let m1 = self.velocity(index + 1) * duration;
+                [
+                    p0 * 2.0 + m0 - p1 * 2.0 + m1,
+                    p0 * -3.0 - m0 * 2.0 + p1 * 3.0 - m1,
+                    m0,
+                    p0,
+                ]
+            }
+        }
+    }
+
+    /// Velocity at keyframe `index` of the Catmull-Rom spline, from its
+    /// neighbors, or from itself at the ends.
+    fn velocity(&self, index: usize) -> Vec3<f64> {
+        let before = index.saturating_sub(1);
+        let after = (index + 1).min(self.keyframes.len() - 1);
+        let (t0, p0) = self.keyframes[before];
+        let (t1, p1) = self.keyframes[after];
+        if t1 > t0 {
+            (p1 - p0) / (t1 - t0)
+        } else {
+            Vec3::zeros()
+        }
+    }
+
+    /// Box enclosing every position over `time_range`: the positions at both
+    /// ends, the keyframes in between, and the extrema of the segments.
+    pub fn bounds(&self, time_range: Range<f64>) -> AABB {
+        let (start, end) = (
+            time_range.start.min(time_range.end),
+            time_range.start.max(time_range.end),
+        );
+        let mut points = vec![self.position(start), self.position(end)];
+
+        for (index, window) in self.keyframes.windows(2).enumerate() {
+            let ((from, p0), (to, _)) = (window[0], window[1]);
+            if to <= start || end <= from {
+                continue;
+            }
+            if start < from {
+                points.push(p0);
+            }
+            // extrema where the derivative `3a s^2 + 2b s + c` of an axis is zero
+            let [a, b, c, _] = self.segment_cubic(index);
+            for axis in 0..3 {
+                for s in quadratic_roots(3.0 * a[axis], 2.0 * b[axis], c[axis]) {
+                    let time = from + s * (to - from);
+                    if 0.0 < s && s < 1.0 && start < time && time < end {
+                        points.push(self.segment_position(index, s));
+                    }
+                }
+            }
+        }
+
+        let min = points.iter().fold(points[0], |min, point| min.min(point));
+        let max = points.iter().fold(points[0], |max, point| max.max(point));
+        AABB::new(min, max)
+    }
+}
+
+/// Real roots of `a x^2 + b x + c`, or of `b x + c` if `a` is zero.
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return if b == 0.0 { vec![] } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    let sqrt = discriminant.sqrt();
+    vec![(-b - sqrt) / (2.0 * a), (-b + sqrt) / (2.0 * a)]
+}
+
+impl Motion for MotionPath {
+    fn offset(&self, time: f64) -> Vec3<f64> {
+        self.position(time)
+    }
+
+    fn offset_bounds(&self, time_range: Range<f64>) -> Option<AABB> {
+        Some(self.bounds(time_range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Quarter circle of radius 1 around the origin, from +x to +z.
+    fn arc() -> MotionPath {
+        let keyframes = (0..=4)
+            .map(|i| {
+                let angle = std::f64::consts::FRAC_PI_8 * i as f64;
+                (i as f64 / 4.0, Point3::new(angle.cos(), 0.0, angle.sin()))
+            })
+            .collect();
+        MotionPath::catmull_rom(keyframes)
+    }
+
+    #[test]
+    fn keyframes_are_passed_through() {
+        let path = MotionPath::linear(vec![
+            (1.0, Point3::new(2.0, 0.0, 0.0)),
+            (0.0, Point3::zeros()),
+        ]);
+        assert_eq!(path.time_range(), 0.0..1.0);
+        assert_eq!(path.position(0.25), Point3::new(0.5, 0.0, 0.0));
+        // held outside the keyframes
+        assert_eq!(path.position(-1.0), Point3::zeros());
+        assert_eq!(path.position(2.0), Point3::new(2.0, 0.0, 0.0));
+
+        let arc = arc();
+        for &(time, point) in arc.keyframes() {
+            assert!((arc.position(time) - point).norm() < 1e-12);
+        }
+        // close to the circle between keyframes too
+        assert!((arc.position(0.375).norm() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bounds_contain_the_curve() {
+        let path = MotionPath::catmull_rom(vec![
+            (0.0, Point3::zeros()),
+            (1.0, Point3::new(1.0, 1.0, 0.0)),
+            (2.0, Point3::new(2.0, 0.0, 0.0)),
+            (3.0, Point3::new(3.0, 1.0, 0.0)),
+        ]);
+        for range in [0.0..3.0, 0.5..1.5, 1.2..1.3, -1.0..0.5] {
+            let bounds = path.bounds(range.clone());
+            let mut top = f64::NEG_INFINITY;
+            for i in 0..=100 {
+                let time = range.start + (range.end - range.start) * i as f64 / 100.0;
+                let point = path.position(time);
+                top = top.max(point.y());
+                assert!(
+                    (0..3)
+                        .all(|i| bounds.min[i] - 1e-12 <= point[i]
+                            && point[i] <= bounds.max[i] + 1e-12),
+                    "{point} outside {bounds:?}"
+                );
+            }
+            // and are tight
+            assert!(bounds.max.y() - top < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fmt::Debug, ops::Range, sync::Arc};
+
+use crate::{
+    hit::OutwardHitRecord,
+    material::Isotropic,
+    random,
+    texture::{SolidColor, Texture},
+    Color, Hit, Interval, Point3, Ray, Vec3,
+};
+
+use super::{constant::segments_inside, AABB};
+
+/// A volume whose density changes from point to point, such as smoke that is
+/// thicker near the ground.
+///
+/// The density at a point is given by a function, and must stay between zero
+/// and `max_density`; larger values are treated as `max_density`. Like
+/// [`super::ConstantMedium`], the ray scatters with the [`Isotropic`] material
+/// of the texture, and only between entries and exits of the boundary.
+///
+/// The point where the ray scatters is sampled by delta tracking: tentative
+/// points are sampled as in a medium of `max_density` everywhere, and each is
+/// accepted with probability `density / max_density`, otherwise the ray goes
+/// on. Where the density is zero, the ray never scatters.
+#[derive(Clone)]
+pub struct NonUniformMedium<H: Hit, T: Texture, D> {
+    /// Object to be filled with the medium.
+    boundary: H,
+    /// Material of the medium.
+    material: Arc<Isotropic<T>>,
+    /// Density at a point
+    density: D,
+    /// Upper bound of the density
+    max_density: f64,
+}
+
+impl<H: Hit, T: Texture, D: Fn(Point3) -> f64 + Sync + Send> NonUniformMedium<H, T, D> {
+    /// # Panics
+    ///
+    /// Panics if `max_density` is not positive and finite.
+    pub fn new(boundary: H, texture: T, density: D, max_density: f64) -> Self {
+        assert!(
+            max_density > 0.0 && max_density.is_finite(),
+            "maximum density must be positive and finite"
+        );
+        Self {
+            boundary,
+            material: Arc::new(Isotropic::new(texture)),
+            density,
+            max_density,
+        }
+    }
+}
+
+impl<H: Hit, D: Fn(Point3) -> f64 + Sync + Send> NonUniformMedium<H, SolidColor, D> {
+    pub fn new_solid(boundary: H, color: Color, density: D, max_density: f64) -> Self {
+        Self::new(boundary, SolidColor::new(color), density, max_density)
+    }
+}
+
+impl<H: Hit, T: Texture, D> Debug for NonUniformMedium<H, T, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NonUniformMedium")
+            .field("boundary", &self.boundary)
+            .field("material", &self.material)
+            .field("max_density", &self.max_density)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<H: Hit, T: Texture + 'static, D: Fn(Point3) -> f64 + Sync + Send> Hit
+    for NonUniformMedium<H, T, D>
+{
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        // parameter step per unit of optical depth at the maximum density
+        let step = 1.0 / (self.max_density * ray.direction().norm());
+        let t = segments_inside(&self.boundary, &ray, t_range).find_map(|(t_min, t_max)| {
+            let mut t = t_min;
+            loop {
+                t -= step * (1.0 - random::random::<f64>()).ln();
+                if t >= t_max {
+                    return None;
+                }
+                let density = (self.density)(ray.at(t)).clamp(0.0, self.max_density);
+                if random::random::<f64>() * self.max_density < density {
+                    return Some(t);
+                }
+            }
+        })?;
+
+        // as in the constant medium, the normal and texture coordinates are
+        // not used by the isotropic material
+        Some(OutwardHitRecord::new(
+            ray.at(t),
+            &ray,
+            Vec3::constant(f64::NAN),
+            t,
+            self.material.clone(),
+            (f64::NAN, f64::NAN),
+        ))
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.boundary.bounding_box(time_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, object::Block};
+
+    fn block() -> Block {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        Block::new(Point3::zeros(), Point3::new(4.0, 1.0, 1.0), material)
+    }
+
+    #[test]
+    fn empty_regions_never_scatter() {
+        // dense in the first and last quarter along x, empty in the middle
+        let density = |point: Point3| {
+            if (1.0..3.0).contains(&point.x()) {
+                0.0
+            } else {
+                5.0
+            }
+        };
+        let medium = NonUniformMedium::new_solid(block(), Color::constant(0.5), density, 5.0);
+
+        random::seed(8);
+        let ray = Ray::new(Point3::new(1.0, 0.5, 0.5), Vec3::unit_x(), 0.0);
+        let scattered: Vec<_> = (0..1000)
+            .filter_map(|_| medium.hit(ray.clone(), Interval::from_min(0.0)))
+            .map(|hit| hit.point.x())
+            .collect();
+        assert!(scattered.iter().all(|x| (3.0..=4.0).contains(x)));
+        // about 1 - e^-5 of the rays scatter in the last quarter
+        assert!(scattered.len() > 950);
+    }
+
+    #[test]
+    fn uniform_density_matches_constant_medium() {
+        let density = 0.5;
+        let medium = NonUniformMedium::new_solid(block(), Color::constant(0.5), |_| density, 2.0);
+
+        random::seed(9);
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::unit_x(), 0.0);
+        let hits = (0..4000)
+            .filter(|_| medium.hit_any(&ray, Interval::from_min(0.0)))
+            .count();
+        // the chance to scatter through a length of 4 is 1 - e^-2
+        let expected = 1.0 - (-density * 4.0f64).exp();
+        assert!((hits as f64 / 4000.0 - expected).abs() < 0.03);
+    }
+}
@@ -1,118 +1,517 @@
-use std::sync::RwLock;
+use std::ops::Range;
 
-use crate::{Hit, Ray, Vec3};
+use crate::{Hit, Interval, Point3, Quat, Ray, Vec3};
 
-use super::{OutwardHitRecord, AABB};
+use super::{swept_bounds, OutwardHitRecord, AABB};
 
-#[derive(Debug)]
+/// Time range the bounding box of a [`Rotate`] is computed for when it is
+/// created, the default shutter of the camera.
+const PRECOMPUTED_TIME_RANGE: Range<f64> = 0.0..1.0;
+
+/// Instance rotated around an axis through a pivot point.
+///
+/// Rays are rotated into the object space, and hits are rotated back out.
+///
+/// The bounding box is computed once when the rotation is created, for the
+/// time range `0.0..1.0`. Most objects have the same box at any time, but
+/// moving ones do not, so the box for any other time range is computed again
+/// when it is asked for.
+#[derive(Debug, Clone)]
 pub struct Rotate<H: Hit> {
     object: H,
-    /// Rotation angle in radians
-    angle: f64,
-    /// Sine of rotation angle, used for lazy evaluation
+    /// Sine of rotation angle
     sin: f64,
-    /// Cosine of rotation angle, used for lazy evaluation
+    /// Cosine of rotation angle
     cos: f64,
+    /// Unit vector of the axis to rotate around, right-handed
+    axis: Vec3<f64>,
+    /// Point the axis goes through
+    pivot: Point3,
+    /// Bounding box over [`PRECOMPUTED_TIME_RANGE`]
+    bounding_box: Option<AABB>,
+}
+
+impl<H: Hit> Rotate<H> {
+    /// Rotate by `degree` around `axis` through `pivot`, counterclockwise when
+    /// `axis` points towards the viewer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` is zero or not finite.
+    pub fn new_axis(object: H, axis: Vec3<f64>, degree: f64, pivot: Point3) -> Self {
+        let length = axis.norm();
+        assert!(
+            length > 0.0 && length.is_finite(),
+            "rotation axis must be non-zero and finite"
+        );
+        let (sin, cos) = degree.to_radians().sin_cos();
+
+        let mut rotate = Self {
+            object,
+            sin,
+            cos,
+            axis: axis / length,
+            pivot,
+            bounding_box: None,
+        };
+        rotate.bounding_box = rotate.rotated_bounding_box(PRECOMPUTED_TIME_RANGE);
+        rotate
+    }
+
+    pub fn new_x(object: H, degree: f64) -> Self {
+        Self::new_axis(object, -Vec3::unit_x(), degree, Point3::zeros())
+    }
+
+    pub fn new_y(object: H, degree: f64) -> Self {
+        Self::new_axis(object, Vec3::unit_y(), degree, Point3::zeros())
+    }
+
+    pub fn new_z(object: H, degree: f64) -> Self {
+        Self::new_axis(object, -Vec3::unit_z(), degree, Point3::zeros())
+    }
+
+    /// Vector rotated from the world space to the object space.
+    fn rotate(&self, vector: &Vec3<f64>) -> Vec3<f64> {
+        rodrigues(self.axis, -self.sin, self.cos, vector)
+    }
+
+    /// Point rotated from the world space to the object space.
+    fn rotate_point(&self, point: &Point3) -> Point3 {
+        self.pivot + self.rotate(&(*point - self.pivot))
+    }
+
+    fn rotate_ray(&self, ray: &Ray) -> Ray {
+        let origin = self.rotate_point(&ray.origin());
+        let direction = self.rotate(&ray.direction());
+        Ray::new(origin, direction, ray.time())
+    }
+
+    /// Vector rotated from the object space to the world space.
+    fn rotate_inv(&self, vector: &Vec3<f64>) -> Vec3<f64> {
+        rodrigues(self.axis, self.sin, self.cos, vector)
+    }
+
+    fn rotate_point_inv(&self, point: &Point3) -> Point3 {
+        self.pivot + self.rotate_inv(&(*point - self.pivot))
+    }
+
+    /// Box of the object over `time_range`, rotated out of the object space.
+    fn rotated_bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.object.bounding_box(time_range).map(|aabb| {
+            aabb.into_iter_corners().fold(AABB::EMPTY, |aabb, corner| {
+                aabb.include(&self.rotate_point_inv(&corner))
+            })
+        })
+    }
+}
+
+/// Rotate `vector` around the unit vector `axis`, right-handed, by the angle
+/// with `sin` and `cos`, using Rodrigues' rotation formula.
+fn rodrigues(axis: Vec3<f64>, sin: f64, cos: f64, vector: &Vec3<f64>) -> Vec3<f64> {
+    *vector * cos + axis.cross(*vector) * sin + axis * (axis.dot(*vector) * (1.0 - cos))
+}
+
+/// Rotate `point` around `axis[0]`, by the angle with `sin` and `cos`, from
+/// `axis[1]` towards `axis[2]`.
+fn rotate_about(axis: [usize; 3], sin: f64, cos: f64, point: &Vec3<f64>) -> Vec3<f64> {
+    let mut vec = Vec3::zeros();
+    vec[axis[0]] = point[axis[0]];
+    vec[axis[1]] = cos * point[axis[1]] - sin * point[axis[2]];
+    vec[axis[2]] = sin * point[axis[1]] + cos * point[axis[2]];
+    vec
+}
+
+impl<H: Hit> Hit for Rotate<H> {
+    fn hit(&self, ray: crate::Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let rotated_ray = self.rotate_ray(&ray);
+
+        rotated_ray.hit(&self.object, t_range).map(|hit| {
+            let point = self.rotate_point_inv(&hit.point);
+            let normal_outward = self.rotate_inv(&hit.normal_outward);
+            hit.transformed(&ray, point, normal_outward, |vector| {
+                self.rotate_inv(&vector)
+            })
+        })
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        if time_range == PRECOMPUTED_TIME_RANGE {
+            self.bounding_box.clone()
+        } else {
+            self.rotated_bounding_box(time_range)
+        }
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.object.hit_any(&self.rotate_ray(ray), t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.object
+            .pdf_value(self.rotate_point(&origin), self.rotate(&direction), time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.object
+            .random_point_towards(self.rotate_point(&origin), time)
+            .map(|direction| self.rotate_inv(&direction))
+    }
+}
+
+/// Number of times the rotation is sampled for the bounding box.
+const ANIMATED_BOUNDS_SAMPLES: usize = 16;
+
+/// Instance rotating around a coordinate axis over time, for motion blur.
+///
+/// The angle goes linearly from the first to the last of `degrees` over
+/// `time_range`, and stays there before and after it. Like [`Rotate`], the
+/// angle turns the same way for the same degrees, but it is computed for the
+/// time of each ray.
+#[derive(Debug, Clone)]
+pub struct RotateAnimated<H: Hit> {
+    object: H,
+    /// Rotation angles in radians at the start and end of `time_range`
+    angles: Range<f64>,
+    time_range: Range<f64>,
     /// Rotate axis, first index is the axis to rotate around
     axis: [usize; 3],
-    /// Time range of bounding box, used for lazy evaluation
-    time_range: RwLock<Option<(f64, f64)>>,
-    /// Bounding box of the object, lazily evaluated
-    bounding_box: RwLock<Option<AABB>>,
 }
 
-impl<H: Hit + Clone> Clone for Rotate<H> {
-    fn clone(&self) -> Self {
+impl<H: Hit> RotateAnimated<H> {
+    fn new(object: H, degrees: Range<f64>, time_range: Range<f64>, axis: [usize; 3]) -> Self {
         Self {
-            object: self.object.clone(),
-            angle: self.angle,
-            sin: self.sin,
-            cos: self.cos,
-            axis: self.axis,
-            time_range: RwLock::new(None),
-            bounding_box: RwLock::new(None),
+            object,
+            angles: degrees.start.to_radians()..degrees.end.to_radians(),
+            time_range,
+            axis,
         }
     }
+
+    pub fn new_x(object: H, degrees: Range<f64>, time_range: Range<f64>) -> Self {
+        Self::new(object, degrees, time_range, [0, 1, 2])
+    }
+
+    pub fn new_y(object: H, degrees: Range<f64>, time_range: Range<f64>) -> Self {
+        Self::new(object, degrees, time_range, [1, 0, 2])
+    }
+
+    pub fn new_z(object: H, degrees: Range<f64>, time_range: Range<f64>) -> Self {
+        Self::new(object, degrees, time_range, [2, 0, 1])
+    }
+
+    /// Rotation angle in radians at `time`.
+    fn angle(&self, time: f64) -> f64 {
+        let duration = self.time_range.end - self.time_range.start;
+        if duration == 0.0 {
+            return self.angles.end;
+        }
+        let ratio = ((time - self.time_range.start) / duration).clamp(0.0, 1.0);
+        self.angles.start + (self.angles.end - self.angles.start) * ratio
+    }
+
+    /// Ray moved into the object space at the time of the ray, and the sine
+    /// and cosine of the angle at that time.
+    fn rotate_ray(&self, ray: &Ray) -> (Ray, f64, f64) {
+        let (sin, cos) = self.angle(ray.time()).sin_cos();
+        let origin = rotate_about(self.axis, sin, cos, &ray.origin());
+        let direction = rotate_about(self.axis, sin, cos, &ray.direction());
+        (Ray::new(origin, direction, ray.time()), sin, cos)
+    }
 }
 
-impl<H: Hit> Rotate<H> {
-    fn new(object: H, degree: f64, axis: [usize; 3]) -> Self {
-        let angle = degree.to_radians();
-        let sin = angle.sin();
-        let cos = angle.cos();
+impl<H: Hit> Hit for RotateAnimated<H> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let (rotated_ray, sin, cos) = self.rotate_ray(&ray);
+
+        rotated_ray.hit(&self.object, t_range).map(|hit| {
+            let point = rotate_about(self.axis, -sin, cos, &hit.point);
+            let normal_outward = rotate_about(self.axis, -sin, cos, &hit.normal_outward);
+            hit.transformed(&ray, point, normal_outward, |vector| {
+                rotate_about(self.axis, -sin, cos, &vector)
+            })
+        })
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        let aabb = self.object.bounding_box(time_range.clone())?;
+        let rotated_at = |time: f64| {
+            let (sin, cos) = self.angle(time).sin_cos();
+            aabb.clone()
+                .into_iter_corners()
+                .fold(AABB::EMPTY, |rotated, corner| {
+                    rotated.include(&rotate_about(self.axis, -sin, cos, &corner))
+                })
+        };
+
+        // between two samples, a corner at distance `r` from the axis moves
+        // along an arc at most `r (1 - cos(step / 2))` off the chord
+        let step = (self.angle(time_range.end) - self.angle(time_range.start)).abs()
+            / (ANIMATED_BOUNDS_SAMPLES - 1) as f64;
+        let radius = aabb
+            .clone()
+            .into_iter_corners()
+            .map(|corner| corner[self.axis[1]].hypot(corner[self.axis[2]]))
+            .fold(0.0, f64::max);
+        let padding = radius * (1.0 - (step / 2.0).cos());
+
+        Some(swept_bounds(
+            time_range,
+            ANIMATED_BOUNDS_SAMPLES,
+            padding,
+            rotated_at,
+        ))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.object.hit_any(&self.rotate_ray(ray).0, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let (rotated, _, _) = self.rotate_ray(&Ray::new(origin, direction, time));
+        self.object
+            .pdf_value(rotated.origin(), rotated.direction(), time)
+    }
 
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        let (sin, cos) = self.angle(time).sin_cos();
+        let origin = rotate_about(self.axis, sin, cos, &origin);
+        self.object
+            .random_point_towards(origin, time)
+            .map(|direction| rotate_about(self.axis, -sin, cos, &direction))
+    }
+}
+
+/// Instance of an object rotated by a quaternion.
+///
+/// Unlike nesting [`Rotate`], any rotation is a single wrapper.
+#[derive(Debug, Clone)]
+pub struct RotateQuat<H: Hit> {
+    object: H,
+    /// Rotation from object space to world space, normalized
+    rotation: Quat,
+}
+
+impl<H: Hit> RotateQuat<H> {
+    pub fn new(object: H, rotation: Quat) -> Self {
         Self {
             object,
-            angle,
-            sin,
-            cos,
-            axis,
-            time_range: RwLock::new(None),
-            bounding_box: RwLock::new(None),
+            rotation: rotation.normalized(),
         }
     }
 
-    pub fn new_x(object: H, degree: f64) -> Self {
-        Self::new(object, degree, [0, 1, 2])
+    pub fn rotation(&self) -> Quat {
+        self.rotation
     }
 
-    pub fn new_y(object: H, degree: f64) -> Self {
-        Self::new(object, degree, [1, 0, 2])
+    fn rotate_ray(&self, ray: &Ray) -> Ray {
+        let inverse = self.rotation.conjugate();
+        let origin = inverse.rotate_vec(ray.origin());
+        let direction = inverse.rotate_vec(ray.direction());
+        Ray::new(origin, direction, ray.time())
     }
+}
 
-    pub fn new_z(object: H, degree: f64) -> Self {
-        Self::new(object, degree, [2, 0, 1])
+impl<H: Hit> Hit for RotateQuat<H> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let rotated_ray = self.rotate_ray(&ray);
+
+        rotated_ray.hit(&self.object, t_range).map(|hit| {
+            let point = self.rotation.rotate_vec(hit.point);
+            let normal_outward = self.rotation.rotate_vec(hit.normal_outward);
+            hit.transformed(&ray, point, normal_outward, |vector| {
+                self.rotation.rotate_vec(vector)
+            })
+        })
     }
 
-    fn rotate(&self, point: &Vec3<f64>) -> Vec3<f64> {
-        let mut vec = Vec3::zeros();
-        vec[self.axis[0]] = point[self.axis[0]];
-        vec[self.axis[1]] = self.cos * point[self.axis[1]] - self.sin * point[self.axis[2]];
-        vec[self.axis[2]] = self.sin * point[self.axis[1]] + self.cos * point[self.axis[2]];
-        vec
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.object.bounding_box(time_range).map(|aabb| {
+            aabb.into_iter_corners().fold(AABB::EMPTY, |aabb, corner| {
+                aabb.include(&self.rotation.rotate_vec(corner))
+            })
+        })
     }
 
-    fn rotate_inv(&self, point: &Vec3<f64>) -> Vec3<f64> {
-        let mut vec = Vec3::zeros();
-        vec[self.axis[0]] = point[self.axis[0]];
-        vec[self.axis[1]] = self.cos * point[self.axis[1]] + self.sin * point[self.axis[2]];
-        vec[self.axis[2]] = -self.sin * point[self.axis[1]] + self.cos * point[self.axis[2]];
-        vec
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.object.hit_any(&self.rotate_ray(ray), t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let rotated = self.rotate_ray(&Ray::new(origin, direction, time));
+        self.object
+            .pdf_value(rotated.origin(), rotated.direction(), time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        let origin = self.rotation.conjugate().rotate_vec(origin);
+        self.object
+            .random_point_towards(origin, time)
+            .map(|direction| self.rotation.rotate_vec(direction))
     }
 }
 
-impl<H: Hit> Hit for Rotate<H> {
-    fn hit(&self, ray: crate::Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        let origin = self.rotate(&ray.origin());
-        let direction = self.rotate(&ray.direction());
-        let rotated_ray = Ray::new(origin, direction, ray.time());
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
 
-        rotated_ray.hit(&self.object, t_min, t_max).map(|mut hit| {
-            hit.point = self.rotate_inv(&hit.point);
-            hit.normal_outward = self.rotate_inv(&hit.normal_outward);
-            hit
-        })
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{material::Lambertian, object::Block, Color, HitExt};
+
+    #[test]
+    fn quat_matches_rotate_y() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let block = Block::new(
+            Point3::new(-1.0, -0.5, -0.2),
+            Point3::new(2.0, 1.0, 0.7),
+            material,
+        );
+        let rotate = Rotate::new_y(block.clone(), 90.0);
+        let quat = RotateQuat::new(block, Quat::from_axis_angle(Vec3::unit_y(), 90.0));
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut point = || Point3::new(rng.gen(), rng.gen(), rng.gen()) * 6.0 - Vec3::constant(3.0);
+        for _ in 0..500 {
+            let (from, to) = (point(), point());
+            let ray = Ray::new(from, to - from, 0.0);
+            let expected = rotate.hit(ray.clone(), Interval::from_min(1e-6));
+            let actual = quat.hit(ray, Interval::from_min(1e-6));
+            assert_eq!(actual.is_some(), expected.is_some());
+            if let (Some(actual), Some(expected)) = (actual, expected) {
+                assert!((actual.t - expected.t).abs() < 1e-9);
+                assert!((actual.point - expected.point).norm() < 1e-9);
+                assert!((actual.normal_outward - expected.normal_outward).norm() < 1e-9);
+            }
+        }
+
+        let (expected, actual) = (rotate.bounding_box(0.0..1.0), quat.bounding_box(0.0..1.0));
+        let (expected, actual) = (expected.unwrap(), actual.unwrap());
+        assert!((expected.min() - actual.min()).norm() < 1e-9);
+        assert!((expected.max() - actual.max()).norm() < 1e-9);
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB> {
-        // If the time range is the same as the last time we calculated the bounding box, we can
-        // return the cached value.
+    #[test]
+    fn animated_rotation_sweeps() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let blade = Block::new(
+            Point3::new(-2.0, -0.1, -0.05),
+            Point3::new(2.0, 0.1, 0.05),
+            material,
+        );
+        let spinning = RotateAnimated::new_z(blade.clone(), 0.0..90.0, 0.0..1.0);
 
-        if let Some(time_range) = *self.time_range.read().unwrap() {
-            if time_range.0 == time_from && time_range.1 == time_to {
-                return self.bounding_box.read().unwrap().clone();
+        // at each end of the shutter, the same as a fixed rotation
+        for (time, degree) in [(0.0, 0.0), (0.5, 45.0), (1.0, 90.0), (2.0, 90.0)] {
+            let fixed = Rotate::new_z(blade.clone(), degree);
+            let origin = Point3::new(0.0, 0.0, -5.0);
+            let mut rng = StdRng::seed_from_u64(7);
+            for _ in 0..100 {
+                let target = Point3::new(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0), 0.0);
+                let ray = Ray::new(origin, target - origin, time);
+                let expected = fixed.hit(ray.clone(), Interval::from_min(0.0));
+                let actual = spinning.hit(ray, Interval::from_min(0.0));
+                assert_eq!(actual.is_some(), expected.is_some());
+                if let (Some(actual), Some(expected)) = (actual, expected) {
+                    assert!((actual.point - expected.point).norm() < 1e-9);
+                    assert!((actual.normal_outward - expected.normal_outward).norm() < 1e-9);
+                }
             }
+        }
+
+        // the swept box holds the blade at every angle in between
+        let swept = spinning.bounding_box(0.0..1.0).unwrap();
+        for degree in (0..=90).map(f64::from) {
+            let aabb = Rotate::new_z(blade.clone(), degree)
+                .bounding_box(0.0..1.0)
+                .unwrap();
+            assert!((0..3).all(|i| swept.min[i] <= aabb.min[i] && aabb.max[i] <= swept.max[i]));
+        }
+        // but only over the queried time
+        let early = spinning.bounding_box(0.0..0.1).unwrap();
+        assert!(early.max.y() < 0.5);
+    }
+
+    #[test]
+    fn rotation_about_own_center() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let axis = Vec3::new(1.0, 1.0, 0.0);
+        let rotated_at = |offset: Vec3<f64>| {
+            let block = Block::new(offset, offset + Vec3::new(2.0, 1.0, 4.0), material.clone());
+            let pivot = offset + Vec3::new(1.0, 0.5, 2.0);
+            Rotate::new_axis(block, axis, 45.0, pivot)
         };
 
-        // Otherwise, we need to calculate the bounding box again.
-        *self.time_range.write().unwrap() = Some((time_from, time_to));
-        *self.bounding_box.write().unwrap() =
-            self.object.bounding_box(time_from, time_to).map(|aabb| {
-                aabb.into_iter_corners().fold(AABB::EMPTY, |aabb, corner| {
-                    aabb.include(&self.rotate(&corner))
-                })
-            });
+        let here = rotated_at(Vec3::zeros()).bounding_box(0.0..1.0).unwrap();
+        let offset = Vec3::new(-30.0, 12.5, 7.0);
+        let there = rotated_at(offset).bounding_box(0.0..1.0).unwrap();
+        assert!((there.min() - offset - here.min()).norm() < 1e-9);
+        assert!((there.max() - offset - here.max()).norm() < 1e-9);
+        // the center stays where it was
+        let center = (here.min() + here.max()) / 2.0;
+        assert!((center - Vec3::new(1.0, 0.5, 2.0)).norm() < 1e-9);
+
+        // and hits agree with rotating about the origin between two translations
+        let pivot = offset + Vec3::new(1.0, 0.5, 2.0);
+        let nested = RotateQuat::new(
+            Block::new(offset, offset + Vec3::new(2.0, 1.0, 4.0), material.clone())
+                .translate(-pivot),
+            Quat::from_axis_angle(axis, 45.0),
+        )
+        .translate(pivot);
+        let rotated = rotated_at(offset);
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut point =
+            || pivot + Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 10.0 - Vec3::constant(5.0);
+        for _ in 0..500 {
+            let (from, to) = (point(), point());
+            let ray = Ray::new(from, to - from, 0.0);
+            let expected = nested.hit(ray.clone(), Interval::from_min(1e-6));
+            let actual = rotated.hit(ray, Interval::from_min(1e-6));
+            assert_eq!(actual.is_some(), expected.is_some());
+            if let (Some(actual), Some(expected)) = (actual, expected) {
+                assert!((actual.point - expected.point).norm() < 1e-9);
+                assert!((actual.normal_outward - expected.normal_outward).norm() < 1e-9);
+            }
+        }
+    }
+
+    /// Block that counts how many times its box is asked for.
+    #[derive(Debug)]
+    struct Counted(Block, AtomicUsize);
+
+    impl Hit for Counted {
+        fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+            self.0.hit(ray, t_range)
+        }
+
+        fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+            self.1.fetch_add(1, Ordering::Relaxed);
+            self.0.bounding_box(time_range)
+        }
+    }
+
+    #[test]
+    fn bounding_box_is_precomputed() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let block = Block::new(Point3::zeros(), Point3::new(1.0, 2.0, 3.0), material);
+        let rotate = Rotate::new_y(Counted(block, AtomicUsize::new(0)), 30.0);
+        assert_eq!(rotate.object.1.load(Ordering::Relaxed), 1);
+
+        // the default time range never reaches the object again
+        let corners = |aabb: Option<AABB>| aabb.map(|aabb| (aabb.min, aabb.max));
+        let expected = corners(rotate.bounding_box(0.0..1.0));
+        for _ in 0..10 {
+            assert_eq!(corners(rotate.bounding_box(0.0..1.0)), expected);
+        }
+        assert_eq!(rotate.object.1.load(Ordering::Relaxed), 1);
 
-        self.bounding_box.read().unwrap().clone()
+        // other time ranges are computed, but not stored
+        assert_eq!(corners(rotate.bounding_box(0.0..0.5)), expected);
+        assert_eq!(rotate.object.1.load(Ordering::Relaxed), 2);
+        assert_eq!(corners(rotate.bounding_box.clone()), expected);
     }
 }
@@ -0,0 +1,137 @@
+use std::ops::Range;
+
+use crate::{Hit, Interval, Point3, Ray, Vec3};
+
+use super::{solid_angle_ratio, OutwardHitRecord, AABB};
+
+/// Instance scaling, i.e. stretching an object along each axis
+///
+/// Like [`super::translation::Translate`], the ray is transformed by the
+/// inverse instead. Since the transformation is linear, a point on the ray has
+/// the same parameter `t` in both spaces.
+#[derive(Debug, Clone)]
+pub struct Scale<H: Hit> {
+    object: H,
+    factors: Vec3<f64>,
+}
+
+impl<H: Hit> Scale<H> {
+    /// Scale by `factors` along the x, y and z axes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a factor is zero or not finite.
+    pub fn new(object: H, factors: Vec3<f64>) -> Self {
+        assert!(
+            factors
+                .iter()
+                .all(|factor| factor.is_finite() && *factor != 0.0),
+            "scale factors must be finite and non-zero"
+        );
+        Self { object, factors }
+    }
+
+    /// Scale by `factor` along every axis.
+    pub fn uniform(object: H, factor: f64) -> Self {
+        Self::new(object, Vec3::constant(factor))
+    }
+
+    fn scale_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            ray.origin() / self.factors,
+            ray.direction() / self.factors,
+            ray.time(),
+        )
+    }
+}
+
+impl<H: Hit> Hit for Scale<H> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.scale_ray(&ray).hit(&self.object, t_range).map(|hit| {
+            let point = hit.point * self.factors;
+            // normals transform by the inverse transpose of the scaling
+            let normal_outward = (hit.normal_outward / self.factors).normalized();
+            hit.transformed(&ray, point, normal_outward, |vector| vector * self.factors)
+        })
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        // negative factors mirror the box, so the corners are sorted again
+        self.object.bounding_box(time_range).map(|aabb| {
+            aabb.into_iter_corners().fold(AABB::EMPTY, |aabb, corner| {
+                aabb.include(&(corner * self.factors))
+            })
+        })
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.object.hit_any(&self.scale_ray(ray), t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let scaled = self.scale_ray(&Ray::new(origin, direction, time));
+        let determinant = self.factors.x() * self.factors.y() * self.factors.z();
+        self.object
+            .pdf_value(scaled.origin(), scaled.direction(), time)
+            * solid_angle_ratio(direction, scaled.direction(), determinant)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.object
+            .random_point_towards(origin / self.factors, time)
+            .map(|direction| direction * self.factors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{material::Lambertian, Color, HitExt, Point3, Sphere};
+
+    #[test]
+    fn scaled_sphere_is_an_ellipsoid() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let radii = Vec3::new(3.0, 1.0, 0.5);
+        let ellipsoid = Sphere::new(Point3::zeros(), 1.0, material).scale(radii);
+
+        let aabb = ellipsoid.bounding_box(0.0..1.0).unwrap();
+        assert_eq!((aabb.min, aabb.max), (-radii, radii));
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut hits = 0;
+        for _ in 0..200 {
+            let origin = Point3::new(rng.gen(), rng.gen(), rng.gen()) * 10.0 - Vec3::constant(5.0);
+            let target = Point3::new(rng.gen(), rng.gen(), rng.gen()) * 2.0 - Vec3::ones();
+            let ray = Ray::new(origin, (target - origin) * 0.3, 0.0);
+
+            // sum of (x_i / r_i)^2 = 1 along the ray
+            let (o, d) = (origin / radii, ray.direction() / radii);
+            let (a, half_b, c) = (d.dot(d), o.dot(d), o.dot(o) - 1.0);
+            let discriminant = half_b * half_b - a * c;
+            let expected = (discriminant >= 0.0)
+                .then(|| {
+                    let sqrt = discriminant.sqrt();
+                    [(-half_b - sqrt) / a, (-half_b + sqrt) / a]
+                        .into_iter()
+                        .find(|t| *t >= 1e-6)
+                })
+                .flatten();
+
+            let actual = ellipsoid.hit(ray.clone(), Interval::from_min(1e-6));
+            assert_eq!(actual.is_some(), expected.is_some());
+            if let (Some(hit), Some(t)) = (actual, expected) {
+                hits += 1;
+                assert!((hit.t - t).abs() < 1e-9);
+                assert!((hit.point - ray.at(t)).norm() < 1e-9);
+                // the gradient of the implicit function
+                let normal = (hit.point / (radii * radii)).normalized();
+                assert!((hit.normal_outward - normal).norm() < 1e-9);
+            }
+        }
+        assert!(hits > 20);
+    }
+}
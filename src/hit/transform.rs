@@ -0,0 +1,198 @@
+use std::ops::Range;
+
+use crate::{Affine, Hit, Interval, Mat3, Point3, Quat, Ray, Vec3};
+
+use super::{solid_angle_ratio, OutwardHitRecord, AABB};
+
+/// Instance placed by an affine map, such as any combination of rotations,
+/// scaling and translations, in a single wrapper.
+///
+/// The builder methods compose in the order they are called, e.g.
+/// `Transform::new(block).rotate_y(15.0).translate(offset)` rotates the
+/// block first. Rays are moved into the object space by the inverse map, and
+/// hits are moved back out, with the normal transformed by the inverse
+/// transpose. The map is affine, so the hit keeps the parameter `t` of the
+/// original ray.
+#[derive(Debug, Clone)]
+pub struct Transform<H: Hit> {
+    object: H,
+    /// Map from object space to world space
+    to_world: Affine,
+    /// Inverse of `to_world`
+    to_object: Affine,
+}
+
+impl<H: Hit> Transform<H> {
+    /// The object as it is, ready for the builder methods.
+    pub fn new(object: H) -> Self {
+        Self {
+            object,
+            to_world: Affine::IDENTITY,
+            to_object: Affine::IDENTITY,
+        }
+    }
+
+    /// The object placed by `to_world`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `to_world` can not be inverted.
+    pub fn with_matrix(object: H, to_world: Affine) -> Self {
+        Self::new(object).then(to_world)
+    }
+
+    pub fn matrix(&self) -> Affine {
+        self.to_world
+    }
+
+    /// Apply `map` after the current transformation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result can not be inverted.
+    pub fn then(mut self, map: Affine) -> Self {
+        self.to_world = map * self.to_world;
+        self.to_object = self
+            .to_world
+            .inverse()
+            .expect("transformation must be invertible");
+        self
+    }
+
+    /// Rotate by `degree` around the X axis, the same way as [`super::rotation::Rotate::new_x`].
+    pub fn rotate_x(self, degree: f64) -> Self {
+        self.rotate(Quat::from_axis_angle(Vec3::unit_x(), -degree))
+    }
+
+    /// Rotate by `degree` around the Y axis, the same way as [`super::rotation::Rotate::new_y`].
+    pub fn rotate_y(self, degree: f64) -> Self {
+        self.rotate(Quat::from_axis_angle(Vec3::unit_y(), degree))
+    }
+
+    /// Rotate by `degree` around the Z axis, the same way as [`super::rotation::Rotate::new_z`].
+    pub fn rotate_z(self, degree: f64) -> Self {
+        self.rotate(Quat::from_axis_angle(Vec3::unit_z(), -degree))
+    }
+
+    /// Rotate by the quaternion `rotation`.
+    pub fn rotate(self, rotation: Quat) -> Self {
+        self.then(Affine::from_linear(Mat3::from_quat(rotation.normalized())))
+    }
+
+    /// Scale by `factors` along each axis.
+    pub fn scale(self, factors: Vec3<f64>) -> Self {
+        self.then(Affine::from_linear(Mat3::from_diagonal(factors)))
+    }
+
+    /// Move by `offset`.
+    pub fn translate(self, offset: Vec3<f64>) -> Self {
+        self.then(Affine::from_translation(offset))
+    }
+
+    fn transform_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.to_object.transform_point(ray.origin()),
+            self.to_object.transform_vector(ray.direction()),
+            ray.time(),
+        )
+    }
+}
+
+impl<H: Hit> Hit for Transform<H> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.transform_ray(&ray)
+            .hit(&self.object, t_range)
+            .map(|hit| {
+                let point = self.to_world.transform_point(hit.point);
+                // the inverse transpose of the linear part
+                let normal = self.to_object.linear.transpose() * hit.normal_outward;
+                hit.transformed(&ray, point, normal.normalized(), |vector| {
+                    self.to_world.transform_vector(vector)
+                })
+            })
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.object.bounding_box(time_range).map(|aabb| {
+            aabb.into_iter_corners().fold(AABB::EMPTY, |aabb, corner| {
+                aabb.include(&self.to_world.transform_point(corner))
+            })
+        })
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.object.hit_any(&self.transform_ray(ray), t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let transformed = self.transform_ray(&Ray::new(origin, direction, time));
+        let determinant = self.to_world.linear.determinant();
+        self.object
+            .pdf_value(transformed.origin(), transformed.direction(), time)
+            * solid_angle_ratio(direction, transformed.direction(), determinant)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.object
+            .random_point_towards(self.to_object.transform_point(origin), time)
+            .map(|direction| self.to_world.transform_vector(direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{material::Lambertian, object::Block, Color, HitExt, Point3};
+
+    #[test]
+    fn matches_nested_instances() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let block = Block::new(
+            Point3::new(-1.0, -0.5, -0.2),
+            Point3::new(2.0, 1.0, 0.7),
+            material,
+        );
+        let offset = Vec3::new(0.5, -1.0, 2.0);
+        let factors = Vec3::new(1.5, 0.5, 2.0);
+        let nested = block
+            .clone()
+            .scale(factors)
+            .rotate_x(20.0)
+            .rotate_y(-35.0)
+            .rotate_z(50.0)
+            .translate(offset);
+        let transform = Transform::new(block)
+            .scale(factors)
+            .rotate_x(20.0)
+            .rotate_y(-35.0)
+            .rotate_z(50.0)
+            .translate(offset);
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut point = || Point3::new(rng.gen(), rng.gen(), rng.gen()) * 8.0 - Vec3::constant(4.0);
+        for _ in 0..500 {
+            let (from, to) = (point(), point());
+            let ray = Ray::new(from, to - from, 0.0);
+            let expected = nested.hit(ray.clone(), Interval::from_min(1e-6));
+            let actual = transform.hit(ray, Interval::from_min(1e-6));
+            assert_eq!(actual.is_some(), expected.is_some());
+            if let (Some(actual), Some(expected)) = (actual, expected) {
+                assert!((actual.t - expected.t).abs() < 1e-9);
+                assert!((actual.point - expected.point).norm() < 1e-9);
+                assert!((actual.normal_outward - expected.normal_outward).norm() < 1e-9);
+            }
+        }
+
+        // the single wrapper boxes the corners once, so it is no larger
+        let (nested, transform) = (
+            nested.bounding_box(0.0..1.0).unwrap(),
+            transform.bounding_box(0.0..1.0).unwrap(),
+        );
+        assert!((0..3).all(|i| nested.min[i] <= transform.min[i] + 1e-9
+            && transform.max[i] <= nested.max[i] + 1e-9));
+    }
+}
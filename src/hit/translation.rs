@@ -1,4 +1,6 @@
-use crate::{Hit, Vec3, Ray};
+use std::ops::Range;
+
+use crate::{Hit, Interval, Point3, Ray, Vec3};
 
 use super::OutwardHitRecord;
 
@@ -18,17 +20,30 @@ impl<H: Hit> Translate<H> {
 }
 
 impl<H: Hit> Hit for Translate<H> {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
         let ray = ray.move_origin_by(-self.offset);
-        ray.hit(&self.object, t_min, t_max).map(|mut hit| {
+        ray.hit(&self.object, t_range).map(|mut hit| {
             hit.point += self.offset;
             hit
         })
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<super::AABB> {
-        self.object.bounding_box(time_from, time_to).map(|aabb| {
-            aabb.move_by(self.offset)
-        })
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<super::AABB> {
+        self.object
+            .bounding_box(time_range)
+            .map(|aabb| aabb.move_by(self.offset))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        let ray = ray.clone().move_origin_by(-self.offset);
+        self.object.hit_any(&ray, t_range)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        self.object.pdf_value(origin - self.offset, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        self.object.random_point_towards(origin - self.offset, time)
     }
 }
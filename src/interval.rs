@@ -0,0 +1,117 @@
+/// Closed interval `[min, max]`, mostly used for the range of the ray
+/// parameter `t` in which hits are accepted.
+///
+/// An interval with `min > max` is empty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Interval {
+    /// Interval containing nothing.
+    pub const EMPTY: Self = Self::new(f64::INFINITY, f64::NEG_INFINITY);
+    /// Interval containing every number.
+    pub const UNIVERSE: Self = Self::new(f64::NEG_INFINITY, f64::INFINITY);
+
+    pub const fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// Interval from `min` to positive infinity.
+    pub const fn from_min(min: f64) -> Self {
+        Self::new(min, f64::INFINITY)
+    }
+
+    pub fn size(&self) -> f64 {
+        self.max - self.min
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min > self.max || self.min.is_nan() || self.max.is_nan()
+    }
+
+    /// Whether `x` is inside the interval, including both ends.
+    pub fn contains(&self, x: f64) -> bool {
+        self.min <= x && x <= self.max
+    }
+
+    /// Whether `x` is inside the interval, excluding both ends.
+    pub fn surrounds(&self, x: f64) -> bool {
+        self.min < x && x < self.max
+    }
+
+    /// Closest point of the interval to `x`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval is empty.
+    pub fn clamp(&self, x: f64) -> f64 {
+        x.clamp(self.min, self.max)
+    }
+
+    /// Interval padded by `delta / 2` on both sides.
+    pub fn expand(&self, delta: f64) -> Self {
+        let padding = delta / 2.0;
+        Self::new(self.min - padding, self.max + padding)
+    }
+
+    /// Same interval starting at `min` instead.
+    pub fn with_min(self, min: f64) -> Self {
+        Self::new(min, self.max)
+    }
+
+    /// Same interval ending at `max` instead, e.g. at the closest hit found so far.
+    pub fn with_max(self, max: f64) -> Self {
+        Self::new(self.min, max)
+    }
+
+    /// Intersection of two intervals.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self::new(self.min.max(other.min), self.max.min(other.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_and_surrounds() {
+        let interval = Interval::new(1.0, 2.0);
+        assert!(interval.contains(1.0) && interval.contains(2.0));
+        assert!(!interval.surrounds(1.0) && !interval.surrounds(2.0));
+        assert!(interval.surrounds(1.5));
+        assert!(!interval.contains(0.5) && !interval.contains(f64::NAN));
+
+        assert!(!Interval::EMPTY.contains(0.0));
+        assert!(Interval::UNIVERSE.surrounds(f64::MAX));
+        assert!(!Interval::UNIVERSE.surrounds(f64::INFINITY));
+    }
+
+    #[test]
+    fn clamp_and_expand() {
+        let interval = Interval::new(1.0, 2.0);
+        assert_eq!(interval.clamp(0.0), 1.0);
+        assert_eq!(interval.clamp(1.5), 1.5);
+        assert_eq!(interval.clamp(3.0), 2.0);
+
+        assert_eq!(interval.expand(1.0), Interval::new(0.5, 2.5));
+        assert_eq!(interval.expand(1.0).size(), 2.0);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(Interval::EMPTY.is_empty());
+        assert!(!Interval::UNIVERSE.is_empty());
+        assert!(!Interval::new(1.0, 1.0).is_empty());
+        assert!(Interval::new(1.0, f64::NAN).is_empty());
+        assert!(Interval::new(0.0, 1.0)
+            .intersect(&Interval::new(2.0, 3.0))
+            .is_empty());
+        assert_eq!(
+            Interval::new(0.0, 2.0).intersect(&Interval::new(1.0, 3.0)),
+            Interval::new(1.0, 2.0)
+        );
+    }
+}
@@ -1,36 +1,143 @@
+pub mod background;
+pub mod buffer;
 pub mod camera;
+pub mod denoise;
 pub mod hit;
+mod interval;
 pub mod material;
 pub mod object;
+pub mod output;
+pub mod pdf;
+pub mod progress;
+pub mod random;
 mod ray;
+pub mod scenes;
+pub mod sink;
+pub mod target;
 pub mod texture;
 mod vec3;
 
+use background::Background;
+pub use buffer::RenderBuffer;
 pub use camera::Camera;
-pub use hit::Hit;
-use indicatif::ParallelProgressIterator;
+use denoise::DenoiseInputs;
+use hit::BVH;
+pub use hit::{Hit, HitExt};
+pub use interval::Interval;
 use log::debug;
+use material::ScatterRecord;
+use pdf::{HitPdf, MixturePdf, Pdf};
 pub use material::Material;
 pub use object::Sphere;
 pub use object::World;
-use rand::Rng;
+use progress::{ProgressBarSink, ProgressSink, ProgressTracker};
 pub use ray::Ray;
-pub use vec3::{Color, Point3, Vec3};
+pub use sink::{CancelToken, PixelSink, SharedFramebuffer};
+pub use target::{RenderStats, RenderTarget, StopReason};
+pub use vec3::{Affine, Color, Mat3, Onb, Point3, Quat, Vec3, VISIBLE_WAVELENGTHS};
 
 use rayon::prelude::*;
-use std::{error::Error, io::Write};
+use std::{error::Error, io::Write, sync::Arc};
+
+/// What the ray tracer computes for each pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Regular path-traced shading.
+    #[default]
+    Shaded,
+    /// Debug view of the acceleration structure. Each pixel shows how many BVH
+    /// nodes the primary ray through its center visited, mapped through
+    /// [`Color::heat`] so that `max_visits` or more visits are red.
+    BvhHeat { max_visits: u64 },
+}
+
+/// How the image is split into work items for the parallel render loop.
+///
+/// Few large items leave threads idle at the end of the frame, while many
+/// small ones cost more to schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WorkGranularity {
+    /// Rows for small images, and 32x32 tiles for large ones.
+    #[default]
+    Auto,
+    /// One item per row.
+    Rows,
+    /// Bands of the given number of rows.
+    RowBands(u64),
+    /// Tiles of the given size, smaller on the right and bottom border.
+    Tiles { width: u64, height: u64 },
+    /// One item per pixel.
+    Pixels,
+}
+
+impl WorkGranularity {
+    /// Images with more pixels than this are split into tiles by [`WorkGranularity::Auto`].
+    const AUTO_TILE_PIXELS: u64 = 256 * 256;
+
+    /// Split an image of `width * height` pixels into rectangles
+    /// `(x0, y0, x1, y1)` covering columns `x0..x1` and rows `y0..y1`, in
+    /// row-major order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a band or tile size is zero.
+    pub fn split(self, width: u64, height: u64) -> Vec<(u64, u64, u64, u64)> {
+        let (tile_width, tile_height) = match self {
+            Self::Auto if width * height > Self::AUTO_TILE_PIXELS => (32, 32),
+            Self::Auto | Self::Rows => (width.max(1), 1),
+            Self::RowBands(rows) => (width.max(1), rows),
+            Self::Tiles { width, height } => (width, height),
+            Self::Pixels => (1, 1),
+        };
+        assert!(
+            tile_width > 0 && tile_height > 0,
+            "work items must not be empty"
+        );
+
+        (0..height)
+            .step_by(tile_height as usize)
+            .flat_map(|y0| {
+                (0..width).step_by(tile_width as usize).map(move |x0| {
+                    let x1 = (x0 + tile_width).min(width);
+                    let y1 = (y0 + tile_height).min(height);
+                    (x0, y0, x1, y1)
+                })
+            })
+            .collect()
+    }
+}
 
 pub struct RayTracer<H: Hit> {
     pub world: H,
     pub camera: Camera,
-    pub background: Color,
+    /// What the rays which hit nothing see, such as a color or a sky
+    pub background: Arc<dyn Background>,
     pub max_depth: i64,
     pub samples_per_pixel: u64,
     pub image_height: u64,
+    pub render_mode: RenderMode,
+    /// When set, the random generator is reseeded from this value and the pixel
+    /// position before each pixel, which makes renders reproducible.
+    pub seed: Option<u64>,
+    /// How [`RayTracer::render`] splits the image between threads.
+    pub work_granularity: WorkGranularity,
+    /// When set, every surface that does not emit light scatters with this
+    /// material instead of its own, e.g. a gray diffuse for a clay render.
+    pub material_override: Option<Arc<dyn Material>>,
+    /// Objects which diffuse surfaces send rays towards, usually the lights.
+    /// They are sampled by [`Hit::random_point_towards`], and must also be
+    /// part of the world to be seen. Where they cannot be sampled, rays
+    /// follow the material alone, but a list should only hold objects which
+    /// can, since a list picks any of them.
+    pub lights: Option<Arc<dyn Hit>>,
 }
 
 const COLOR_MAX: u8 = 255;
 
+// To fix the shadow acne problem, which some hit rays may not at exactly t = 0
+// I have seen 0.0000000000000002775557561562895, so f64::EPSILON is not a choice here
+const T_RANGE: Interval = Interval::from_min(1e-10);
+
 impl<H: Hit> RayTracer<H> {
     fn aspect_ratio(&self) -> f64 {
         self.camera.aspect_ratio()
@@ -42,10 +149,17 @@ impl<H: Hit> RayTracer<H> {
         j: u64,
         image_width: u64,
         image_height: u64,
-        t_min: f64,
-        t_max: f64,
+        t_range: Interval,
     ) -> Color {
-        let mut rng = rand::thread_rng();
+        if let RenderMode::BvhHeat { max_visits } = self.render_mode {
+            let visits = self.visit_count(i, j, image_width, image_height, t_range);
+            return Color::heat(visits as f64 / max_visits.max(1) as f64);
+        }
+
+        if let Some(seed) = self.seed {
+            random::seed(seed ^ (j * image_width + i).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        }
+
         let (width, height) = (image_width as f64, image_height as f64);
         let (i, j) = (i as f64, height - j as f64 - 1.0);
 
@@ -56,33 +170,19 @@ impl<H: Hit> RayTracer<H> {
             let (u, v) = (i / (width - 1.0), j / (height - 1.0));
             let ray = self.camera.cast(u, v);
 
-            pixel_color_sum += ray_color(
-                ray,
-                self.background,
-                &self.world,
-                self.max_depth,
-                t_min,
-                t_max,
-            );
+            pixel_color_sum += self.ray_color(ray, t_range);
         }
 
         for run in 1..self.samples_per_pixel {
             debug!("## {} {} ({})", i, j, run);
             // u: left 0.0 -> 1.0 right
             // v: botm 0.0 -> 1.0 up
-            // rng.gen: standard distribution, [0, 1)
-            let u = (i + rng.gen::<f64>()) / (width - 1.0);
-            let v = (j + rng.gen::<f64>()) / (height - 1.0);
+            // random: standard distribution, [0, 1)
+            let u = (i + random::random::<f64>()) / (width - 1.0);
+            let v = (j + random::random::<f64>()) / (height - 1.0);
 
             let ray = self.camera.cast(u, v);
-            pixel_color_sum += ray_color(
-                ray,
-                self.background,
-                &self.world,
-                self.max_depth,
-                t_min,
-                t_max,
-            );
+            pixel_color_sum += self.ray_color(ray, t_range);
         }
 
         debug!(
@@ -92,41 +192,282 @@ impl<H: Hit> RayTracer<H> {
         pixel_color_sum / (self.samples_per_pixel as f64)
     }
 
-    pub fn trace_in<T: Write>(
+    fn ray_color(&self, ray: Ray, t_range: Interval) -> Color {
+        let material_override = self.material_override.as_deref();
+        ray_color_with_override(
+            ray,
+            self.background.as_ref(),
+            &self.world,
+            self.max_depth,
+            t_range,
+            material_override,
+            self.lights.as_deref(),
+        )
+    }
+
+    /// Returns the number of BVH nodes visited by the primary ray through the
+    /// center of pixel `(i, j)`.
+    ///
+    /// Only nodes of [`BVH`]s reachable from the world are counted, so a world
+    /// without any BVH always reports zero.
+    pub fn visit_count(
         &self,
-        buffer: &mut T,
-        t_min: f64,
-        t_max: f64,
-    ) -> Result<(), Box<dyn Error>> {
+        i: u64,
+        j: u64,
+        image_width: u64,
+        image_height: u64,
+        t_range: Interval,
+    ) -> u64 {
+        let u = i as f64 / (image_width as f64 - 1.0);
+        let v = (image_height - j - 1) as f64 / (image_height as f64 - 1.0);
+        let ray = self.camera.cast(u, v);
+
+        BVH::reset_visit_count();
+        ray.hit(&self.world, t_range);
+        BVH::visit_count()
+    }
+
+    pub fn image_width(&self) -> u64 {
+        (self.aspect_ratio() * self.image_height as f64) as u64
+    }
+
+    /// Trace every pixel of the image into a [`RenderBuffer`], reporting the
+    /// progress to `progress` after each work item of the
+    /// [`RayTracer::work_granularity`].
+    pub fn render_in_with_progress<P: ProgressSink>(
+        &self,
+        t_range: Interval,
+        progress: &P,
+    ) -> RenderBuffer {
+        let items = self
+            .work_granularity
+            .split(self.image_width(), self.image_height);
+        self.render_items(&items, t_range, &(), progress, &CancelToken::new())
+    }
+
+    /// Trace every pixel of the image into a [`RenderBuffer`], showing the
+    /// progress on a progress bar.
+    pub fn render_in(&self, t_range: Interval) -> RenderBuffer {
+        self.render_in_with_progress(t_range, &ProgressBarSink::new())
+    }
+
+    pub fn render(&self) -> RenderBuffer {
+        self.render_in(T_RANGE)
+    }
+
+    /// Render the image together with the albedo and normal of the first
+    /// surface hit, as inputs for a denoiser.
+    ///
+    /// The auxiliary buffers are averaged over as many jittered primary rays
+    /// as the color, but the rays are not the same ones.
+    pub fn render_with_aovs(&self) -> DenoiseInputs {
+        let color = self.render();
         let image_height = self.image_height;
-        let image_width: u64 = (self.aspect_ratio() * image_height as f64) as u64;
+        let image_width = self.image_width();
+
+        let (albedo, normal): (Vec<_>, Vec<_>) = (0..image_height)
+            .into_par_iter()
+            .flat_map_iter(|j| {
+                (0..image_width)
+                    .map(move |i| self.trace_aovs(i, j, image_width, image_height, T_RANGE))
+            })
+            .unzip();
 
-        writeln!(buffer, "P3")?;
-        writeln!(buffer, "{} {}", image_width, image_height)?;
-        writeln!(buffer, "{}", COLOR_MAX)?;
+        DenoiseInputs {
+            color,
+            albedo: RenderBuffer::from_pixels(image_width, image_height, albedo),
+            normal: RenderBuffer::from_pixels(image_width, image_height, normal),
+        }
+    }
 
-        // bar.set_position(j);
-        let colors = (0..image_height)
+    /// Average albedo and normal seen through pixel `(i, j)`.
+    fn trace_aovs(
+        &self,
+        i: u64,
+        j: u64,
+        image_width: u64,
+        image_height: u64,
+        t_range: Interval,
+    ) -> (Color, Vec3<f64>) {
+        let (width, height) = (image_width as f64, image_height as f64);
+        let (i, j) = (i as f64, height - j as f64 - 1.0);
+
+        let mut albedo_sum = Color::zeros();
+        let mut normal_sum = Vec3::zeros();
+        for _ in 0..self.samples_per_pixel {
+            let u = (i + random::random::<f64>()) / (width - 1.0);
+            let v = (j + random::random::<f64>()) / (height - 1.0);
+
+            let ray = self.camera.cast(u, v);
+            let (albedo, normal) = first_hit_aovs(ray, self.background.as_ref(), &self.world, t_range);
+            albedo_sum += albedo;
+            normal_sum += normal;
+        }
+
+        let samples = self.samples_per_pixel as f64;
+        (albedo_sum / samples, normal_sum / samples)
+    }
+
+    /// Trace only the pixels in columns `x0..x1` and rows `y0..y1` of the image.
+    ///
+    /// The pixels are computed exactly as in a full render, so with a `seed`
+    /// the result equals the same region of [`RayTracer::render`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the region is empty or not inside the image.
+    pub fn render_region(&self, x0: u64, y0: u64, x1: u64, y1: u64) -> RenderBuffer {
+        let image_height = self.image_height;
+        let image_width = self.image_width();
+        assert!(x0 < x1 && y0 < y1, "region must not be empty");
+        assert!(
+            x1 <= image_width && y1 <= image_height,
+            "region must be inside the image"
+        );
+
+        let colors = (y0..y1)
             .into_par_iter()
-            .progress_count(image_height)
             .flat_map(|j| {
-                (0..image_width)
+                (x0..x1)
                     .into_par_iter()
-                    .map(move |i| self.trace_single(i, j, image_width, image_height, t_min, t_max))
+                    .map(move |i| self.trace_single(i, j, image_width, image_height, T_RANGE))
             })
             .collect::<Vec<_>>();
 
-        for pixel_color in colors {
-            writeln!(buffer, "{}", pixel_color.format_color())?;
+        RenderBuffer::from_pixels(x1 - x0, y1 - y0, colors)
+    }
+
+    /// Trace the image in square tiles of `tile_size` pixels, handing each
+    /// tile to `sink` as soon as it is done.
+    ///
+    /// Tiles on the right and bottom border may be smaller.
+    pub fn render_tiles<S: PixelSink>(&self, tile_size: u64, sink: &S) -> RenderBuffer {
+        self.render_tiles_with_progress(tile_size, sink, &ProgressBarSink::new())
+    }
+
+    /// Same as [`RayTracer::render_tiles`], reporting the progress to
+    /// `progress` after each tile.
+    pub fn render_tiles_with_progress<S: PixelSink, P: ProgressSink>(
+        &self,
+        tile_size: u64,
+        sink: &S,
+        progress: &P,
+    ) -> RenderBuffer {
+        self.render_tiles_until(tile_size, sink, progress, &CancelToken::new())
+    }
+
+    /// Render the image in tiles into `framebuffer`, so that other threads
+    /// can poll it with [`SharedFramebuffer::snapshot`] while it is rendered.
+    ///
+    /// Once `cancel` is cancelled, the tiles not started yet are skipped and
+    /// stay black, both in the framebuffer and in the returned buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the framebuffer does not have the size of the image.
+    pub fn render_into_shared(
+        &self,
+        framebuffer: SharedFramebuffer,
+        cancel: CancelToken,
+    ) -> RenderBuffer {
+        const TILE_SIZE: u64 = 16;
+        assert_eq!(
+            (framebuffer.width(), framebuffer.height()),
+            (self.image_width(), self.image_height),
+            "framebuffer size must match the image"
+        );
+        self.render_tiles_until(TILE_SIZE, &framebuffer, &ProgressBarSink::new(), &cancel)
+    }
+
+    fn render_tiles_until<S: PixelSink, P: ProgressSink>(
+        &self,
+        tile_size: u64,
+        sink: &S,
+        progress: &P,
+        cancel: &CancelToken,
+    ) -> RenderBuffer {
+        assert!(tile_size > 0, "tile size must be positive");
+        let tiles = WorkGranularity::Tiles {
+            width: tile_size,
+            height: tile_size,
         }
+        .split(self.image_width(), self.image_height);
+        self.render_items(&tiles, T_RANGE, sink, progress, cancel)
+    }
 
+    /// Trace the rectangles `items` in parallel, see [`WorkGranularity::split`],
+    /// handing each to `sink` when it is done. Items not started when `cancel`
+    /// is cancelled are skipped and left black.
+    fn render_items<S: PixelSink, P: ProgressSink>(
+        &self,
+        items: &[(u64, u64, u64, u64)],
+        t_range: Interval,
+        sink: &S,
+        progress: &P,
+        cancel: &CancelToken,
+    ) -> RenderBuffer {
+        let image_height = self.image_height;
+        let image_width = self.image_width();
+        let tracker = ProgressTracker::new(
+            progress,
+            image_width * image_height * self.samples_per_pixel,
+        );
+
+        let items = items
+            .par_iter()
+            .filter(|_| !cancel.is_cancelled())
+            .map(|&(x0, y0, x1, y1)| {
+                let rays = progress::thread_ray_count();
+                let pixels = (y0..y1)
+                    .flat_map(|j| {
+                        (x0..x1).map(move |i| {
+                            self.trace_single(i, j, image_width, image_height, t_range)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                sink.on_tile(x0, y0, x1 - x0, y1 - y0, &pixels);
+                tracker.advance(
+                    pixels.len() as u64 * self.samples_per_pixel,
+                    progress::thread_ray_count() - rays,
+                );
+                (x0, y0, x1 - x0, pixels)
+            })
+            .collect::<Vec<_>>();
+
+        let mut buffer = RenderBuffer::new(image_width, image_height);
+        for (x0, y0, width, pixels) in items {
+            for (index, color) in pixels.into_iter().enumerate() {
+                let index = index as u64;
+                buffer.set(x0 + index % width, y0 + index / width, color);
+            }
+        }
+        buffer
+    }
+
+    pub fn trace_in<T: Write>(
+        &self,
+        buffer: &mut T,
+        t_range: Interval,
+    ) -> Result<(), Box<dyn Error>> {
+        self.render_in(t_range).write_ppm(buffer)?;
         Ok(())
     }
 
     pub fn trace<T: Write>(&self, buffer: &mut T) -> Result<(), Box<dyn Error>> {
-        // To fix the shadow acne problem, which some hit rays may not at exactly t = 0
-        // I have seen 0.0000000000000002775557561562895, so f64::EPSILON is not a choice here
-        self.trace_in(buffer, 1e-10, f64::INFINITY)
+        self.trace_in(buffer, T_RANGE)
+    }
+}
+
+impl RayTracer<World> {
+    /// Put the world into a BVH if it has more than `threshold` objects, with
+    /// the objects without a bounding box beside it, see
+    /// [`World::into_accelerated`], which drops its hidden groups.
+    pub fn with_auto_bvh(mut self, threshold: usize) -> Self {
+        if self.world.visible_len() > threshold {
+            let time_range = self.camera.time_range().clone();
+            self.world = self.world.into_accelerated(time_range);
+        }
+        self
     }
 }
 
@@ -134,46 +475,619 @@ impl<H: Hit> RayTracer<H> {
 ///
 /// When our ray hits a sphere, the color is red.
 ///
-/// Background color is a simple gradient, which
-/// linearly blends white and blue depending on the height of the y coordinate.
+/// Rays which hit nothing see the `background` in their direction.
 pub fn ray_color<T: Hit>(
     ray: Ray,
-    background: Color,
+    background: &dyn Background,
+    object: &T,
+    depth: i64,
+    t_range: Interval,
+) -> Color {
+    ray_color_with_override(ray, background, object, depth, t_range, None, None)
+}
+
+/// Like [`ray_color`], but surfaces that do not emit light scatter with
+/// `material_override` instead of their own material when it is set.
+///
+/// Diffuse surfaces send half of their scattered rays towards `lights` when
+/// it is set, which finds small lights much more often.
+pub fn ray_color_with_override<T: Hit>(
+    ray: Ray,
+    background: &dyn Background,
     object: &T,
     depth: i64,
-    t_min: f64,
-    t_max: f64,
+    t_range: Interval,
+    material_override: Option<&dyn Material>,
+    lights: Option<&dyn Hit>,
 ) -> Color {
+    progress::count_ray();
     debug!("  [{}] ray: {} -> {}", depth, ray.origin(), ray.direction());
     let color = if depth <= 0 {
         // If we've exceeded the ray bounce limit, no more light is gathered
         Color::BLACK
-    } else if let Some(hit) = ray.clone().hit(object, t_min, t_max) {
+    } else if let Some(hit) = ray.clone().hit(object, t_range) {
         let emitted = hit.emitted;
         debug!(
             "  [{}]   hit at t = {} {}, normal {}",
             depth, hit.t, hit.point, hit.normal_outward
         );
         let hit = hit.into_against_ray();
+        let material = match material_override {
+            // lights keep their own material
+            Some(material) if emitted == Color::BLACK => material,
+            _ => hit.material.as_ref(),
+        };
+
+        // importance sample the direction, weighting it by the scattering
+        // density over the density it was sampled from
+        let sample = |pdf: &dyn Pdf, direction: Vec3<f64>, attenuation: Color| {
+            let scattered = Ray::new(hit.point, direction, ray.time());
+            let pdf_value = pdf.value(scattered.direction());
+            (pdf_value > 0.0).then(|| {
+                let scattering_pdf = material.scattering_pdf(&ray, &hit, &scattered);
+                (scattered, attenuation * (scattering_pdf / pdf_value))
+            })
+        };
+        let scattered = match material.scatter_record(&ray, &hit) {
+            Some(ScatterRecord::Specular { ray, attenuation }) => Some((ray, attenuation)),
+            Some(ScatterRecord::Diffuse { pdf, attenuation }) => {
+                // half of the rays go towards the lights, if they can be
+                // sampled from here, otherwise all follow the material
+                let towards = lights.and_then(|lights| {
+                    let towards = lights.random_point_towards(hit.point, ray.time())?;
+                    Some((lights, towards))
+                });
+                match towards {
+                    Some((lights, towards)) => {
+                        let mixture = MixturePdf::new(
+                            HitPdf::new(lights, hit.point, ray.time()),
+                            pdf.as_ref(),
+                        );
+                        let direction = if random::random::<f64>() < 0.5 {
+                            towards
+                        } else {
+                            pdf.generate()
+                        };
+                        sample(&mixture, direction, attenuation)
+                    }
+                    None => sample(pdf.as_ref(), pdf.generate(), attenuation),
+                }
+            }
+            None => None,
+        };
 
-        let color = if let Some((ray, attenuation)) = hit.material.scatter(&ray, &hit) {
-            debug!("  [{}]   attenuation: {}", depth, attenuation);
-            if attenuation.is_near_zero() {
-                // short circuit
-                debug!("  [{}]   attenuation is zero, short circuit", depth);
-                return Color::BLACK;
+        let color = match scattered {
+            Some((ray, attenuation)) if !attenuation.is_near_zero() => {
+                debug!("  [{}]   attenuation: {}", depth, attenuation);
+                // the scattered ray
+                let color = ray_color_with_override(
+                    ray,
+                    background,
+                    object,
+                    depth - 1,
+                    t_range,
+                    material_override,
+                    lights,
+                );
+                attenuation * color
             }
-            // the scattered ray
-            attenuation * ray_color(ray, background, object, depth - 1, t_min, t_max)
-        } else {
-            Color::BLACK
+            // nothing scattered, or the attenuation is zero, e.g. for a
+            // direction towards the lights from behind the surface
+            _ => Color::BLACK,
         };
 
         emitted + color
     } else {
         // The ray hits nothing, return the background color
-        background
+        background.color(ray.direction())
     };
     debug!("  [{}]   color: {}", depth, color);
     color
 }
+
+/// Albedo and normal of the first surface `ray` hits, or the background
+/// and a zero normal if it hits nothing.
+fn first_hit_aovs<T: Hit>(
+    ray: Ray,
+    background: &dyn Background,
+    object: &T,
+    t_range: Interval,
+) -> (Color, Vec3<f64>) {
+    let Some(hit) = ray.clone().hit(object, t_range) else {
+        let background = background.color(ray.direction());
+        return (background.clamp(0.0, 1.0), Vec3::zeros());
+    };
+
+    let emitted = hit.emitted;
+    let hit = hit.into_against_ray();
+    // the attenuation of the scattered ray is the albedo of the materials we
+    // have, lights do not scatter and count with their own color
+    let albedo = match hit.material.scatter(&ray, &hit) {
+        Some((_, attenuation)) => attenuation,
+        None => emitted,
+    };
+
+    (albedo.clamp(0.0, 1.0), hit.normal_against_ray)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        hit::Transform,
+        material::{DiffuseLight, Lambertian, Metal, Mix},
+        object::{rectangle::AxisAlignedRectangle, Block},
+    };
+
+    #[test]
+    fn light_sampling_cleans_up_the_cornell_box() {
+        // mean brightness and mean squared difference of pairs of renders
+        // with different seeds, at a few samples per pixel
+        const PAIRS: u64 = 4;
+        let brightness_and_noise = |sample_lights: bool| {
+            let render = |seed| {
+                let mut scene = scenes::cornell_box();
+                if !sample_lights {
+                    scene.lights = None;
+                }
+                let mut tracer = scene.build_tracer(8);
+                tracer.image_height = 30;
+                tracer.samples_per_pixel = 4;
+                tracer.seed = Some(seed);
+                tracer.render_region(0, 0, 30, 30)
+            };
+            let (mut brightness, mut noise) = (0.0, 0.0);
+            for pair in 0..PAIRS {
+                let (first, second) = (render(2 * pair + 1), render(2 * pair + 2));
+                for y in 0..30 {
+                    for x in 0..30 {
+                        let (a, b) = (first.get(x, y), second.get(x, y));
+                        brightness += (a + b).iter().sum::<f64>() / 6.0;
+                        noise += (a - b).len_squared() / 3.0;
+                    }
+                }
+            }
+            let pixels = (900 * PAIRS) as f64;
+            (brightness / pixels, noise / pixels)
+        };
+
+        let (brightness, noise) = brightness_and_noise(false);
+        let (sampled_brightness, sampled_noise) = brightness_and_noise(true);
+        assert!(
+            (sampled_brightness / brightness - 1.0).abs() < 0.05,
+            "{sampled_brightness} vs {brightness}"
+        );
+        assert!(sampled_noise < noise / 2.0, "{sampled_noise} vs {noise}");
+    }
+
+    #[test]
+    fn light_sampling_keeps_mixed_materials_bright() {
+        // the Cornell box in half diffuse, half glossy plastic
+        let plastic: Arc<dyn Material> = Arc::new(Mix::new(
+            Lambertian::new_solid(Color::constant(0.73)),
+            Metal::new(Color::constant(0.73), 0.3),
+            0.5,
+        ));
+        let brightness = |sample_lights: bool| {
+            let mut brightness = 0.0;
+            for seed in 1..=4 {
+                let mut scene = scenes::cornell_box();
+                if !sample_lights {
+                    scene.lights = None;
+                }
+                let mut tracer = scene.build_tracer(8);
+                tracer.material_override = Some(plastic.clone());
+                tracer.image_height = 30;
+                tracer.samples_per_pixel = 8;
+                tracer.seed = Some(seed);
+                let image = tracer.render_region(0, 0, 30, 30);
+                for y in 0..30 {
+                    for x in 0..30 {
+                        brightness += image.get(x, y).iter().sum::<f64>() / 3.0;
+                    }
+                }
+            }
+            brightness / (4.0 * 900.0)
+        };
+
+        let (plain, sampled) = (brightness(false), brightness(true));
+        assert!((sampled / plain - 1.0).abs() < 0.05, "{sampled} vs {plain}");
+    }
+
+    #[test]
+    fn wrapped_lights_sample_like_plain_ones() {
+        // mean brightness of the Cornell box, sampling `lights` instead of
+        // its ceiling light
+        let brightness = |lights: Arc<dyn Hit>| {
+            let mut brightness = 0.0;
+            for seed in 1..=4 {
+                let mut scene = scenes::cornell_box();
+                scene.lights = Some(lights.clone());
+                let mut tracer = scene.build_tracer(8);
+                tracer.image_height = 30;
+                tracer.samples_per_pixel = 4;
+                tracer.seed = Some(seed);
+                let image = tracer.render_region(0, 0, 30, 30);
+                for y in 0..30 {
+                    for x in 0..30 {
+                        brightness += image.get(x, y).iter().sum::<f64>() / 3.0;
+                    }
+                }
+            }
+            brightness / (4.0 * 900.0)
+        };
+
+        // the same 130 by 105 rectangle under the ceiling, built in many ways
+        let material = Arc::new(Lambertian::new_solid(Color::WHITE));
+        let center = Vec3::new(278.0, 554.0, 279.5);
+        let rectangle = |(x, z): (f64, f64)| {
+            AxisAlignedRectangle::new_xz((-x, -z), (x, z), 0.0, material.clone())
+        };
+        let plain = brightness(Arc::new(
+            AxisAlignedRectangle::new_xz((213.0, 227.0), (343.0, 332.0), 554.0, material.clone())
+                .flip_faces(),
+        ));
+        let wrapped: Vec<(&str, Arc<dyn Hit>)> = vec![
+            ("translated", Arc::new(rectangle((65.0, 52.5)).translate(center))),
+            (
+                "rotated",
+                Arc::new(rectangle((52.5, 65.0)).rotate_y(90.0).translate(center)),
+            ),
+            (
+                "rotated by a quaternion",
+                Arc::new(
+                    rectangle((52.5, 65.0))
+                        .rotate(Quat::from_axis_angle(Vec3::unit_y(), 90.0))
+                        .translate(center),
+                ),
+            ),
+            (
+                "scaled",
+                Arc::new(
+                    rectangle((1.0, 1.0))
+                        .scale(Vec3::new(65.0, 1.0, 52.5))
+                        .translate(center),
+                ),
+            ),
+            (
+                "transformed",
+                Arc::new(
+                    Transform::new(rectangle((1.0, 1.0)))
+                        .scale(Vec3::new(52.5, 1.0, 65.0))
+                        .rotate_y(90.0)
+                        .translate(center),
+                ),
+            ),
+            (
+                "in a BVH",
+                Arc::new(BVH::new(
+                    vec![Box::new(rectangle((65.0, 52.5)).translate(center)) as Box<dyn Hit>],
+                    0.0..1.0,
+                )),
+            ),
+        ];
+        for (name, lights) in wrapped {
+            let brightness = brightness(lights);
+            assert!(
+                (brightness / plain - 1.0).abs() < 0.05,
+                "{name}: {brightness} vs {plain}"
+            );
+        }
+    }
+
+    #[test]
+    fn bvh_heat_counts_node_visits() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let objects: Vec<Box<dyn Hit>> = (0..16)
+            .map(|i| {
+                let offset = Vec3::new((i % 4) as f64, (i / 4) as f64, 0.0) * 0.1;
+                let center = Point3::new(-0.15, -0.15, -1.0) + offset;
+                Box::new(Sphere::new(center, 0.05, material.clone())) as Box<dyn Hit>
+            })
+            .collect();
+
+        let tracer = RayTracer {
+            world: BVH::new(objects, 0.0..1.0),
+            camera: Camera::builder().aspect_ratio(1.0).build(),
+            background: Color::BLACK.into(),
+            max_depth: 1,
+            samples_per_pixel: 1,
+            image_height: 33,
+            render_mode: RenderMode::BvhHeat { max_visits: 16 },
+            seed: None,
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        };
+
+        // the corner ray misses the root bounding box
+        assert_eq!(
+            tracer.visit_count(0, 0, 33, 33, Interval::from_min(1e-10)),
+            1
+        );
+        // the center ray goes through the middle of the cluster
+        assert!(tracer.visit_count(16, 16, 33, 33, Interval::from_min(1e-10)) > 1);
+
+        let corner = tracer.trace_single(0, 0, 33, 33, Interval::from_min(1e-10));
+        assert!(corner.b() > corner.r());
+    }
+
+    #[test]
+    fn region_matches_full_render() {
+        let material = Arc::new(Lambertian::new_solid(Color::new(0.8, 0.3, 0.3)));
+        let mut world = World::new();
+        let ground = Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, material.clone());
+        world.add(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material));
+        world.add(ground);
+
+        let tracer = RayTracer {
+            world,
+            camera: Camera::builder().aspect_ratio(1.0).build(),
+            background: Color::new(0.7, 0.8, 1.0).into(),
+            max_depth: 5,
+            samples_per_pixel: 4,
+            image_height: 64,
+            render_mode: RenderMode::Shaded,
+            seed: Some(7),
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        };
+
+        let full = tracer.render();
+        let region = tracer.render_region(24, 32, 40, 48);
+        assert_eq!((region.width(), region.height()), (16, 16));
+        for y in 0..16 {
+            for x in 0..16 {
+                assert_eq!(region.get(x, y), full.get(x + 24, y + 32));
+            }
+        }
+    }
+
+    #[test]
+    fn granularities_give_same_image() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let mut tracer = RayTracer {
+            world: Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material),
+            camera: Camera::builder().aspect_ratio(1.5).build(),
+            background: Color::WHITE.into(),
+            max_depth: 3,
+            samples_per_pixel: 2,
+            image_height: 20,
+            render_mode: RenderMode::Shaded,
+            seed: Some(4),
+            work_granularity: WorkGranularity::Rows,
+            material_override: None,
+            lights: None,
+        };
+        let expected = tracer.render();
+
+        for granularity in [
+            WorkGranularity::Auto,
+            WorkGranularity::RowBands(3),
+            WorkGranularity::Tiles {
+                width: 7,
+                height: 4,
+            },
+            WorkGranularity::Pixels,
+        ] {
+            let mut covered = vec![0; 30 * 20];
+            for (x0, y0, x1, y1) in granularity.split(30, 20) {
+                for (x, y) in (y0..y1).flat_map(|y| (x0..x1).map(move |x| (x, y))) {
+                    covered[(y * 30 + x) as usize] += 1;
+                }
+            }
+            assert!(covered.into_iter().all(|count| count == 1));
+
+            tracer.work_granularity = granularity;
+            assert_eq!(tracer.render(), expected, "{:?}", granularity);
+        }
+
+        assert_eq!(WorkGranularity::Auto.split(30, 20).len(), 20);
+        assert_eq!(WorkGranularity::Auto.split(300, 300).len(), 100);
+    }
+
+    #[test]
+    fn tiles_cover_every_pixel_once() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let tracer = RayTracer {
+            world: Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material),
+            camera: Camera::builder().aspect_ratio(1.0).build(),
+            background: Color::WHITE.into(),
+            max_depth: 2,
+            samples_per_pixel: 1,
+            image_height: 64,
+            render_mode: RenderMode::Shaded,
+            seed: Some(1),
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        };
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let buffer = tracer.render_tiles(12, &sink::ChannelSink::new(sender));
+
+        let mut delivered = vec![0; 64 * 64];
+        for tile in receiver.try_iter() {
+            assert_eq!(tile.pixels.len() as u64, tile.width * tile.height);
+            for (index, color) in tile.pixels.into_iter().enumerate() {
+                let index = index as u64;
+                let (x, y) = (tile.x0 + index % tile.width, tile.y0 + index / tile.width);
+                assert_eq!(buffer.get(x, y), color);
+                delivered[(y * 64 + x) as usize] += 1;
+            }
+        }
+        assert!(delivered.into_iter().all(|count| count == 1));
+    }
+
+    #[test]
+    fn clay_keeps_lights() {
+        let red = Arc::new(Lambertian::new_solid(Color::new(0.65, 0.05, 0.05)));
+        let green = Arc::new(Lambertian::new_solid(Color::new(0.12, 0.45, 0.15)));
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(15.0)));
+        let mut world = World::new();
+        world.add(AxisAlignedRectangle::new_yz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            green,
+        ));
+        world.add(AxisAlignedRectangle::new_yz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            0.0,
+            red.clone(),
+        ));
+        world.add(AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            0.0,
+            red.clone(),
+        ));
+        world.add(AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            red.clone(),
+        ));
+        world.add(AxisAlignedRectangle::new_xy(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            red.clone(),
+        ));
+        world.add(AxisAlignedRectangle::new_xz(
+            (213.0, 227.0),
+            (343.0, 332.0),
+            554.0,
+            light,
+        ));
+        world.add(Block::new(
+            Point3::new(130.0, 0.0, 65.0),
+            Point3::new(295.0, 165.0, 230.0),
+            red,
+        ));
+
+        let camera = Camera::builder()
+            .look_from(278.0, 278.0, -800.0)
+            .look_at(278.0, 278.0, 0.0)
+            .vertical_field_of_view(40.0)
+            .aspect_ratio(1.0)
+            .build();
+        let tracer = RayTracer {
+            world,
+            camera,
+            background: Color::constant(0.2).into(),
+            max_depth: 8,
+            samples_per_pixel: 64,
+            image_height: 16,
+            render_mode: RenderMode::Shaded,
+            seed: Some(5),
+            work_granularity: WorkGranularity::Auto,
+            material_override: Some(Arc::new(Lambertian::new_solid(Color::constant(0.5)))),
+            lights: None,
+        };
+        let buffer = tracer.render();
+
+        let gray = |color: Color| {
+            (color.r() - color.g()).abs() < 1e-9 && (color.g() - color.b()).abs() < 1e-9
+        };
+        // left and right walls, the floor and the block
+        for (x, y) in [(1, 8), (14, 8), (8, 14), (6, 12)] {
+            let color = buffer.get(x, y);
+            assert!(gray(color) && color.r() > 0.0, "({x}, {y}) is {color}");
+        }
+        // the ceiling light, seen edge-on near the top
+        let brightest = (0..16 * 16)
+            .max_by(|&lhs, &rhs| {
+                buffer.pixels()[lhs]
+                    .r()
+                    .total_cmp(&buffer.pixels()[rhs].r())
+            })
+            .unwrap();
+        assert!(buffer.pixels()[brightest].r() > 1.0);
+        assert!(
+            brightest / 16 < 4,
+            "brightest pixel is at row {}",
+            brightest / 16
+        );
+    }
+
+    #[test]
+    fn aovs_see_first_hit() {
+        let color = Color::new(0.8, 0.3, 0.3);
+        let material = Arc::new(Lambertian::new_solid(color));
+        let tracer = RayTracer {
+            world: Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material),
+            camera: Camera::builder().aspect_ratio(1.0).build(),
+            background: Color::new(0.7, 0.8, 1.0).into(),
+            max_depth: 4,
+            samples_per_pixel: 4,
+            image_height: 17,
+            render_mode: RenderMode::Shaded,
+            seed: Some(2),
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        };
+
+        let inputs = tracer.render_with_aovs();
+        assert_eq!(inputs.color, tracer.render());
+        assert!((inputs.albedo.get(8, 8) - color).norm() < 1e-9);
+        assert!(inputs.normal.get(8, 8).z() > 0.9);
+        assert_eq!(inputs.albedo.get(0, 0), Color::new(0.7, 0.8, 1.0));
+        assert_eq!(inputs.normal.get(0, 0), Vec3::zeros());
+    }
+
+    #[test]
+    fn shared_framebuffer_is_polled_while_rendering() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let tracer = RayTracer {
+            world: Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material),
+            camera: Camera::builder().aspect_ratio(1.0).build(),
+            background: Color::WHITE.into(),
+            max_depth: 8,
+            samples_per_pixel: 32,
+            image_height: 64,
+            render_mode: RenderMode::Shaded,
+            seed: Some(1),
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        };
+
+        let framebuffer = SharedFramebuffer::new(64, 64);
+        let done = std::sync::atomic::AtomicBool::new(false);
+        let (buffer, counts) = std::thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                let mut counts = vec![];
+                while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                    let _ = framebuffer.snapshot();
+                    // polls between two tiles read the same count, so only
+                    // keep the changes
+                    let count = framebuffer.tile_count();
+                    if counts.last() != Some(&count) {
+                        counts.push(count);
+                    }
+                }
+                counts
+            });
+            let buffer = tracer.render_into_shared(framebuffer.clone(), CancelToken::new());
+            done.store(true, std::sync::atomic::Ordering::Relaxed);
+            (buffer, reader.join().unwrap())
+        });
+
+        assert!(counts.windows(2).all(|pair| pair[0] < pair[1]), "{counts:?}");
+        assert_eq!(framebuffer.tile_count(), 16);
+        assert_eq!(framebuffer.snapshot(), buffer);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let framebuffer = SharedFramebuffer::new(64, 64);
+        let buffer = tracer.render_into_shared(framebuffer.clone(), cancel);
+        assert_eq!(framebuffer.tile_count(), 0);
+        assert_eq!(buffer, RenderBuffer::new(64, 64));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    hit::AgainstRayHitRecord, texture::CheckerPattern, Color, Material, Point3, Ray, Vec3,
+};
+
+use super::ScatterRecord;
+
+/// A 3D checker pattern of two materials, such as a floor of mirror and
+/// diffuse tiles.
+///
+/// The cells are those of [`crate::texture::Checker`], so a checker texture
+/// and a checker material of the same [`CheckerPattern`] line up.
+#[derive(Debug, Clone)]
+pub struct CheckerMaterial<O: Material, E: Material> {
+    odd: O,
+    even: E,
+    pattern: CheckerPattern,
+}
+
+impl<O: Material, E: Material> CheckerMaterial<O, E> {
+    pub fn new(odd: O, even: E) -> Self {
+        Self {
+            odd,
+            even,
+            pattern: CheckerPattern::default(),
+        }
+    }
+
+    /// The same checker with the cells of `pattern`.
+    pub fn pattern(self, pattern: CheckerPattern) -> Self {
+        Self { pattern, ..self }
+    }
+
+    /// The material at `point`.
+    fn at(&self, point: Point3) -> &dyn Material {
+        if self.pattern.is_odd(point) {
+            &self.odd
+        } else {
+            &self.even
+        }
+    }
+}
+
+impl<O: Material, E: Material> Material for CheckerMaterial<O, E> {
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        self.at(hit_record.point).scatter(ray, hit_record)
+    }
+
+    fn scatter_record(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<ScatterRecord> {
+        self.at(hit_record.point).scatter_record(ray, hit_record)
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        self.at(hit_record.point)
+            .scattering_pdf(ray, hit_record, scattered)
+    }
+
+    fn emit(&self, point: Point3, u: f64, v: f64) -> Color {
+        self.at(point).emit(point, u, v)
+    }
+
+    fn emit_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        self.at(point).emit_at_time(point, u, v, time)
+    }
+
+    fn emit_towards(
+        &self,
+        ray: &Ray,
+        point: Point3,
+        normal_outward: Vec3<f64>,
+        u: f64,
+        v: f64,
+    ) -> Color {
+        self.at(point)
+            .emit_towards(ray, point, normal_outward, u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::{DiffuseLight, Metal},
+        object::rectangle::AxisAlignedRectangle,
+        texture::{Checker, Texture},
+        Hit, Interval,
+    };
+
+    #[test]
+    fn squares_line_up_with_the_texture() {
+        let (odd, even) = (Color::new(4.0, 0.0, 0.0), Color::new(0.0, 0.0, 2.0));
+        let texture = Checker::new_solids(odd, even);
+        let lights =
+            CheckerMaterial::new(DiffuseLight::new_solid(odd), DiffuseLight::new_solid(even));
+        for i in 0..100 {
+            let point = Point3::new(0.37 * i as f64, 0.05, -0.21 * i as f64);
+            assert_eq!(lights.emit(point, 0.0, 0.0), texture.color(point, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn mirror_and_diffuse_tiles() {
+        // tiles 1 wide, on a floor lying on a boundary of the cells
+        let floor = CheckerMaterial::new(
+            Metal::new(Color::WHITE, 0.0),
+            DiffuseLight::new_solid(Color::WHITE),
+        )
+        .pattern(CheckerPattern::Parity { scale: 1.0 });
+        let floor =
+            AxisAlignedRectangle::new_xz((-10.0, -10.0), (10.0, 10.0), 0.0, Arc::new(floor));
+        let hit = |x: f64, z: f64| {
+            let ray = Ray::new(Point3::new(x - 1.0, 1.0, z), Vec3::new(1.0, -1.0, 0.0), 0.0);
+            let hit = floor
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            (hit.material.scatter(&ray, &hit), hit.emitted)
+        };
+
+        let (mirror, emitted) = hit(1.5, 0.5);
+        assert_eq!(mirror.unwrap().0.direction().y(), 1.0 / 2f64.sqrt());
+        assert_eq!(emitted, Color::BLACK);
+        let (light, emitted) = hit(1.5, 1.5);
+        assert!(light.is_none());
+        assert_eq!(emitted, Color::WHITE);
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+
+use crate::{hit::AgainstRayHitRecord, Color, Material, Point3, Ray};
+
+/// Material whose scattering is a closure, for trying out an idea without
+/// writing a type for it.
+///
+/// The emission is black, unless another closure is given with
+/// [`FnMaterial::with_emit`]. For example, a mirror like
+/// [`super::Metal`]:
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use rtweekend::{material::FnMaterial, Color, Point3, Ray, Sphere};
+///
+/// let mirror = FnMaterial::new(|ray: &Ray, hit| {
+///     let reflected = ray.direction().normalized().reflect(hit.normal_against_ray);
+///     let scattered = Ray::new(hit.point, reflected, ray.time());
+///     Some((scattered, Color::new(0.8, 0.8, 0.9)))
+/// });
+/// let sphere = Sphere::new(Point3::zeros(), 1.0, Arc::new(mirror));
+/// ```
+pub struct FnMaterial<F, E = fn(Point3, f64, f64) -> Color> {
+    scatter: F,
+    emit: E,
+}
+
+impl<F> FnMaterial<F>
+where
+    F: Fn(&Ray, &AgainstRayHitRecord) -> Option<(Ray, Color)> + Sync + Send,
+{
+    pub fn new(scatter: F) -> Self {
+        Self {
+            scatter,
+            emit: |_, _, _| Color::BLACK,
+        }
+    }
+}
+
+impl<F, E> FnMaterial<F, E> {
+    /// The same material, emitting the color given by `emit` for the point
+    /// and the texture coordinates, see [`Material::emit`].
+    pub fn with_emit<G>(self, emit: G) -> FnMaterial<F, G>
+    where
+        G: Fn(Point3, f64, f64) -> Color + Sync + Send,
+    {
+        FnMaterial {
+            scatter: self.scatter,
+            emit,
+        }
+    }
+}
+
+// closures are not `Debug`
+impl<F, E> fmt::Debug for FnMaterial<F, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnMaterial").finish_non_exhaustive()
+    }
+}
+
+impl<F, E> Material for FnMaterial<F, E>
+where
+    F: Fn(&Ray, &AgainstRayHitRecord) -> Option<(Ray, Color)> + Sync + Send,
+    E: Fn(Point3, f64, f64) -> Color + Sync + Send,
+{
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        (self.scatter)(ray, hit_record)
+    }
+
+    fn emit(&self, point: Point3, u: f64, v: f64) -> Color {
+        (self.emit)(point, u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{material::Metal, Hit, Interval, Sphere, Vec3};
+
+    #[test]
+    fn closure_mirror_matches_metal() {
+        let color = Color::new(0.8, 0.6, 0.2);
+        let mirror = FnMaterial::new(move |ray: &Ray, hit: &AgainstRayHitRecord| {
+            let reflected = ray.direction().reflect(hit.normal_against_ray).normalized();
+            Some((Ray::new(hit.point, reflected, ray.time()), color))
+        });
+        let ray = Ray::new(Point3::new(0.3, 0.2, 5.0), -Vec3::unit_z(), 0.0);
+        let scatter = |material: Arc<dyn Material>| {
+            let sphere = Sphere::new(Point3::zeros(), 1.0, material);
+            let hit = sphere
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            let (scattered, attenuation) = hit.material.scatter(&ray, &hit).unwrap();
+            (scattered.origin(), scattered.direction(), attenuation)
+        };
+        assert_eq!(
+            scatter(Arc::new(mirror)),
+            scatter(Arc::new(Metal::new(color, 0.0)))
+        );
+
+        let glow = FnMaterial::new(|_: &Ray, _: &AgainstRayHitRecord| None)
+            .with_emit(|_, u, v| Color::new(u, v, 1.0));
+        assert_eq!(
+            glow.emit(Point3::zeros(), 0.25, 0.5),
+            Color::new(0.25, 0.5, 1.0)
+        );
+        assert_eq!(format!("{glow:?}"), "FnMaterial { .. }");
+    }
+}
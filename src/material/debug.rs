@@ -0,0 +1,83 @@
+use crate::{hit::AgainstRayHitRecord, Color, Material, Point3, Ray, Vec3};
+
+/// Debug material showing the outward normal as a color, `0.5 * (n + 1)`
+/// for the unit normal `n`.
+///
+/// It scatters nothing and emits the color, so a render with a black
+/// background and one bounce shows the normals of every surface. Surfaces
+/// facing +X are pinkish red, +Y light green and +Z light blue.
+#[derive(Debug, Clone, Default)]
+pub struct NormalMaterial;
+
+impl Material for NormalMaterial {
+    fn scatter(&self, _ray: &Ray, _hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        None
+    }
+
+    fn emit_towards(
+        &self,
+        _ray: &Ray,
+        _point: Point3,
+        normal_outward: Vec3<f64>,
+        _u: f64,
+        _v: f64,
+    ) -> Color {
+        (normal_outward.normalized() + Color::WHITE) * 0.5
+    }
+}
+
+/// Debug material showing the texture coordinates as a color, `(u, v, 0)`.
+///
+/// Like [`NormalMaterial`], it scatters nothing and emits the color.
+#[derive(Debug, Clone, Default)]
+pub struct UvMaterial;
+
+impl Material for UvMaterial {
+    fn scatter(&self, _ray: &Ray, _hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        None
+    }
+
+    fn emit(&self, _point: Point3, u: f64, v: f64) -> Color {
+        Color::new(u, v, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        object::{rectangle::AxisAlignedRectangle, Block},
+        Hit, HitExt, Interval,
+    };
+
+    #[test]
+    fn normals_of_a_rotated_block() {
+        let block = Block::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Arc::new(NormalMaterial),
+        )
+        .rotate_y(90.0);
+        // the face towards +z was facing +x before the rotation
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), -Vec3::unit_z(), 0.0);
+        let hit = block.hit(ray, Interval::from_min(0.0)).unwrap();
+        assert!((hit.emitted - Color::new(0.5, 0.5, 1.0)).norm() < 1e-12);
+
+        let ray = Ray::new(Point3::new(0.0, -5.0, 0.0), Vec3::unit_y(), 0.0);
+        let hit = block.hit(ray.clone(), Interval::from_min(0.0)).unwrap();
+        assert!((hit.emitted - Color::new(0.5, 0.0, 0.5)).norm() < 1e-12);
+        let hit = hit.into_against_ray();
+        assert!(hit.material.scatter(&ray, &hit).is_none());
+    }
+
+    #[test]
+    fn uv_of_a_rectangle() {
+        let rectangle =
+            AxisAlignedRectangle::new_xy((0.0, 0.0), (4.0, 2.0), 0.0, Arc::new(UvMaterial));
+        let ray = Ray::new(Point3::new(1.0, 1.5, 1.0), -Vec3::unit_z(), 0.0);
+        let hit = rectangle.hit(ray, Interval::from_min(0.0)).unwrap();
+        assert_eq!(hit.emitted, Color::new(0.25, 0.75, 0.0));
+    }
+}
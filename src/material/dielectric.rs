@@ -1,14 +1,138 @@
-use crate::{Material, Ray, Color, hit::AgainstRayHitRecord};
+use rand::Rng;
+
+use crate::{hit::AgainstRayHitRecord, random, Color, Material, Ray, Vec3, VISIBLE_WAVELENGTHS};
+
+/// Number of microsurface normals tried for rough glass, before falling back
+/// to the smooth normal when none scatters to the right side of the surface.
+const ROUGH_ATTEMPTS: usize = 16;
 
 #[derive(Debug, Clone)]
 pub struct Dielectric {
     index_of_refraction: f64,
+    /// Fraction of each channel absorbed per unit length inside, see
+    /// [`Dielectric::with_attenuation`]
+    absorption: Color,
+    /// Coefficient of the wavelength term of Cauchy's equation in μm², zero
+    /// for glass without dispersion, see [`Dielectric::with_dispersion`]
+    cauchy_b: f64,
+    /// Fuzziness of the microsurface normals, zero for smooth glass, see
+    /// [`Dielectric::with_roughness`]
+    roughness: f64,
 }
 
 impl Dielectric {
     pub fn new(index_of_refraction: f64) -> Self {
         Self {
             index_of_refraction,
+            absorption: Color::BLACK,
+            cauchy_b: 0.0,
+            roughness: 0.0,
+        }
+    }
+
+    /// Glass whose index of refraction depends on the wavelength, which
+    /// splits white light into a rainbow.
+    ///
+    /// The index is `a + b / λ²` by Cauchy's equation, with the wavelength λ
+    /// in micrometers, e.g. `a = 1.5046` and `b = 0.0042` for BK7 glass.
+    ///
+    /// A ray of white light picks a wavelength from [`VISIBLE_WAVELENGTHS`]
+    /// when it hits the glass, and is attenuated by the color of that
+    /// wavelength, see [`Color::from_wavelength`]. The scattered ray carries
+    /// the wavelength, so it refracts the same way through the rest of the
+    /// glass.
+    pub fn with_dispersion(a: f64, b: f64) -> Self {
+        Self {
+            cauchy_b: b,
+            ..Self::new(a)
+        }
+    }
+
+    /// Tinted glass, which absorbs light along the way through it by the
+    /// Beer–Lambert law.
+    ///
+    /// Light that travels a distance `d` inside keeps `exp(-density * (1 -
+    /// color) * d)` of each channel, so `color` is the tint, white glass stays
+    /// clear, and thicker glass is darker. The absorption is applied when the
+    /// ray hits the surface from inside, which assumes that it got there
+    /// through the glass, i.e. the object is closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `density` is negative or not finite.
+    pub fn with_attenuation(index_of_refraction: f64, color: Color, density: f64) -> Self {
+        assert!(
+            density >= 0.0 && density.is_finite(),
+            "absorption density must be non-negative and finite"
+        );
+        Self {
+            index_of_refraction,
+            absorption: (Color::WHITE - color.clamp(0.0, 1.0)) * density,
+            cauchy_b: 0.0,
+            roughness: 0.0,
+        }
+    }
+
+    /// The same glass with a rough surface, such as frosted glass, which
+    /// blurs both the reflections and what is seen through it.
+    ///
+    /// Each scattering moves the normal by the roughness times a random unit
+    /// vector, as the fuzz of [`super::Metal`] moves the reflected direction,
+    /// and reflects or refracts off that microsurface normal. Directions which
+    /// end up on the wrong side of the surface, i.e. reflected into it or
+    /// refracted out of it, are sampled again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `roughness` is negative or not finite.
+    pub fn with_roughness(self, roughness: f64) -> Self {
+        assert!(
+            roughness >= 0.0 && roughness.is_finite(),
+            "roughness must be non-negative and finite"
+        );
+        Self { roughness, ..self }
+    }
+
+    /// Index of refraction for the light along `ray`, with the wavelength
+    /// the scattered ray carries and the weight of picking it.
+    fn dispersed_index(&self, ray: &Ray) -> (f64, Option<f64>, Color) {
+        if self.cauchy_b == 0.0 {
+            return (self.index_of_refraction, ray.wavelength(), Color::WHITE);
+        }
+
+        let (wavelength, weight) = match ray.wavelength() {
+            Some(wavelength) => (wavelength, Color::WHITE),
+            None => {
+                let wavelength = random::with_rng(|rng| rng.gen_range(VISIBLE_WAVELENGTHS));
+                (wavelength, Color::from_wavelength(wavelength))
+            }
+        };
+        let micrometers = wavelength / 1000.0;
+        let index = self.index_of_refraction + self.cauchy_b / (micrometers * micrometers);
+        (index, Some(wavelength), weight)
+    }
+
+    /// Direction of a ray along `unit_direction` reflected or refracted off a
+    /// surface with `normal` against it, and whether it was reflected.
+    fn scatter_off(
+        unit_direction: Vec3<f64>,
+        normal: Vec3<f64>,
+        index_of_refraction: f64,
+        refraction_ratio: f64,
+    ) -> (Vec3<f64>, bool) {
+        // `theta` is the angle from the normal
+        let cos_theta = (-unit_direction).dot(normal).min(1.0);
+        let sin_theta = (1.0 - cos_theta.powi(2)).sqrt();
+
+        let reflectance = Self::reflectance(cos_theta, index_of_refraction);
+        let cannot_refract = refraction_ratio * sin_theta > 1.0;
+        let will_reflect = reflectance > random::random::<f64>();
+
+        if cannot_refract || will_reflect {
+            // Refraction is not possible, must reflect
+            (unit_direction.reflect(normal), true)
+        } else {
+            (unit_direction.refract(normal, refraction_ratio), false)
         }
     }
 
@@ -20,7 +144,7 @@ impl Dielectric {
     /// R(\theta) = R_0 + (1 - R_0)(1 - \cos \theta)^5
     /// ```
     /// where
-    /// ```
+    /// ```math
     /// R_0 = \frac{(n_1 - n_2)^2}{(n_1 + n_2)^2}
     /// ```
     /// and $\theta$ is the angle between the incident ray and the normal.
@@ -30,7 +154,7 @@ impl Dielectric {
     /// # Arguments
     /// * `cosine` - cosine of the angle between the incident ray and the normal
     /// * `index_of_refraction` - index of refraction of the material
-    fn reflectance(cosine: f64, index_of_refraction: f64) -> f64 {
+    pub(super) fn reflectance(cosine: f64, index_of_refraction: f64) -> f64 {
         let r0 = ((1.0 - index_of_refraction) / (1.0 + index_of_refraction)).powi(2);
         r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
     }
@@ -38,34 +162,190 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        let (index_of_refraction, wavelength, weight) = self.dispersed_index(ray);
         let refraction_ratio = if hit_record.is_front() {
-            1.0 / self.index_of_refraction
+            1.0 / index_of_refraction
         } else {
-            self.index_of_refraction / 1.0
+            index_of_refraction / 1.0
         };
 
         let unit_direction = ray.direction().normalized();
-        // `theta` is the angle from the normal
-        // TODO: normal_outward or normal_against_ray
-        let cos_theta = (-unit_direction)
-            .dot(hit_record.normal_against_ray)
-            .min(1.0);
-        let sin_theta = (1.0 - cos_theta.powi(2)).sqrt();
+        let normal = hit_record.normal_against_ray;
+        let direction = if self.roughness == 0.0 {
+            Self::scatter_off(
+                unit_direction,
+                normal,
+                index_of_refraction,
+                refraction_ratio,
+            )
+            .0
+        } else {
+            (0..ROUGH_ATTEMPTS)
+                .find_map(|_| {
+                    let micro_normal = normal + self.roughness * Vec3::random_unit_vector();
+                    // only facets facing the ray can be hit
+                    if micro_normal.is_near_zero() || micro_normal.dot(unit_direction) >= 0.0 {
+                        return None;
+                    }
+                    let (direction, reflected) = Self::scatter_off(
+                        unit_direction,
+                        micro_normal.normalized(),
+                        index_of_refraction,
+                        refraction_ratio,
+                    );
+                    let outside = direction.dot(normal) > 0.0;
+                    (outside == reflected).then_some(direction)
+                })
+                .unwrap_or_else(|| {
+                    Self::scatter_off(
+                        unit_direction,
+                        normal,
+                        index_of_refraction,
+                        refraction_ratio,
+                    )
+                    .0
+                })
+        };
 
-        let reflectance = Self::reflectance(cos_theta, self.index_of_refraction);
-        let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let will_reflect = reflectance > rand::random::<f64>();
+        let mut scattered = Ray::new(hit_record.point, direction, ray.time());
+        if let Some(wavelength) = wavelength {
+            scattered = scattered.with_wavelength(wavelength);
+        }
 
-        let direction = if cannot_refract || will_reflect {
-            // Refraction is not possible, must reflect
-            unit_direction.reflect(hit_record.normal_against_ray)
+        // the surface absorbs nothing, only the way through the inside does
+        let attenuation = if hit_record.is_front() {
+            Color::WHITE
         } else {
-            unit_direction.refract(hit_record.normal_against_ray, refraction_ratio)
+            let distance = hit_record.t * ray.direction().norm();
+            (-self.absorption * distance).apply(f64::exp)
+        };
+        Some((scattered, attenuation * weight))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{Hit, Interval, Point3, Sphere, Vec3};
+
+    #[test]
+    fn thicker_glass_absorbs_more() {
+        let green = Color::new(0.2, 0.9, 0.2);
+        let attenuation = |material: Dielectric, radius: f64| {
+            let sphere = Sphere::new(Point3::zeros(), radius, Arc::new(material));
+            // from the center of the sphere out, i.e. a radius inside
+            let ray = Ray::new(Point3::zeros(), Vec3::new(0.0, 0.0, -2.0), 0.0);
+            let hit = sphere
+                .hit(ray.clone(), Interval::from_min(1e-6))
+                .unwrap()
+                .into_against_ray();
+            assert!(!hit.is_front());
+            hit.material.scatter(&ray, &hit).unwrap().1
+        };
+
+        assert_eq!(attenuation(Dielectric::new(1.5), 1.0), Color::WHITE);
+        let thin = attenuation(Dielectric::with_attenuation(1.5, green, 2.0), 0.5);
+        let thick = attenuation(Dielectric::with_attenuation(1.5, green, 2.0), 1.0);
+        assert!((thin.g() - (-0.1f64).exp()).abs() < 1e-12);
+        assert!((thin.r() - (-0.8f64).exp()).abs() < 1e-12);
+        assert!((thick.r() - thin.r() * thin.r()).abs() < 1e-12);
+        assert!(thick.g() > thick.r());
+    }
+
+    #[test]
+    fn blue_bends_more_than_red() {
+        let glass = Dielectric::with_dispersion(1.6, 0.02);
+        let ground = crate::object::rectangle::AxisAlignedRectangle::new_xz(
+            (-1e3, -1e3),
+            (1e3, 1e3),
+            0.0,
+            Arc::new(glass),
+        );
+        let ray = Ray::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), 0.0);
+        let hit = ground
+            .hit(ray.clone(), Interval::from_min(0.0))
+            .unwrap()
+            .into_against_ray();
+
+        // retry until the ray is not reflected
+        let refracted = |ray: &Ray| loop {
+            let (scattered, weight) = hit.material.scatter(ray, &hit).unwrap();
+            if scattered.direction().y() < 0.0 {
+                return (scattered, weight);
+            }
+        };
+        // sine of the angle from the normal of the ray refracted at `wavelength`
+        let sine = |wavelength: f64| {
+            let (scattered, weight) = refracted(&ray.clone().with_wavelength(wavelength));
+            assert_eq!(scattered.wavelength(), Some(wavelength));
+            assert_eq!(weight, Color::WHITE);
+            scattered.direction().normalized().x()
+        };
+        let (blue, red) = (sine(450.0), sine(650.0));
+        let index = |wavelength: f64| 1.6 + 0.02 / (wavelength / 1000.0).powi(2);
+        assert!((blue - 0.5f64.sqrt() / index(450.0)).abs() < 1e-12);
+        assert!((red - 0.5f64.sqrt() / index(650.0)).abs() < 1e-12);
+        assert!(blue < red);
+
+        // white light picks a wavelength and keeps it, weighted by its color
+        random::seed(61);
+        let (scattered, weight) = refracted(&ray);
+        let wavelength = scattered.wavelength().unwrap();
+        assert!(VISIBLE_WAVELENGTHS.contains(&wavelength));
+        assert_eq!(weight, Color::from_wavelength(wavelength));
+    }
+
+    #[test]
+    fn frosted_glass_stays_on_the_right_side() {
+        let ground = |glass: Dielectric| {
+            crate::object::rectangle::AxisAlignedRectangle::new_xz(
+                (-1e3, -1e3),
+                (1e3, 1e3),
+                0.0,
+                Arc::new(glass),
+            )
+        };
+        // refracted directions from a ray coming in at 45 degrees, and the
+        // fraction reflected back inside of one leaving at 45 degrees, past
+        // the critical angle of a smooth surface
+        let scatter = |glass: Dielectric| {
+            let ground = ground(glass);
+            let scatter_from = |origin: Point3, direction: Vec3<f64>| {
+                let ray = Ray::new(origin, direction, 0.0);
+                let hit = ground
+                    .hit(ray.clone(), Interval::from_min(0.0))
+                    .unwrap()
+                    .into_against_ray();
+                (0..2000)
+                    .map(|_| hit.material.scatter(&ray, &hit).unwrap().0.direction())
+                    .collect::<Vec<_>>()
+            };
+
+            let entering = scatter_from(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+            let refracted: Vec<_> = entering.into_iter().filter(|d| d.y() < 0.0).collect();
+            let leaving = scatter_from(Point3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+            let reflected = leaving.iter().filter(|d| d.y() < 0.0).count();
+            (refracted, reflected as f64 / 2000.0)
         };
 
-        let scattered = Ray::new(hit_record.point, direction, ray.time());
+        random::seed(56);
+        let (smooth, reflected) = scatter(Dielectric::new(1.5));
+        assert_eq!(reflected, 1.0);
+        let (frosted, reflected) = scatter(Dielectric::new(1.5).with_roughness(0.3));
+        assert!(reflected > 0.5 && reflected < 1.0, "{reflected}");
 
-        // attenuation is always 1 as the glass surface absorbs nothing
-        Some((scattered, Color::WHITE))
+        // the blur spreads the refracted rays around the sharp direction
+        let sharp = smooth[0].normalized();
+        assert!(smooth
+            .iter()
+            .all(|d| (d.normalized() - sharp).norm() < 1e-12));
+        let spread = frosted
+            .iter()
+            .map(|d| (d.normalized() - sharp).norm())
+            .sum::<f64>()
+            / frosted.len() as f64;
+        assert!(spread > 0.05 && spread < 0.5, "{spread}");
     }
 }
@@ -1,17 +1,99 @@
 use crate::{
+    hit::{is_front_face, AgainstRayHitRecord},
     texture::{SolidColor, Texture},
-    Color, Material, Point3, hit::AgainstRayHitRecord,
+    Color, Material, Point3, Ray, Vec3,
 };
 
+/// How the emission of a [`DiffuseLight`] falls off with the angle between
+/// the direction it is seen from and the surface normal.
+///
+/// Two-sided lights use the same profile on both sides of the surface.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum EmissionProfile {
+    /// Same emission in every direction
+    #[default]
+    Uniform,
+    /// Emission scaled by the cosine of the angle raised to this power, so that
+    /// larger powers give a narrower, spot-like light
+    CosinePower(f64),
+    /// Emission scaled by the factors in the table, which are spread evenly
+    /// from 0 degrees (along the normal) to 90 degrees (grazing), and
+    /// interpolated linearly between them
+    Table(Vec<f64>),
+}
+
+impl EmissionProfile {
+    /// Factor of the emission seen from a direction whose angle to the normal
+    /// has cosine `cos_theta`.
+    pub fn factor(&self, cos_theta: f64) -> f64 {
+        let cos_theta = cos_theta.abs().min(1.0);
+        match self {
+            EmissionProfile::Uniform => 1.0,
+            EmissionProfile::CosinePower(power) => cos_theta.powf(*power),
+            EmissionProfile::Table(table) => match table.len() {
+                0 => 1.0,
+                1 => table[0],
+                len => {
+                    let position =
+                        cos_theta.acos() / std::f64::consts::FRAC_PI_2 * (len - 1) as f64;
+                    let index = (position.floor() as usize).min(len - 2);
+                    let fraction = position - index as f64;
+                    table[index] * (1.0 - fraction) + table[index + 1] * fraction
+                }
+            },
+        }
+    }
+}
+
 /// A material which emits light with color from a texture.
+///
+/// Lights emit from both sides of a surface, unless made [one-sided], and
+/// the color of the texture is multiplied by a scale, so that a texture in
+/// the usual range can give a bright light.
+///
+/// [one-sided]: DiffuseLight::one_sided
 #[derive(Debug, Clone)]
 pub struct DiffuseLight<T: Texture> {
     texture: T,
+    profile: EmissionProfile,
+    /// Factor of the color of the texture
+    scale: f64,
+    /// Whether only the front emits
+    one_sided: bool,
 }
 
 impl<T: Texture> DiffuseLight<T> {
     pub fn new(texture: T) -> Self {
-        Self { texture }
+        Self::with_scale(texture, 1.0)
+    }
+
+    /// Light emitting `scale` times the color of `texture`.
+    pub fn with_scale(texture: T, scale: f64) -> Self {
+        Self {
+            texture,
+            profile: EmissionProfile::Uniform,
+            scale,
+            one_sided: false,
+        }
+    }
+
+    /// The same light emitting with `profile`.
+    pub fn with_profile(mut self, profile: EmissionProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// The same light emitting only from the front, the side the outward
+    /// normal points to. The back is black.
+    ///
+    /// Like the profile, this needs the direction the light is seen from, so
+    /// only [`Material::emit_towards`] honours it, which gives the emission
+    /// of hits and which wrappers such as [`super::CheckerMaterial`] and
+    /// [`super::TwoSided`] forward. [`Material::emit`] and
+    /// [`Material::emit_at_time`] give the emission of the front.
+    pub fn one_sided(mut self) -> Self {
+        self.one_sided = true;
+        self
     }
 }
 
@@ -19,6 +101,12 @@ impl DiffuseLight<SolidColor> {
     pub fn new_solid(color: Color) -> Self {
         Self::new(SolidColor::new(color))
     }
+
+    /// Light with the color of a blackbody at `temperature` Kelvin, see
+    /// [`Color::from_kelvin`], with the brightest channel at `intensity`.
+    pub fn new_kelvin(temperature: f64, intensity: f64) -> Self {
+        Self::new_solid(Color::from_kelvin(temperature) * intensity)
+    }
 }
 
 impl<T: Texture> Material for DiffuseLight<T> {
@@ -31,6 +119,96 @@ impl<T: Texture> Material for DiffuseLight<T> {
     }
 
     fn emit(&self, point: Point3, u: f64, v: f64) -> crate::Color {
-        self.texture.color(point, u, v)
+        self.texture.color(point, u, v) * self.scale
+    }
+
+    fn emit_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> crate::Color {
+        self.texture.color_at_time(point, u, v, time) * self.scale
+    }
+
+    fn emit_towards(
+        &self,
+        ray: &Ray,
+        point: Point3,
+        normal_outward: Vec3<f64>,
+        u: f64,
+        v: f64,
+    ) -> Color {
+        if self.one_sided && !is_front_face(ray, normal_outward) {
+            return Color::BLACK;
+        }
+        let color = self.emit_at_time(point, u, v, ray.time());
+        if self.profile == EmissionProfile::Uniform {
+            return color;
+        }
+        let cos_theta = ray
+            .direction()
+            .normalized()
+            .dot(normal_outward.normalized());
+        color * self.profile.factor(cos_theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::{CheckerMaterial, TwoSided},
+        object::rectangle::AxisAlignedRectangle,
+        Hit, Interval,
+    };
+
+    #[test]
+    fn cosine_profile_dims_grazing_views() {
+        let light = DiffuseLight::new_solid(Color::constant(15.0))
+            .with_profile(EmissionProfile::CosinePower(1.0));
+        let light =
+            AxisAlignedRectangle::new_xz((213.0, 227.0), (343.0, 332.0), 554.0, Arc::new(light));
+        let center = Point3::new(278.0, 554.0, 279.5);
+        let emitted = |direction: Vec3<f64>| {
+            let ray = Ray::new(center - direction * 100.0, direction, 0.0);
+            light.hit(ray, Interval::from_min(1e-3)).unwrap().emitted
+        };
+
+        let head_on = emitted(Vec3::unit_y());
+        assert_eq!(head_on, Color::constant(15.0));
+        // 60 degrees from the normal
+        let grazing = emitted(Vec3::new(3f64.sqrt(), 1.0, 0.0));
+        assert!((grazing - head_on * 0.5).norm() < 1e-9);
+
+        let table = EmissionProfile::Table(vec![1.0, 0.5, 0.0]);
+        assert_eq!(table.factor(1.0), 1.0);
+        assert!((table.factor(std::f64::consts::FRAC_PI_8.cos()) - 0.75).abs() < 1e-9);
+        assert_eq!(table.factor(0.0), 0.0);
+    }
+
+    #[test]
+    fn one_sided_light_is_dark_behind() {
+        let emitted = |light: Arc<dyn Material>, y: f64| {
+            // normal along +y, so the front is above
+            let light = AxisAlignedRectangle::new_xz((0.0, 0.0), (1.0, 1.0), 0.0, light);
+            let ray = Ray::new(Point3::new(0.5, y, 0.5), Vec3::new(0.0, -y, 0.0), 0.0);
+            light.hit(ray, Interval::from_min(0.0)).unwrap().emitted
+        };
+        let color = Color::new(0.5, 0.25, 1.0);
+
+        let two_sided = || DiffuseLight::with_scale(SolidColor::new(color), 8.0);
+        assert_eq!(emitted(Arc::new(two_sided()), 1.0), color * 8.0);
+        assert_eq!(emitted(Arc::new(two_sided()), -1.0), color * 8.0);
+        let one_sided = || two_sided().one_sided();
+        assert_eq!(emitted(Arc::new(one_sided()), 1.0), color * 8.0);
+        assert_eq!(emitted(Arc::new(one_sided()), -1.0), Color::BLACK);
+
+        // wrappers keep the back dark
+        let wrapped: [Arc<dyn Material>; 2] = [
+            Arc::new(CheckerMaterial::new(one_sided(), one_sided())),
+            Arc::new(TwoSided::new(one_sided(), one_sided())),
+        ];
+        for light in wrapped {
+            assert_eq!(emitted(light.clone(), 1.0), color * 8.0);
+            assert_eq!(emitted(light, -1.0), Color::BLACK);
+        }
     }
 }
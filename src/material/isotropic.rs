@@ -1,20 +1,86 @@
 use crate::{texture::Texture, Material, Ray, Vec3};
 
 /// Isotropic material, which reflects light equally in all directions.
+///
+/// The scattered rays go along random unit vectors. Before, they went towards
+/// random points inside the unit sphere, which are as isotropic but not of
+/// the same length; see [`Isotropic::scatter_in_sphere`] to get them back.
 #[derive(Debug, Clone)]
 pub struct Isotropic<T: Texture> {
     /// The texture of the material
     albedo: T,
+    /// Whether the scattered direction is a point inside the unit sphere
+    /// instead of on it
+    scatter_in_sphere: bool,
 }
 
 impl<T: Texture> Isotropic<T> {
-    pub fn new(albedo: T) -> Self { Self { albedo } }
+    pub fn new(albedo: T) -> Self {
+        Self {
+            albedo,
+            scatter_in_sphere: false,
+        }
+    }
+
+    /// The same material, scattering towards random points inside the unit
+    /// sphere as in the second book, for comparing with images rendered that
+    /// way.
+    pub fn scatter_in_sphere(mut self) -> Self {
+        self.scatter_in_sphere = true;
+        self
+    }
 }
 
 impl<T: Texture> Material for Isotropic<T> {
-    fn scatter(&self, ray: &crate::Ray, hit_record: &crate::hit::AgainstRayHitRecord) -> Option<(crate::Ray, crate::Color)> {
-        let ray = Ray::new(hit_record.point, Vec3::random_in_unit_sphere(), ray.time());
-        let attenuation = self.albedo.color(hit_record.point, hit_record.u, hit_record.v);
+    fn scatter(
+        &self,
+        ray: &crate::Ray,
+        hit_record: &crate::hit::AgainstRayHitRecord,
+    ) -> Option<(crate::Ray, crate::Color)> {
+        let direction = if self.scatter_in_sphere {
+            Vec3::random_in_unit_sphere()
+        } else {
+            Vec3::random_unit_vector()
+        };
+        let ray = Ray::new(hit_record.point, direction, ray.time());
+        let attenuation =
+            self.albedo
+                .color_at_time(hit_record.point, hit_record.u, hit_record.v, ray.time());
         Some((ray, attenuation))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        object::rectangle::AxisAlignedRectangle, texture::SolidColor, Color, Hit, Interval, Point3,
+    };
+
+    #[test]
+    fn scatters_in_the_sphere_on_request() {
+        let ray = Ray::new(Point3::new(0.5, 1.0, 0.5), -Vec3::unit_y(), 0.0);
+        // mean length of the scattered directions
+        let length = |material: Isotropic<SolidColor>| {
+            let fog = AxisAlignedRectangle::new_xz((0.0, 0.0), (1.0, 1.0), 0.0, Arc::new(material));
+            let hit = fog
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            (0..20000)
+                .filter_map(|_| hit.material.scatter(&ray, &hit))
+                .map(|(scattered, _)| scattered.direction().norm())
+                .sum::<f64>()
+                / 20000.0
+        };
+
+        crate::random::seed(29);
+        let gray = || Isotropic::new(SolidColor::new(Color::constant(0.5)));
+        assert!((length(gray()) - 1.0).abs() < 1e-12);
+        // the mean distance from the center of the unit sphere is 3 / 4
+        let in_sphere = length(gray().scatter_in_sphere());
+        assert!((in_sphere - 0.75).abs() < 0.01, "{in_sphere}");
+    }
+}
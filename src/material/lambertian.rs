@@ -1,12 +1,16 @@
-use crate::Vec3;
+use std::f64::consts::PI;
+
 use crate::hit::AgainstRayHitRecord;
+use crate::pdf::{CosinePdf, Pdf};
 use crate::texture::SolidColor;
-use crate::{Material, Ray, Color, texture::Texture};
+use crate::{texture::Texture, Color, Material, Ray};
+
+use super::ScatterRecord;
 
 /// Diffuse material, which can either scatter always and attenuate by its
 /// reflectance R, or it can scatter with no attenuation but absorb the
 /// fraction 1-R of the rays, or it could be a mixture of the two.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Lambertian<T: Texture> {
     /// The texture of the material
     albedo: T,
@@ -24,21 +28,32 @@ impl Lambertian<SolidColor> {
     }
 }
 
+impl<T: Texture> Lambertian<T> {
+    fn albedo(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Color {
+        self.albedo
+            .color_at_time(hit_record.point, hit_record.u, hit_record.v, ray.time())
+    }
+}
+
 impl<T: Texture> Material for Lambertian<T> {
     fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
-        let scatter_direction =
-            hit_record.normal_against_ray + Vec3::random_in_unit_sphere().normalized();
-
-        // scatter_direction near zero may leads to infinite or NaNs, which
-        // may cause problems later on. So we need to handle this case.
-        let direction = if scatter_direction.is_near_zero() {
-            hit_record.normal_against_ray
-        } else {
-            scatter_direction
-        };
+        // cosine-weighted directions, whose density cancels the BRDF
+        let direction = CosinePdf::new(hit_record.normal_against_ray).generate();
         let scattered = Ray::new(hit_record.point, direction, ray.time());
-        let attenuation = self.albedo.color(hit_record.point, hit_record.u, hit_record.v);
+        Some((scattered, self.albedo(ray, hit_record)))
+    }
+
+    fn scatter_record(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Diffuse {
+            pdf: Box::new(CosinePdf::new(hit_record.normal_against_ray)),
+            attenuation: self.albedo(ray, hit_record),
+        })
+    }
 
-        Some((scattered, attenuation))
+    fn scattering_pdf(&self, _ray: &Ray, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        let cosine = hit_record
+            .normal_against_ray
+            .dot(scattered.direction().normalized());
+        cosine.max(0.0) / PI
     }
 }
@@ -1,33 +1,157 @@
-use crate::{Color, Material, Ray, Vec3, hit::AgainstRayHitRecord};
+use crate::{
+    hit::AgainstRayHitRecord,
+    texture::{SolidColor, Texture},
+    Color, Material, Ray, Vec3,
+};
 
+/// Reflective material, whose reflections are blurred by its fuzziness.
+///
+/// The reflected direction is moved by the fuzziness times a random unit
+/// vector. Before, it was moved by a random point inside the unit sphere,
+/// which gives a tighter blur for the same fuzziness; see
+/// [`Metal::fuzz_in_sphere`] to get it back.
+///
+/// Both the albedo and the fuzziness are textures, so they can change over
+/// the surface, e.g. for brushed metal with a roughness map. The fuzziness is
+/// the average of the channels of its texture, so a gray texture gives it
+/// directly.
 #[derive(Debug, Clone)]
-pub struct Metal {
+pub struct Metal<A: Texture = SolidColor, R: Texture = SolidColor> {
     /// The color reflected by the surface
-    albedo: Color,
+    albedo: A,
     /// Fuzziness of the material, zero means no perturbation.
-    fuzziness: f64,
+    fuzziness: R,
+    /// Whether the fuzz is a point inside the unit sphere instead of on it
+    fuzz_in_sphere: bool,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzziness: f64) -> Self {
-        Self { albedo, fuzziness }
+        Self::textured(
+            SolidColor::new(albedo),
+            SolidColor::new(Color::constant(fuzziness)),
+        )
     }
 }
 
-impl Material for Metal {
+impl<A: Texture, R: Texture> Metal<A, R> {
+    /// Metal with the albedo and the fuzziness looked up from textures at the
+    /// hit point.
+    pub fn textured(albedo: A, fuzziness: R) -> Self {
+        Self {
+            albedo,
+            fuzziness,
+            fuzz_in_sphere: false,
+        }
+    }
+
+    /// The same metal, blurred by random points inside the unit sphere as in
+    /// the first book, for comparing with images rendered that way.
+    pub fn fuzz_in_sphere(mut self) -> Self {
+        self.fuzz_in_sphere = true;
+        self
+    }
+}
+
+impl<A: Texture, R: Texture> Material for Metal<A, R> {
     fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        let (point, u, v) = (hit_record.point, hit_record.u, hit_record.v);
+        let fuzziness = self.fuzziness.color_at_time(point, u, v, ray.time());
+        let fuzziness = fuzziness.iter().sum::<f64>() / 3.0;
+
         let reflected = ray
             .direction()
             .reflect(hit_record.normal_against_ray)
             .normalized();
-        let direction = reflected + self.fuzziness * Vec3::random_in_unit_sphere();
-        let scattered = Ray::new(hit_record.point, direction, ray.time());
+        let fuzz = if self.fuzz_in_sphere {
+            Vec3::random_in_unit_sphere()
+        } else {
+            Vec3::random_unit_vector()
+        };
+        let direction = reflected + fuzziness * fuzz;
+        let scattered = Ray::new(point, direction, ray.time());
 
         // if the ray is reflected towards the surface, then we scatter it
         if scattered.direction().dot(hit_record.normal_against_ray) > 0.0 {
-            Some((scattered, self.albedo))
+            let albedo = self.albedo.color_at_time(point, u, v, ray.time());
+            Some((scattered, albedo))
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{object::rectangle::AxisAlignedRectangle, texture::UvGrid, Hit, Interval, Point3};
+
+    #[test]
+    fn roughness_map_blurs_parts_of_the_surface() {
+        // sharp on the left half of the texture, blurry on the right
+        #[derive(Debug)]
+        struct HalfRough;
+        impl Texture for HalfRough {
+            fn color(&self, _point: Point3, u: f64, _v: f64) -> Color {
+                Color::constant(if u < 0.5 { 0.0 } else { 0.6 })
+            }
+        }
+
+        let metal = Metal::textured(UvGrid::default(), HalfRough);
+        let ground = AxisAlignedRectangle::new_xz((0.0, 0.0), (2.0, 2.0), 0.0, Arc::new(metal));
+        let direction = Vec3::new(1.0, -1.0, 0.0);
+        let reflected = Vec3::new(1.0, 1.0, 0.0).normalized();
+        let scatter = |x: f64| {
+            let ray = Ray::new(Point3::new(x - 1.0, 1.0, 0.75), direction, 0.0);
+            let hit = ground
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            hit.material.scatter(&ray, &hit)
+        };
+
+        for _ in 0..20 {
+            let (scattered, albedo) = scatter(0.5).unwrap();
+            assert!((scattered.direction() - reflected).norm() < 1e-12);
+            assert_eq!(albedo, Color::new(0.25, 0.375, 0.0));
+        }
+        let blurred = (0..20)
+            .filter_map(|_| scatter(1.5))
+            .filter(|(scattered, _)| (scattered.direction() - reflected).norm() > 1e-3)
+            .count();
+        assert!(blurred > 10);
+    }
+
+    #[test]
+    fn fuzz_on_the_sphere_blurs_more() {
+        let ground = |metal: Metal| {
+            AxisAlignedRectangle::new_xz((-1e3, -1e3), (1e3, 1e3), 0.0, Arc::new(metal))
+        };
+        let ray = Ray::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), 0.0);
+        let reflected = Vec3::new(1.0, 1.0, 0.0).normalized();
+        // mean distance of the scattered directions from the mirror direction
+        let spread = |metal: Metal| {
+            let ground = ground(metal);
+            let hit = ground
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            let (sum, count) = (0..20000)
+                .filter_map(|_| hit.material.scatter(&ray, &hit))
+                .fold((0.0, 0), |(sum, count), (scattered, _)| {
+                    let offset = scattered.direction() - reflected;
+                    (sum + offset.norm(), count + 1)
+                });
+            sum / count as f64
+        };
+
+        crate::random::seed(73);
+        let on_sphere = spread(Metal::new(Color::WHITE, 0.3));
+        let in_sphere = spread(Metal::new(Color::WHITE, 0.3).fuzz_in_sphere());
+        // the mean distance from the center of the unit sphere is 3 / 4
+        assert!((on_sphere - 0.3).abs() < 0.01, "{on_sphere}");
+        assert!((in_sphere - 0.225).abs() < 0.01, "{in_sphere}");
+    }
+}
@@ -0,0 +1,221 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hit::AgainstRayHitRecord,
+    random,
+    texture::{SolidColor, Texture},
+    Color, Material, Onb, Ray, Vec3,
+};
+
+/// Physically based metal, made of tiny mirrors whose normals follow the GGX
+/// (Trowbridge–Reitz) distribution.
+///
+/// Unlike the fuzz of [`super::Metal`], rough reflections spread out like on
+/// real metal, and lose energy at grazing angles through the Smith
+/// shadowing-masking term. The color is the reflectance at normal incidence,
+/// which goes towards white at grazing angles by the Fresnel-Schlick
+/// approximation.
+///
+/// Like [`super::Metal`], the color and the roughness are textures, and the
+/// roughness is the average of the channels of its texture. A roughness of 0
+/// is a perfect mirror, and 1 is very rough. It is squared to get the GGX
+/// width, so that the blur changes evenly.
+#[derive(Debug, Clone)]
+pub struct MicrofacetMetal<A: Texture = SolidColor, R: Texture = SolidColor> {
+    /// Reflectance at normal incidence
+    color: A,
+    roughness: R,
+}
+
+impl MicrofacetMetal {
+    pub fn new(color: Color, roughness: f64) -> Self {
+        Self::textured(
+            SolidColor::new(color),
+            SolidColor::new(Color::constant(roughness)),
+        )
+    }
+}
+
+impl<A: Texture, R: Texture> MicrofacetMetal<A, R> {
+    /// Metal with the color and the roughness looked up from textures at the
+    /// hit point.
+    pub fn textured(color: A, roughness: R) -> Self {
+        Self { color, roughness }
+    }
+}
+
+/// Smith's auxiliary function for the direction `w` in the local frame, where
+/// the normal is +z.
+fn smith_lambda(w: Vec3<f64>, alpha: f64) -> f64 {
+    let tan_squared = (w.x() * w.x() + w.y() * w.y()) / (w.z() * w.z());
+    ((1.0 + alpha * alpha * tan_squared).sqrt() - 1.0) / 2.0
+}
+
+/// Microfacet normal seen from `view` in the local frame, sampled from the
+/// distribution of visible normals of Heitz, "Sampling the GGX Distribution of
+/// Visible Normals" (2018).
+fn sample_visible_normal(view: Vec3<f64>, alpha: f64, u1: f64, u2: f64) -> Vec3<f64> {
+    // stretch the view to the configuration where the roughness is 1
+    let view = Vec3::new(alpha * view.x(), alpha * view.y(), view.z()).normalized();
+
+    let len_squared = view.x() * view.x() + view.y() * view.y();
+    let t1 = if len_squared > 0.0 {
+        Vec3::new(-view.y(), view.x(), 0.0) / len_squared.sqrt()
+    } else {
+        Vec3::unit_x()
+    };
+    let t2 = view.cross(t1);
+
+    // uniform point on the disk, squeezed to the visible half
+    let (r, phi) = (u1.sqrt(), 2.0 * PI * u2);
+    let (p1, p2) = (r * phi.cos(), r * phi.sin());
+    let s = (1.0 + view.z()) / 2.0;
+    let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * p2;
+    let height = (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
+    let normal = t1 * p1 + t2 * p2 + view * height;
+
+    // and back to the ellipsoid
+    let normal = Vec3::new(alpha * normal.x(), alpha * normal.y(), normal.z().max(0.0));
+    if normal.is_near_zero() {
+        Vec3::unit_z()
+    } else {
+        normal.normalized()
+    }
+}
+
+impl<A: Texture, R: Texture> Material for MicrofacetMetal<A, R> {
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        let (point, u, v) = (hit_record.point, hit_record.u, hit_record.v);
+        let roughness = self.roughness.color_at_time(point, u, v, ray.time());
+        let roughness = (roughness.iter().sum::<f64>() / 3.0).clamp(0.0, 1.0);
+        let alpha = roughness * roughness;
+
+        // the frame where the normal is +z
+        let frame = Onb::from_w(hit_record.normal_against_ray);
+
+        let view = frame.to_local(-ray.direction().normalized());
+        if view.z() <= 0.0 {
+            return None;
+        }
+        let half = sample_visible_normal(view, alpha, random::random(), random::random());
+        let cos_view_half = view.dot(half);
+        let incoming = half * (2.0 * cos_view_half) - view;
+        // reflected into the surface by a microfacet, and lost
+        if incoming.z() <= 0.0 {
+            return None;
+        }
+
+        // the BRDF over the pdf of the visible normals reduces to F G2 / G1
+        let f0 = self
+            .color
+            .color_at_time(point, u, v, ray.time())
+            .clamp(0.0, 1.0);
+        let fresnel = f0 + (Color::WHITE - f0) * (1.0 - cos_view_half).max(0.0).powi(5);
+        let lambda_view = smith_lambda(view, alpha);
+        let masking = (1.0 + lambda_view) / (1.0 + lambda_view + smith_lambda(incoming, alpha));
+
+        let direction = frame.local(incoming);
+        Some((Ray::new(point, direction, ray.time()), fresnel * masking))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{material::Metal, object::rectangle::AxisAlignedRectangle, Hit, Interval, Point3};
+
+    /// Scatter rays coming from `direction` on a ground made of `material`.
+    fn scatter_on_ground(
+        material: impl Material + 'static,
+        direction: Vec3<f64>,
+        count: usize,
+    ) -> Vec<Option<(Ray, Color)>> {
+        let ground =
+            AxisAlignedRectangle::new_xz((-1e3, -1e3), (1e3, 1e3), 0.0, Arc::new(material));
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0) - direction, direction, 0.0);
+        let hit = ground
+            .hit(ray.clone(), Interval::from_min(0.0))
+            .unwrap()
+            .into_against_ray();
+        (0..count)
+            .map(|_| hit.material.scatter(&ray, &hit))
+            .collect()
+    }
+
+    #[test]
+    fn white_furnace_never_gains_energy() {
+        random::seed(21);
+        let mut rng = StdRng::seed_from_u64(21);
+        for roughness in [0.0, 0.2, 0.5, 0.8, 1.0] {
+            for _ in 0..10 {
+                // from any direction above the ground, down to nearly grazing
+                let (angle, elevation) = (rng.gen_range(0.0..2.0 * PI), rng.gen_range(0.01..1.0));
+                let direction = Vec3::new(angle.cos(), -elevation, angle.sin());
+                let samples = scatter_on_ground(
+                    MicrofacetMetal::new(Color::WHITE, roughness),
+                    direction,
+                    200,
+                );
+                let mut energy = 0.0;
+                for (scattered, attenuation) in samples.iter().flatten() {
+                    assert!(attenuation.iter().all(|&x| (0.0..=1.0).contains(&x)));
+                    assert!(scattered.direction().y() > 0.0);
+                    energy += attenuation.r();
+                }
+                assert!(energy <= samples.len() as f64);
+            }
+        }
+    }
+
+    #[test]
+    fn roughness_spreads_like_fuzz() {
+        let direction = Vec3::new(1.0, -1.0, 0.0);
+        let mirror = Vec3::new(1.0, 1.0, 0.0).normalized();
+        let color = Color::new(0.9, 0.6, 0.3);
+
+        // smooth metal is a mirror with the color head-on, like `Metal`
+        random::seed(22);
+        let sharp = scatter_on_ground(MicrofacetMetal::new(color, 0.0), direction, 1)[0]
+            .clone()
+            .unwrap();
+        let metal = scatter_on_ground(Metal::new(color, 0.0), direction, 1)[0]
+            .clone()
+            .unwrap();
+        assert!((sharp.0.direction().normalized() - mirror).norm() < 1e-12);
+        assert!((sharp.0.direction().normalized() - metal.0.direction()).norm() < 1e-12);
+        // brighter than the color at 45 degrees by Fresnel
+        assert!(sharp.1.iter().zip(color.iter()).all(|(x, c)| x >= c));
+
+        // mean angle from the mirror direction, which grows with the roughness,
+        // starting out like the fuzz but spreading wider at high roughness
+        let spread = |samples: Vec<Option<(Ray, Color)>>| {
+            let angles: Vec<f64> = samples
+                .into_iter()
+                .flatten()
+                .map(|(ray, _)| ray.direction().normalized().dot(mirror).min(1.0).acos())
+                .collect();
+            angles.iter().sum::<f64>() / angles.len() as f64
+        };
+        let mut previous = 0.0;
+        for (roughness, ratio) in [(0.2, 0.6..1.4), (0.5, 1.0..2.5), (0.8, 1.0..3.0)] {
+            random::seed(23);
+            let ggx = spread(scatter_on_ground(
+                MicrofacetMetal::new(color, roughness),
+                direction,
+                2000,
+            ));
+            let fuzz = spread(scatter_on_ground(
+                Metal::new(color, roughness),
+                direction,
+                2000,
+            ));
+            assert!(ggx > previous);
+            assert!(ratio.contains(&(ggx / fuzz)), "{ggx} vs {fuzz}");
+            previous = ggx;
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    hit::AgainstRayHitRecord,
+    random,
+    texture::{SolidColor, Texture},
+    Color, Material, Point3, Ray, Vec3,
+};
+
+use super::ScatterRecord;
+
+/// Mixture of two materials, such as plastic from a [`super::Lambertian`]
+/// and a [`super::Metal`], or dusty glass.
+///
+/// Each scattering picks the first material with probability given by the
+/// weight and the second one otherwise, so on average the surface behaves
+/// like the weighted sum of the two. The emission is the weighted sum of the
+/// emissions.
+///
+/// [`Material::scatter_record`] and [`Material::scattering_pdf`] are
+/// forwarded to one of the two materials, picked with the same weight. Since
+/// the density must come from the material which scattered, the pick is not
+/// random but a hash of the ray and the hit point, so both calls for the
+/// same hit agree.
+///
+/// The weight is looked up from a mask texture at the hit point, as the
+/// average of its channels clamped to `[0, 1]`, so a gray texture gives it
+/// directly.
+#[derive(Debug, Clone)]
+pub struct Mix<A: Material, B: Material, W: Texture = SolidColor> {
+    first: A,
+    second: B,
+    /// Weight of the first material
+    mask: W,
+}
+
+impl<A: Material, B: Material> Mix<A, B> {
+    /// Mixture with the same `weight` of `first` everywhere.
+    pub fn new(first: A, second: B, weight: f64) -> Self {
+        Self::with_mask(first, second, SolidColor::new(Color::constant(weight)))
+    }
+}
+
+impl<A: Material, B: Material, W: Texture> Mix<A, B, W> {
+    /// Mixture with the weight of `first` looked up from `mask`.
+    pub fn with_mask(first: A, second: B, mask: W) -> Self {
+        Self {
+            first,
+            second,
+            mask,
+        }
+    }
+
+    /// Weight of the first material at a point.
+    fn weight(&self, point: Point3, u: f64, v: f64, time: f64) -> f64 {
+        let weight = self.mask.color_at_time(point, u, v, time);
+        (weight.iter().sum::<f64>() / 3.0).clamp(0.0, 1.0)
+    }
+
+    /// Whether the first material scatters `ray` at the hit, picked by a
+    /// hash of both, which is uniform in `[0, 1)` over many hits.
+    fn picks_first(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> bool {
+        let mut hasher = DefaultHasher::new();
+        let (origin, direction, point) = (ray.origin(), ray.direction(), hit_record.point);
+        for value in origin.iter().chain(direction.iter()).chain(point.iter()) {
+            value.to_bits().hash(&mut hasher);
+        }
+        let uniform = (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64;
+        uniform < self.weight(hit_record.point, hit_record.u, hit_record.v, ray.time())
+    }
+}
+
+impl<A: Material, B: Material, W: Texture> Material for Mix<A, B, W> {
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        let weight = self.weight(hit_record.point, hit_record.u, hit_record.v, ray.time());
+        if random::random::<f64>() < weight {
+            self.first.scatter(ray, hit_record)
+        } else {
+            self.second.scatter(ray, hit_record)
+        }
+    }
+
+    fn scatter_record(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<ScatterRecord> {
+        if self.picks_first(ray, hit_record) {
+            self.first.scatter_record(ray, hit_record)
+        } else {
+            self.second.scatter_record(ray, hit_record)
+        }
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        if self.picks_first(ray, hit_record) {
+            self.first.scattering_pdf(ray, hit_record, scattered)
+        } else {
+            self.second.scattering_pdf(ray, hit_record, scattered)
+        }
+    }
+
+    fn emit(&self, point: Point3, u: f64, v: f64) -> Color {
+        let weight = self.weight(point, u, v, 0.0);
+        self.first.emit(point, u, v) * weight + self.second.emit(point, u, v) * (1.0 - weight)
+    }
+
+    fn emit_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        let weight = self.weight(point, u, v, time);
+        self.first.emit_at_time(point, u, v, time) * weight
+            + self.second.emit_at_time(point, u, v, time) * (1.0 - weight)
+    }
+
+    fn emit_towards(
+        &self,
+        ray: &Ray,
+        point: Point3,
+        normal_outward: Vec3<f64>,
+        u: f64,
+        v: f64,
+    ) -> Color {
+        let weight = self.weight(point, u, v, ray.time());
+        self.first.emit_towards(ray, point, normal_outward, u, v) * weight
+            + self.second.emit_towards(ray, point, normal_outward, u, v) * (1.0 - weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::{DiffuseLight, Lambertian, Metal},
+        object::rectangle::AxisAlignedRectangle,
+        Hit, Interval,
+    };
+
+    fn hit_at(material: impl Material + 'static, u: f64) -> (Ray, AgainstRayHitRecord) {
+        let ground = AxisAlignedRectangle::new_xz((0.0, 0.0), (1.0, 1.0), 0.0, Arc::new(material));
+        let ray = Ray::new(Point3::new(u, 1.0, 0.75), -Vec3::unit_y(), 0.0);
+        let hit = ground.hit(ray.clone(), Interval::from_min(0.0)).unwrap();
+        (ray, hit.into_against_ray())
+    }
+
+    #[test]
+    fn scatters_by_the_weight() {
+        // lights never scatter, so the scattered rays come from the first material
+        let plastic = || {
+            Mix::new(
+                Lambertian::new_solid(Color::constant(0.5)),
+                DiffuseLight::new_solid(Color::BLACK),
+                0.3,
+            )
+        };
+        let (ray, hit) = hit_at(plastic(), 0.5);
+        random::seed(12);
+        let scattered = (0..10000)
+            .filter(|_| hit.material.scatter(&ray, &hit).is_some())
+            .count();
+        assert!((scattered as f64 / 10000.0 - 0.3).abs() < 0.02);
+
+        // a mask of the u coordinate gives only the second material at u = 0
+        #[derive(Debug)]
+        struct Gradient;
+        impl Texture for Gradient {
+            fn color(&self, _point: Point3, u: f64, _v: f64) -> Color {
+                Color::constant(u)
+            }
+        }
+        let masked = || {
+            Mix::with_mask(
+                Lambertian::new_solid(Color::constant(0.5)),
+                DiffuseLight::new_solid(Color::BLACK),
+                Gradient,
+            )
+        };
+        let (ray, hit) = hit_at(masked(), 0.0);
+        assert!((0..100).all(|_| hit.material.scatter(&ray, &hit).is_none()));
+        let (ray, hit) = hit_at(masked(), 1.0);
+        assert!((0..100).all(|_| hit.material.scatter(&ray, &hit).is_some()));
+    }
+
+    #[test]
+    fn scatter_records_follow_one_material() {
+        let lambertian = Lambertian::new_solid(Color::constant(0.5));
+        let plastic = || {
+            Mix::new(
+                Lambertian::new_solid(Color::constant(0.5)),
+                Metal::new(Color::WHITE, 0.0),
+                0.3,
+            )
+        };
+
+        const HITS: usize = 4000;
+        let mut diffuse = 0;
+        for i in 0..HITS {
+            let (ray, hit) = hit_at(plastic(), (i as f64 + 0.5) / HITS as f64);
+            let record = hit.material.scatter_record(&ray, &hit).unwrap();
+            // the same hit always picks the same material
+            let again = hit.material.scatter_record(&ray, &hit).unwrap();
+            assert_eq!(
+                matches!(record, ScatterRecord::Diffuse { .. }),
+                matches!(again, ScatterRecord::Diffuse { .. })
+            );
+
+            let scattered = match record {
+                ScatterRecord::Diffuse { pdf, .. } => {
+                    diffuse += 1;
+                    let scattered = Ray::new(hit.point, pdf.generate(), ray.time());
+                    let expected = lambertian.scattering_pdf(&ray, &hit, &scattered);
+                    assert!(expected > 0.0);
+                    assert_eq!(
+                        hit.material.scattering_pdf(&ray, &hit, &scattered),
+                        expected
+                    );
+                    continue;
+                }
+                ScatterRecord::Specular { ray, .. } => ray,
+            };
+            assert_eq!(hit.material.scattering_pdf(&ray, &hit, &scattered), 0.0);
+        }
+        assert!(
+            (diffuse as f64 / HITS as f64 - 0.3).abs() < 0.03,
+            "{diffuse}"
+        );
+    }
+
+    #[test]
+    fn emission_is_blended() {
+        let (red, blue) = (Color::new(4.0, 0.0, 0.0), Color::new(0.0, 0.0, 2.0));
+        let light = Mix::new(
+            DiffuseLight::new_solid(red),
+            DiffuseLight::new_solid(blue),
+            0.25,
+        );
+        let (_, hit) = hit_at(light, 0.5);
+        assert_eq!(hit.emitted, red * 0.25 + blue * 0.75);
+
+        let light = Mix::new(
+            DiffuseLight::new_solid(red),
+            Lambertian::new_solid(Color::WHITE),
+            0.5,
+        );
+        assert_eq!(light.emit(Point3::zeros(), 0.0, 0.0), red * 0.5);
+    }
+}
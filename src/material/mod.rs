@@ -3,16 +3,47 @@ mod lambertian;
 mod metal;
 mod diffuse_light;
 mod isotropic;
+mod mix;
+mod microfacet;
+mod plastic;
+mod two_sided;
+mod debug;
+mod closure;
+mod translucent;
+mod checker;
+mod normal_map;
 
 pub use dielectric::Dielectric;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
-pub use diffuse_light::DiffuseLight;
+pub use diffuse_light::{DiffuseLight, EmissionProfile};
 pub use isotropic::Isotropic;
+pub use mix::Mix;
+pub use microfacet::MicrofacetMetal;
+pub use plastic::Plastic;
+pub use two_sided::TwoSided;
+pub use debug::{NormalMaterial, UvMaterial};
+pub use closure::FnMaterial;
+pub use translucent::Translucent;
+pub use checker::CheckerMaterial;
+pub use normal_map::NormalMap;
 
-use crate::{Color, Point3, Ray, hit::AgainstRayHitRecord};
+use crate::{Color, Point3, Ray, Vec3, hit::AgainstRayHitRecord, pdf::Pdf};
 use std::fmt::Debug;
 
+/// How a material scatters a ray, see [`Material::scatter_record`].
+#[derive(Debug)]
+pub enum ScatterRecord {
+    /// The material picked the scattered ray itself, such as the mirror
+    /// reflection of a metal. The attenuation is the weight of the ray, with
+    /// no density to divide by.
+    Specular { ray: Ray, attenuation: Color },
+    /// The scattered direction should be sampled from `pdf`, and weighted by
+    /// the attenuation times [`Material::scattering_pdf`] over the density
+    /// of the sampled direction.
+    Diffuse { pdf: Box<dyn Pdf>, attenuation: Color },
+}
+
 /// A material that can be hit by a ray
 pub trait Material: Debug + Sync + Send {
     /// Scatter a ray, returning the ray scattered and the attenuation of the ray.
@@ -21,6 +52,22 @@ pub trait Material: Debug + Sync + Send {
     /// in the Physically Based Rendering book.
     fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)>;
 
+    /// Scatter a ray, returning either the scattered ray or the density to
+    /// sample it from, which lets the renderer importance sample it.
+    ///
+    /// The default is the ray of [`Material::scatter`] as a specular one.
+    fn scatter_record(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<ScatterRecord> {
+        self.scatter(ray, hit_record)
+            .map(|(ray, attenuation)| ScatterRecord::Specular { ray, attenuation })
+    }
+
+    /// Density of the material scattering `ray` into `scattered`, for
+    /// materials returning [`ScatterRecord::Diffuse`].
+    #[allow(unused_variables)] // This is a default implementation, so the arguments may not be used.
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        0.0
+    }
+
     /// Return the emitted color of material. For non-emissive materials, this
     /// is always black.
     ///
@@ -32,4 +79,31 @@ pub trait Material: Debug + Sync + Send {
     fn emit(&self, point: Point3, u: f64, v: f64) -> Color {
         Color::BLACK
     }
+
+    /// Return the emitted color of material for a ray at `time`.
+    ///
+    /// Only materials with animated textures depend on the time, the default
+    /// ignores it.
+    #[allow(unused_variables)] // This is a default implementation, so the arguments may not be used.
+    fn emit_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        self.emit(point, u, v)
+    }
+
+    /// Return the color emitted back along `ray`, which hits the surface at
+    /// `point` with outward normal `normal_outward`.
+    ///
+    /// Materials whose emission depends on the direction it is seen from use
+    /// the ray and the normal, the default ignores them. The side of the
+    /// surface that is seen is given by [`crate::hit::is_front_face`].
+    #[allow(unused_variables)] // This is a default implementation, so the arguments may not be used.
+    fn emit_towards(
+        &self,
+        ray: &Ray,
+        point: Point3,
+        normal_outward: Vec3<f64>,
+        u: f64,
+        v: f64,
+    ) -> Color {
+        self.emit_at_time(point, u, v, ray.time())
+    }
 }
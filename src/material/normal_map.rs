@@ -0,0 +1,286 @@
+use crate::{
+    hit::AgainstRayHitRecord,
+    texture::{Image, Texture},
+    Color, Material, Point3, Ray, Vec3,
+};
+
+use super::ScatterRecord;
+
+/// Step in texture coordinates for the slopes of a bump map
+const BUMP_DELTA: f64 = 1e-3;
+
+/// How the texture of a [`NormalMap`] turns the normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mapping {
+    /// Colors are tangent-space normals, with the x and y components
+    /// multiplied by `strength`
+    Normals { strength: f64 },
+    /// Luminances are heights along the normal, multiplied by `scale`
+    Bump { scale: f64 },
+}
+
+/// A material whose shading normal is turned by a texture, which gives a flat
+/// surface the look of relief.
+///
+/// The normal is turned in the tangent space of the hit, given by its
+/// [`tangents`](crate::hit::OutwardHitRecord::tangents), before the inner
+/// material scatters the ray. Hits without tangents, such as those of objects
+/// which do not compute them, keep their normal. Emission is not affected.
+#[derive(Debug, Clone)]
+pub struct NormalMap<M: Material, T: Texture> {
+    material: M,
+    texture: T,
+    mapping: Mapping,
+}
+
+impl<M: Material, T: Texture> NormalMap<M, T> {
+    /// Normals from the colors of `normals`, each channel mapped from `[0, 1]`
+    /// to `[-1, 1]` as in a usual normal map: red along dp/du, green along
+    /// dp/dv and blue along the normal.
+    ///
+    /// The colors are used as they are, so an 8-bit [`Image`] must be
+    /// [`Image::linear`], as made by [`NormalMap::from_image`]. Decoded from
+    /// sRGB, the flat color (128, 128, 255) tilts every normal by about 35
+    /// degrees.
+    pub fn new(material: M, normals: T) -> Self {
+        Self {
+            material,
+            texture: normals,
+            mapping: Mapping::Normals { strength: 1.0 },
+        }
+    }
+
+    /// Normals of the surface raised by the luminance of `heights` times
+    /// `scale`, in world units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` is not finite.
+    pub fn bump(material: M, heights: T, scale: f64) -> Self {
+        assert!(scale.is_finite(), "bump scale must be finite");
+        Self {
+            material,
+            texture: heights,
+            mapping: Mapping::Bump { scale },
+        }
+    }
+
+    /// The same normal map, with the tilt of the normals multiplied by
+    /// `strength`. A strength of zero gives the flat surface back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `strength` is not finite.
+    pub fn with_strength(self, strength: f64) -> Self {
+        assert!(strength.is_finite(), "strength must be finite");
+        let mapping = match self.mapping {
+            Mapping::Normals { strength: s } => Mapping::Normals {
+                strength: s * strength,
+            },
+            Mapping::Bump { scale } => Mapping::Bump {
+                scale: scale * strength,
+            },
+        };
+        Self { mapping, ..self }
+    }
+
+    /// Outward normal turned by the texture, or `None` if the hit has no
+    /// tangents or they are degenerate.
+    fn mapped_normal(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<Vec3<f64>> {
+        let (dpdu, dpdv) = hit_record.tangents?;
+        let normal = hit_record.normal_outward();
+        let (point, u, v) = (hit_record.point, hit_record.u, hit_record.v);
+        let texture = |point, u, v| self.texture.color_at_time(point, u, v, ray.time());
+
+        let mapped = match self.mapping {
+            Mapping::Normals { strength } => {
+                let tangent = (dpdu - normal * normal.dot(dpdu)).normalized();
+                let mut bitangent = normal.cross(tangent);
+                if bitangent.dot(dpdv) < 0.0 {
+                    bitangent = -bitangent;
+                }
+                let color = texture(point, u, v);
+                let [x, y, z] = [color.r(), color.g(), color.b()].map(|c| 2.0 * c - 1.0);
+                tangent * (x * strength) + bitangent * (y * strength) + normal * z
+            }
+            Mapping::Bump { scale } => {
+                let height = |point, u, v| scale * texture(point, u, v).luminance();
+                let base = height(point, u, v);
+                let du = height(point + dpdu * BUMP_DELTA, u + BUMP_DELTA, v) - base;
+                let dv = height(point + dpdv * BUMP_DELTA, u, v + BUMP_DELTA) - base;
+                // the surface raised along the normal, ignoring its curvature
+                let raised_u = dpdu + normal * (du / BUMP_DELTA);
+                let raised_v = dpdv + normal * (dv / BUMP_DELTA);
+                let mapped = raised_u.cross(raised_v);
+                if mapped.dot(normal) < 0.0 {
+                    -mapped
+                } else {
+                    mapped
+                }
+            }
+        };
+
+        let length = mapped.norm();
+        (length.is_finite() && length > 1e-12).then(|| mapped / length)
+    }
+
+    /// The hit as seen by the inner material, with the mapped normal.
+    fn mapped(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> AgainstRayHitRecord {
+        let mut mapped = hit_record.clone();
+        if let Some(normal) = self.mapped_normal(ray, hit_record) {
+            mapped.normal_against_ray = if hit_record.is_front() {
+                normal
+            } else {
+                -normal
+            };
+        }
+        mapped
+    }
+}
+
+impl<M: Material> NormalMap<M, Image> {
+    /// Normals from a normal map image, whose 8-bit pixels are taken as
+    /// linear, see [`NormalMap::new`].
+    pub fn from_image(material: M, normals: Image) -> Self {
+        Self::new(material, normals.linear())
+    }
+}
+
+impl<M: Material, T: Texture> Material for NormalMap<M, T> {
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        self.material.scatter(ray, &self.mapped(ray, hit_record))
+    }
+
+    fn scatter_record(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<ScatterRecord> {
+        self.material
+            .scatter_record(ray, &self.mapped(ray, hit_record))
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        self.material
+            .scattering_pdf(ray, &self.mapped(ray, hit_record), scattered)
+    }
+
+    fn emit(&self, point: Point3, u: f64, v: f64) -> Color {
+        self.material.emit(point, u, v)
+    }
+
+    fn emit_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        self.material.emit_at_time(point, u, v, time)
+    }
+
+    fn emit_towards(
+        &self,
+        ray: &Ray,
+        point: Point3,
+        normal_outward: Vec3<f64>,
+        u: f64,
+        v: f64,
+    ) -> Color {
+        self.material.emit_towards(ray, point, normal_outward, u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        hit::Transform,
+        material::Metal,
+        object::rectangle::AxisAlignedRectangle,
+        texture::{FnTexture, SolidColor},
+        Hit, HitExt, Interval, Quat,
+    };
+
+    /// Direction of the mirror reflection of a ray straight down onto a
+    /// floor in the XZ plane, with `material` wrapped around a mirror.
+    fn reflected<T: Texture + 'static>(
+        material: impl FnOnce(Metal) -> NormalMap<Metal, T>,
+    ) -> Vec3<f64> {
+        let mirror = material(Metal::new(Color::WHITE, 0.0));
+        let floor = AxisAlignedRectangle::new_xz((-1.0, -1.0), (1.0, 1.0), 0.0, Arc::new(mirror));
+        let ray = Ray::new(Point3::new(0.2, 1.0, 0.3), -Vec3::unit_y(), 0.0);
+        let hit = floor
+            .hit(ray.clone(), Interval::from_min(0.0))
+            .unwrap()
+            .into_against_ray();
+        hit.material.scatter(&ray, &hit).unwrap().0.direction()
+    }
+
+    #[test]
+    fn flat_maps_keep_the_normal() {
+        let flat = Color::new(0.5, 0.5, 1.0);
+        let direction = reflected(|metal| NormalMap::new(metal, SolidColor::new(flat)));
+        assert!((direction - Vec3::unit_y()).norm() < 1e-12);
+        let direction = reflected(|metal| NormalMap::bump(metal, SolidColor::new(flat), 1.0));
+        assert!((direction - Vec3::unit_y()).norm() < 1e-12);
+    }
+
+    #[test]
+    fn flat_normal_map_images_keep_the_normal() {
+        let flat = image::RgbImage::from_pixel(2, 2, image::Rgb([128, 128, 255]));
+        let direction = reflected(|metal| NormalMap::from_image(metal, Image::new(flat.clone())));
+        // 128 is half a step above the middle, a tilt of a few milliradians
+        assert!((direction - Vec3::unit_y()).norm() < 0.02, "{direction}");
+        // decoded from sRGB, the same image tilts the normal
+        let direction = reflected(|metal| NormalMap::new(metal, Image::new(flat)));
+        assert!((direction - Vec3::unit_y()).norm() > 0.5, "{direction}");
+    }
+
+    #[test]
+    fn tilted_normals_turn_the_reflection() {
+        // the floor's u runs along x, so red tilts the normal towards +x
+        let tilted = Color::new(0.75, 0.5, 1.0);
+        let direction = reflected(|metal| NormalMap::new(metal, SolidColor::new(tilted)));
+        assert!(direction.x() > 0.5 && direction.y() > 0.0);
+        assert!(direction.z().abs() < 1e-12);
+        let flat =
+            reflected(|metal| NormalMap::new(metal, SolidColor::new(tilted)).with_strength(0.0));
+        assert!((flat - Vec3::unit_y()).norm() < 1e-12);
+
+        // a ramp rising along u, i.e. towards +x, tilts the normal back to -x
+        let ramp = FnTexture::from_uv(|u, _| Color::constant(u));
+        let direction = reflected(|metal| NormalMap::bump(metal, ramp, 1.0));
+        assert!(direction.x() < -0.5 && direction.y() > 0.0);
+    }
+
+    #[test]
+    fn rotated_floors_turn_the_tangents() {
+        let tilted = Color::new(0.75, 0.5, 1.0);
+        let mirror = Arc::new(NormalMap::new(
+            Metal::new(Color::WHITE, 0.0),
+            SolidColor::new(tilted),
+        ));
+        let floor = || AxisAlignedRectangle::new_xz((-1.0, -1.0), (1.0, 1.0), 0.0, mirror.clone());
+        // a quarter turn around the Y axis, which takes the floor's u from
+        // the X axis to the Z axis
+        let rotated: Vec<Box<dyn Hit>> = vec![
+            Box::new(floor().rotate_y(90.0)),
+            Box::new(floor().rotate(Quat::from_axis_angle(Vec3::unit_y(), 90.0))),
+            Box::new(Transform::new(floor()).rotate_y(90.0)),
+            Box::new(
+                Transform::new(floor())
+                    .rotate_y(90.0)
+                    .scale(Vec3::constant(2.0)),
+            ),
+        ];
+
+        let ray = Ray::new(Point3::new(0.2, 1.0, 0.3), -Vec3::unit_y(), 0.0);
+        for floor in rotated {
+            let hit = floor
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            let (dpdu, dpdv) = hit.tangents.unwrap();
+            assert!(dpdu.x().abs() < 1e-12 && dpdu.y().abs() < 1e-12);
+            assert!(dpdv.y().abs() < 1e-12 && dpdu.dot(dpdv).abs() < 1e-12);
+
+            // red tilts the normal, and so the reflection, along the new u
+            let direction = hit.material.scatter(&ray, &hit).unwrap().0.direction();
+            assert!(direction.dot(dpdu.normalized()) > 0.5 && direction.y() > 0.0);
+            assert!(direction.x().abs() < 1e-12);
+        }
+    }
+}
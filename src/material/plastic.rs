@@ -0,0 +1,182 @@
+use crate::{
+    hit::AgainstRayHitRecord,
+    random,
+    texture::{SolidColor, Texture},
+    Color, Material, Ray,
+};
+
+use super::{Dielectric, Lambertian, ScatterRecord};
+
+/// Shiny plastic: a diffuse base under a thin clear coat.
+///
+/// The coat reflects like glass of `index_of_refraction`, more so at grazing
+/// angles. Each scattering picks the mirror reflection off the coat with the
+/// probability given by the Fresnel term of the incoming ray, and the diffuse
+/// base otherwise. Light from the base passes through the coat on the way out
+/// too, so it is attenuated by the part of the scattered ray not reflected
+/// back by the coat.
+///
+/// For importance sampling, the coat is a specular [`ScatterRecord`] and the
+/// base a diffuse one, whose [`Material::scattering_pdf`] is attenuated by
+/// the coat the same way.
+#[derive(Debug, Clone)]
+pub struct Plastic<T: Texture> {
+    base: Lambertian<T>,
+    index_of_refraction: f64,
+}
+
+impl<T: Texture> Plastic<T> {
+    pub fn new(base: T, index_of_refraction: f64) -> Self {
+        Self {
+            base: Lambertian::new(base),
+            index_of_refraction,
+        }
+    }
+}
+
+impl<T: Texture> Plastic<T> {
+    /// Fraction of the light reflected by the coat, at `cosine` to the normal.
+    fn coat(&self, cosine: f64) -> f64 {
+        Dielectric::reflectance(cosine, self.index_of_refraction)
+    }
+
+    /// Mirror reflection of `ray` off the coat, or `None` if it goes through
+    /// the coat to the base, with the probability given by the coat.
+    fn coat_reflection(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<Ray> {
+        let normal = hit_record.normal_against_ray;
+        let unit_direction = ray.direction().normalized();
+        let cos_in = (-unit_direction).dot(normal).clamp(0.0, 1.0);
+        (random::random::<f64>() < self.coat(cos_in))
+            .then(|| Ray::new(hit_record.point, unit_direction.reflect(normal), ray.time()))
+    }
+
+    /// Fraction of the light from the base that goes through the coat along
+    /// `scattered`.
+    fn through_coat(&self, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        let cos_out = scattered
+            .direction()
+            .normalized()
+            .dot(hit_record.normal_against_ray)
+            .clamp(0.0, 1.0);
+        1.0 - self.coat(cos_out)
+    }
+}
+
+impl Plastic<SolidColor> {
+    pub fn new_solid(base: Color, index_of_refraction: f64) -> Self {
+        Self::new(SolidColor::new(base), index_of_refraction)
+    }
+}
+
+impl<T: Texture> Material for Plastic<T> {
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        if let Some(reflected) = self.coat_reflection(ray, hit_record) {
+            return Some((reflected, Color::WHITE));
+        }
+
+        let (scattered, albedo) = self.base.scatter(ray, hit_record)?;
+        let through_coat = self.through_coat(hit_record, &scattered);
+        Some((scattered, albedo * through_coat))
+    }
+
+    fn scatter_record(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<ScatterRecord> {
+        if let Some(reflected) = self.coat_reflection(ray, hit_record) {
+            return Some(ScatterRecord::Specular {
+                ray: reflected,
+                attenuation: Color::WHITE,
+            });
+        }
+        self.base.scatter_record(ray, hit_record)
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        self.base.scattering_pdf(ray, hit_record, scattered)
+            * self.through_coat(hit_record, scattered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{object::rectangle::AxisAlignedRectangle, Hit, Interval, Point3, Vec3};
+
+    /// A ray coming from `direction` and its hit on a plastic floor.
+    fn hit_floor(index_of_refraction: f64, direction: Vec3<f64>) -> (Ray, AgainstRayHitRecord) {
+        let plastic = Plastic::new_solid(Color::new(0.8, 0.1, 0.1), index_of_refraction);
+        let ground = AxisAlignedRectangle::new_xz((-1e3, -1e3), (1e3, 1e3), 0.0, Arc::new(plastic));
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0) - direction, direction, 0.0);
+        let hit = ground
+            .hit(ray.clone(), Interval::from_min(0.0))
+            .unwrap()
+            .into_against_ray();
+        (ray, hit)
+    }
+
+    /// Fraction of rays coming from `direction` that reflect off the coat.
+    fn specular_fraction(index_of_refraction: f64, direction: Vec3<f64>) -> f64 {
+        let (ray, hit) = hit_floor(index_of_refraction, direction);
+        let mirror = direction.normalized().reflect(Vec3::unit_y());
+
+        let specular = (0..4000)
+            .filter_map(|_| hit.material.scatter(&ray, &hit))
+            .filter(|(scattered, _)| (scattered.direction() - mirror).norm() < 1e-12)
+            .count();
+        specular as f64 / 4000.0
+    }
+
+    #[test]
+    fn coat_dominates_at_grazing_angles() {
+        random::seed(31);
+        let head_on = specular_fraction(1.5, -Vec3::unit_y());
+        let grazing = specular_fraction(1.5, Vec3::new(1.0, -0.02, 0.0));
+        assert!((head_on - 0.04).abs() < 0.015, "{head_on}");
+        assert!(grazing > 0.5, "{grazing}");
+
+        // a higher index reflects more, and 1 is nearly no coat at all
+        assert!(specular_fraction(1.0, Vec3::new(1.0, -1.0, 0.0)) < 0.01);
+        let edges =
+            [1.2, 1.5, 2.0].map(|index| specular_fraction(index, Vec3::new(1.0, -0.3, 0.0)));
+        assert!(edges[0] < edges[1] && edges[1] < edges[2], "{edges:?}");
+    }
+
+    #[test]
+    fn scatter_records_reflect_as_much() {
+        random::seed(37);
+        const SAMPLES: usize = 40000;
+        for direction in [-Vec3::unit_y(), Vec3::new(1.0, -0.3, 0.0)] {
+            let (ray, hit) = hit_floor(1.5, direction);
+            let scattered = (0..SAMPLES)
+                .filter_map(|_| hit.material.scatter(&ray, &hit))
+                .fold(Color::BLACK, |sum, (_, attenuation)| sum + attenuation);
+
+            // the same mean weight, with the base importance sampled
+            let (mut sampled, mut specular) = (Color::BLACK, 0);
+            for _ in 0..SAMPLES {
+                match hit.material.scatter_record(&ray, &hit).unwrap() {
+                    ScatterRecord::Specular { attenuation, .. } => {
+                        sampled += attenuation;
+                        specular += 1;
+                    }
+                    ScatterRecord::Diffuse { pdf, attenuation } => {
+                        let scattered = Ray::new(hit.point, pdf.generate(), ray.time());
+                        let scattering_pdf = hit.material.scattering_pdf(&ray, &hit, &scattered);
+                        sampled += attenuation * scattering_pdf / pdf.value(scattered.direction());
+                    }
+                }
+            }
+            let (scattered, sampled) = (scattered / SAMPLES as f64, sampled / SAMPLES as f64);
+            assert!(
+                (sampled - scattered).norm() < 0.02,
+                "{sampled} vs {scattered}"
+            );
+            let fraction = specular as f64 / SAMPLES as f64;
+            let expected = specular_fraction(1.5, direction);
+            assert!(
+                (fraction - expected).abs() < 0.02,
+                "{fraction} vs {expected}"
+            );
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    hit::AgainstRayHitRecord,
+    pdf::{CosinePdf, Pdf},
+    random,
+    texture::{SolidColor, Texture},
+    Color, Material, Ray,
+};
+
+/// Thin diffuse surface which lets some light through, such as paper or a
+/// lampshade.
+///
+/// Each scattering goes through the surface with probability given by the
+/// transmission weight, cosine-weighted around the back of the surface, and
+/// is reflected like by a [`super::Lambertian`] otherwise. Both are
+/// attenuated by the texture, so a thin quad of it glows when lit from
+/// behind.
+#[derive(Debug)]
+pub struct Translucent<T: Texture> {
+    albedo: T,
+    /// Probability of going through the surface
+    transmission: f64,
+}
+
+impl<T: Texture> Translucent<T> {
+    /// # Panics
+    ///
+    /// Panics if `transmission` is not between 0 and 1.
+    pub fn new(albedo: T, transmission: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&transmission),
+            "transmission weight must be between 0 and 1"
+        );
+        Self {
+            albedo,
+            transmission,
+        }
+    }
+}
+
+impl Translucent<SolidColor> {
+    pub fn new_solid(albedo: Color, transmission: f64) -> Self {
+        Self::new(SolidColor::new(albedo), transmission)
+    }
+}
+
+impl<T: Texture> Material for Translucent<T> {
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        let normal = hit_record.normal_against_ray;
+        let transmitted = self.transmission > 0.0 && random::random::<f64>() < self.transmission;
+        let normal = if transmitted { -normal } else { normal };
+
+        let direction = CosinePdf::new(normal).generate();
+        let scattered = Ray::new(hit_record.point, direction, ray.time());
+        let albedo =
+            self.albedo
+                .color_at_time(hit_record.point, hit_record.u, hit_record.v, ray.time());
+        Some((scattered, albedo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::Lambertian, object::rectangle::AxisAlignedRectangle, Hit, Interval, Point3, Vec3,
+    };
+
+    fn scatter_on_ground(material: impl Material + 'static) -> Vec<(Ray, Color)> {
+        let ground =
+            AxisAlignedRectangle::new_xz((-1.0, -1.0), (1.0, 1.0), 0.0, Arc::new(material));
+        let ray = Ray::new(Point3::new(0.3, 1.0, 0.2), Vec3::new(-0.2, -1.0, 0.1), 0.0);
+        let hit = ground
+            .hit(ray.clone(), Interval::from_min(0.0))
+            .unwrap()
+            .into_against_ray();
+        (0..1000)
+            .map(|_| hit.material.scatter(&ray, &hit).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn transmission_picks_the_hemisphere() {
+        let color = Color::new(0.9, 0.8, 0.5);
+        random::seed(55);
+        let opaque = scatter_on_ground(Translucent::new_solid(color, 0.0));
+        random::seed(55);
+        let lambertian = scatter_on_ground(Lambertian::new_solid(color));
+        for ((translucent, attenuation), (diffuse, albedo)) in opaque.iter().zip(&lambertian) {
+            assert_eq!(translucent.direction(), diffuse.direction());
+            assert_eq!(attenuation, albedo);
+        }
+
+        let through = scatter_on_ground(Translucent::new_solid(color, 1.0));
+        assert!(through
+            .iter()
+            .all(|(ray, attenuation)| ray.direction().y() < 0.0 && *attenuation == color));
+
+        let half = scatter_on_ground(Translucent::new_solid(color, 0.3));
+        let below = half.iter().filter(|(ray, _)| ray.direction().y() < 0.0);
+        assert!((below.count() as f64 / 1000.0 - 0.3).abs() < 0.05);
+    }
+}
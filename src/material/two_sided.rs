@@ -0,0 +1,124 @@
+use crate::{
+    hit::{is_front_face, AgainstRayHitRecord},
+    Color, Material, Point3, Ray, Vec3,
+};
+
+use super::ScatterRecord;
+
+/// A surface with a different material on each side, such as a leaf or a
+/// one-way mirror.
+///
+/// Rays hitting the front, i.e. coming from the side the outward normal
+/// points to, see the `front` material, and the others see `back`. Without a
+/// ray, as in [`Material::emit`], the front is used.
+#[derive(Debug, Clone)]
+pub struct TwoSided<F: Material, B: Material> {
+    front: F,
+    back: B,
+}
+
+impl<F: Material, B: Material> TwoSided<F, B> {
+    pub fn new(front: F, back: B) -> Self {
+        Self { front, back }
+    }
+}
+
+impl<F: Material, B: Material> Material for TwoSided<F, B> {
+    fn scatter(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<(Ray, Color)> {
+        if hit_record.is_front() {
+            self.front.scatter(ray, hit_record)
+        } else {
+            self.back.scatter(ray, hit_record)
+        }
+    }
+
+    fn scatter_record(&self, ray: &Ray, hit_record: &AgainstRayHitRecord) -> Option<ScatterRecord> {
+        if hit_record.is_front() {
+            self.front.scatter_record(ray, hit_record)
+        } else {
+            self.back.scatter_record(ray, hit_record)
+        }
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, hit_record: &AgainstRayHitRecord, scattered: &Ray) -> f64 {
+        if hit_record.is_front() {
+            self.front.scattering_pdf(ray, hit_record, scattered)
+        } else {
+            self.back.scattering_pdf(ray, hit_record, scattered)
+        }
+    }
+
+    fn emit(&self, point: Point3, u: f64, v: f64) -> Color {
+        self.front.emit(point, u, v)
+    }
+
+    fn emit_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        self.front.emit_at_time(point, u, v, time)
+    }
+
+    fn emit_towards(
+        &self,
+        ray: &Ray,
+        point: Point3,
+        normal_outward: Vec3<f64>,
+        u: f64,
+        v: f64,
+    ) -> Color {
+        if is_front_face(ray, normal_outward) {
+            self.front.emit_towards(ray, point, normal_outward, u, v)
+        } else {
+            self.back.emit_towards(ray, point, normal_outward, u, v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::{DiffuseLight, Lambertian, Metal},
+        object::rectangle::AxisAlignedRectangle,
+        Hit, Interval,
+    };
+
+    #[test]
+    fn each_side_has_its_material() {
+        let red = Color::new(0.8, 0.1, 0.1);
+        let panel = TwoSided::new(Lambertian::new_solid(red), Metal::new(Color::WHITE, 0.0));
+        // normal along +y, so the front is above
+        let panel = AxisAlignedRectangle::new_xz((-1.0, -1.0), (1.0, 1.0), 0.0, Arc::new(panel));
+        let scatter = |from: Point3| {
+            let ray = Ray::new(from, Vec3::new(0.5, 0.0, 0.0) - from, 0.0);
+            let hit = panel
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            hit.material.scatter(&ray, &hit).unwrap()
+        };
+
+        let (_, attenuation) = scatter(Point3::new(0.0, 1.0, 0.0));
+        assert_eq!(attenuation, red);
+        // the mirror below reflects about the plane
+        let (reflected, attenuation) = scatter(Point3::new(0.0, -1.0, 0.0));
+        assert_eq!(attenuation, Color::WHITE);
+        assert!((reflected.direction() - Vec3::new(0.5, -1.0, 0.0).normalized()).norm() < 1e-12);
+    }
+
+    #[test]
+    fn each_side_has_its_emission() {
+        let (top, bottom) = (Color::constant(4.0), Color::new(0.0, 0.0, 1.0));
+        let light = TwoSided::new(
+            DiffuseLight::new_solid(top),
+            DiffuseLight::new_solid(bottom),
+        );
+        let light = AxisAlignedRectangle::new_xz((-1.0, -1.0), (1.0, 1.0), 0.0, Arc::new(light));
+        let emitted = |y: f64| {
+            let ray = Ray::new(Point3::new(0.0, y, 0.0), Vec3::new(0.0, -y, 0.0), 0.0);
+            light.hit(ray, Interval::from_min(0.0)).unwrap().emitted
+        };
+        assert_eq!(emitted(1.0), top);
+        assert_eq!(emitted(-1.0), bottom);
+    }
+}
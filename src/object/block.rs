@@ -1,13 +1,14 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 use crate::{
     hit::{OutwardHitRecord, AABB},
-    Hit, Material, Point3, Ray,
+    Hit, Interval, Material, Point3, Ray, Vec3,
 };
-use super::rectangle::AxisAlignedRectangle;
+use super::rectangle::{AxisAlignedRectangle, GeometryError};
 
 /// An axis-aligned block of space.
-/// It holds 6 rectangles, one for each face.
+/// It holds 6 rectangles, one for each face, and the outward normals point
+/// out of the block on every face.
 #[derive(Debug, Clone)]
 pub struct Block {
     /// The 6 rectangles that make up the block.
@@ -16,6 +17,29 @@ pub struct Block {
     min_point: Point3,
     /// Maximum point of the block.
     max_point: Point3,
+    /// How texture coordinates are laid out over the faces.
+    uv_layout: BlockUv,
+}
+
+/// Layout of the texture coordinates of a [`Block`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockUv {
+    /// Every face covers the whole texture on its own, as its rectangle does
+    #[default]
+    PerFace,
+    /// The faces are unfolded into a cross on a grid of 4 by 3 cells, so one
+    /// image covers the whole block like a die. Rows go from `v = 0` to
+    /// `v = 1`, columns from `u = 0` to `u = 1`:
+    ///
+    /// ```text
+    ///      .   +y   .    .
+    ///     -x   +z  +x   -z
+    ///      .   -y   .    .
+    /// ```
+    ///
+    /// Each face is seen from outside, with `+y` up for the side faces, and
+    /// the top and bottom faces attached to the edges of the `+z` face.
+    Cross,
 }
 
 macro_rules! rectangle {
@@ -31,42 +55,298 @@ macro_rules! rectangle {
 }
 
 macro_rules! rectangles {
-    (($min:ident, $max:ident), $material:ident) => {
+    (($min:ident, $max:ident), [$min_z:expr, $max_z:expr, $min_y:expr, $max_y:expr, $min_x:expr, $max_x:expr]) => {
         [
-            rectangle!(($min, $max), $min, [2, 0, 1], $material.clone()),
-            rectangle!(($min, $max), $max, [2, 0, 1], $material.clone()),
-            rectangle!(($min, $max), $min, [1, 0, 2], $material.clone()),
-            rectangle!(($min, $max), $max, [1, 0, 2], $material.clone()),
-            rectangle!(($min, $max), $min, [0, 1, 2], $material.clone()),
-            rectangle!(($min, $max), $max, [0, 1, 2], $material),
+            rectangle!(($min, $max), $min, [2, 0, 1], $min_z),
+            rectangle!(($min, $max), $max, [2, 0, 1], $max_z),
+            rectangle!(($min, $max), $min, [1, 0, 2], $min_y),
+            rectangle!(($min, $max), $max, [1, 0, 2], $max_y),
+            rectangle!(($min, $max), $min, [0, 1, 2], $min_x),
+            rectangle!(($min, $max), $max, [0, 1, 2], $max_x),
         ]
     };
 }
 
 impl Block {
+    /// Block between the opposite corners `min_point` and `max_point`.
+    ///
+    /// The corners may be given in any order, each coordinate is sorted. A
+    /// block that is flat along an axis is made [`AABB::MIN_THICKNESS`] thick
+    /// there, so that it can still be hit. Use [`Block::try_new`] to reject
+    /// such input instead.
     pub fn new(min_point: Point3, max_point: Point3, material: Arc<dyn Material>) -> Self {
-        assert!(
-            min_point.x() < max_point.x()
-                && min_point.y() < max_point.y()
-                && min_point.z() < max_point.z(),
-            "Block must have positive volume"
-        );
+        let materials = [(); 6].map(|_| material.clone());
+        Self::with_materials(min_point, max_point, materials)
+    }
+
+    /// Like [`Block::new`], but with a material for each face, in the order
+    /// -z, +z, -y, +y, -x, +x.
+    pub fn with_materials(
+        min_point: Point3,
+        max_point: Point3,
+        materials: [Arc<dyn Material>; 6],
+    ) -> Self {
+        let bounds = AABB::new(min_point.min(&max_point), min_point.max(&max_point))
+            .padded(AABB::MIN_THICKNESS);
+        let (min_point, max_point) = (bounds.min(), bounds.max());
 
-        let rectangles = rectangles!((min_point, max_point), material);
+        let [min_z, max_z, min_y, max_y, min_x, max_x] = materials;
+        let rectangles = rectangles!(
+            (min_point, max_point),
+            [min_z, max_z, min_y, max_y, min_x, max_x]
+        );
         Self {
             rectangles,
             min_point,
             max_point,
+            uv_layout: BlockUv::PerFace,
         }
     }
+
+    /// The same block with texture coordinates laid out by `uv_layout`.
+    pub fn with_uv_layout(mut self, uv_layout: BlockUv) -> Self {
+        self.uv_layout = uv_layout;
+        self
+    }
+
+    /// Texture coordinates of `point` on the face with outward normal along
+    /// `axis` in the [`BlockUv::Cross`] layout, and the partial derivatives
+    /// of the point by them.
+    fn cross_uv(&self, point: Point3, axis: usize) -> ((f64, f64), (Vec3<f64>, Vec3<f64>)) {
+        // position in the block from 0 to 1 along an axis, counted from the
+        // maximum side if `flip`, and its derivative by the point
+        let size = self.max_point - self.min_point;
+        let local = |axis: usize, flip: bool| {
+            let position = ((point[axis] - self.min_point[axis]) / size[axis]).clamp(0.0, 1.0);
+            let mut tangent = Vec3::zeros();
+            tangent[axis] = if flip { -size[axis] } else { size[axis] };
+            (if flip { 1.0 - position } else { position }, tangent)
+        };
+        let on_max = local(axis, false).0 >= 0.5;
+
+        // cell of the face, and the position on the face as seen from outside
+        let ((column, row), (s, t)) = match (axis, on_max) {
+            (0, false) => ((0.0, 1.0), (local(2, false), local(1, false))),
+            (2, true) => ((1.0, 1.0), (local(0, false), local(1, false))),
+            (0, true) => ((2.0, 1.0), (local(2, true), local(1, false))),
+            (2, false) => ((3.0, 1.0), (local(0, true), local(1, false))),
+            (1, true) => ((1.0, 2.0), (local(0, false), local(2, true))),
+            _ => ((1.0, 0.0), (local(0, false), local(2, false))),
+        };
+        let uv = ((column + s.0) / 4.0, (row + t.0) / 3.0);
+        // a cell is a quarter of the width and a third of the height
+        (uv, (s.1 * 4.0, t.1 * 3.0))
+    }
+
+    /// Like [`Block::new`], but fails if the block is empty along any axis,
+    /// or a coordinate is not finite.
+    pub fn try_new(
+        min_point: Point3,
+        max_point: Point3,
+        material: Arc<dyn Material>,
+    ) -> Result<Self, GeometryError> {
+        for axis in 0..3 {
+            let (min, max) = (min_point[axis], max_point[axis]);
+            if !min.is_finite() || !max.is_finite() || min == max {
+                return Err(GeometryError::EmptyExtent { axis, min, max });
+            }
+        }
+        Ok(Self::new(min_point, max_point, material))
+    }
 }
 
 impl Hit for Block {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        self.rectangles.hit(ray, t_min, t_max)
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let hit = self.rectangles.hit(ray.clone(), t_range)?;
+
+        // the rectangles all face the positive axis, so the faces on the
+        // minimum side are turned outwards
+        let normal = hit.normal_outward;
+        let axis = (0..3)
+            .max_by(|&a, &b| normal[a].abs().total_cmp(&normal[b].abs()))
+            .unwrap();
+        let on_min = (hit.point[axis] - self.min_point[axis]).abs()
+            < (hit.point[axis] - self.max_point[axis]).abs();
+        if !on_min && self.uv_layout == BlockUv::PerFace {
+            return Some(hit);
+        }
+
+        // the emitted color depends on the normal and the texture
+        // coordinates, so the record is made again with the new ones
+        let normal = if on_min { -normal } else { normal };
+        let (uv, tangents) = match self.uv_layout {
+            BlockUv::PerFace => ((hit.u, hit.v), hit.tangents),
+            BlockUv::Cross => {
+                let (uv, tangents) = self.cross_uv(hit.point, axis);
+                (uv, Some(tangents))
+            }
+        };
+        Some(OutwardHitRecord {
+            tangents,
+            ..OutwardHitRecord::new(hit.point, &ray, normal, hit.t, hit.material, uv)
+        })
     }
 
-    fn bounding_box(&self, _time_from: f64, _time_to: f64) -> Option<AABB> {
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
         Some(AABB::new(self.min_point, self.max_point))
     }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.rectangles.hit_any(ray, t_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{DiffuseLight, Lambertian},
+        texture::{Image, Texture},
+        Color, HitExt, Vec3,
+    };
+
+    #[test]
+    fn degenerate_corners_are_tolerated() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let ordered = Block::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 2.0, 3.0),
+            material.clone(),
+        );
+        let swapped = Block::new(
+            Point3::new(1.0, 0.0, 3.0),
+            Point3::new(0.0, 2.0, 0.0),
+            material.clone(),
+        );
+        assert_eq!(
+            (swapped.min_point, swapped.max_point),
+            (ordered.min_point, ordered.max_point)
+        );
+
+        let flat = Block::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            material.clone(),
+        );
+        // the top face from above, and the bottom face from below
+        for direction in [-Vec3::unit_y(), Vec3::unit_y()] {
+            let ray = Ray::new(Point3::new(0.5, 1.0, 0.5) - direction * 2.0, direction, 0.0);
+            let hit = flat.hit(ray, Interval::from_min(0.0)).unwrap();
+            assert!((hit.t - 2.0).abs() < AABB::MIN_THICKNESS);
+            assert!((hit.normal_outward + direction).norm() < 1e-12);
+            assert!(hit.is_front());
+        }
+
+        let strict = |max_point| Block::try_new(Point3::zeros(), max_point, material.clone());
+        assert!(strict(Point3::new(-1.0, 2.0, 3.0)).is_ok());
+        assert_eq!(
+            strict(Point3::new(1.0, 0.0, 3.0)).unwrap_err(),
+            GeometryError::EmptyExtent {
+                axis: 1,
+                min: 0.0,
+                max: 0.0
+            }
+        );
+        assert!(strict(Point3::new(1.0, f64::INFINITY, 3.0)).is_err());
+    }
+
+    #[test]
+    fn each_face_has_its_material() {
+        let colors = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0].map(Color::constant);
+        let materials =
+            colors.map(|color| -> Arc<dyn Material> { Arc::new(DiffuseLight::new_solid(color)) });
+        let block = Block::with_materials(Point3::zeros(), Point3::ones(), materials);
+
+        let center = Point3::constant(0.5);
+        let directions = [
+            -Vec3::unit_z(),
+            Vec3::unit_z(),
+            -Vec3::unit_y(),
+            Vec3::unit_y(),
+            -Vec3::unit_x(),
+            Vec3::unit_x(),
+        ];
+        for (direction, color) in directions.into_iter().zip(colors) {
+            // from outside the face towards the center
+            let ray = Ray::new(center + direction * 2.0, -direction, 0.0);
+            let hit = block.hit(ray, Interval::from_min(0.0)).unwrap();
+            assert_eq!(hit.t, 1.5);
+            assert_eq!(hit.emitted, color);
+        }
+    }
+
+    #[test]
+    fn every_face_has_tangents() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let block = Block::new(Point3::zeros(), Point3::new(2.0, 1.0, 3.0), material);
+        let center = Point3::new(1.0, 0.5, 1.5);
+        let directions = [Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z()]
+            .into_iter()
+            .flat_map(|direction| [direction, -direction]);
+
+        for direction in directions {
+            let point = center + direction * 5.0 + Vec3::new(0.1, 0.05, 0.2);
+            let ray = Ray::new(point, -direction, 0.0);
+            let hit = |object: &dyn Hit| object.hit(ray.clone(), Interval::from_min(0.0)).unwrap();
+            assert!(hit(&block).tangents.is_some(), "face {direction}");
+            let flipped = block.clone().flip_faces();
+            assert!(hit(&flipped).tangents.is_some(), "flipped face {direction}");
+
+            // in the cross layout, moving along dp/du and dp/dv moves the
+            // texture coordinates by the same amount
+            let cross = block.clone().with_uv_layout(BlockUv::Cross);
+            let hit = hit(&cross);
+            let (dpdu, dpdv) = hit.tangents.unwrap();
+            let uv_at = |point: Point3| {
+                let ray = Ray::new(point + direction, -direction, 0.0);
+                let hit = cross.hit(ray, Interval::from_min(0.0)).unwrap();
+                (hit.u, hit.v)
+            };
+            let step = 1e-3;
+            let (u, v) = uv_at(hit.point + dpdu * step);
+            assert!((u - hit.u - step).abs() < 1e-9 && (v - hit.v).abs() < 1e-9);
+            let (u, v) = uv_at(hit.point + dpdv * step);
+            assert!((u - hit.u).abs() < 1e-9 && (v - hit.v - step).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn cross_layout_unfolds_the_faces() {
+        // a pixel of a different color for each cell of the cross
+        let pixels =
+            image::RgbImage::from_fn(4, 3, |x, y| image::Rgb([60 * x as u8, 80 * y as u8, 7]));
+        let texture = Image::new(pixels);
+        let cell_color = |column: f64, row: f64| {
+            texture.color(Point3::zeros(), (column + 0.5) / 4.0, (row + 0.5) / 3.0)
+        };
+        let material = Arc::new(DiffuseLight::new(texture.clone()));
+        let block = Block::new(Point3::zeros(), Point3::new(2.0, 1.0, 3.0), material)
+            .with_uv_layout(BlockUv::Cross);
+
+        let center = Point3::new(1.0, 0.5, 1.5);
+        let faces = [
+            (-Vec3::unit_x(), (0.0, 1.0)),
+            (Vec3::unit_z(), (1.0, 1.0)),
+            (Vec3::unit_x(), (2.0, 1.0)),
+            (-Vec3::unit_z(), (3.0, 1.0)),
+            (Vec3::unit_y(), (1.0, 2.0)),
+            (-Vec3::unit_y(), (1.0, 0.0)),
+        ];
+        for (direction, (column, row)) in faces {
+            let ray = Ray::new(center + direction * 5.0, -direction, 0.0);
+            let hit = block.hit(ray, Interval::from_min(0.0)).unwrap();
+            assert_eq!(hit.emitted, cell_color(column, row), "face {direction}");
+        }
+
+        // the +z face continues into the +x face across their shared edge
+        let uv_at = |point: Point3, direction: Vec3<f64>| {
+            let ray = Ray::new(point + direction, -direction, 0.0);
+            let hit = block.hit(ray, Interval::from_min(0.0)).unwrap();
+            (hit.u, hit.v)
+        };
+        let front = uv_at(Point3::new(2.0 - 1e-9, 0.5, 3.0), Vec3::unit_z());
+        let side = uv_at(Point3::new(2.0, 0.5, 3.0 - 1e-9), Vec3::unit_x());
+        assert!((front.0 - 0.5).abs() < 1e-6 && (side.0 - 0.5).abs() < 1e-6);
+        assert_eq!(front.1, side.1);
+    }
 }
@@ -0,0 +1,261 @@
+use std::{f64::consts::PI, ops::Range, sync::Arc};
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+/// A cone from a round base up to its apex, closed by the base.
+///
+/// Texture coordinates wrap around the axis: `u` is the angle around it as a
+/// fraction of a full turn, and `v` goes from 0 at the base to 1 at the apex.
+/// The base uses the same `u`, with `v` of 0.
+#[derive(Debug, Clone)]
+pub struct Cone {
+    apex: Point3,
+    /// Unit vector from the apex to the center of the base
+    axis: Vec3<f64>,
+    /// Unit vector perpendicular to the axis, where `u` is zero
+    tangent: Vec3<f64>,
+    height: f64,
+    radius: f64,
+    material: Arc<dyn Material>,
+}
+
+/// Part of a cone a ray hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Surface {
+    Side,
+    Base,
+}
+
+impl Cone {
+    /// Cone with a base of `radius` around `base`, narrowing to `apex`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` and `apex` are the same point, or `radius` is not positive.
+    pub fn new(base: Point3, apex: Point3, radius: f64, material: Arc<dyn Material>) -> Self {
+        let height = (base - apex).norm();
+        assert!(height > 0.0, "cone base and apex must be different");
+        assert!(radius > 0.0, "cone radius must be positive");
+        let axis = (base - apex) / height;
+        // any direction not parallel to the axis gives a tangent
+        let helper = if axis.x().abs() < 0.9 {
+            Vec3::unit_x()
+        } else {
+            Vec3::unit_y()
+        };
+        let tangent = helper.cross(axis).normalized();
+
+        Self {
+            apex,
+            axis,
+            tangent,
+            height,
+            radius,
+            material,
+        }
+    }
+
+    /// Cone standing on `base` along `axis`, whose sides make `half_angle`
+    /// degrees with the axis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` is zero, or `half_angle` is not strictly between 0 and 90.
+    pub fn with_half_angle(
+        base: Point3,
+        axis: Vec3<f64>,
+        half_angle: f64,
+        height: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(axis.len_squared() > 0.0, "cone axis must not be zero");
+        assert!(
+            0.0 < half_angle && half_angle < 90.0,
+            "cone half angle must be between 0 and 90 degrees"
+        );
+        let apex = base + axis.normalized() * height;
+        Self::new(base, apex, height * half_angle.to_radians().tan(), material)
+    }
+
+    /// Tangent of the half angle at the apex, the radius per unit of height.
+    fn slope(&self) -> f64 {
+        self.radius / self.height
+    }
+
+    /// Nearest parameter in `t_range` where the ray hits the side or the base,
+    /// and which one it hits.
+    fn intersect(&self, ray: &Ray, t_range: Interval) -> Option<(f64, Surface)> {
+        // split the origin and direction into parts along and across the axis,
+        // measured from the apex
+        let origin = ray.origin() - self.apex;
+        let direction = ray.direction();
+        let (origin_along, direction_along) = (origin.dot(self.axis), direction.dot(self.axis));
+        let origin_across = origin - self.axis * origin_along;
+        let direction_across = direction - self.axis * direction_along;
+        let depth_at = |t: f64| origin_along + t * direction_along;
+
+        // the nearest so far, kept without collecting every candidate
+        let mut nearest: Option<(f64, Surface)> = None;
+        let mut consider = |t: f64, surface| {
+            if t_range.contains(t) && nearest.is_none_or(|(nearest, _)| t < nearest) {
+                nearest = Some((t, surface));
+            }
+        };
+
+        // side: |across| = slope * along, on both halves of the double cone,
+        // of which only the one with the base is kept
+        let slope2 = self.slope() * self.slope();
+        let a = direction_across.len_squared() - slope2 * direction_along * direction_along;
+        let half_b = origin_across.dot(direction_across) - slope2 * origin_along * direction_along;
+        let c = origin_across.len_squared() - slope2 * origin_along * origin_along;
+        let roots = if a != 0.0 {
+            let discriminant = half_b * half_b - a * c;
+            if discriminant >= 0.0 {
+                let sqrt = discriminant.sqrt();
+                [Some((-half_b - sqrt) / a), Some((-half_b + sqrt) / a)]
+            } else {
+                [None, None]
+            }
+        } else if half_b != 0.0 {
+            // parallel to a line of the cone, which it crosses only once
+            [Some(-c / (2.0 * half_b)), None]
+        } else {
+            [None, None]
+        };
+        for t in roots.into_iter().flatten() {
+            if (0.0..=self.height).contains(&depth_at(t)) {
+                consider(t, Surface::Side);
+            }
+        }
+
+        // base: the plane at the far end, within the radius
+        if direction_along != 0.0 {
+            let t = (self.height - origin_along) / direction_along;
+            if (origin_across + direction_across * t).len_squared() <= self.radius * self.radius {
+                consider(t, Surface::Base);
+            }
+        }
+
+        nearest
+    }
+}
+
+impl Hit for Cone {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let (t, surface) = self.intersect(&ray, t_range)?;
+        let point = ray.at(t);
+
+        let offset = point - self.apex;
+        let along = offset.dot(self.axis);
+        let across = offset - self.axis * along;
+        let bitangent = self.axis.cross(self.tangent);
+        let angle = across.dot(bitangent).atan2(across.dot(self.tangent));
+        let u = angle.rem_euclid(2.0 * PI) / (2.0 * PI);
+
+        let (normal_outward, v) = match surface {
+            Surface::Side => {
+                // the apex has no direction away from the axis, pick any
+                let radial = if across.len_squared() > 0.0 {
+                    across.normalized()
+                } else {
+                    self.tangent
+                };
+                let normal = (radial - self.axis * self.slope()).normalized();
+                (normal, (1.0 - along / self.height).clamp(0.0, 1.0))
+            }
+            Surface::Base => (self.axis, 0.0),
+        };
+
+        Some(OutwardHitRecord::new(
+            point,
+            &ray,
+            normal_outward,
+            t,
+            self.material.clone(),
+            (u, v),
+        ))
+    }
+
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+        // the base reaches `radius * sin(angle between axis and coordinate
+        // axis)` from its center along each coordinate axis
+        let mut extent = Vec3::zeros();
+        for i in 0..3 {
+            extent[i] = self.radius * (1.0 - self.axis[i] * self.axis[i]).max(0.0).sqrt();
+        }
+        let base = self.apex + self.axis * self.height;
+        Some(AABB::new(
+            (base - extent).min(&self.apex),
+            (base + extent).max(&self.apex),
+        ))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.intersect(ray, t_range).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, Color};
+
+    /// Cone of height 2 and radius 1 standing on the origin.
+    fn cone() -> Cone {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        Cone::new(Point3::zeros(), Point3::new(0.0, 2.0, 0.0), 1.0, material)
+    }
+
+    #[test]
+    fn side_and_base() {
+        let cone = cone();
+
+        // halfway up, the radius is a half
+        let side = Ray::new(Point3::new(-3.0, 1.0, 0.0), Vec3::unit_x(), 0.0);
+        let hit = cone.hit(side, Interval::from_min(0.0)).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-12);
+        assert!((hit.v - 0.5).abs() < 1e-12);
+        let expected = Vec3::new(-2.0, 1.0, 0.0).normalized();
+        assert!((hit.normal_outward - expected).norm() < 1e-12);
+
+        let up = Ray::new(Point3::new(0.5, -1.0, 0.0), Vec3::unit_y(), 0.0);
+        let hit = cone.hit(up, Interval::from_min(0.0)).unwrap();
+        assert_eq!((hit.t, hit.normal_outward), (1.0, -Vec3::unit_y()));
+        assert_eq!(hit.v, 0.0);
+
+        // from inside, leaving through the side
+        let inside = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::unit_x(), 0.0);
+        let hit = cone.hit(inside, Interval::from_min(0.0)).unwrap();
+        assert!((hit.t - 0.75).abs() < 1e-12);
+        assert!(!hit.is_front());
+    }
+
+    #[test]
+    fn mirror_cone_is_rejected() {
+        let cone = cone();
+        // passes through the upper half of the double cone, above the apex
+        let above = Ray::new(Point3::new(-3.0, 3.0, 0.0), Vec3::unit_x(), 0.0);
+        assert!(!cone.hit_any(&above, Interval::from_min(0.0)));
+        // comes down through the mirror cone and still hits the real one
+        let down = Ray::new(Point3::new(0.25, 4.0, 0.0), -Vec3::unit_y(), 0.0);
+        let hit = cone.hit(down, Interval::from_min(0.0)).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-12);
+        assert!(hit.normal_outward.y() > 0.0);
+    }
+
+    #[test]
+    fn bounding_box_ends_at_the_apex() {
+        let aabb = cone().bounding_box(0.0..1.0).unwrap();
+        assert_eq!(aabb.min(), Point3::new(-1.0, 0.0, -1.0));
+        assert_eq!(aabb.max(), Point3::new(1.0, 2.0, 1.0));
+
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let cone = Cone::with_half_angle(Point3::zeros(), Vec3::unit_z(), 45.0, 2.0, material);
+        let aabb = cone.bounding_box(0.0..1.0).unwrap();
+        assert!((aabb.min() - Point3::new(-2.0, -2.0, 0.0)).norm() < 1e-12);
+        assert!((aabb.max() - Point3::new(2.0, 2.0, 2.0)).norm() < 1e-12);
+    }
+}
@@ -0,0 +1,216 @@
+use std::{f64::consts::PI, ops::Range, sync::Arc};
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+/// A cylinder between two end points, closed by flat caps.
+///
+/// Texture coordinates wrap around the barrel: `u` is the angle around the
+/// axis as a fraction of a full turn, and `v` goes from 0 at the bottom to 1
+/// at the top. The caps use the same `u`, with `v` of their end.
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    bottom: Point3,
+    /// Unit vector from the bottom to the top
+    axis: Vec3<f64>,
+    /// Unit vector perpendicular to the axis, where `u` is zero
+    tangent: Vec3<f64>,
+    height: f64,
+    radius: f64,
+    material: Arc<dyn Material>,
+}
+
+/// Part of a cylinder a ray hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Surface {
+    Side,
+    Bottom,
+    Top,
+}
+
+impl Cylinder {
+    /// Cylinder from the center of the bottom cap to the center of the top cap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bottom` and `top` are the same point, or `radius` is not positive.
+    pub fn new(bottom: Point3, top: Point3, radius: f64, material: Arc<dyn Material>) -> Self {
+        let height = (top - bottom).norm();
+        assert!(height > 0.0, "cylinder ends must be different");
+        assert!(radius > 0.0, "cylinder radius must be positive");
+        let axis = (top - bottom) / height;
+        // any direction not parallel to the axis gives a tangent
+        let helper = if axis.x().abs() < 0.9 {
+            Vec3::unit_x()
+        } else {
+            Vec3::unit_y()
+        };
+        let tangent = helper.cross(axis).normalized();
+
+        Self {
+            bottom,
+            axis,
+            tangent,
+            height,
+            radius,
+            material,
+        }
+    }
+
+    /// Nearest parameter in `t_range` where the ray hits the side or a cap,
+    /// and which one it hits.
+    fn intersect(&self, ray: &Ray, t_range: Interval) -> Option<(f64, Surface)> {
+        // split the origin and direction into parts along and across the axis
+        let origin = ray.origin() - self.bottom;
+        let direction = ray.direction();
+        let (origin_along, direction_along) = (origin.dot(self.axis), direction.dot(self.axis));
+        let origin_across = origin - self.axis * origin_along;
+        let direction_across = direction - self.axis * direction_along;
+        let height_at = |t: f64| origin_along + t * direction_along;
+        let inside_radius = |t: f64| {
+            (origin_across + direction_across * t).len_squared() <= self.radius * self.radius
+        };
+
+        // the nearest so far, kept without collecting every candidate
+        let mut nearest: Option<(f64, Surface)> = None;
+        let mut consider = |t: f64, surface| {
+            if t_range.contains(t) && nearest.is_none_or(|(nearest, _)| t < nearest) {
+                nearest = Some((t, surface));
+            }
+        };
+
+        // side: |origin_across + t direction_across| = radius
+        let a = direction_across.len_squared();
+        let half_b = origin_across.dot(direction_across);
+        let c = origin_across.len_squared() - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if a > 0.0 && discriminant >= 0.0 {
+            let sqrt = discriminant.sqrt();
+            for t in [(-half_b - sqrt) / a, (-half_b + sqrt) / a] {
+                if (0.0..=self.height).contains(&height_at(t)) {
+                    consider(t, Surface::Side);
+                }
+            }
+        }
+
+        // caps: the planes at both ends, within the radius
+        if direction_along != 0.0 {
+            for (end, surface) in [(0.0, Surface::Bottom), (self.height, Surface::Top)] {
+                let t = (end - origin_along) / direction_along;
+                if inside_radius(t) {
+                    consider(t, surface);
+                }
+            }
+        }
+
+        nearest
+    }
+}
+
+impl Hit for Cylinder {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let (t, surface) = self.intersect(&ray, t_range)?;
+        let point = ray.at(t);
+
+        let offset = point - self.bottom;
+        let along = offset.dot(self.axis);
+        let across = offset - self.axis * along;
+        let bitangent = self.axis.cross(self.tangent);
+        let angle = across.dot(bitangent).atan2(across.dot(self.tangent));
+        let u = angle.rem_euclid(2.0 * PI) / (2.0 * PI);
+
+        let (normal_outward, v) = match surface {
+            Surface::Side => (across / self.radius, (along / self.height).clamp(0.0, 1.0)),
+            Surface::Bottom => (-self.axis, 0.0),
+            Surface::Top => (self.axis, 1.0),
+        };
+
+        Some(OutwardHitRecord::new(
+            point,
+            &ray,
+            normal_outward,
+            t,
+            self.material.clone(),
+            (u, v),
+        ))
+    }
+
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+        // the caps reach `radius * sin(angle between axis and coordinate axis)`
+        // from the ends along each coordinate axis
+        let mut extent = Vec3::zeros();
+        for i in 0..3 {
+            extent[i] = self.radius * (1.0 - self.axis[i] * self.axis[i]).max(0.0).sqrt();
+        }
+        let top = self.bottom + self.axis * self.height;
+        Some(AABB::new(
+            self.bottom.min(&top) - extent,
+            self.bottom.max(&top) + extent,
+        ))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.intersect(ray, t_range).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, Color, HitExt};
+
+    fn cylinder() -> Cylinder {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        Cylinder::new(Point3::zeros(), Point3::new(0.0, 2.0, 0.0), 1.0, material)
+    }
+
+    #[test]
+    fn nearest_of_side_and_caps() {
+        let cylinder = cylinder();
+
+        let side = Ray::new(Point3::new(-3.0, 1.5, 0.0), Vec3::unit_x(), 0.0);
+        let hit = cylinder.hit(side.clone(), Interval::from_min(0.0)).unwrap();
+        assert_eq!((hit.t, hit.normal_outward), (2.0, -Vec3::unit_x()));
+        assert!((hit.v - 0.75).abs() < 1e-12);
+        // from inside, only the far side is left
+        let hit = cylinder.hit(side, Interval::from_min(3.0)).unwrap();
+        assert_eq!((hit.t, hit.normal_outward), (4.0, Vec3::unit_x()));
+        assert!(!hit.is_front());
+
+        let up = Ray::new(Point3::new(0.5, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        let hit = cylinder.hit(up, Interval::from_min(0.0)).unwrap();
+        assert_eq!(
+            (hit.t, hit.normal_outward),
+            (1.0, Vec3::new(0.0, -1.0, 0.0))
+        );
+        assert_eq!(hit.v, 0.0);
+
+        // enters through the top cap rather than the side
+        let slanted = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.5, -1.0, 0.0), 0.0);
+        let hit = cylinder.hit(slanted, Interval::from_min(0.0)).unwrap();
+        assert_eq!((hit.t, hit.normal_outward), (1.0, Vec3::unit_y()));
+
+        let above = Ray::new(Point3::new(-3.0, 2.5, 0.0), Vec3::unit_x(), 0.0);
+        assert!(!cylinder.hit_any(&above, Interval::from_min(0.0)));
+    }
+
+    #[test]
+    fn tight_bounding_box() {
+        let aabb = cylinder().bounding_box(0.0..1.0).unwrap();
+        assert_eq!(aabb.min(), Point3::new(-1.0, 0.0, -1.0));
+        assert_eq!(aabb.max(), Point3::new(1.0, 2.0, 1.0));
+
+        // lying along x after a quarter turn, and moved up
+        let lying = cylinder()
+            .rotate_z(90.0)
+            .translate(Vec3::new(0.0, 5.0, 0.0));
+        let aabb = lying.bounding_box(0.0..1.0).unwrap();
+        assert!((aabb.min() - Point3::new(0.0, 4.0, -1.0)).norm() < 1e-9);
+        assert!((aabb.max() - Point3::new(2.0, 6.0, 1.0)).norm() < 1e-9);
+        let down = Ray::new(Point3::new(1.0, 8.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = lying.hit(down, Interval::from_min(0.0)).unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,177 @@
+use std::{f64::consts::PI, ops::Range, sync::Arc};
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+/// A flat disk, or an annulus if it has an inner radius.
+///
+/// Points exactly on the inner or outer rim are on the disk. Texture
+/// coordinates are polar: `u` is the angle around the normal as a fraction of
+/// a full turn, and `v` goes from 0 on the inner rim to 1 on the outer rim.
+#[derive(Debug, Clone)]
+pub struct Disk {
+    center: Point3,
+    /// Unit normal, which is the outward normal
+    normal: Vec3<f64>,
+    /// Unit vector in the plane of the disk, where `u` is zero
+    tangent: Vec3<f64>,
+    inner_radius: f64,
+    radius: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Disk {
+    /// # Panics
+    ///
+    /// Panics if `normal` is zero, or `radius` is not positive.
+    pub fn new(
+        center: Point3,
+        normal: Vec3<f64>,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self::annulus(center, normal, 0.0, radius, material)
+    }
+
+    /// Ring between `inner_radius` and `radius`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `normal` is zero, or unless `0 <= inner_radius < radius`.
+    pub fn annulus(
+        center: Point3,
+        normal: Vec3<f64>,
+        inner_radius: f64,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(normal.len_squared() > 0.0, "disk normal must not be zero");
+        assert!(
+            0.0 <= inner_radius && inner_radius < radius,
+            "disk radii must satisfy 0 <= inner < outer"
+        );
+        let normal = normal.normalized();
+        // any direction not parallel to the normal gives a tangent
+        let helper = if normal.x().abs() < 0.9 {
+            Vec3::unit_x()
+        } else {
+            Vec3::unit_y()
+        };
+        let tangent = helper.cross(normal).normalized();
+
+        Self {
+            center,
+            normal,
+            tangent,
+            inner_radius,
+            radius,
+            material,
+        }
+    }
+
+    /// Parameter and point where the ray hits the disk, if it does so in `t_range`.
+    fn intersect(&self, ray: &Ray, t_range: Interval) -> Option<(f64, Point3)> {
+        // the ray is parallel to the plane when this is zero, and t is not
+        // finite, so it is never contained
+        let t = (self.center - ray.origin()).dot(self.normal) / ray.direction().dot(self.normal);
+        if !t_range.contains(t) {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let distance_squared = (point - self.center).len_squared();
+        let on_disk = self.inner_radius * self.inner_radius <= distance_squared
+            && distance_squared <= self.radius * self.radius;
+        on_disk.then_some((t, point))
+    }
+}
+
+impl Hit for Disk {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let (t, point) = self.intersect(&ray, t_range)?;
+
+        let offset = point - self.center;
+        let bitangent = self.normal.cross(self.tangent);
+        let angle = offset.dot(bitangent).atan2(offset.dot(self.tangent));
+        let u = angle.rem_euclid(2.0 * PI) / (2.0 * PI);
+        let v = (offset.norm() - self.inner_radius) / (self.radius - self.inner_radius);
+
+        Some(OutwardHitRecord::new(
+            point,
+            &ray,
+            self.normal,
+            t,
+            self.material.clone(),
+            (u, v.clamp(0.0, 1.0)),
+        ))
+    }
+
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+        // the disk reaches `radius * sin(angle to the axis)` along each axis
+        let mut extent = Vec3::zeros();
+        for axis in 0..3 {
+            let sine = (1.0 - self.normal[axis] * self.normal[axis])
+                .max(0.0)
+                .sqrt();
+            extent[axis] = self.radius * sine;
+        }
+        Some(AABB::new(self.center - extent, self.center + extent).padded(AABB::MIN_THICKNESS))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.intersect(ray, t_range).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::DiffuseLight, Color};
+
+    /// Round light in place of the rectangle on the ceiling of the Cornell box.
+    fn ceiling_light(inner_radius: f64) -> Disk {
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(15.0)));
+        let center = Point3::new(278.0, 554.0, 279.5);
+        Disk::annulus(center, Vec3::new(0.0, -1.0, 0.0), inner_radius, 65.0, light)
+    }
+
+    fn up_at(x: f64, z: f64) -> Ray {
+        Ray::new(Point3::new(x, 0.0, z), Vec3::new(0.0, 1.0, 0.0), 0.0)
+    }
+
+    #[test]
+    fn round_light_is_hit_up_to_the_rim() {
+        let disk = ceiling_light(0.0);
+        let hit = disk
+            .hit(up_at(278.0, 279.5), Interval::from_min(0.0))
+            .unwrap();
+        assert_eq!(hit.t, 554.0);
+        assert_eq!(hit.emitted, Color::constant(15.0));
+        assert!(hit.is_front());
+        assert_eq!(hit.v, 0.0);
+
+        let rim = disk.hit(up_at(278.0 + 65.0, 279.5), Interval::from_min(0.0));
+        assert_eq!(rim.unwrap().v, 1.0);
+        assert!(!disk.hit_any(&up_at(278.0 + 65.1, 279.5), Interval::from_min(0.0)));
+
+        let aabb = disk.bounding_box(0.0..1.0).unwrap();
+        assert_eq!(
+            (aabb.min().x(), aabb.max().x()),
+            (278.0 - 65.0, 278.0 + 65.0)
+        );
+        assert!(aabb.max().y() > aabb.min().y());
+    }
+
+    #[test]
+    fn annulus_has_a_hole() {
+        let ring = ceiling_light(30.0);
+        assert!(!ring.hit_any(&up_at(278.0, 279.5), Interval::from_min(0.0)));
+        assert!(ring.hit_any(&up_at(278.0, 279.5 + 30.0), Interval::from_min(0.0)));
+
+        let u_at = |x, z| ring.hit(up_at(x, z), Interval::from_min(0.0)).unwrap().u;
+        let quarter = (u_at(278.0 + 40.0, 279.5) - u_at(278.0, 279.5 + 40.0)).rem_euclid(1.0);
+        assert!((quarter - 0.25).abs() < 1e-12 || (quarter - 0.75).abs() < 1e-12);
+    }
+}
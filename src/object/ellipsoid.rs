@@ -0,0 +1,149 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+use super::sphere::{solve, to_sphere_uv};
+
+/// A sphere stretched by different radii along the x, y and z axes.
+///
+/// The ray is moved into the space where the ellipsoid is the unit sphere,
+/// which keeps the parameter `t`, and the normal is moved back by the inverse
+/// transpose of the stretch. Texture coordinates are those of the unit sphere,
+/// so a texture wraps around the ellipsoid like around a [`super::Sphere`].
+///
+/// This is the same surface as a sphere wrapped in [`crate::hit::scaling::Scale`],
+/// without the extra indirection.
+#[derive(Debug, Clone)]
+pub struct Ellipsoid {
+    center: Point3,
+    radii: Vec3<f64>,
+    material: Arc<dyn Material>,
+}
+
+impl Ellipsoid {
+    /// # Panics
+    ///
+    /// Panics if a radius is not positive and finite.
+    pub fn new(center: Point3, radii: Vec3<f64>, material: Arc<dyn Material>) -> Self {
+        assert!(
+            radii
+                .iter()
+                .all(|radius| *radius > 0.0 && radius.is_finite()),
+            "ellipsoid radii must be positive and finite"
+        );
+        Self {
+            center,
+            radii,
+            material,
+        }
+    }
+
+    pub fn center(&self) -> Point3 {
+        self.center
+    }
+
+    pub fn radii(&self) -> Vec3<f64> {
+        self.radii
+    }
+
+    /// The ray in the space where the ellipsoid is the unit sphere at the origin.
+    fn to_unit_space(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            (ray.origin() - self.center) / self.radii,
+            ray.direction() / self.radii,
+            ray.time(),
+        )
+    }
+}
+
+impl Hit for Ellipsoid {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let unit_ray = self.to_unit_space(&ray);
+        let t = solve(Point3::zeros(), 1.0, &unit_ray, t_range)?;
+
+        let unit_normal = unit_ray.at(t);
+        // the inverse transpose of the stretch divides by the radii
+        let normal_outward = (unit_normal / self.radii).normalized();
+
+        Some(OutwardHitRecord::new(
+            ray.at(t),
+            &ray,
+            normal_outward,
+            t,
+            self.material.clone(),
+            to_sphere_uv(&unit_normal),
+        ))
+    }
+
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+        Some(AABB::new(
+            self.center - self.radii,
+            self.center + self.radii,
+        ))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        solve(Point3::zeros(), 1.0, &self.to_unit_space(ray), t_range).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{material::Lambertian, Color, Sphere};
+
+    #[test]
+    fn equal_radii_match_the_sphere() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let center = Point3::new(1.0, -2.0, 0.5);
+        let sphere = Sphere::new(center, 2.0, material.clone());
+        let ellipsoid = Ellipsoid::new(center, Vec3::constant(2.0), material);
+
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut point = || Point3::new(rng.gen(), rng.gen(), rng.gen()) * 8.0 - Vec3::constant(4.0);
+        let mut hits = 0;
+        for _ in 0..300 {
+            let (from, to) = (point() + center, point() * 0.5 + center);
+            let ray = Ray::new(from, to - from, 0.0);
+            let expected = sphere.hit(ray.clone(), Interval::from_min(1e-6));
+            let actual = ellipsoid.hit(ray, Interval::from_min(1e-6));
+            assert_eq!(actual.is_some(), expected.is_some());
+            if let (Some(actual), Some(expected)) = (actual, expected) {
+                hits += 1;
+                assert!((actual.t - expected.t).abs() < 1e-9);
+                assert!((actual.point - expected.point).norm() < 1e-9);
+                assert!((actual.normal_outward - expected.normal_outward).norm() < 1e-9);
+                assert!((actual.u - expected.u).abs() < 1e-9);
+                assert!((actual.v - expected.v).abs() < 1e-9);
+            }
+        }
+        assert!(hits > 50);
+
+        let (expected, actual) = (
+            sphere.bounding_box(0.0..1.0).unwrap(),
+            ellipsoid.bounding_box(0.0..1.0).unwrap(),
+        );
+        assert_eq!((actual.min, actual.max), (expected.min, expected.max));
+    }
+
+    #[test]
+    fn normals_follow_the_surface() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let ellipsoid = Ellipsoid::new(Point3::zeros(), Vec3::new(4.0, 1.0, 1.0), material);
+
+        // on the slope of the long side, halfway along x
+        let x = 2.0;
+        let y = (1.0 - x * x / 16.0_f64).sqrt();
+        let ray = Ray::new(Point3::new(x, 5.0, 0.0), -Vec3::unit_y(), 0.0);
+        let hit = ellipsoid.hit(ray, Interval::from_min(0.0)).unwrap();
+        assert!((hit.point - Point3::new(x, y, 0.0)).norm() < 1e-12);
+        // the gradient of x^2 / 16 + y^2 + z^2
+        let normal = Vec3::new(x / 16.0, y, 0.0).normalized();
+        assert!((hit.normal_outward - normal).norm() < 1e-12);
+    }
+}
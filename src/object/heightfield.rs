@@ -0,0 +1,220 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use image::GrayImage;
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    texture::Image,
+    Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+use super::TriangleMesh;
+
+/// Terrain whose height is read from a grayscale image.
+///
+/// Each pixel is a vertex of a grid spanning the XZ rectangle, with black at
+/// the bottom of the height range and white at the top. The top row of the
+/// image is at the far end in z, so that the texture coordinates of the
+/// terrain are those of the same picture as an [`crate::texture::Image`],
+/// and a color image of the terrain can be draped over it.
+///
+/// The grid is hit as a [`TriangleMesh`] of two faces per cell, shaded with
+/// vertex normals from the slopes of the height map.
+#[derive(Debug)]
+pub struct Heightfield {
+    mesh: TriangleMesh,
+}
+
+impl Heightfield {
+    /// Terrain between the corners `min_xz` and `max_xz` with heights in
+    /// `height_range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image is narrower or shorter than 2 pixels, or the XZ
+    /// rectangle has no area.
+    pub fn new(
+        heights: &GrayImage,
+        min_xz: (f64, f64),
+        max_xz: (f64, f64),
+        height_range: Range<f64>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let (width, depth) = (heights.width() as usize, heights.height() as usize);
+        assert!(
+            width >= 2 && depth >= 2,
+            "height map must be at least 2 by 2 pixels"
+        );
+        let size = (max_xz.0 - min_xz.0, max_xz.1 - min_xz.1);
+        assert!(
+            size.0 != 0.0 && size.1 != 0.0,
+            "terrain must have a non-zero extent in x and z"
+        );
+
+        // the vertex in column `i` and row `k`, counting rows from the bottom
+        // of the image, which is where z is at its minimum
+        let height = |i: usize, k: usize| {
+            let level = heights.get_pixel(i as u32, (depth - 1 - k) as u32).0[0] as f64 / 255.0;
+            height_range.start + level * (height_range.end - height_range.start)
+        };
+        let uv =
+            |i: usize, k: usize| (i as f64 / (width - 1) as f64, k as f64 / (depth - 1) as f64);
+        let (step_x, step_z) = (size.0 / (width - 1) as f64, size.1 / (depth - 1) as f64);
+
+        let mut vertices = Vec::with_capacity(width * depth);
+        let mut normals = Vec::with_capacity(width * depth);
+        let mut uvs = Vec::with_capacity(width * depth);
+        for k in 0..depth {
+            for i in 0..width {
+                let (u, v) = uv(i, k);
+                vertices.push(Point3::new(
+                    min_xz.0 + u * size.0,
+                    height(i, k),
+                    min_xz.1 + v * size.1,
+                ));
+                uvs.push((u, v));
+
+                // slopes by differences with the neighbors, one-sided at the border
+                let (left, right) = (i.saturating_sub(1), (i + 1).min(width - 1));
+                let (near, far) = (k.saturating_sub(1), (k + 1).min(depth - 1));
+                let slope_x =
+                    (height(right, k) - height(left, k)) / ((right - left) as f64 * step_x);
+                let slope_z = (height(i, far) - height(i, near)) / ((far - near) as f64 * step_z);
+                normals.push(Vec3::new(-slope_x, 1.0, -slope_z));
+            }
+        }
+
+        let index = |i: usize, k: usize| k * width + i;
+        let mut indices = Vec::with_capacity(2 * (width - 1) * (depth - 1));
+        for k in 0..depth - 1 {
+            for i in 0..width - 1 {
+                // wound so that the faces point up, unless an axis is reversed
+                indices.push([index(i, k), index(i, k + 1), index(i + 1, k)]);
+                indices.push([index(i, k + 1), index(i + 1, k + 1), index(i + 1, k)]);
+            }
+        }
+
+        // the vertex normals point up even where an axis is reversed
+        let mesh =
+            TriangleMesh::with_attributes(vertices, indices, Some(normals), Some(uvs), material);
+        Self { mesh }
+    }
+
+    /// Terrain from the image file at `path`, read as by [`Image::open`] and
+    /// converted to grayscale, see [`Heightfield::new`].
+    pub fn open(
+        path: impl AsRef<Path>,
+        min_xz: (f64, f64),
+        max_xz: (f64, f64),
+        height_range: Range<f64>,
+        material: Arc<dyn Material>,
+    ) -> Result<Self, image::ImageError> {
+        let image = Image::open(path)?;
+        Ok(Self::new(
+            &image.to_luma8(),
+            min_xz,
+            max_xz,
+            height_range,
+            material,
+        ))
+    }
+}
+
+impl Hit for Heightfield {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.mesh.hit(ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.mesh.bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.mesh.hit_any(ray, t_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, Color};
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new_solid(Color::constant(0.5)))
+    }
+
+    fn down(x: f64, z: f64) -> Ray {
+        Ray::new(Point3::new(x, 10.0, z), Vec3::new(0.0, -1.0, 0.0), 0.0)
+    }
+
+    #[test]
+    fn ramp_rises_along_x() {
+        // black on the left, white on the right
+        let ramp = GrayImage::from_fn(3, 2, |x, _| image::Luma([[0, 51, 255][x as usize]]));
+        let terrain = Heightfield::new(&ramp, (0.0, 0.0), (4.0, 2.0), 1.0..6.0, material());
+
+        let hit = terrain
+            .hit(down(1.0, 0.5), Interval::from_min(0.0))
+            .unwrap();
+        // halfway between the heights 1 and 2
+        assert!((hit.point.y() - 1.5).abs() < 1e-12);
+        assert!(hit.normal_outward.y() > 0.0 && hit.normal_outward.x() < 0.0);
+        assert!((hit.u - 0.25).abs() < 1e-12 && (hit.v - 0.25).abs() < 1e-12);
+
+        let hit = terrain
+            .hit(down(3.0, 2.0), Interval::from_min(0.0))
+            .unwrap();
+        assert!((hit.point.y() - 4.0).abs() < 1e-12);
+        assert!(!terrain.hit_any(&down(4.5, 1.0), Interval::from_min(0.0)));
+
+        let aabb = terrain.bounding_box(0.0..1.0).unwrap();
+        assert_eq!(
+            (aabb.min, aabb.max),
+            (Point3::new(0.0, 1.0, 0.0), Point3::new(4.0, 6.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn open_reads_the_heights() {
+        let ramp = GrayImage::from_fn(3, 2, |x, _| image::Luma([[0, 51, 255][x as usize]]));
+        let dir = std::env::temp_dir().join(format!("rtweekend-heights-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ramp.png");
+        ramp.save(&path).unwrap();
+
+        let opened = Heightfield::open(&path, (0.0, 0.0), (4.0, 2.0), 1.0..6.0, material());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let opened = opened.unwrap();
+        let terrain = Heightfield::new(&ramp, (0.0, 0.0), (4.0, 2.0), 1.0..6.0, material());
+        for x in [0.5, 1.0, 2.5, 3.5] {
+            let ray = down(x, 0.5);
+            let (expected, actual) = (
+                terrain.hit(ray.clone(), Interval::from_min(0.0)).unwrap(),
+                opened.hit(ray, Interval::from_min(0.0)).unwrap(),
+            );
+            assert_eq!(actual.point, expected.point);
+        }
+        assert!(Heightfield::open(
+            dir.join("missing.png"),
+            (0.0, 0.0),
+            (1.0, 1.0),
+            0.0..1.0,
+            material()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn top_row_of_the_image_is_far() {
+        let image = GrayImage::from_fn(2, 2, |_, y| image::Luma([if y == 0 { 255 } else { 0 }]));
+        for (min, max) in [((0.0, 0.0), (1.0, 1.0)), ((1.0, 0.0), (0.0, 1.0))] {
+            let terrain = Heightfield::new(&image, min, max, 0.0..1.0, material());
+            let hit = terrain
+                .hit(down(0.5, 0.75), Interval::from_min(0.0))
+                .unwrap();
+            assert!((hit.point.y() - 0.75).abs() < 1e-12);
+            assert!((hit.v - 0.75).abs() < 1e-12);
+            assert!(hit.normal_outward.y() > 0.0);
+        }
+    }
+}
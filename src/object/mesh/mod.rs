@@ -0,0 +1,268 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    hit::{OutwardHitRecord, AABB, BVH},
+    Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+use super::triangle;
+
+mod obj;
+mod stl;
+
+pub use obj::{load_obj, ObjError};
+pub use stl::{load_stl, StlError};
+
+/// Many triangles sharing vertices and a material, hit through a BVH of its
+/// faces.
+///
+/// Faces are triples of indices into the vertex buffer. With per-vertex
+/// normals, the normal at a hit point is interpolated from the normals of the
+/// face's vertices, otherwise it is the geometric normal of the face as for a
+/// [`super::Triangle`]. Likewise, texture coordinates are interpolated from
+/// per-vertex ones if there are any, and are the barycentric coordinates of the
+/// face otherwise.
+#[derive(Debug)]
+pub struct TriangleMesh {
+    data: Arc<MeshData>,
+    bvh: BVH,
+}
+
+#[derive(Debug)]
+struct MeshData {
+    vertices: Vec<Point3>,
+    indices: Vec<[usize; 3]>,
+    normals: Option<Vec<Vec3<f64>>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    material: Arc<dyn Material>,
+}
+
+/// One face of a mesh, which is what the BVH of the mesh is built from.
+#[derive(Debug)]
+struct Face {
+    data: Arc<MeshData>,
+    index: usize,
+}
+
+impl TriangleMesh {
+    /// Mesh of the faces `indices` between `vertices`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no faces, or an index is out of range.
+    pub fn new(
+        vertices: Vec<Point3>,
+        indices: Vec<[usize; 3]>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self::with_attributes(vertices, indices, None, None, material)
+    }
+
+    /// Like [`TriangleMesh::new`], with optional per-vertex normals and texture
+    /// coordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no faces, an index is out of range, or there are
+    /// normals or texture coordinates but not one for every vertex.
+    pub fn with_attributes(
+        vertices: Vec<Point3>,
+        indices: Vec<[usize; 3]>,
+        normals: Option<Vec<Vec3<f64>>>,
+        uvs: Option<Vec<(f64, f64)>>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(!indices.is_empty(), "mesh must have faces");
+        assert!(
+            indices
+                .iter()
+                .flatten()
+                .all(|&index| index < vertices.len()),
+            "vertex index out of range"
+        );
+        if let Some(normals) = &normals {
+            assert_eq!(normals.len(), vertices.len(), "one normal per vertex");
+        }
+        if let Some(uvs) = &uvs {
+            assert_eq!(
+                uvs.len(),
+                vertices.len(),
+                "one texture coordinate per vertex"
+            );
+        }
+
+        let data = Arc::new(MeshData {
+            vertices,
+            indices,
+            normals: normals.map(|normals| normals.into_iter().map(Vec3::normalized).collect()),
+            uvs,
+            material,
+        });
+        let faces = (0..data.indices.len())
+            .map(|index| -> Box<dyn Hit> {
+                Box::new(Face {
+                    data: data.clone(),
+                    index,
+                })
+            })
+            .collect();
+        // faces do not move, so the time range does not matter
+        let bvh = BVH::new(faces, 0.0..1.0);
+
+        Self { data, bvh }
+    }
+
+    /// Parallelogram from `corner` spanned by `u` and `v`, made of two faces,
+    /// with texture coordinates going from 0 to 1 along `u` and `v`.
+    pub fn quad(corner: Point3, u: Vec3<f64>, v: Vec3<f64>, material: Arc<dyn Material>) -> Self {
+        let vertices = vec![corner, corner + u, corner + u + v, corner + v];
+        let uvs = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        Self::with_attributes(
+            vertices,
+            vec![[0, 1, 2], [0, 2, 3]],
+            None,
+            Some(uvs),
+            material,
+        )
+    }
+
+    pub fn vertices(&self) -> &[Point3] {
+        &self.data.vertices
+    }
+
+    pub fn indices(&self) -> &[[usize; 3]] {
+        &self.data.indices
+    }
+}
+
+impl Face {
+    fn vertices(&self) -> [Point3; 3] {
+        self.data.indices[self.index].map(|index| self.data.vertices[index])
+    }
+}
+
+impl Hit for Face {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let [a, b, c] = self.vertices();
+        let (edge_ab, edge_ac) = (b - a, c - a);
+        let (t, u, v) = triangle::intersect(a, edge_ab, edge_ac, &ray, t_range)?;
+        let weights = [1.0 - u - v, u, v];
+        let indices = self.data.indices[self.index];
+
+        let normal_outward = match &self.data.normals {
+            Some(normals) => indices
+                .iter()
+                .zip(weights)
+                .map(|(&index, weight)| normals[index] * weight)
+                .fold(Vec3::zeros(), |sum, normal| sum + normal)
+                .normalized(),
+            None => edge_ab.cross(edge_ac).normalized(),
+        };
+        let uv = match &self.data.uvs {
+            Some(uvs) => indices
+                .iter()
+                .zip(weights)
+                .fold((0.0, 0.0), |(u, v), (&index, weight)| {
+                    (u + uvs[index].0 * weight, v + uvs[index].1 * weight)
+                }),
+            None => (u, v),
+        };
+
+        Some(OutwardHitRecord::new(
+            ray.at(t),
+            &ray,
+            normal_outward,
+            t,
+            self.data.material.clone(),
+            uv,
+        ))
+    }
+
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+        Some(triangle::bounding_box(self.vertices()))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        let [a, b, c] = self.vertices();
+        triangle::intersect(a, b - a, c - a, ray, t_range).is_some()
+    }
+}
+
+impl Hit for TriangleMesh {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        self.bvh.hit(ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        self.bvh.bounding_box(time_range)
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.bvh.hit_any(ray, t_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, Color};
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new_solid(Color::constant(0.5)))
+    }
+
+    #[test]
+    fn quad_interpolates_texture_coordinates() {
+        let quad = TriangleMesh::quad(
+            Point3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 4.0),
+            material(),
+        );
+        let down = |x, z| Ray::new(Point3::new(x, 1.0, z), Vec3::new(0.0, -1.0, 0.0), 0.0);
+
+        for (x, z) in [(1.5, 1.0), (2.5, 3.0), (3.0, 4.0)] {
+            let hit = quad.hit(down(x, z), Interval::from_min(0.0)).unwrap();
+            assert_eq!(hit.t, 1.0);
+            assert!((hit.u - (x - 1.0) / 2.0).abs() < 1e-12);
+            assert!((hit.v - z / 4.0).abs() < 1e-12);
+            assert_eq!(hit.normal_outward, Vec3::new(0.0, -1.0, 0.0));
+        }
+        assert!(!quad.hit_any(&down(0.5, 1.0), Interval::from_min(0.0)));
+
+        let aabb = quad.bounding_box(0.0..1.0).unwrap();
+        assert_eq!((aabb.min().x(), aabb.max().x()), (1.0, 3.0));
+        assert_eq!((aabb.min().z(), aabb.max().z()), (0.0, 4.0));
+    }
+
+    #[test]
+    fn vertex_normals_are_interpolated() {
+        let vertices = vec![
+            Point3::zeros(),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        let normals = vec![
+            Vec3::unit_z(),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+        ];
+        let mesh = TriangleMesh::with_attributes(
+            vertices,
+            vec![[0, 1, 2]],
+            Some(normals),
+            None,
+            material(),
+        );
+
+        let down = |x, y| Ray::new(Point3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let normal_at = |x, y| {
+            mesh.hit(down(x, y), Interval::from_min(0.0))
+                .unwrap()
+                .normal_outward
+        };
+        assert_eq!(normal_at(0.0, 0.0), Vec3::unit_z());
+        let normal = normal_at(0.5, 0.0);
+        assert!((normal - Vec3::new(1.0, 0.0, 1.0 + 2f64.sqrt()).normalized()).norm() < 1e-12);
+    }
+}
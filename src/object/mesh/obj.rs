@@ -0,0 +1,255 @@
+//! Loading of Wavefront OBJ files.
+//!
+//! Only geometry is read: positions (`v`), texture coordinates (`vt`),
+//! normals (`vn`) and faces (`f`). Every other statement, such as groups and
+//! material libraries, is ignored.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    sync::Arc,
+};
+
+use crate::{Material, Point3, Vec3};
+
+use super::TriangleMesh;
+
+/// Failure to load an OBJ file.
+#[derive(Debug)]
+pub enum ObjError {
+    /// The file cannot be read
+    Io(io::Error),
+    /// Line `line`, counting from 1, is not valid
+    Parse { line: usize, message: String },
+    /// The file has no faces
+    NoFaces,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(error) => write!(f, "failed to read OBJ file: {}", error),
+            ObjError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            ObjError::NoFaces => write!(f, "OBJ file has no faces"),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(error: io::Error) -> Self {
+        ObjError::Io(error)
+    }
+}
+
+/// Load the OBJ file at `path` as one mesh of `material`.
+///
+/// Polygons with more than three vertices are split into a fan of triangles.
+/// If the file has normals, corners of faces without one get the geometric
+/// normal of their face; if it has none at all, every face uses its geometric
+/// normal. Corners without texture coordinates get `(0, 0)`.
+pub fn load_obj(
+    path: impl AsRef<Path>,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, ObjError> {
+    let file = File::open(path)?;
+    parse_obj(BufReader::new(file), material)
+}
+
+/// Normal of a corner of a face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum CornerNormal {
+    /// Index of a normal of the file
+    File(usize),
+    /// Geometric normal of the face with this index
+    Face(usize),
+}
+
+/// Indices of the position, texture coordinate and normal of a corner.
+type Corner = (usize, Option<usize>, Option<usize>);
+
+fn parse_obj<R: BufRead>(reader: R, material: Arc<dyn Material>) -> Result<TriangleMesh, ObjError> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut triangles: Vec<[Corner; 3]> = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let error = |message: String| ObjError::Parse {
+            line: index + 1,
+            message,
+        };
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => positions.push(parse_floats::<3>(words).map_err(error)?),
+            Some("vt") => uvs.push(parse_floats::<2>(words).map_err(error)?),
+            Some("vn") => normals.push(parse_floats::<3>(words).map_err(error)?),
+            Some("f") => {
+                let lengths = (positions.len(), uvs.len(), normals.len());
+                let corners = words
+                    .map(|word| parse_corner(word, lengths))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if corners.len() < 3 {
+                    return Err(error(format!("face has only {} vertices", corners.len())));
+                }
+                for i in 1..corners.len() - 1 {
+                    triangles.push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    if triangles.is_empty() {
+        return Err(ObjError::NoFaces);
+    }
+
+    // a mesh vertex for every distinct combination of attributes
+    let has_normals = triangles.iter().flatten().any(|corner| corner.2.is_some());
+    let mut vertex_indices = HashMap::new();
+    let mut mesh_positions = Vec::new();
+    let mut mesh_uvs = Vec::new();
+    let mut mesh_normals = Vec::new();
+    let mut indices = Vec::with_capacity(triangles.len());
+    for (face, triangle) in triangles.iter().enumerate() {
+        let [a, b, c] = triangle.map(|(position, _, _)| Point3::from(positions[position]));
+        let face_normal = (b - a).cross(c - a);
+
+        let face_indices = triangle.map(|(position, uv, normal)| {
+            let normal = has_normals.then_some(match normal {
+                Some(normal) => CornerNormal::File(normal),
+                None => CornerNormal::Face(face),
+            });
+            *vertex_indices
+                .entry((position, uv, normal))
+                .or_insert_with(|| {
+                    mesh_positions.push(Point3::from(positions[position]));
+                    mesh_uvs.push(uv.map_or((0.0, 0.0), |uv| {
+                        let [u, v]: [f64; 2] = uvs[uv];
+                        (u, v)
+                    }));
+                    if let Some(normal) = normal {
+                        mesh_normals.push(match normal {
+                            CornerNormal::File(normal) => Vec3::from(normals[normal]),
+                            CornerNormal::Face(_) => face_normal,
+                        });
+                    }
+                    mesh_positions.len() - 1
+                })
+        });
+        indices.push(face_indices);
+    }
+
+    Ok(TriangleMesh::with_attributes(
+        mesh_positions,
+        indices,
+        has_normals.then_some(mesh_normals),
+        Some(mesh_uvs),
+        material,
+    ))
+}
+
+/// The first `N` numbers of a statement, any further ones are ignored, such
+/// as the optional weight of a position. Numbers must be finite.
+fn parse_floats<'a, const N: usize>(
+    mut words: impl Iterator<Item = &'a str>,
+) -> Result<[f64; N], String> {
+    let mut values = [0.0; N];
+    for value in &mut values {
+        let word = words.next().ok_or(format!("expected {} numbers", N))?;
+        *value = word
+            .parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+            .ok_or(format!("invalid number {:?}", word))?;
+    }
+    Ok(values)
+}
+
+/// A face corner such as `1`, `1/2`, `1//3` or `1/2/3`, with indices counting
+/// from 1, or backwards from -1 for the last element so far.
+///
+/// `lengths` are the numbers of positions, texture coordinates and normals
+/// read so far.
+fn parse_corner(word: &str, lengths: (usize, usize, usize)) -> Result<Corner, String> {
+    let mut parts = word.split('/');
+    let mut index = |len: usize, name: &str| -> Result<Option<usize>, String> {
+        let part = match parts.next() {
+            None | Some("") => return Ok(None),
+            Some(part) => part,
+        };
+        let value: i64 = part
+            .parse()
+            .map_err(|_| format!("invalid {} index {:?}", name, part))?;
+        let resolved = match value {
+            1.. => value - 1,
+            ..=-1 => len as i64 + value,
+            0 => -1,
+        };
+        if resolved < 0 || resolved >= len as i64 {
+            return Err(format!("{} index {} out of range", name, value));
+        }
+        Ok(Some(resolved as usize))
+    };
+
+    let position =
+        index(lengths.0, "position")?.ok_or(format!("missing position in {:?}", word))?;
+    let uv = index(lengths.1, "texture coordinate")?;
+    let normal = index(lengths.2, "normal")?;
+    Ok((position, uv, normal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, Color, Hit};
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new_solid(Color::constant(0.5)))
+    }
+
+    #[test]
+    fn load_pyramid() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pyramid.obj");
+        let mesh = load_obj(path, material()).unwrap();
+
+        // the quad base is split in two
+        assert_eq!(mesh.indices().len(), 6);
+        // the base shares its four corners, and the corners of the sides
+        // without normals in the file each get the normal of their face
+        assert_eq!(mesh.vertices().len(), 4 + 4 * 3);
+
+        // up to the padding of the flat base
+        let aabb = mesh.bounding_box(0.0..1.0).unwrap();
+        assert!((aabb.min() - Point3::new(-1.0, 0.0, -1.0)).norm() < 1e-5);
+        assert!((aabb.max() - Point3::new(1.0, 2.0, 1.0)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn invalid_lines_are_reported() {
+        let parse = |source: &str| parse_obj(source.as_bytes(), material()).err().unwrap();
+
+        assert!(matches!(parse("v 0 0 0\n"), ObjError::NoFaces));
+        let error = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n");
+        assert!(matches!(error, ObjError::Parse { line: 4, .. }));
+        let error = parse("v 0 0 zero\n");
+        assert!(matches!(error, ObjError::Parse { line: 1, .. }));
+        // Rust parses these, but they are not coordinates
+        let error = parse("v 0 0 0\nv nan 0 0\n");
+        assert!(matches!(error, ObjError::Parse { line: 2, .. }));
+        let error = parse("v 0 0 0\nv 1 0 0\nvt 0 inf\n");
+        assert!(matches!(error, ObjError::Parse { line: 3, .. }));
+    }
+}
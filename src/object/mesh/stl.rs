@@ -0,0 +1,243 @@
+//! Loading of STL files, in both the binary and the ASCII format.
+
+use std::{fmt, fs, io, path::Path, sync::Arc};
+
+use log::warn;
+
+use crate::{Material, Point3};
+
+use super::TriangleMesh;
+
+/// Failure to load an STL file.
+#[derive(Debug)]
+pub enum StlError {
+    /// The file cannot be read
+    Io(io::Error),
+    /// The file is not valid binary or ASCII STL
+    Parse(String),
+    /// The file has no facets with an area
+    NoFacets {
+        /// Number of facets without area that are skipped
+        degenerate: usize,
+    },
+}
+
+impl fmt::Display for StlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StlError::Io(error) => write!(f, "failed to read STL file: {}", error),
+            StlError::Parse(message) => write!(f, "invalid STL file: {}", message),
+            StlError::NoFacets { degenerate } => write!(
+                f,
+                "STL file has no facets with an area, {} are degenerate",
+                degenerate
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StlError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StlError {
+    fn from(error: io::Error) -> Self {
+        StlError::Io(error)
+    }
+}
+
+/// Size of the header of a binary STL file, followed by the number of facets.
+const HEADER_SIZE: usize = 80;
+/// Size of a facet in a binary STL file: normal, three vertices and a
+/// two-byte attribute.
+const FACET_SIZE: usize = 50;
+
+/// Load the STL file at `path` as one mesh of `material`.
+///
+/// The format is detected from the contents: a file whose size matches the
+/// facet count in its header is binary, even if the header starts with
+/// `solid` like an ASCII file does. Normals are computed from the vertices,
+/// since the stored ones are often unreliable. STL has no texture coordinates,
+/// so the vertices of each facet get `(0, 0)`, `(1, 0)` and `(0, 1)`.
+///
+/// Facets without area are skipped with a warning.
+pub fn load_stl(
+    path: impl AsRef<Path>,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, StlError> {
+    parse_stl(&fs::read(path)?, material)
+}
+
+fn parse_stl(bytes: &[u8], material: Arc<dyn Material>) -> Result<TriangleMesh, StlError> {
+    let facets = if is_binary(bytes) {
+        parse_binary(bytes)
+    } else if bytes.trim_ascii_start().starts_with(b"solid") {
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| StlError::Parse("ASCII file is not valid UTF-8".to_string()))?;
+        parse_ascii(text)?
+    } else {
+        return Err(StlError::Parse(
+            "neither a binary file of the right size nor an ASCII file".to_string(),
+        ));
+    };
+
+    let total = facets.len();
+    let facets: Vec<_> = facets
+        .into_iter()
+        .filter(|[a, b, c]| (*b - *a).cross(*c - *a).len_squared() > 0.0)
+        .collect();
+    let degenerate = total - facets.len();
+    if facets.is_empty() {
+        return Err(StlError::NoFacets { degenerate });
+    }
+    if degenerate > 0 {
+        warn!(
+            "skipped {} of {} STL facets without area",
+            degenerate, total
+        );
+    }
+
+    let vertices = facets.iter().flatten().copied().collect();
+    let indices = (0..facets.len())
+        .map(|facet| [3 * facet, 3 * facet + 1, 3 * facet + 2])
+        .collect();
+    let uvs = (0..facets.len())
+        .flat_map(|_| [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)])
+        .collect();
+    Ok(TriangleMesh::with_attributes(
+        vertices,
+        indices,
+        None,
+        Some(uvs),
+        material,
+    ))
+}
+
+/// Whether the size of `bytes` matches the facet count of a binary header.
+fn is_binary(bytes: &[u8]) -> bool {
+    facet_count(bytes).is_some_and(|count| bytes.len() == HEADER_SIZE + 4 + count * FACET_SIZE)
+}
+
+fn facet_count(bytes: &[u8]) -> Option<usize> {
+    let count = bytes.get(HEADER_SIZE..HEADER_SIZE + 4)?;
+    Some(u32::from_le_bytes(count.try_into().unwrap()) as usize)
+}
+
+/// Vertices of the facets of a binary file, whose size is checked by [`is_binary`].
+fn parse_binary(bytes: &[u8]) -> Vec<[Point3; 3]> {
+    bytes[HEADER_SIZE + 4..]
+        .chunks_exact(FACET_SIZE)
+        .map(|facet| {
+            let float = |index: usize| {
+                let start = 4 * index;
+                f32::from_le_bytes(facet[start..start + 4].try_into().unwrap()) as f64
+            };
+            // the normal comes first
+            let vertex = |index: usize| {
+                let start = 3 + 3 * index;
+                Point3::new(float(start), float(start + 1), float(start + 2))
+            };
+            [vertex(0), vertex(1), vertex(2)]
+        })
+        .collect()
+}
+
+/// Vertices of the facets of an ASCII file.
+fn parse_ascii(text: &str) -> Result<Vec<[Point3; 3]>, StlError> {
+    let mut facets = Vec::new();
+    let mut vertices = Vec::with_capacity(3);
+    let mut words = text.split_whitespace();
+
+    while let Some(word) = words.next() {
+        match word {
+            "vertex" => {
+                let mut coordinate = || -> Result<f64, StlError> {
+                    let word = words
+                        .next()
+                        .ok_or_else(|| StlError::Parse("incomplete vertex".to_string()))?;
+                    word.parse()
+                        .map_err(|_| StlError::Parse(format!("invalid number {:?}", word)))
+                };
+                vertices.push(Point3::new(coordinate()?, coordinate()?, coordinate()?));
+            }
+            "endfacet" => {
+                let facet = vertices.as_slice().try_into().map_err(|_| {
+                    StlError::Parse(format!("facet has {} vertices", vertices.len()))
+                })?;
+                facets.push(facet);
+                vertices.clear();
+            }
+            // `solid`, `facet normal`, `outer loop` and the rest carry no geometry
+            _ => {}
+        }
+    }
+
+    Ok(facets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, Color, Hit};
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new_solid(Color::constant(0.5)))
+    }
+
+    /// Tetrahedron with its tip at `(0, 0, 1)`, and one facet without area.
+    fn facets() -> Vec<[[f32; 3]; 3]> {
+        let (o, x, y, z) = ([0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]);
+        vec![[o, y, x], [o, x, z], [o, z, y], [x, y, z], [x, x, y]]
+    }
+
+    #[test]
+    fn binary_and_ascii_agree() {
+        // the header starts with `solid` to make sure the size decides
+        let mut binary = b"solid but binary".to_vec();
+        binary.resize(HEADER_SIZE, 0);
+        binary.extend((facets().len() as u32).to_le_bytes());
+        let mut ascii = "solid tetrahedron\n".to_string();
+        for facet in facets() {
+            binary.extend([0u8; 12]);
+            ascii += "  facet normal 0 0 0\n    outer loop\n";
+            for vertex in facet {
+                binary.extend(vertex.iter().flat_map(|value| value.to_le_bytes()));
+                ascii += &format!("      vertex {} {} {}\n", vertex[0], vertex[1], vertex[2]);
+            }
+            binary.extend([0u8; 2]);
+            ascii += "    endloop\n  endfacet\n";
+        }
+        ascii += "endsolid tetrahedron\n";
+
+        for bytes in [binary.as_slice(), ascii.as_bytes()] {
+            let mesh = parse_stl(bytes, material()).unwrap();
+            assert_eq!(mesh.indices().len(), 4);
+            assert_eq!(
+                mesh.vertices()[3..6],
+                [Point3::zeros(), Point3::unit_x(), Point3::unit_z()]
+            );
+            // up to the padding of the flat facets
+            let aabb = mesh.bounding_box(0.0..1.0).unwrap();
+            assert!(aabb.min().norm() < 1e-5);
+            assert_eq!(aabb.max(), Point3::ones());
+        }
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        let flat = "solid flat\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 2 0 0\nendloop\nendfacet\nendsolid\n";
+        assert!(matches!(
+            parse_stl(flat.as_bytes(), material()),
+            Err(StlError::NoFacets { degenerate: 1 })
+        ));
+        assert!(matches!(
+            parse_stl(b"not an stl file", material()),
+            Err(StlError::Parse(_))
+        ));
+    }
+}
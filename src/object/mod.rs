@@ -2,7 +2,24 @@ pub mod sphere;
 mod world;
 pub mod rectangle;
 mod block;
+mod triangle;
+pub mod mesh;
+mod disk;
+mod cylinder;
+mod cone;
+mod quad;
+mod ellipsoid;
+mod heightfield;
 
 pub use sphere::Sphere;
-pub use world::World;
-pub use block::Block;
+pub use world::{AcceleratedWorld, World, WorldError};
+pub use block::{Block, BlockUv};
+pub use triangle::Triangle;
+pub use mesh::TriangleMesh;
+pub use disk::Disk;
+pub use cylinder::Cylinder;
+pub use cone::Cone;
+pub use quad::Quad;
+pub use rectangle::GeometryError;
+pub use ellipsoid::Ellipsoid;
+pub use heightfield::Heightfield;
@@ -0,0 +1,333 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    random, Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+/// A parallelogram in any orientation, spanned by two edges from a corner.
+///
+/// The outward normal is `edge_u × edge_v`, except for [`Quad::new_xz`]. Texture coordinates are planar:
+/// `u` goes from 0 to 1 along `edge_u`, and `v` along `edge_v`, so a point is
+/// `origin + u * edge_u + v * edge_v`. Points exactly on an edge are on the quad.
+#[derive(Debug, Clone)]
+pub struct Quad {
+    origin: Point3,
+    edge_u: Vec3<f64>,
+    edge_v: Vec3<f64>,
+    /// Unit normal, which is the outward normal
+    normal: Vec3<f64>,
+    /// `edge_u × edge_v / |edge_u × edge_v|²`, which gives the planar
+    /// coordinates of a point by cross products with the edges
+    w: Vec3<f64>,
+    material: Arc<dyn Material>,
+}
+
+impl Quad {
+    /// # Panics
+    ///
+    /// Panics if the edges are parallel, or either of them is zero.
+    pub fn new(
+        origin: Point3,
+        edge_u: Vec3<f64>,
+        edge_v: Vec3<f64>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let n = edge_u.cross(edge_v);
+        let len_squared = n.len_squared();
+        assert!(
+            len_squared > 0.0 && len_squared.is_finite(),
+            "quad edges must not be zero or parallel"
+        );
+        Self {
+            origin,
+            edge_u,
+            edge_v,
+            normal: n / len_squared.sqrt(),
+            w: n / len_squared,
+            material,
+        }
+    }
+
+    /// Same as [`super::rectangle::AxisAlignedRectangle::new_xy`], with the
+    /// normal along +z.
+    ///
+    /// The corners may be given in any order, each coordinate is sorted. Where
+    /// the corners share a coordinate, the quad is widened to
+    /// [`AABB::MIN_THICKNESS`] around it, so that it still has some area.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is NaN.
+    pub fn new_xy(
+        min_coord: (f64, f64),
+        max_coord: (f64, f64),
+        z: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let ((x0, x1), (y0, y1)) = extents(min_coord, max_coord);
+        Self::new(
+            Point3::new(x0, y0, z),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            material,
+        )
+    }
+
+    /// Same as [`Quad::new_xy`] in the XZ plane, with the normal along +y.
+    ///
+    /// As in [`super::rectangle::AxisAlignedRectangle::new_xz`], `u` follows x
+    /// and `v` follows z, so the normal is against `edge_u × edge_v`.
+    pub fn new_xz(
+        min_coord: (f64, f64),
+        max_coord: (f64, f64),
+        y: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let ((x0, x1), (z0, z1)) = extents(min_coord, max_coord);
+        let mut quad = Self::new(
+            Point3::new(x0, y, z0),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            material,
+        );
+        // the planar coordinates only depend on `w`, so turning the normal
+        // over keeps them
+        quad.normal = -quad.normal;
+        quad
+    }
+
+    /// Same as [`Quad::new_xy`] in the YZ plane, with the normal along +x.
+    pub fn new_yz(
+        min_coord: (f64, f64),
+        max_coord: (f64, f64),
+        x: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let ((y0, y1), (z0, z1)) = extents(min_coord, max_coord);
+        Self::new(
+            Point3::new(x, y0, z0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            material,
+        )
+    }
+
+    pub fn corners(&self) -> [Point3; 4] {
+        [
+            self.origin,
+            self.origin + self.edge_u,
+            self.origin + self.edge_v,
+            self.origin + self.edge_u + self.edge_v,
+        ]
+    }
+
+    /// Parameter and texture coordinates where the ray hits the quad, if it
+    /// does so in `t_range`.
+    fn intersect(&self, ray: &Ray, t_range: Interval) -> Option<(f64, f64, f64)> {
+        let denominator = self.normal.dot(ray.direction());
+        // parallel to the plane
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = self.normal.dot(self.origin - ray.origin()) / denominator;
+        // NaN is never contained
+        if !t_range.contains(t) {
+            return None;
+        }
+
+        let planar = ray.at(t) - self.origin;
+        let u = self.w.dot(planar.cross(self.edge_v));
+        let v = self.w.dot(self.edge_u.cross(planar));
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return None;
+        }
+        Some((t, u, v))
+    }
+}
+
+/// Each coordinate of the two corners sorted, as `((x0, x1), (y0, y1))`, and
+/// widened to at least [`AABB::MIN_THICKNESS`].
+fn extents(a: (f64, f64), b: (f64, f64)) -> ((f64, f64), (f64, f64)) {
+    let extent = |a: f64, b: f64| {
+        let padding = (AABB::MIN_THICKNESS - (b - a).abs()).max(0.0) / 2.0;
+        (a.min(b) - padding, a.max(b) + padding)
+    };
+    (extent(a.0, b.0), extent(a.1, b.1))
+}
+
+impl Hit for Quad {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let (t, u, v) = self.intersect(&ray, t_range)?;
+        Some(
+            OutwardHitRecord::new(
+                ray.at(t),
+                &ray,
+                self.normal,
+                t,
+                self.material.clone(),
+                (u, v),
+            )
+            .with_tangents(self.edge_u, self.edge_v),
+        )
+    }
+
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+        let corners = self.corners();
+        let min = corners
+            .iter()
+            .fold(corners[0], |min, corner| min.min(corner));
+        let max = corners
+            .iter()
+            .fold(corners[0], |max, corner| max.max(corner));
+        Some(AABB::new(min, max).padded(AABB::MIN_THICKNESS))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.intersect(ray, t_range).is_some()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let ray = Ray::new(origin, direction, time);
+        let Some((t, _, _)) = self.intersect(&ray, crate::T_RANGE) else {
+            return 0.0;
+        };
+
+        let area = self.edge_u.cross(self.edge_v).norm();
+        let distance_squared = t * t * direction.len_squared();
+        let cosine = (direction.dot(self.normal) / direction.norm()).abs();
+        distance_squared / (cosine * area)
+    }
+
+    fn random_point_towards(&self, origin: Point3, _time: f64) -> Option<Vec3<f64>> {
+        let (u, v): (f64, f64) = (random::random(), random::random());
+        Some(self.origin + self.edge_u * u + self.edge_v * v - origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::DiffuseLight, object::rectangle::AxisAlignedRectangle, scenes, Color, HitExt,
+        Mat3,
+    };
+
+    #[test]
+    fn matches_the_axis_aligned_rectangles() {
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(4.0)));
+        let pairs = [
+            (
+                Quad::new_xy((2.0, 1.0), (0.0, 3.0), 0.5, light.clone()),
+                AxisAlignedRectangle::new_xy((2.0, 1.0), (0.0, 3.0), 0.5, light.clone()),
+            ),
+            (
+                Quad::new_xz((0.0, 1.0), (2.0, 3.0), 0.5, light.clone()),
+                AxisAlignedRectangle::new_xz((0.0, 1.0), (2.0, 3.0), 0.5, light.clone()),
+            ),
+            (
+                Quad::new_yz((0.0, 1.0), (2.0, 3.0), 0.5, light.clone()),
+                AxisAlignedRectangle::new_yz((0.0, 1.0), (2.0, 3.0), 0.5, light.clone()),
+            ),
+        ];
+        for (quad, rectangle) in pairs {
+            for (origin, direction) in [
+                (Point3::new(1.5, 2.0, 4.0), Vec3::new(-0.3, 0.1, -1.0)),
+                (Point3::new(4.0, 1.5, 2.0), Vec3::new(-1.0, 0.2, 0.1)),
+                (Point3::new(1.0, -3.0, 2.5), Vec3::new(0.1, 1.0, -0.2)),
+            ] {
+                let ray = Ray::new(origin, direction, 0.0);
+                let expected = rectangle.hit(ray.clone(), Interval::from_min(0.0));
+                let actual = quad.hit(ray, Interval::from_min(0.0));
+                assert_eq!(actual.is_some(), expected.is_some());
+                if let (Some(actual), Some(expected)) = (actual, expected) {
+                    assert!((actual.t - expected.t).abs() < 1e-12);
+                    assert_eq!(actual.normal_outward, expected.normal_outward);
+                    assert!((actual.u - expected.u).abs() < 1e-12);
+                    assert!((actual.v - expected.v).abs() < 1e-12);
+                    assert_eq!(actual.tangents, expected.tangents);
+                }
+            }
+            let aabb = quad.bounding_box(0.0..1.0).unwrap();
+            assert!((0..3).all(|i| aabb.max[i] > aabb.min[i]));
+        }
+
+        let quad = Quad::new_yz((0.0, 1.0), (2.0, 3.0), 0.5, light);
+        let ray = Ray::new(Point3::new(1.0, 0.5, 2.5), -Vec3::unit_x(), 0.0);
+        let hit = quad.hit(ray, Interval::from_min(0.0)).unwrap();
+        assert_eq!((hit.u, hit.v), (0.25, 0.75));
+    }
+
+    #[test]
+    fn flat_corners_are_widened() {
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(4.0)));
+        // each quad is flat along its first coordinate, with a point on it
+        let quads = [
+            (
+                Quad::new_xy((1.0, 1.0), (1.0, 3.0), 0.5, light.clone()),
+                Point3::new(1.0, 2.0, 0.5),
+                0,
+            ),
+            (
+                Quad::new_xz((1.0, 1.0), (1.0, 3.0), 0.5, light.clone()),
+                Point3::new(1.0, 0.5, 2.0),
+                0,
+            ),
+            (
+                Quad::new_yz((1.0, 1.0), (1.0, 3.0), 0.5, light.clone()),
+                Point3::new(0.5, 1.0, 2.0),
+                1,
+            ),
+        ];
+        for (quad, point, flat_axis) in quads {
+            let ray = Ray::new(point + quad.normal, -quad.normal, 0.0);
+            let hit = quad.hit(ray, Interval::from_min(0.0)).unwrap();
+            assert!((hit.point - point).norm() < 1e-12);
+            assert!(hit.u.is_finite() && hit.v.is_finite());
+
+            let aabb = quad.bounding_box(0.0..1.0).unwrap();
+            let width = aabb.max[flat_axis] - aabb.min[flat_axis];
+            assert!(width >= 0.99 * AABB::MIN_THICKNESS, "{width}");
+        }
+    }
+
+    #[test]
+    fn slanted_light_matches_rotated_rectangle() {
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(15.0)));
+        let (min, max) = ((-65.0, -52.5), (65.0, 52.5));
+        let center = Vec3::new(278.0, 450.0, 279.5);
+        let rectangle = AxisAlignedRectangle::new_xz(min, max, 0.0, light.clone())
+            .rotate_x(30.0)
+            .translate(center);
+
+        // `rotate_x` turns clockwise when looking along +x
+        let rotation = Mat3::from_axis_angle(Vec3::unit_x(), -30.0);
+        let quad = Quad::new(
+            rotation * Vec3::new(min.0, 0.0, min.1) + center,
+            rotation * Vec3::new(0.0, 0.0, max.1 - min.1),
+            rotation * Vec3::new(max.0 - min.0, 0.0, 0.0),
+            light,
+        );
+
+        let render = |slanted: Box<dyn Hit>| {
+            let mut scene = scenes::cornell_box();
+            scene.world.add(slanted);
+            let mut tracer = scene.build_tracer(8);
+            tracer.image_height = 40;
+            tracer.samples_per_pixel = 4;
+            tracer.seed = Some(5);
+            tracer.render_region(0, 0, 40, 40)
+        };
+        let (expected, actual) = (render(rectangle.boxed()), render(quad.boxed()));
+        for y in 0..40 {
+            for x in 0..40 {
+                let (expected, actual) = (expected.get(x, y), actual.get(x, y));
+                assert!(
+                    (actual - expected).norm() <= 1e-6 * (1.0 + expected.norm()),
+                    "pixel ({x}, {y}): {actual} != {expected}"
+                );
+            }
+        }
+    }
+}
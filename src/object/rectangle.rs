@@ -1,11 +1,38 @@
 use crate::Ray;
-use std::sync::Arc;
+use std::{fmt, ops::Range, sync::Arc};
+
+use rand::Rng;
 
 use crate::{
     hit::{OutwardHitRecord, AABB},
-    Hit, Material, Vec3,
+    random, Hit, Interval, Material, Point3, Vec3,
 };
 
+/// Why a rectangle or a block cannot be made from its coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeometryError {
+    /// The axes of a rectangle are not all different
+    RepeatedAxis { axis: [usize; 3] },
+    /// There is no extent from `min` to `max` along `axis`, or they are not
+    /// finite
+    EmptyExtent { axis: usize, min: f64, max: f64 },
+}
+
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeometryError::RepeatedAxis { axis } => {
+                write!(f, "axis must be different, got {:?}", axis)
+            }
+            GeometryError::EmptyExtent { axis, min, max } => {
+                write!(f, "axis {}: nothing from {} to {}", axis, min, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeometryError {}
+
 #[derive(Debug, Clone)]
 pub struct AxisAlignedRectangle {
     x0: f64,
@@ -20,6 +47,17 @@ pub struct AxisAlignedRectangle {
 }
 
 impl AxisAlignedRectangle {
+    /// Rectangle between the corners `min_coord` and `max_coord` on the plane
+    /// where axis `axis[0]` is `z`.
+    ///
+    /// The corners may be given in any order, each coordinate is sorted. A
+    /// rectangle with zero width is kept as it is: it has no area, so rays
+    /// never hit it. Use [`AxisAlignedRectangle::try_new`] to
+    /// reject such input instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the three axes are not different.
     pub fn new(
         min_coord: (f64, f64),
         max_coord: (f64, f64),
@@ -34,27 +72,41 @@ impl AxisAlignedRectangle {
             axis[0] != axis[1] && axis[0] != axis[2] && axis[1] != axis[2],
             "axis must be different"
         );
-        assert!(
-            x0 < x1,
-            "axis {}: min coordinate must be less than max",
-            axis[1]
-        );
-        assert!(
-            y0 < y1,
-            "axis {}: min coordinate must be less than max",
-            axis[2]
-        );
         Self {
-            x0,
-            x1,
-            y0,
-            y1,
+            x0: x0.min(x1),
+            x1: x0.max(x1),
+            y0: y0.min(y1),
+            y1: y0.max(y1),
             z,
             axis,
             material,
         }
     }
 
+    /// Like [`AxisAlignedRectangle::new`], but fails unless the axes are
+    /// different and `min_coord` is strictly less than `max_coord` in both
+    /// coordinates.
+    pub fn try_new(
+        min_coord: (f64, f64),
+        max_coord: (f64, f64),
+        z: f64,
+        axis: [usize; 3],
+        material: Arc<dyn Material>,
+    ) -> Result<Self, GeometryError> {
+        if axis[0] == axis[1] || axis[0] == axis[2] || axis[1] == axis[2] {
+            return Err(GeometryError::RepeatedAxis { axis });
+        }
+        for (min, max, axis) in [
+            (min_coord.0, max_coord.0, axis[1]),
+            (min_coord.1, max_coord.1, axis[2]),
+        ] {
+            if min.is_nan() || max.is_nan() || min >= max {
+                return Err(GeometryError::EmptyExtent { axis, min, max });
+            }
+        }
+        Ok(Self::new(min_coord, max_coord, z, axis, material))
+    }
+
     pub fn new_xy(
         min_coord: (f64, f64),
         max_coord: (f64, f64),
@@ -83,18 +135,25 @@ impl AxisAlignedRectangle {
     }
 }
 
-impl Hit for AxisAlignedRectangle {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
+impl AxisAlignedRectangle {
+    /// Parameter and point where the ray hits the rectangle, if it does so in `t_range`.
+    fn intersect(&self, ray: &Ray, t_range: Interval) -> Option<(f64, Point3)> {
         let z_axis = self.axis[0];
         let x_axis = self.axis[1];
         let y_axis = self.axis[2];
 
+        // a rectangle without area has no surface coordinates to hit
+        if self.x0 == self.x1 || self.y0 == self.y1 {
+            return None;
+        }
+
         // for a ray P(t) = A + t b,
         // where A is the origin and b is the direction,
         // the intersection with the plane z = k is
         let t = (self.z - ray.origin()[z_axis]) / ray.direction()[z_axis];
 
-        if t < t_min || t > t_max || t.is_nan() {
+        // NaN is never contained
+        if !t_range.contains(t) {
             return None;
         }
 
@@ -110,6 +169,17 @@ impl Hit for AxisAlignedRectangle {
             return None;
         }
 
+        Some((t, point))
+    }
+}
+
+impl Hit for AxisAlignedRectangle {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let z_axis = self.axis[0];
+        let (t, point) = self.intersect(&ray, t_range)?;
+        let x = point[self.axis[1]];
+        let y = point[self.axis[2]];
+
         // find surface coordinates
         let u = (x - self.x0) / (self.x1 - self.x0);
         let v = (y - self.y0) / (self.y1 - self.y0);
@@ -117,26 +187,30 @@ impl Hit for AxisAlignedRectangle {
         // the outward normal is always a unit vector along the plane's normal
         let mut normal_outward = Vec3::zeros();
         normal_outward[z_axis] = 1.0;
+        let mut dpdu = Vec3::zeros();
+        dpdu[self.axis[1]] = self.x1 - self.x0;
+        let mut dpdv = Vec3::zeros();
+        dpdv[self.axis[2]] = self.y1 - self.y0;
 
-        Some(OutwardHitRecord::new(
-            point,
-            &ray,
-            normal_outward,
-            t,
-            self.material.clone(),
-            (u, v),
-        ))
+        Some(
+            OutwardHitRecord::new(
+                point,
+                &ray,
+                normal_outward,
+                t,
+                self.material.clone(),
+                (u, v),
+            )
+            .with_tangents(dpdu, dpdv),
+        )
     }
 
-    fn bounding_box(&self, _time_from: f64, _time_too: f64) -> Option<AABB> {
-        // The bounding box must have non-zero width in each dimension, so pad the Z
-        // dimension a small amount.
-        let padding = f64::EPSILON;
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
         let mut min = Vec3::zeros();
         let mut max = Vec3::zeros();
 
-        min[self.axis[0]] = self.z - padding;
-        max[self.axis[0]] = self.z + padding;
+        min[self.axis[0]] = self.z;
+        max[self.axis[0]] = self.z;
 
         min[self.axis[1]] = self.x0;
         max[self.axis[1]] = self.x1;
@@ -144,6 +218,131 @@ impl Hit for AxisAlignedRectangle {
         min[self.axis[2]] = self.y0;
         max[self.axis[2]] = self.y1;
 
-        Some(AABB::new(min, max))
+        // the bounding box must have non-zero width in each dimension
+        Some(AABB::new(min, max).padded(AABB::MIN_THICKNESS))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.intersect(ray, t_range).is_some()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let ray = Ray::new(origin, direction, time);
+        let Some((t, _)) = self.intersect(&ray, crate::T_RANGE) else {
+            return 0.0;
+        };
+
+        // the area around the point, seen from the origin, spans this solid angle
+        let area = (self.x1 - self.x0) * (self.y1 - self.y0);
+        let distance_squared = t * t * direction.len_squared();
+        let cosine = (direction[self.axis[0]] / direction.norm()).abs();
+        distance_squared / (cosine * area)
+    }
+
+    fn random_point_towards(&self, origin: Point3, _time: f64) -> Option<Vec3<f64>> {
+        let (x, y) = random::with_rng(|rng| {
+            (
+                rng.gen_range(self.x0..=self.x1),
+                rng.gen_range(self.y0..=self.y1),
+            )
+        });
+        let mut point = Point3::zeros();
+        point[self.axis[0]] = self.z;
+        point[self.axis[1]] = x;
+        point[self.axis[2]] = y;
+        Some(point - origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::{material::DiffuseLight, texture::UvGrid, Color};
+
+    #[test]
+    fn corners_in_any_order() {
+        let material = Arc::new(DiffuseLight::new(UvGrid::default()));
+        let ordered = AxisAlignedRectangle::new_xy((0.0, 1.0), (2.0, 3.0), 0.5, material.clone());
+        let reversed = AxisAlignedRectangle::new_xy((2.0, 1.0), (0.0, 3.0), 0.5, material.clone());
+        let ray = Ray::new(Point3::new(0.5, 2.5, 2.0), -Vec3::unit_z(), 0.0);
+        let uv = |rectangle: &AxisAlignedRectangle| {
+            let hit = rectangle.hit(ray.clone(), Interval::from_min(0.0)).unwrap();
+            (hit.u, hit.v)
+        };
+        assert_eq!(uv(&reversed), uv(&ordered));
+        assert_eq!((reversed.x0, reversed.x1), (0.0, 2.0));
+
+        let flat = AxisAlignedRectangle::new_xy((1.0, 1.0), (1.0, 3.0), 0.5, material.clone());
+        assert!(flat.hit(ray.clone(), Interval::from_min(0.0)).is_none());
+        // not even a ray right on its edge, which would have no finite u
+        let on_edge = Ray::new(Point3::new(1.0, 2.5, 2.0), -Vec3::unit_z(), 0.0);
+        assert!(flat.hit(on_edge.clone(), Interval::from_min(0.0)).is_none());
+        assert!(!flat.hit_any(&on_edge, Interval::from_min(0.0)));
+        assert!(flat.bounding_box(0.0..1.0).is_some());
+
+        let strict = |min, max| {
+            AxisAlignedRectangle::try_new(min, max, 0.5, [2, 0, 1], material.clone()).err()
+        };
+        assert_eq!(strict((0.0, 1.0), (2.0, 3.0)), None);
+        assert_eq!(
+            strict((2.0, 1.0), (0.0, 3.0)),
+            Some(GeometryError::EmptyExtent {
+                axis: 0,
+                min: 2.0,
+                max: 0.0
+            })
+        );
+        assert_eq!(
+            strict((1.0, 1.0), (1.0, 1.0)),
+            Some(GeometryError::EmptyExtent {
+                axis: 0,
+                min: 1.0,
+                max: 1.0
+            })
+        );
+        assert!(strict((f64::NAN, 1.0), (1.0, 3.0)).is_some());
+        assert_eq!(
+            AxisAlignedRectangle::try_new((0.0, 1.0), (2.0, 3.0), 0.5, [2, 0, 2], material.clone())
+                .unwrap_err(),
+            GeometryError::RepeatedAxis { axis: [2, 0, 2] }
+        );
+    }
+
+    #[test]
+    fn uv_spans_the_rectangle() {
+        let material = Arc::new(DiffuseLight::new(UvGrid::default()));
+        let rectangle = AxisAlignedRectangle::new_xz((0.0, 0.0), (2.0, 1.0), 0.0, material);
+        let emitted_at = |x, z| {
+            let ray = Ray::new(Point3::new(x, 1.0, z), -Vec3::unit_y(), 0.0);
+            rectangle.hit(ray, Interval::from_min(0.0)).unwrap().emitted
+        };
+
+        assert_eq!(emitted_at(0.5, 0.75), Color::new(0.25, 0.75, 0.0));
+        assert_eq!(emitted_at(2.0, 0.0), Color::new(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn light_pdf_integrates_to_one() {
+        random::seed(51);
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(4.0)));
+        let ceiling = AxisAlignedRectangle::new_xz((0.0, 0.0), (1.0, 2.0), 1.0, light);
+        let origin = Point3::new(0.25, 0.0, 0.5);
+
+        // uniform directions over the sphere have the density 1 / 4 pi
+        const SAMPLES: usize = 400_000;
+        let integral = (0..SAMPLES)
+            .map(|_| ceiling.pdf_value(origin, Vec3::random_unit_vector(), 0.0) * 4.0 * PI)
+            .sum::<f64>()
+            / SAMPLES as f64;
+        assert!((integral - 1.0).abs() < 0.03, "{integral}");
+
+        for _ in 0..100 {
+            let direction = ceiling.random_point_towards(origin, 0.0).unwrap();
+            assert!(ceiling.hit_any(&Ray::new(origin, direction, 0.0), Interval::from_min(0.0)));
+            assert!(ceiling.pdf_value(origin, direction, 0.0) > 0.0);
+        }
+        assert_eq!(ceiling.pdf_value(origin, -Vec3::unit_y(), 0.0), 0.0);
     }
 }
@@ -1,8 +1,8 @@
 use std::{f64::consts::PI, ops::Range, sync::Arc};
 
 use crate::{
-    hit::{OutwardHitRecord, AABB},
-    Hit, Material, Point3, Ray, Vec3,
+    hit::{swept_bounds, MotionPath, OutwardHitRecord, AABB},
+    random, Hit, Interval, Material, Onb, Point3, Ray, Vec3,
 };
 
 #[derive(Debug, Clone)]
@@ -10,25 +10,57 @@ pub struct Sphere {
     center: Point3,
     radius: f64,
     material: Arc<dyn Material>,
+    /// Added to the texture coordinates, see [`Sphere::with_uv_rotation`]
+    uv_offset: (f64, f64),
 }
 
-/// Compute the surface coordinates (u, v) from hitpoint P
-fn to_sphere_uv(normal_outward: &Vec3<f64>) -> (f64, f64) {
-    let (r, theta, phi) = normal_outward.to_spherical().into_tuple();
-    let u = phi / (2.0 * PI) / r;
-    let v = theta / PI / r;
-    (u, v)
+/// Compute the surface coordinates (u, v) from the direction of the hit point
+/// from the center, which does not need to be a unit vector.
+///
+/// `u` is the angle around the Y axis from -X, as a fraction of a full turn,
+/// and `v` goes from 0 at the south pole -Y to 1 at the north pole +Y.
+pub(crate) fn to_sphere_uv(direction: &Vec3<f64>) -> (f64, f64) {
+    let n = direction.normalized();
+    // rounding may push a component at a pole slightly past 1
+    let theta = (-n.y()).clamp(-1.0, 1.0).acos();
+    let phi = (-n.z()).atan2(n.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// Texture coordinate offset for a rotation of the texture by `longitude`
+/// and `latitude` degrees.
+fn uv_rotation(longitude: f64, latitude: f64) -> (f64, f64) {
+    (longitude / 360.0, latitude / 180.0)
+}
+
+/// Shift `(u, v)` by `offset`, wrapping `u` around the sphere and stopping
+/// `v` at the poles.
+fn offset_uv((u, v): (f64, f64), (du, dv): (f64, f64)) -> (f64, f64) {
+    ((u + du).rem_euclid(1.0), (v + dv).clamp(0.0, 1.0))
 }
 
 impl Sphere {
+    /// A negative `radius` gives the same sphere with the normals pointing
+    /// inwards, such as the inner surface of a hollow glass ball.
     pub fn new<P: Into<Point3>>(center: P, radius: f64, material: Arc<dyn Material>) -> Self {
         Self {
             center: center.into(),
             radius,
             material,
+            uv_offset: (0.0, 0.0),
         }
     }
 
+    /// Same sphere with its texture turned by `longitude` degrees around the
+    /// poles and shifted by `latitude` degrees towards the south pole, without
+    /// rotating the sphere itself.
+    ///
+    /// The texture wraps around the sphere, but stops at the poles.
+    pub fn with_uv_rotation(mut self, longitude: f64, latitude: f64) -> Self {
+        self.uv_offset = uv_rotation(longitude, latitude);
+        self
+    }
+
     pub fn center(&self) -> Vec3<f64> {
         self.center
     }
@@ -46,6 +78,10 @@ pub struct MovingSphere {
     center_to: Point3,
     radius: f64,
     material: Arc<dyn Material>,
+    /// Added to the texture coordinates, see [`Sphere::with_uv_rotation`]
+    uv_offset: (f64, f64),
+    /// Path of the center, in place of the straight line when set
+    path: Option<MotionPath>,
 }
 
 impl MovingSphere {
@@ -63,12 +99,44 @@ impl MovingSphere {
             center_to,
             radius,
             material,
+            uv_offset: (0.0, 0.0),
+            path: None,
+        }
+    }
+
+    /// Sphere whose center follows `path`.
+    pub fn with_path(path: MotionPath, radius: f64, material: Arc<dyn Material>) -> Self {
+        let time_range = path.time_range();
+        Self {
+            path: Some(path.clone()),
+            ..Self::new(
+                time_range.clone(),
+                path.position(time_range.start),
+                path.position(time_range.end),
+                radius,
+                material,
+            )
         }
     }
 
+    /// Same sphere with its texture rotated, see [`Sphere::with_uv_rotation`].
+    pub fn with_uv_rotation(mut self, longitude: f64, latitude: f64) -> Self {
+        self.uv_offset = uv_rotation(longitude, latitude);
+        self
+    }
+
+    /// Center at `time`, which stays at the end points outside the time range
+    /// of the motion, and at `center_from` if the range is a single instant.
     pub fn center(&self, time: f64) -> Vec3<f64> {
+        if let Some(path) = &self.path {
+            return path.position(time);
+        }
+        // a single instant has no ratio, only infinities on either side of it
+        if self.time_to == self.time_from {
+            return self.center_from;
+        }
         let ratio = (time - self.time_from) / (self.time_to - self.time_from);
-        self.center_from.lerp(self.center_to, ratio)
+        self.center_from.lerp(self.center_to, ratio.clamp(0.0, 1.0))
     }
 
     pub fn radius(&self) -> f64 {
@@ -77,25 +145,24 @@ impl MovingSphere {
 }
 
 impl Sphere {
+    /// The sphere moving to `center_to` over `time_range`, keeping its
+    /// texture rotation.
     pub fn into_moving(self, time_range: Range<f64>, center_to: Point3) -> MovingSphere {
-        MovingSphere::new(
-            time_range,
-            self.center,
-            center_to,
-            self.radius,
-            self.material,
-        )
+        MovingSphere {
+            uv_offset: self.uv_offset,
+            ..MovingSphere::new(
+                time_range,
+                self.center,
+                center_to,
+                self.radius,
+                self.material,
+            )
+        }
     }
 }
 
-fn hit(
-    center: Point3,
-    radius: f64,
-    material: Arc<dyn Material>,
-    ray: &Ray,
-    t_min: f64,
-    t_max: f64,
-) -> Option<OutwardHitRecord> {
+/// Smallest root of the sphere equation in `t_range`.
+pub(crate) fn solve(center: Point3, radius: f64, ray: &Ray, t_range: Interval) -> Option<f64> {
     // oc is (A - C)
     let oc = ray.origin() - center;
 
@@ -123,80 +190,454 @@ fn hit(
     let roots = [(-h - discriminant_s) / a, (-h + discriminant_s) / a];
 
     // Compute the roots and find acceptable one
-    let t = roots
-        .into_iter()
-        .find(|root| root >= &t_min && root <= &t_max)?;
+    roots.into_iter().find(|&root| t_range.contains(root))
+}
+
+fn hit(
+    center: Point3,
+    radius: f64,
+    material: Arc<dyn Material>,
+    uv_offset: (f64, f64),
+    ray: &Ray,
+    t_range: Interval,
+) -> Option<OutwardHitRecord> {
+    let t = solve(center, radius, ray, t_range)?;
 
     let point = ray.at(t);
+    // a negative radius turns the normal inwards, for hollow spheres, but the
+    // texture stays where it is on the surface
     let normal_outward = (point - center) / radius;
-    let uv = to_sphere_uv(&normal_outward);
+    let direction = normal_outward * radius.signum();
+    let uv = offset_uv(to_sphere_uv(&direction), uv_offset);
+
+    let record = OutwardHitRecord::new(point, ray, normal_outward, t, material, uv);
+    Some(match sphere_tangents(direction, radius.abs()) {
+        Some((dpdu, dpdv)) => record.with_tangents(dpdu, dpdv),
+        None => record,
+    })
+}
+
+/// Partial derivatives of the point in `direction` from the center by the
+/// texture coordinates of [`to_sphere_uv`], or `None` at the poles, where
+/// they are undefined.
+fn sphere_tangents(direction: Vec3<f64>, radius: f64) -> Option<(Vec3<f64>, Vec3<f64>)> {
+    let (x, y, z) = (direction.x(), direction.y(), direction.z());
+    let sin_theta = (x * x + z * z).sqrt();
+    if sin_theta < 1e-8 {
+        return None;
+    }
+    let dpdu = Vec3::new(z, 0.0, -x) * (2.0 * PI * radius);
+    let dpdv = Vec3::new(-x * y / sin_theta, sin_theta, -z * y / sin_theta) * (PI * radius);
+    Some((dpdu, dpdv))
+}
+
+/// Cosine of the half angle of the cone around the sphere seen from
+/// `origin`, or `None` from inside the sphere.
+fn cone_cosine(center: Point3, radius: f64, origin: Point3) -> Option<f64> {
+    let distance_squared = (center - origin).len_squared();
+    let radius_squared = radius * radius;
+    (distance_squared > radius_squared).then(|| (1.0 - radius_squared / distance_squared).sqrt())
+}
+
+/// Density of [`random_point_towards`] giving `direction` from `origin`.
+///
+/// Directions are sampled uniformly in the cone around the sphere seen from
+/// `origin`, so the density is one over its solid angle. From inside, there
+/// is no such cone, and points are sampled uniformly over the whole surface
+/// instead, whose density over solid angle is the squared distance over the
+/// cosine at the point and the area.
+fn pdf_value(center: Point3, radius: f64, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+    let ray = Ray::new(origin, direction, time);
+    match cone_cosine(center, radius, origin) {
+        Some(cos_theta_max) if solve(center, radius, &ray, crate::T_RANGE).is_some() => {
+            1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+        }
+        Some(_) => 0.0,
+        None => {
+            let Some(t) = solve(center, radius, &ray, crate::T_RANGE) else {
+                return 0.0;
+            };
+            let point = ray.at(t);
+            let distance_squared = t * t * direction.len_squared();
+            let cosine =
+                ((point - center).dot(direction) / (radius.abs() * direction.norm())).abs();
+            let area = 4.0 * PI * radius * radius;
+            distance_squared / (cosine * area)
+        }
+    }
+}
 
-    Some(OutwardHitRecord::new(
-        point,
-        ray,
-        normal_outward,
-        t,
-        material,
-        uv,
-    ))
+/// Direction from `origin` towards a random point of the sphere, see
+/// [`pdf_value`] for the distribution.
+fn random_point_towards(center: Point3, radius: f64, origin: Point3) -> Vec3<f64> {
+    let Some(cos_theta_max) = cone_cosine(center, radius, origin) else {
+        return center + Vec3::random_unit_vector() * radius.abs() - origin;
+    };
+    let (r1, r2): (f64, f64) = (random::random(), random::random());
+    let z = 1.0 + r2 * (cos_theta_max - 1.0);
+    let phi = 2.0 * PI * r1;
+    let sin_theta = (1.0 - z * z).sqrt();
+    let local = Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z);
+    Onb::from_w(center - origin).local(local)
 }
 
 impl Hit for Sphere {
-    /// Returns a [`HitRecord`] if `ray` hit to a point in `[t_min, t_max]`,
+    /// Returns a [`HitRecord`] if `ray` hit to a point in `t_range`,
     /// or `None` if does not hit
     ///
     /// The equation of the sphere in vector form is
     ///
-    ///     (P - C) . (P - C) = r^2
+    /// ```text
+    /// (P - C) . (P - C) = r^2
+    /// ```
     ///
     /// where C is the vector from sphere center, and P is the point.
     ///
     /// When P is the ray P(t) = A + tb for some t,  the equation expands to
     ///
-    ///     (A + t b - C) . (A + t b - C) = r^2
+    /// ```text
+    /// (A + t b - C) . (A + t b - C) = r^2
+    /// ```
     ///
     /// where b: ray.direction, A: ray.origin, C: sphere.center.
     ///
     /// In quadratic form
     ///
-    ///     (b.b) t^2 + (2b.(A-C)) t + ((A-C).(A-C) - r^2) = 0
+    /// ```text
+    /// (b.b) t^2 + (2b.(A-C)) t + ((A-C).(A-C) - r^2) = 0
+    /// ```
     ///
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
         let center = self.center();
         let radius = self.radius();
         let material = self.material.clone();
 
-        hit(center, radius, material, &ray, t_min, t_max)
+        hit(center, radius, material, self.uv_offset, &ray, t_range)
     }
 
-    fn bounding_box(&self, _: f64, _: f64) -> Option<AABB> {
+    fn bounding_box(&self, _: Range<f64>) -> Option<AABB> {
         let center = self.center();
-        let offset = Vec3::constant(self.radius());
+        let offset = Vec3::constant(self.radius().abs());
         Some(AABB::new(center - offset, center + offset))
     }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        solve(self.center(), self.radius(), ray, t_range).is_some()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        pdf_value(self.center(), self.radius(), origin, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, _time: f64) -> Option<Vec3<f64>> {
+        Some(random_point_towards(self.center(), self.radius(), origin))
+    }
 }
 
 impl Hit for MovingSphere {
-    /// Returns a [`HitRecord`] if `ray` hit to a point in `[t_min, t_max]`,
+    /// Returns a [`HitRecord`] if `ray` hit to a point in `t_range`,
     /// or `None` if does not hit.
     ///
     /// Refer to [`Sphere::hit`] for the equation.
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
         let center = self.center(ray.time());
         let radius = self.radius();
         let material = self.material.clone();
 
-        hit(center, radius, material, &ray, t_min, t_max)
+        hit(center, radius, material, self.uv_offset, &ray, t_range)
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        let offset = Vec3::constant(self.radius().abs());
+        if let Some(path) = &self.path {
+            let centers = path.bounds(time_range);
+            return Some(AABB::new(centers.min - offset, centers.max + offset));
+        }
+
+        // the motion is linear, so the two end points are exact
+        Some(swept_bounds(time_range, 2, 0.0, |time| {
+            let center = self.center(time);
+            AABB::new(center - offset, center + offset)
+        }))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        let center = self.center(ray.time());
+        solve(center, self.radius(), ray, t_range).is_some()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        pdf_value(self.center(time), self.radius(), origin, direction, time)
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        Some(random_point_towards(
+            self.center(time),
+            self.radius(),
+            origin,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hit::BVH,
+        material::{Dielectric, DiffuseLight, Lambertian},
+        texture::UvGrid,
+        Camera, Color, RayTracer, RenderMode, WorkGranularity, World,
+    };
+
+    #[test]
+    fn uv_rotation_turns_texture() {
+        let material = Arc::new(DiffuseLight::new(UvGrid::default()));
+        let sphere = Sphere::new(Point3::zeros(), 1.0, material);
+        let ray = Ray::new(2.0 * Vec3::unit_z(), -Vec3::unit_z(), 0.0);
+        let uv = |sphere: &dyn Hit| {
+            let hit = sphere.hit(ray.clone(), Interval::from_min(0.0)).unwrap();
+            (hit.u, hit.v)
+        };
+
+        assert_eq!(uv(&sphere), (0.25, 0.5));
+        assert_eq!(
+            uv(&sphere.clone().with_uv_rotation(180.0, 0.0)),
+            (0.75, 0.5)
+        );
+        assert_eq!(uv(&sphere.clone().with_uv_rotation(-90.0, 0.0)), (0.0, 0.5));
+        assert_eq!(
+            uv(&sphere.clone().with_uv_rotation(0.0, 45.0)),
+            (0.25, 0.75)
+        );
+        let moving = sphere
+            .with_uv_rotation(180.0, 0.0)
+            .into_moving(0.0..1.0, Point3::zeros());
+        assert_eq!(uv(&moving), (0.75, 0.5));
+    }
+
+    #[test]
+    fn axes_map_to_the_sphere_uv() {
+        let cases = [
+            // on the seam, where `u` is 1 instead with a negative zero z
+            (Vec3::new(-1.0, 0.0, 0.0), (0.0, 0.5)),
+            (Vec3::unit_z(), (0.25, 0.5)),
+            (Vec3::unit_x(), (0.5, 0.5)),
+            (-Vec3::unit_z(), (0.75, 0.5)),
+        ];
+        for (direction, expected) in cases {
+            for length in [1.0, 0.01, 300.0] {
+                let (u, v) = to_sphere_uv(&(direction * length));
+                assert!(
+                    (u - expected.0).abs() < 1e-12 && (v - expected.1).abs() < 1e-12,
+                    "{direction} * {length}: ({u}, {v}) != {expected:?}"
+                );
+            }
+        }
+        // any `u` is fine at the poles
+        assert_eq!(to_sphere_uv(&(-Vec3::unit_y() * 2.0)).1, 0.0);
+        assert_eq!(to_sphere_uv(&(Vec3::unit_y() * 2.0)).1, 1.0);
+
+        // both sides of the seam at -X are at the edges of the texture
+        let (before, _) = to_sphere_uv(&Vec3::new(-1.0, 0.0, 1e-9));
+        let (after, _) = to_sphere_uv(&Vec3::new(-1.0, 0.0, -1e-9));
+        assert!(before < 1e-9 && after > 1.0 - 1e-9);
+        // slightly off the unit length right at a pole
+        let (_, v) = to_sphere_uv(&Vec3::new(0.0, 1.0 + 1e-16, 0.0));
+        assert_eq!(v, 1.0);
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB> {
-        let center_from = self.center(time_from);
-        let center_to = self.center(time_to);
-        let offset = Vec3::constant(self.radius());
+    #[test]
+    fn uv_follows_longitude_and_latitude() {
+        let material = Arc::new(DiffuseLight::new(UvGrid::new(3.0, 0.1)));
+        let sphere = Sphere::new(Point3::zeros(), 1.0, material);
+        let emitted_towards = |direction: Vec3<f64>| {
+            let ray = Ray::new(2.0 * direction, -direction, 0.0);
+            sphere.hit(ray, Interval::from_min(0.0)).unwrap().emitted
+        };
+
+        assert_eq!(emitted_towards(Vec3::unit_z()), Color::new(0.25, 0.5, 0.0));
+        assert_eq!(emitted_towards(Vec3::unit_x()), Color::new(0.5, 0.5, 0.0));
+    }
+
+    #[test]
+    fn hollow_sphere_goes_into_a_bvh() {
+        let glass = Arc::new(Dielectric::new(1.5));
+        let ground = Arc::new(Lambertian::new_solid(Color::new(0.8, 0.8, 0.0)));
+        let objects = || -> Vec<Box<dyn Hit>> {
+            vec![
+                Box::new(Sphere::new(
+                    Point3::new(0.0, -100.5, -1.0),
+                    100.0,
+                    ground.clone(),
+                )),
+                Box::new(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, glass.clone())),
+                Box::new(Sphere::new(
+                    Point3::new(0.0, 0.0, -1.0),
+                    -0.4,
+                    glass.clone(),
+                )),
+            ]
+        };
+
+        let inner = Sphere::new(Point3::zeros(), -0.4, glass.clone());
+        let aabb = inner.bounding_box(0.0..1.0).unwrap();
+        assert_eq!(
+            (aabb.min, aabb.max),
+            (Vec3::constant(-0.4), Vec3::constant(0.4))
+        );
+        // the texture is not turned inside out with the normal
+        let ray = Ray::new(2.0 * Vec3::unit_z(), -Vec3::unit_z(), 0.0);
+        let hit = inner.hit(ray, Interval::from_min(0.0)).unwrap();
+        assert!((hit.normal_outward + Vec3::unit_z()).norm() < 1e-12);
+        assert!((hit.u - 0.25).abs() < 1e-12 && (hit.v - 0.5).abs() < 1e-12);
+
+        let mut world = World::new();
+        world.extend(objects());
+        let tracer = |world| RayTracer {
+            world,
+            camera: Camera::builder().aspect_ratio(1.0).build(),
+            background: Color::new(0.7, 0.8, 1.0).into(),
+            max_depth: 8,
+            samples_per_pixel: 4,
+            image_height: 32,
+            render_mode: RenderMode::Shaded,
+            seed: Some(3),
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        };
+        let linear = tracer(world).render();
+        let mut world = World::new();
+        world.add(BVH::new(objects(), 0.0..1.0));
+        let accelerated = tracer(world).render();
+        for y in 0..32 {
+            for x in 0..32 {
+                assert_eq!(accelerated.get(x, y), linear.get(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn center_stays_within_the_motion() {
+        let material = Arc::new(DiffuseLight::new(UvGrid::default()));
+        let (from, to) = (Point3::zeros(), Point3::new(2.0, 0.0, 0.0));
+        let sphere = MovingSphere::new(0.0..1.0, from, to, 0.5, material.clone());
+        assert_eq!(sphere.center(0.25), Point3::new(0.5, 0.0, 0.0));
+        // held at the ends outside the time range, instead of extrapolated
+        assert_eq!(sphere.center(-1.0), from);
+        assert_eq!(sphere.center(3.0), to);
+        for time in [-1.0, 0.5, 3.0, f64::INFINITY] {
+            let ray = Ray::new(Point3::new(1.0, 0.0, 5.0), -Vec3::unit_z(), time);
+            assert!(sphere.center(time).iter().all(|x| x.is_finite()));
+            let _ = sphere.hit(ray, Interval::from_min(0.0));
+        }
+
+        // a shutter that opens and closes at the same instant
+        let frozen = MovingSphere::new(0.5..0.5, from, to, 0.5, material);
+        // before and after the instant too, though the centers differ
+        for time in [0.5, -1.0, 3.0] {
+            assert_eq!(frozen.center(time), from);
+            let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), -Vec3::unit_z(), time);
+            assert_eq!(frozen.hit(ray, Interval::from_min(0.0)).unwrap().t, 4.5);
+        }
+        let aabb = frozen.bounding_box(0.5..0.5).unwrap();
+        assert_eq!(
+            (aabb.min, aabb.max),
+            (Vec3::constant(-0.5), Vec3::constant(0.5))
+        );
+    }
+
+    #[test]
+    fn sphere_follows_an_arc() {
+        let material = Arc::new(DiffuseLight::new(UvGrid::default()));
+        let path = MotionPath::catmull_rom(vec![
+            (0.0, Point3::new(-1.0, 0.0, 0.0)),
+            (0.5, Point3::new(0.0, 1.0, 0.0)),
+            (1.0, Point3::new(1.0, 0.0, 0.0)),
+        ]);
+        let sphere = MovingSphere::with_path(path, 0.25, material);
+
+        let ray = |x: f64, time: f64| Ray::new(Point3::new(x, 0.0, 5.0), -Vec3::unit_z(), time);
+        assert!(sphere.hit_any(&ray(-1.0, 0.0), Interval::from_min(0.0)));
+        assert!(!sphere.hit_any(&ray(-1.0, 0.5), Interval::from_min(0.0)));
+        assert!(sphere.hit_any(&ray(1.0, 1.0), Interval::from_min(0.0)));
+
+        // the top of the arc is inside the box, unlike with a straight line
+        let aabb = sphere.bounding_box(0.0..1.0).unwrap();
+        assert_eq!(aabb.max.y(), 1.25);
+        assert_eq!((aabb.min.x(), aabb.max.x()), (-1.25, 1.25));
+    }
+
+    #[test]
+    fn light_pdf_integrates_to_one() {
+        random::seed(52);
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(4.0)));
+        let sphere = Sphere::new(Point3::new(1.0, 2.0, -1.0), 1.5, light);
+        let origin = Point3::new(0.0, -1.0, 0.0);
+
+        const SAMPLES: usize = 400_000;
+        // from outside the cone, and from inside the whole surface
+        for origin in [origin, Point3::new(1.5, 1.0, -0.5)] {
+            let integral = (0..SAMPLES)
+                .map(|_| sphere.pdf_value(origin, Vec3::random_unit_vector(), 0.0) * 4.0 * PI)
+                .sum::<f64>()
+                / SAMPLES as f64;
+            assert!((integral - 1.0).abs() < 0.03, "{integral}");
+
+            for _ in 0..100 {
+                let direction = sphere.random_point_towards(origin, 0.0).unwrap();
+                let ray = Ray::new(origin, direction, 0.0);
+                assert!(sphere.hit_any(&ray, Interval::from_min(0.0)));
+                assert!(sphere.pdf_value(origin, direction, 0.0) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn inside_samples_follow_the_density() {
+        random::seed(53);
+        let light = Arc::new(DiffuseLight::new_solid(Color::constant(4.0)));
+        let sphere = Sphere::new(Point3::zeros(), 2.0, light);
+        let origin = Point3::new(1.2, 0.0, 0.0);
+
+        // points are uniform over the surface, so the fraction of them with
+        // x > 0.5 is the fraction of the area there, a zone of height 1.5
+        const SAMPLES: usize = 100_000;
+        let inside = (0..SAMPLES)
+            .filter(|_| {
+                let direction = sphere.random_point_towards(origin, 0.0).unwrap();
+                (origin + direction).x() > 0.5
+            })
+            .count() as f64
+            / SAMPLES as f64;
+        assert!((inside - (1.0 - 0.5 / 2.0) / 2.0).abs() < 0.01, "{inside}");
+    }
 
-        let box_from = AABB::new(center_from - offset, center_from + offset);
-        let box_to = AABB::new(center_to - offset, center_to + offset);
+    #[test]
+    fn sphere_tangents_follow_the_texture() {
+        let sphere = Sphere::new(
+            Point3::new(1.0, 2.0, 3.0),
+            2.0,
+            Arc::new(Lambertian::new_solid(Color::WHITE)),
+        );
+        let hit_at = |u: f64, v: f64| {
+            let (phi, theta) = (2.0 * PI * u, PI * v);
+            let direction = Vec3::new(
+                -phi.cos() * theta.sin(),
+                -theta.cos(),
+                phi.sin() * theta.sin(),
+            );
+            let target = Point3::new(1.0, 2.0, 3.0) + direction * 2.0;
+            let ray = Ray::new(target + direction * 5.0, -direction, 0.0);
+            sphere.hit(ray, Interval::from_min(0.0)).unwrap()
+        };
 
-        Some(box_from.merge(&box_to))
+        let hit = hit_at(0.3, 0.6);
+        let (dpdu, dpdv) = hit.tangents.unwrap();
+        let h = 1e-6;
+        let numeric_u = (hit_at(0.3 + h, 0.6).point - hit.point) / h;
+        let numeric_v = (hit_at(0.3, 0.6 + h).point - hit.point) / h;
+        assert!((dpdu - numeric_u).norm() < 1e-4, "{dpdu:?} {numeric_u:?}");
+        assert!((dpdv - numeric_v).norm() < 1e-4, "{dpdv:?} {numeric_v:?}");
     }
 }
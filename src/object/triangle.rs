@@ -0,0 +1,230 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    hit::{OutwardHitRecord, AABB},
+    Hit, Interval, Material, Point3, Ray, Vec3,
+};
+
+/// A triangle between three vertices.
+///
+/// The outward normal is the geometric normal `(b - a) × (c - a)`, so the
+/// front face is the one from which the vertices are counterclockwise.
+/// Texture coordinates are the barycentric weights of `b` and `c`.
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    a: Point3,
+    /// Edge from `a` to `b`
+    edge_ab: Vec3<f64>,
+    /// Edge from `a` to `c`
+    edge_ac: Vec3<f64>,
+    /// Unit normal of the plane of the triangle
+    normal: Vec3<f64>,
+    material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(a: Point3, b: Point3, c: Point3, material: Arc<dyn Material>) -> Self {
+        let edge_ab = b - a;
+        let edge_ac = c - a;
+        Self {
+            a,
+            edge_ab,
+            edge_ac,
+            normal: edge_ab.cross(edge_ac).normalized(),
+            material,
+        }
+    }
+
+    pub fn vertices(&self) -> [Point3; 3] {
+        [self.a, self.a + self.edge_ab, self.a + self.edge_ac]
+    }
+
+    fn intersect(&self, ray: &Ray, t_range: Interval) -> Option<(f64, f64, f64)> {
+        intersect(self.a, self.edge_ab, self.edge_ac, ray, t_range)
+    }
+}
+
+/// Parameter and barycentric coordinates of `b` and `c` where the ray hits the
+/// triangle at `a` with edges `edge_ab` and `edge_ac`, if it does so in `t_range`.
+///
+/// This is the Möller–Trumbore algorithm. Points on the edges are inside.
+pub(super) fn intersect(
+    a: Point3,
+    edge_ab: Vec3<f64>,
+    edge_ac: Vec3<f64>,
+    ray: &Ray,
+    t_range: Interval,
+) -> Option<(f64, f64, f64)> {
+    let direction = ray.direction();
+    let p = direction.cross(edge_ac);
+    let determinant = edge_ab.dot(p);
+    // the ray is parallel to the plane of the triangle, or the triangle
+    // has no area
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+
+    let to_origin = ray.origin() - a;
+    let u = to_origin.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = to_origin.cross(edge_ab);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge_ac.dot(q) * inverse;
+    if !t_range.contains(t) {
+        return None;
+    }
+
+    Some((t, u, v))
+}
+
+impl Hit for Triangle {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let (t, u, v) = self.intersect(&ray, t_range)?;
+        Some(OutwardHitRecord::new(
+            ray.at(t),
+            &ray,
+            self.normal,
+            t,
+            self.material.clone(),
+            (u, v),
+        ))
+    }
+
+    fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+        Some(bounding_box(self.vertices()))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.intersect(ray, t_range).is_some()
+    }
+}
+
+/// Bounding box of the triangle between `vertices`, padded to
+/// [`AABB::MIN_THICKNESS`] along flat axes.
+pub(super) fn bounding_box([a, b, c]: [Point3; 3]) -> AABB {
+    AABB::new(a.min(&b).min(&c), a.max(&b).max(&c)).padded(AABB::MIN_THICKNESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hit::BVH,
+        material::{DiffuseLight, Lambertian},
+        texture::{Texture, UvGrid},
+        Camera, Color, RayTracer, RenderMode, WorkGranularity,
+    };
+
+    fn triangle() -> Triangle {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        Triangle::new(
+            Point3::zeros(),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            material,
+        )
+    }
+
+    #[test]
+    fn hits_inside_and_on_edges() {
+        let triangle = triangle();
+        let down = |x, y| Ray::new(Point3::new(x, y, 1.0), -Vec3::unit_z(), 0.0);
+
+        let hit = triangle
+            .hit(down(0.25, 0.5), Interval::from_min(0.0))
+            .unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert_eq!(hit.point, Point3::new(0.25, 0.5, 0.0));
+        assert_eq!((hit.u, hit.v), (0.25, 0.5));
+        assert!(hit.is_front());
+
+        assert!(triangle.hit_any(&down(0.5, 0.5), Interval::from_min(0.0)));
+        assert!(triangle.hit_any(&down(0.0, 0.0), Interval::from_min(0.0)));
+        assert!(!triangle.hit_any(&down(0.6, 0.6), Interval::from_min(0.0)));
+        assert!(!triangle.hit_any(&down(0.25, 0.5), Interval::new(0.0, 0.5)));
+
+        let parallel = Ray::new(Point3::new(-1.0, 0.25, 0.0), Vec3::unit_x(), 0.0);
+        assert!(triangle.hit(parallel, Interval::UNIVERSE).is_none());
+    }
+
+    #[test]
+    fn renders_the_uv_grid() {
+        // the texture coordinates of this triangle are its x and y
+        let triangle = Triangle::new(
+            Point3::zeros(),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Arc::new(DiffuseLight::new(UvGrid::new(4.0, 0.2))),
+        );
+        let tracer = RayTracer {
+            world: triangle.clone(),
+            camera: Camera::builder()
+                .look_from(0.5, 0.5, 2.0)
+                .look_at(0.5, 0.5, 0.0)
+                .vertical_field_of_view(40.0)
+                .aspect_ratio(1.0)
+                .build(),
+            background: Color::BLACK.into(),
+            max_depth: 1,
+            samples_per_pixel: 1,
+            image_height: 16,
+            render_mode: RenderMode::Shaded,
+            seed: None,
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        };
+        let image = tracer.render();
+
+        // one sample per pixel goes through the center of the pixel
+        let grid = UvGrid::new(4.0, 0.2);
+        let mut hits = 0;
+        for y in 0..16 {
+            for x in 0..16 {
+                let (u, v) = (x as f64 / 15.0, (15 - y) as f64 / 15.0);
+                let ray = tracer.camera.cast(u, v);
+                let expected = match triangle.hit(ray, Interval::from_min(0.0)) {
+                    Some(hit) => {
+                        hits += 1;
+                        grid.color(hit.point, hit.point.x(), hit.point.y())
+                    }
+                    None => Color::BLACK,
+                };
+                assert_eq!(image.get(x, y), expected, "pixel ({x}, {y})");
+            }
+        }
+        assert!(hits > 50, "{hits}");
+        // red grows to the right and green upwards
+        let (left, right) = (image.get(5, 12), image.get(9, 12));
+        assert!(right.r() > left.r() && left.r() > 0.0);
+        let (bottom, top) = (image.get(5, 12), image.get(5, 7));
+        assert!(top.g() > bottom.g() && bottom.g() > 0.0);
+    }
+
+    #[test]
+    fn back_faces_keep_the_geometric_normal() {
+        let triangle = triangle();
+        let up = Ray::new(Point3::new(0.25, 0.25, -1.0), Vec3::unit_z(), 0.0);
+        let hit = triangle.hit(up, Interval::from_min(0.0)).unwrap();
+        assert_eq!(hit.normal_outward, Vec3::unit_z());
+        assert!(!hit.is_front());
+        assert_eq!(hit.into_against_ray().normal_against_ray, -Vec3::unit_z());
+
+        let aabb = triangle.bounding_box(0.0..1.0).unwrap();
+        assert!(aabb.max().z() > aabb.min().z());
+        let bvh = BVH::new(
+            vec![Box::new(triangle.clone()), Box::new(triangle)],
+            0.0..1.0,
+        );
+        let down = Ray::new(Point3::new(0.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(bvh.hit_any(&down, Interval::from_min(0.0)));
+    }
+}
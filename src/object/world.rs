@@ -1,32 +1,198 @@
-use std::ops::Range;
+use std::{fmt, ops::Range};
 
-use crate::{Hit, hit::{AABB, OutwardHitRecord, BVH}, Ray};
+use rand::Rng;
+
+use crate::{
+    hit::{check_objects, BvhError, OutwardHitRecord, AABB, BVH},
+    random, Hit, Interval, Point3, Ray, Vec3,
+};
 
 // Vec<Box<dyn trait>> has an implict 'static lifetime
 // https://stackoverflow.com/questions/70717050/why-do-i-need-static-lifetime-here-and-how-to-fix-it
 // https://users.rust-lang.org/t/box-with-a-trait-object-requires-static-lifetime/35261/2
+/// A list of objects, with optional named groups of objects nested inside.
+///
+/// Groups are worlds themselves, so they can be nested further. Hidden groups
+/// are skipped when the world is hit, and are not part of its bounding box.
+#[derive(Debug)]
+pub struct World {
+    objects: Vec<Box<dyn Hit>>,
+    groups: Vec<Group>,
+}
+
 #[derive(Debug)]
-pub struct World(Vec<Box<dyn Hit>>);
+struct Group {
+    name: String,
+    world: World,
+    visible: bool,
+}
 
 impl World {
     pub fn new() -> Self {
-        Self(Vec::new())
+        Self::from_vec(Vec::new())
     }
 
     pub fn from_vec(hits: Vec<Box<dyn Hit>>) -> Self {
-        Self(hits)
+        Self {
+            objects: hits,
+            groups: Vec::new(),
+        }
     }
 
     pub fn add<T: Hit + 'static>(&mut self, object: T) {
-        self.0.push(Box::new(object));
+        self.objects.push(Box::new(object));
+    }
+
+    /// Add `objects`, which may be another world, see [`World::into_iter`].
+    pub fn extend(&mut self, objects: impl IntoIterator<Item = Box<dyn Hit>>) {
+        self.objects.extend(objects);
+    }
+
+    /// Number of objects directly in the world, groups are not counted.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Whether there are no objects directly in the world, groups are not counted.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// The objects directly in the world, in the order they are added.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Hit> {
+        self.objects.iter().map(|object| object.as_ref())
+    }
+
+    /// Remove and return the object at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Box<dyn Hit> {
+        self.objects.remove(index)
+    }
+
+    /// Keep only the objects directly in the world for which `predicate` is true.
+    pub fn retain(&mut self, mut predicate: impl FnMut(&dyn Hit) -> bool) {
+        self.objects.retain(|object| predicate(object.as_ref()));
+    }
+
+    /// Remove every object and group.
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.groups.clear();
+    }
+
+    /// The group directly inside this world called `name`, which is created
+    /// empty and visible if there is none.
+    pub fn group(&mut self, name: &str) -> &mut World {
+        let index = match self.groups.iter().position(|group| group.name == name) {
+            Some(index) => index,
+            None => {
+                self.insert_group(name, World::new());
+                self.groups.len() - 1
+            }
+        };
+        &mut self.groups[index].world
+    }
+
+    /// Put `world` into this world as the visible group called `name`,
+    /// replacing the group of that name if there is one.
+    pub fn insert_group(&mut self, name: &str, world: World) {
+        self.groups.retain(|group| group.name != name);
+        self.groups.push(Group {
+            name: name.to_string(),
+            world,
+            visible: true,
+        });
+    }
+
+    /// The first group called `name`, searching this world and then its
+    /// groups depth first.
+    pub fn find_group(&self, name: &str) -> Option<&World> {
+        self.find(name).map(|group| &group.world)
+    }
+
+    /// Show or hide every group called `name`, anywhere in the hierarchy.
+    ///
+    /// Returns whether any group is found.
+    pub fn set_group_visible(&mut self, name: &str, visible: bool) -> bool {
+        let mut found = false;
+        for group in &mut self.groups {
+            if group.name == name {
+                group.visible = visible;
+                found = true;
+            }
+            found |= group.world.set_group_visible(name, visible);
+        }
+        found
+    }
+
+    fn find(&self, name: &str) -> Option<&Group> {
+        let direct = self.groups.iter().find(|group| group.name == name);
+        direct.or_else(|| self.groups.iter().find_map(|group| group.world.find(name)))
+    }
+
+    fn visible_groups(&self) -> impl Iterator<Item = &World> {
+        self.groups
+            .iter()
+            .filter(|group| group.visible)
+            .map(|group| &group.world)
+    }
+
+    /// Wrap the whole world in `transform`, e.g. `|world| world.rotate_y(30.0)`,
+    /// to transform a group as one object.
+    ///
+    /// The result is a world with the transformed world as its only object, so
+    /// it can be put back with [`World::insert_group`]. Objects added to it
+    /// later are not transformed.
+    pub fn into_transformed<H, F>(self, transform: F) -> World
+    where
+        H: Hit + 'static,
+        F: FnOnce(World) -> H,
+    {
+        let mut world = World::new();
+        world.add(transform(self));
+        world
     }
 
-    pub fn extend(&mut self, objects: Vec<Box<dyn Hit>>) {
-        self.0.extend(objects);
+    /// All objects of the world and its visible groups, hidden groups are dropped.
+    fn into_objects(self) -> Vec<Box<dyn Hit>> {
+        let mut objects = self.objects;
+        for group in self.groups.into_iter().filter(|group| group.visible) {
+            objects.extend(group.world.into_objects());
+        }
+        objects
     }
 
+    /// Build a BVH of the objects of the world and its visible groups.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world is empty, or an object has no bounding box, see
+    /// [`World::into_accelerated`] for a version that keeps such objects.
     pub fn into_bvh(self, time_range: Range<f64>) -> BVH {
-        BVH::new(self.0, time_range)
+        BVH::new(self.into_objects(), time_range)
+    }
+
+    /// World with a BVH of the objects of the world and its visible groups
+    /// that have a bounding box, and the others in a list beside it.
+    pub fn into_accelerated(self, time_range: Range<f64>) -> World {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = self
+            .into_objects()
+            .into_iter()
+            .partition(|object| check_objects(std::slice::from_ref(object), &time_range).is_ok());
+
+        let mut world = World::from_vec(unbounded);
+        if !bounded.is_empty() {
+            world.add(BVH::new(bounded, time_range));
+        }
+        world
+    }
+
+    /// Number of objects in the world and its visible groups.
+    pub(crate) fn visible_len(&self) -> usize {
+        self.len() + self.visible_groups().map(World::visible_len).sum::<usize>()
     }
 }
 
@@ -36,12 +202,472 @@ impl Default for World {
     }
 }
 
+impl FromIterator<Box<dyn Hit>> for World {
+    fn from_iter<I: IntoIterator<Item = Box<dyn Hit>>>(objects: I) -> Self {
+        Self::from_vec(objects.into_iter().collect())
+    }
+}
+
+/// The objects of the world and its visible groups, hidden groups are dropped.
+impl IntoIterator for World {
+    type Item = Box<dyn Hit>;
+    type IntoIter = std::vec::IntoIter<Box<dyn Hit>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_objects().into_iter()
+    }
+}
+
 impl Hit for World {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        self.0.hit(ray, t_min, t_max)
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        let mut t_range = t_range;
+        let mut closest = self.objects.hit(ray.clone(), t_range);
+        if let Some(hit) = &closest {
+            t_range = t_range.with_max(hit.t);
+        }
+        for world in self.visible_groups() {
+            if let Some(hit) = world.hit(ray.clone(), t_range) {
+                t_range = t_range.with_max(hit.t);
+                closest = Some(hit);
+            }
+        }
+
+        closest
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        let objects = self.objects.bounding_box(time_range.clone());
+        self.visible_groups()
+            .filter_map(|world| world.bounding_box(time_range.clone()))
+            .chain(objects)
+            .reduce(|a, b| a.merge(&b))
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        self.objects.hit_any(ray, t_range)
+            || self
+                .visible_groups()
+                .any(|world| world.hit_any(ray, t_range))
+    }
+
+    /// Average of the densities of the objects and the visible groups, each
+    /// group being sampled like one object.
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        let count = self.objects.len() + self.visible_groups().count();
+        if count == 0 {
+            return 0.0;
+        }
+        let objects = self.objects.pdf_value(origin, direction, time) * self.objects.len() as f64;
+        let groups: f64 = self
+            .visible_groups()
+            .map(|world| world.pdf_value(origin, direction, time))
+            .sum();
+        (objects + groups) / count as f64
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        let count = self.objects.len() + self.visible_groups().count();
+        if count == 0 {
+            return None;
+        }
+        let index = random::with_rng(|rng| rng.gen_range(0..count));
+        match self.objects.get(index) {
+            Some(object) => object.random_point_towards(origin, time),
+            None => self
+                .visible_groups()
+                .nth(index - self.objects.len())
+                .unwrap()
+                .random_point_towards(origin, time),
+        }
+    }
+}
+
+/// Why an [`AcceleratedWorld`] cannot be added to or frozen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldError {
+    /// The world is frozen into a BVH, so it cannot be added to
+    Frozen,
+    /// The BVH of the world cannot be built
+    Bvh(BvhError),
+}
+
+impl fmt::Display for WorldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorldError::Frozen => write!(f, "cannot add to a world frozen into a BVH"),
+            WorldError::Bvh(error) => write!(f, "cannot freeze the world: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for WorldError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorldError::Frozen => None,
+            WorldError::Bvh(error) => Some(error),
+        }
+    }
+}
+
+impl From<BvhError> for WorldError {
+    fn from(error: BvhError) -> Self {
+        WorldError::Bvh(error)
+    }
+}
+
+/// A world that is built up with [`AcceleratedWorld::add`] and then frozen into
+/// a [`BVH`], keeping the same type for both phases.
+///
+/// This lets a `RayTracer<AcceleratedWorld>` be created before the scene is
+/// complete, and render either the linear world or its BVH.
+#[derive(Debug)]
+pub enum AcceleratedWorld {
+    /// World that can still be added to, hit by testing every object
+    Linear(World),
+    /// Frozen world, hit through its BVH
+    Bvh(Box<BVH>),
+}
+
+impl AcceleratedWorld {
+    pub fn new() -> Self {
+        Self::Linear(World::new())
+    }
+
+    /// Add `object` to the world.
+    ///
+    /// Fails if the world is frozen, in which case `object` is dropped.
+    pub fn add<T: Hit + 'static>(&mut self, object: T) -> Result<(), WorldError> {
+        match self {
+            Self::Linear(world) => {
+                world.add(object);
+                Ok(())
+            }
+            Self::Bvh(_) => Err(WorldError::Frozen),
+        }
+    }
+
+    /// Build a BVH of the world, see [`World::into_bvh`]. A frozen world is
+    /// returned as is.
+    pub fn freeze(self, time_range: Range<f64>) -> Result<Self, WorldError> {
+        match self {
+            Self::Linear(world) => {
+                let bvh = BVH::try_new(world.into_objects(), time_range)?;
+                Ok(Self::Bvh(Box::new(bvh)))
+            }
+            frozen => Ok(frozen),
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        matches!(self, Self::Bvh(_))
+    }
+}
+
+impl Default for AcceleratedWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<World> for AcceleratedWorld {
+    fn from(world: World) -> Self {
+        Self::Linear(world)
+    }
+}
+
+impl Hit for AcceleratedWorld {
+    fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+        match self {
+            Self::Linear(world) => world.hit(ray, t_range),
+            Self::Bvh(bvh) => bvh.hit(ray, t_range),
+        }
+    }
+
+    fn bounding_box(&self, time_range: Range<f64>) -> Option<AABB> {
+        match self {
+            Self::Linear(world) => world.bounding_box(time_range),
+            Self::Bvh(bvh) => bvh.bounding_box(time_range),
+        }
+    }
+
+    fn hit_any(&self, ray: &Ray, t_range: Interval) -> bool {
+        match self {
+            Self::Linear(world) => world.hit_any(ray, t_range),
+            Self::Bvh(bvh) => bvh.hit_any(ray, t_range),
+        }
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3<f64>, time: f64) -> f64 {
+        match self {
+            Self::Linear(world) => world.pdf_value(origin, direction, time),
+            Self::Bvh(bvh) => bvh.pdf_value(origin, direction, time),
+        }
+    }
+
+    fn random_point_towards(&self, origin: Point3, time: f64) -> Option<Vec3<f64>> {
+        match self {
+            Self::Linear(world) => world.random_point_towards(origin, time),
+            Self::Bvh(bvh) => bvh.random_point_towards(origin, time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::{Dielectric, Lambertian, Metal},
+        object::{rectangle::AxisAlignedRectangle, Block},
+        random, Color, HitExt, Point3, Sphere, Vec3,
+    };
+
+    fn cornell_box() -> World {
+        let white = Arc::new(Lambertian::new_solid(Color::constant(0.73)));
+        let mut world = World::new();
+        world.add(AxisAlignedRectangle::new_xy(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            white.clone(),
+        ));
+        world.add(AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            0.0,
+            white.clone(),
+        ));
+
+        let furniture = world.group("furniture");
+        furniture.add(Block::new(
+            Point3::new(100.0, 0.0, 100.0),
+            Point3::new(200.0, 300.0, 200.0),
+            white.clone(),
+        ));
+        furniture.group("lamp").add(Block::new(
+            Point3::new(400.0, 0.0, 300.0),
+            Point3::new(450.0, 50.0, 350.0),
+            white,
+        ));
+        world
+    }
+
+    #[test]
+    fn hidden_groups_are_skipped() {
+        let mut world = cornell_box();
+        let towards_block = Ray::new(Point3::new(150.0, 150.0, -100.0), Vec3::unit_z(), 0.0);
+        let towards_lamp = Ray::new(Point3::new(425.0, 25.0, -100.0), Vec3::unit_z(), 0.0);
+        let hit_t = |world: &World, ray: &Ray| {
+            world
+                .hit(ray.clone(), Interval::from_min(1e-3))
+                .map(|hit| hit.t)
+        };
+
+        assert_eq!(hit_t(&world, &towards_block), Some(200.0));
+        assert_eq!(hit_t(&world, &towards_lamp), Some(400.0));
+
+        assert!(world.set_group_visible("furniture", false));
+        assert_eq!(hit_t(&world, &towards_block), Some(655.0));
+        assert_eq!(hit_t(&world, &towards_lamp), Some(655.0));
+        assert!(world.hit_any(&towards_block, Interval::from_min(1e-3)));
+
+        assert!(world.set_group_visible("furniture", true));
+        assert!(world.set_group_visible("lamp", false));
+        assert_eq!(hit_t(&world, &towards_block), Some(200.0));
+        assert_eq!(hit_t(&world, &towards_lamp), Some(655.0));
+        assert!(!world.set_group_visible("sofa", false));
+    }
+
+    #[test]
+    fn group_bounding_boxes_merge() {
+        let mut world = cornell_box();
+        let furniture = world.find_group("furniture").unwrap();
+        let aabb = furniture.bounding_box(0.0..1.0).unwrap();
+        assert_eq!(aabb.min(), Point3::new(100.0, 0.0, 100.0));
+        assert_eq!(aabb.max(), Point3::new(450.0, 300.0, 350.0));
+        let lamp = world.find_group("lamp").unwrap();
+        assert_eq!(lamp.bounding_box(0.0..1.0).unwrap().min().x(), 400.0);
+
+        // lift the furniture through the ceiling, so that it grows the world box
+        let furniture = std::mem::take(world.group("furniture"));
+        let moved = furniture.into_transformed(|world| world.translate(Vec3::unit_y() * 300.0));
+        world.insert_group("furniture", moved);
+        let aabb = world
+            .find_group("furniture")
+            .unwrap()
+            .bounding_box(0.0..1.0)
+            .unwrap();
+        assert_eq!(aabb.max().y(), 600.0);
+
+        assert_eq!(world.bounding_box(0.0..1.0).unwrap().max().y(), 600.0);
+        world.set_group_visible("furniture", false);
+        let aabb = world.bounding_box(0.0..1.0).unwrap();
+        assert_eq!(aabb.max().y(), 555.0);
+        let bvh = world.into_bvh(0.0..1.0).bounding_box(0.0..1.0).unwrap();
+        assert_eq!((bvh.min(), bvh.max()), (aabb.min(), aabb.max()));
     }
 
-    fn bounding_box(&self, time_from: f64, time_to: f64) -> Option<AABB> {
-        self.0.bounding_box(time_from, time_to)
+    #[test]
+    fn frozen_world_hits_the_same() {
+        // the small spheres of the random scene, with its ground and big spheres
+        random::seed(9);
+        let mut world = AcceleratedWorld::new();
+        let ground = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let sphere = Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground);
+        world.add(sphere).unwrap();
+        for a in -11..=11 {
+            for b in -11..=11 {
+                let center = Point3::new(
+                    a as f64 + 0.9 * random::random::<f64>(),
+                    0.2,
+                    b as f64 + 0.9 * random::random::<f64>(),
+                );
+                if random::random::<f64>() < 0.8 {
+                    let material = Arc::new(Lambertian::new_solid(Color::random(0.0..1.0)));
+                    let moved = center + Vec3::unit_y() * random::random::<f64>() * 0.5;
+                    let sphere = Sphere::new(center, 0.2, material).into_moving(0.0..1.0, moved);
+                    world.add(sphere).unwrap();
+                } else {
+                    let sphere = Sphere::new(center, 0.2, Arc::new(Dielectric::new(1.5)));
+                    world.add(sphere).unwrap();
+                }
+            }
+        }
+        let metal = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+        world
+            .add(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, metal))
+            .unwrap();
+
+        let look_from = Point3::new(13.0, 2.0, 3.0);
+        let rays: Vec<_> = (0..400)
+            .map(|i| {
+                let target = Point3::new((i % 20) as f64 - 10.0, 0.0, (i / 20) as f64 - 10.0);
+                Ray::new(look_from, target - look_from, 0.5)
+            })
+            .collect();
+        let hits = |world: &AcceleratedWorld| -> Vec<_> {
+            rays.iter()
+                .map(|ray| {
+                    let hit = world.hit(ray.clone(), Interval::from_min(1e-3));
+                    hit.map(|hit| (hit.t, hit.point))
+                })
+                .collect()
+        };
+
+        let linear = hits(&world);
+        let mut world = world.freeze(0.0..1.0).unwrap();
+        assert!(world.is_frozen());
+        assert_eq!(hits(&world), linear);
+
+        let glass = Arc::new(Dielectric::new(1.5));
+        assert_eq!(
+            world.add(Sphere::new(Point3::zeros(), 1.0, glass)),
+            Err(WorldError::Frozen)
+        );
+        assert_eq!(
+            AcceleratedWorld::new().freeze(0.0..1.0).unwrap_err(),
+            WorldError::Bvh(BvhError::Empty)
+        );
+    }
+
+    #[test]
+    fn extended_worlds_hit_like_one_list() {
+        let spheres = || -> Vec<Box<dyn Hit>> {
+            let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+            (0..8)
+                .map(|i| {
+                    let center = Point3::new(f64::from(i) * 1.5, f64::from(i % 3), 0.0);
+                    Sphere::new(center, 0.5 + 0.1 * f64::from(i), material.clone()).boxed()
+                })
+                .collect()
+        };
+        let manual = World::from_vec(spheres());
+
+        let mut second: World = spheres().into_iter().skip(4).collect();
+        let hidden = second.group("hidden");
+        hidden.add(Sphere::new(
+            Point3::new(5.0, 0.0, -3.0),
+            1.0,
+            Arc::new(Metal::new(Color::WHITE, 0.0)),
+        ));
+        second.set_group_visible("hidden", false);
+        let mut world: World = spheres().into_iter().take(4).collect();
+        world.extend(second);
+        assert_eq!(world.len(), manual.len());
+
+        let origin = Point3::new(5.0, 1.0, -10.0);
+        for i in 0..400 {
+            let target = Point3::new(f64::from(i % 20) - 4.0, f64::from(i / 20) * 0.25 - 1.5, 0.0);
+            let ray = Ray::new(origin, target - origin, 0.0);
+            let hit = |world: &World| {
+                let hit = world.hit(ray.clone(), Interval::from_min(1e-3));
+                hit.map(|hit| (hit.t, hit.point))
+            };
+            assert_eq!(hit(&world), hit(&manual));
+        }
+
+        // remove the spheres at y = 0 one way or another
+        world.remove(0);
+        world.retain(|object| object.bounding_box(0.0..1.0).unwrap().min.y() > -0.5);
+        assert_eq!(world.len(), 5);
+        assert!(world.iter().all(|object| {
+            let aabb = object.bounding_box(0.0..1.0).unwrap();
+            aabb.min.y() + aabb.max.y() > 0.0
+        }));
+        world.clear();
+        assert!(world.is_empty());
+        assert!(world.bounding_box(0.0..1.0).is_none());
+    }
+
+    /// The ground plane `y = 0`, which has no bounding box.
+    #[derive(Debug)]
+    struct Ground(Arc<Lambertian<crate::texture::SolidColor>>);
+
+    impl Hit for Ground {
+        fn hit(&self, ray: Ray, t_range: Interval) -> Option<OutwardHitRecord> {
+            let t = -ray.origin().y() / ray.direction().y();
+            t_range.contains(t).then(|| {
+                let point = ray.at(t);
+                OutwardHitRecord::new(point, &ray, Vec3::unit_y(), t, self.0.clone(), (0.0, 0.0))
+            })
+        }
+
+        fn bounding_box(&self, _time_range: Range<f64>) -> Option<AABB> {
+            None
+        }
+    }
+
+    #[test]
+    fn unbounded_objects_stay_beside_the_bvh() {
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let build = || {
+            let mut world = World::new();
+            world.add(Ground(material.clone()));
+            for i in 0..10 {
+                let center = Point3::new(f64::from(i) - 5.0, 1.0, 0.0);
+                world
+                    .group("spheres")
+                    .add(Sphere::new(center, 0.4, material.clone()));
+            }
+            world
+        };
+        assert_eq!(build().visible_len(), 11);
+
+        let accelerated = build().into_accelerated(0.0..1.0);
+        // the ground and the BVH
+        assert_eq!(accelerated.visible_len(), 2);
+
+        let world = build();
+        let origin = Point3::new(0.0, 3.0, -10.0);
+        for i in 0..200 {
+            let target = Point3::new(f64::from(i % 20) * 0.5 - 5.0, f64::from(i / 20) * 0.3, 0.0);
+            let ray = Ray::new(origin, target - origin, 0.0);
+            let hit = |world: &World| {
+                let hit = world.hit(ray.clone(), Interval::from_min(1e-3));
+                hit.map(|hit| (hit.t, hit.point))
+            };
+            assert_eq!(hit(&accelerated), hit(&world));
+        }
     }
 }
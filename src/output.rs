@@ -0,0 +1,268 @@
+use std::{
+    fmt,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::Color;
+
+/// File format of an [`OutputSpec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Plain text PPM, 8 bits per channel
+    Ppm,
+    /// PNG, 8 bits per channel
+    Png,
+    /// OpenEXR, 32-bit float per channel and not clamped
+    Exr,
+}
+
+impl OutputFormat {
+    /// Guess the format from the extension of `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            "exr" => Some(Self::Exr),
+            _ => None,
+        }
+    }
+
+    /// Whether the format holds values above 1.0.
+    pub fn is_hdr(self) -> bool {
+        self == Self::Exr
+    }
+}
+
+/// Tone mapping from the linear colors of the tracer to displayable ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMap {
+    /// Keep the colors as they are, 8-bit formats clip them at 1.0
+    #[default]
+    None,
+    /// `x / (1 + x)` on each channel
+    Reinhard,
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+impl ToneMap {
+    pub fn apply(self, color: Color) -> Color {
+        match self {
+            Self::None => color,
+            Self::Reinhard => color.apply(|x| x / (1.0 + x)),
+            Self::Aces => color.apply(|x| {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                ((x * (a * x + b)) / (x * (c * x + d) + e)).clamp(0.0, 1.0)
+            }),
+        }
+    }
+}
+
+impl FromStr for ToneMap {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "reinhard" => Ok(Self::Reinhard),
+            "aces" => Ok(Self::Aces),
+            _ => Err(format!("unknown tone map {}", name)),
+        }
+    }
+}
+
+/// One file to write from a render, see [`crate::RenderBuffer::write_all`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputSpec {
+    pub format: OutputFormat,
+    pub path: PathBuf,
+    pub tone_map: ToneMap,
+    /// Gamma correct the colors with gamma 2, as the PPM output always did
+    pub gamma: bool,
+}
+
+impl OutputSpec {
+    /// Output to `path` in `format`, gamma corrected unless the format is HDR.
+    pub fn new<P: Into<PathBuf>>(format: OutputFormat, path: P) -> Self {
+        Self {
+            format,
+            path: path.into(),
+            tone_map: ToneMap::None,
+            gamma: !format.is_hdr(),
+        }
+    }
+
+    /// Output to `path`, in the format given by its extension.
+    pub fn from_path<P: Into<PathBuf>>(path: P) -> Result<Self, String> {
+        let path = path.into();
+        let format = OutputFormat::from_path(&path)
+            .ok_or_else(|| format!("unknown image format of {}", path.display()))?;
+        Ok(Self::new(format, path))
+    }
+
+    pub fn with_tone_map(mut self, tone_map: ToneMap) -> Self {
+        self.tone_map = tone_map;
+        self
+    }
+
+    pub fn with_gamma(mut self, gamma: bool) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Tone mapping and gamma of the output.
+    pub fn settings(&self) -> OutputSettings {
+        OutputSettings {
+            tone_map: self.tone_map,
+            gamma: self.gamma,
+        }
+    }
+
+    /// The color written for `color`, before quantization.
+    pub fn map(&self, color: Color) -> Color {
+        self.settings().map(color)
+    }
+
+    /// The 8-bit channels written for `color`.
+    pub fn map_rgb8(&self, color: Color) -> [u8; 3] {
+        self.settings().map_rgb8(color)
+    }
+}
+
+/// How the linear colors of the tracer are made displayable, for outputs
+/// that are not files, see [`crate::RenderBuffer::write_rgba8_into`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputSettings {
+    pub tone_map: ToneMap,
+    /// Gamma correct the colors with gamma 2
+    pub gamma: bool,
+}
+
+impl Default for OutputSettings {
+    /// No tone mapping and gamma 2, like [`Color::to_rgb8`].
+    fn default() -> Self {
+        Self {
+            tone_map: ToneMap::None,
+            gamma: true,
+        }
+    }
+}
+
+impl OutputSettings {
+    /// The displayable color for `color`, before quantization.
+    pub fn map(&self, color: Color) -> Color {
+        let color = self.tone_map.apply(color);
+        if self.gamma {
+            color.sqrt()
+        } else {
+            color
+        }
+    }
+
+    /// The 8-bit channels for `color`.
+    pub fn map_rgb8(&self, color: Color) -> [u8; 3] {
+        let color = (self.map(color).clamp(0.0, 0.999) * 255.0).round();
+        [color[0] as u8, color[1] as u8, color[2] as u8]
+    }
+}
+
+/// A destination slice whose length does not fit the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferSizeError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for BufferSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "destination has {} elements, but the image needs {}",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for BufferSizeError {}
+
+/// Failure to write one of the outputs of [`crate::RenderBuffer::write_all`].
+#[derive(Debug)]
+pub struct OutputError {
+    pub path: PathBuf,
+    pub error: image::ImageError,
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to write {}: {}", self.path.display(), self.error)
+    }
+}
+
+impl std::error::Error for OutputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// 8-bit image for display, encoded from the linear colors of a
+/// [`crate::RenderBuffer`] by [`crate::RenderBuffer::encode`].
+///
+/// Tone mapping and gamma correction only ever produce an `EncodedImage`, the
+/// buffer it is encoded from keeps the untouched linear radiance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedImage {
+    width: u64,
+    height: u64,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl EncodedImage {
+    pub(crate) fn new(width: u64, height: u64, pixels: Vec<[u8; 3]>) -> Self {
+        assert_eq!(pixels.len() as u64, width * height, "pixel count mismatch");
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u64 {
+        self.width
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Pixels row by row, starting from the top-left corner.
+    pub fn pixels(&self) -> &[[u8; 3]] {
+        &self.pixels
+    }
+
+    pub fn get(&self, x: u64, y: u64) -> [u8; 3] {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Write the image as a plain text PPM image.
+    pub fn write_ppm<T: Write>(&self, buffer: &mut T) -> std::io::Result<()> {
+        writeln!(buffer, "P3")?;
+        writeln!(buffer, "{} {}", self.width, self.height)?;
+        writeln!(buffer, "{}", crate::COLOR_MAX)?;
+
+        for [r, g, b] in &self.pixels {
+            writeln!(buffer, "{} {} {}", r, g, b)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn to_rgb_image(&self) -> image::RgbImage {
+        image::RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            image::Rgb(self.get(x as u64, y as u64))
+        })
+    }
+}
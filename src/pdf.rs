@@ -0,0 +1,185 @@
+//! Probability densities of directions, used to importance sample the rays
+//! scattered by materials.
+//!
+//! A density is over solid angle: integrated over all directions it gives
+//! one. A Monte Carlo estimate divides each sample by the density of its
+//! direction, so any density which is nonzero wherever the integrand is gives
+//! the same expected value, but those shaped like the integrand give less
+//! noise.
+
+use std::{f64::consts::PI, fmt::Debug};
+
+use crate::{random, Hit, Onb, Point3, Vec3};
+
+/// Density of directions which can be sampled.
+pub trait Pdf: Debug {
+    /// Density of `direction`, which needs not be a unit vector.
+    fn value(&self, direction: Vec3<f64>) -> f64;
+
+    /// Random direction distributed following the density.
+    fn generate(&self) -> Vec3<f64>;
+}
+
+/// Directions around a normal with density proportional to the cosine of
+/// their angle with it, and none below the surface.
+///
+/// This is the distribution of light scattered by a
+/// [`crate::material::Lambertian`] surface.
+#[derive(Debug, Clone, Copy)]
+pub struct CosinePdf {
+    basis: Onb,
+}
+
+impl CosinePdf {
+    /// # Panics
+    ///
+    /// Panics if `normal` is zero.
+    pub fn new(normal: Vec3<f64>) -> Self {
+        Self {
+            basis: Onb::from_w(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3<f64>) -> f64 {
+        let cosine = direction.normalized().dot(self.basis.w());
+        cosine.max(0.0) / PI
+    }
+
+    fn generate(&self) -> Vec3<f64> {
+        self.basis.local(Vec3::random_cosine_direction())
+    }
+}
+
+impl<P: Pdf + ?Sized> Pdf for &P {
+    fn value(&self, direction: Vec3<f64>) -> f64 {
+        (**self).value(direction)
+    }
+
+    fn generate(&self) -> Vec3<f64> {
+        (**self).generate()
+    }
+}
+
+/// Directions from `origin` towards the points of an object at `time`, such
+/// as the lights of the scene, see [`Hit::random_point_towards`].
+///
+/// The object must be able to be sampled from `origin`, otherwise
+/// [`Pdf::generate`] panics.
+#[derive(Debug)]
+pub struct HitPdf<'a, H: Hit + ?Sized> {
+    object: &'a H,
+    origin: Point3,
+    time: f64,
+}
+
+impl<'a, H: Hit + ?Sized> HitPdf<'a, H> {
+    pub fn new(object: &'a H, origin: Point3, time: f64) -> Self {
+        Self {
+            object,
+            origin,
+            time,
+        }
+    }
+}
+
+impl<H: Hit + ?Sized> Pdf for HitPdf<'_, H> {
+    fn value(&self, direction: Vec3<f64>) -> f64 {
+        self.object.pdf_value(self.origin, direction, self.time)
+    }
+
+    fn generate(&self) -> Vec3<f64> {
+        self.object
+            .random_point_towards(self.origin, self.time)
+            .expect("object can not be sampled from the origin")
+    }
+}
+
+/// Even mixture of two densities, which samples either with the same
+/// probability.
+///
+/// Mixing the density of a material with one towards the lights finds the
+/// lights more often, while still sampling every direction the material
+/// scatters to.
+#[derive(Debug)]
+pub struct MixturePdf<A: Pdf, B: Pdf> {
+    first: A,
+    second: B,
+}
+
+impl<A: Pdf, B: Pdf> MixturePdf<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: Pdf, B: Pdf> Pdf for MixturePdf<A, B> {
+    fn value(&self, direction: Vec3<f64>) -> f64 {
+        0.5 * self.first.value(direction) + 0.5 * self.second.value(direction)
+    }
+
+    fn generate(&self) -> Vec3<f64> {
+        if random::random::<f64>() < 0.5 {
+            self.first.generate()
+        } else {
+            self.second.generate()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random;
+
+    #[test]
+    fn cosine_pdf_passes_chi_square() {
+        random::seed(41);
+        let normal = Vec3::new(1.0, -2.0, 0.5);
+        let pdf = CosinePdf::new(normal);
+        let basis = Onb::from_w(normal);
+
+        // for cosine-weighted directions, the squared cosine and the azimuth
+        // are both uniform, so the bins have equal probabilities
+        const BINS: usize = 10;
+        const SAMPLES: usize = 100_000;
+        let mut counts = [[0usize; BINS]; BINS];
+        for _ in 0..SAMPLES {
+            let direction = pdf.generate();
+            assert!((direction.norm() - 1.0).abs() < 1e-12);
+            let local = basis.to_local(direction);
+            assert!(local.z() >= 0.0);
+
+            let cosine_squared = local.z() * local.z();
+            let azimuth = local.y().atan2(local.x()) / (2.0 * PI) + 0.5;
+            let bin = |x: f64| ((x * BINS as f64) as usize).min(BINS - 1);
+            counts[bin(cosine_squared)][bin(azimuth)] += 1;
+        }
+
+        let expected = SAMPLES as f64 / (BINS * BINS) as f64;
+        let chi_square: f64 = counts
+            .iter()
+            .flatten()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum();
+        // the critical value for 99 degrees of freedom at p = 0.001
+        assert!(chi_square < 148.2, "{chi_square}");
+    }
+
+    #[test]
+    fn cosine_pdf_integrates_to_one() {
+        random::seed(42);
+        let pdf = CosinePdf::new(Vec3::new(0.0, 0.0, -3.0));
+        assert_eq!(pdf.value(Vec3::unit_z()), 0.0);
+        assert!((pdf.value(-Vec3::unit_z()) - 1.0 / PI).abs() < 1e-12);
+
+        // uniform directions over the sphere have the density 1 / 4 pi
+        const SAMPLES: usize = 200_000;
+        let integral = (0..SAMPLES)
+            .map(|_| pdf.value(Vec3::random_unit_vector()) * 4.0 * PI)
+            .sum::<f64>()
+            / SAMPLES as f64;
+        assert!((integral - 1.0).abs() < 0.01, "{integral}");
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    cell::Cell,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+thread_local! {
+    /// Number of rays traced by the current thread.
+    static RAYS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Count one traced ray on the current thread.
+pub(crate) fn count_ray() {
+    RAYS.with(|rays| rays.set(rays.get() + 1));
+}
+
+/// Number of rays traced by the current thread so far.
+///
+/// The counter never resets, so take the difference of two readings to count
+/// the rays traced in between.
+pub fn thread_ray_count() -> u64 {
+    RAYS.with(|rays| rays.get())
+}
+
+/// State of a render in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressSnapshot {
+    /// Number of samples done
+    pub done: u64,
+    /// Number of samples in the whole image
+    pub total: u64,
+    /// Time since the render started
+    pub elapsed: Duration,
+    /// Number of rays traced so far, including scattered rays
+    pub rays: u64,
+}
+
+impl ProgressSnapshot {
+    /// Average number of samples per second, or zero before any time passed.
+    pub fn samples_per_second(&self) -> f64 {
+        per_second(self.done, self.elapsed)
+    }
+
+    /// Average number of rays per second, or zero before any time passed.
+    pub fn rays_per_second(&self) -> f64 {
+        per_second(self.rays, self.elapsed)
+    }
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        0.0
+    } else {
+        count as f64 / elapsed.as_secs_f64()
+    }
+}
+
+/// Receiver of progress updates of a render.
+///
+/// Updates are delivered in order, but possibly from different threads.
+pub trait ProgressSink: Sync {
+    fn on_progress(&self, snapshot: ProgressSnapshot);
+}
+
+impl ProgressSink for () {
+    fn on_progress(&self, _snapshot: ProgressSnapshot) {}
+}
+
+/// Estimate of the remaining time of a render, based on an exponential moving
+/// average of the sampling rate.
+#[derive(Debug, Clone)]
+pub struct EtaEstimator {
+    /// Weight of the newest rate in the average, in `(0, 1]`
+    smoothing: f64,
+    /// Smoothed samples per second
+    rate: Option<f64>,
+    /// Last snapshot used to update the estimate
+    last: Option<ProgressSnapshot>,
+}
+
+impl EtaEstimator {
+    pub fn new(smoothing: f64) -> Self {
+        assert!(
+            smoothing > 0.0 && smoothing <= 1.0,
+            "smoothing must be in (0, 1]"
+        );
+        Self {
+            smoothing,
+            rate: None,
+            last: None,
+        }
+    }
+
+    /// Feed a new snapshot and return the estimated remaining time, or `None`
+    /// if there is no rate to extrapolate from yet.
+    ///
+    /// Snapshots that are not newer than the previous one are ignored.
+    pub fn update(&mut self, snapshot: ProgressSnapshot) -> Option<Duration> {
+        let (last_done, last_elapsed) = self
+            .last
+            .map_or((0, Duration::ZERO), |last| (last.done, last.elapsed));
+
+        if snapshot.elapsed > last_elapsed && snapshot.done >= last_done {
+            let seconds = (snapshot.elapsed - last_elapsed).as_secs_f64();
+            let rate = (snapshot.done - last_done) as f64 / seconds;
+            self.rate = Some(match self.rate {
+                Some(average) => self.smoothing * rate + (1.0 - self.smoothing) * average,
+                None => rate,
+            });
+            self.last = Some(snapshot);
+        }
+
+        self.eta()
+    }
+
+    /// The current estimate of the remaining time.
+    pub fn eta(&self) -> Option<Duration> {
+        let last = self.last?;
+        let rate = self.rate.filter(|&rate| rate > 0.0)?;
+        let remaining = last.total.saturating_sub(last.done) as f64;
+        Some(Duration::from_secs_f64(remaining / rate))
+    }
+}
+
+impl Default for EtaEstimator {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+/// Format a number with an SI suffix, e.g. `1.50M`.
+fn si(value: f64) -> String {
+    let (value, suffix) = match value {
+        v if v >= 1e9 => (v / 1e9, "G"),
+        v if v >= 1e6 => (v / 1e6, "M"),
+        v if v >= 1e3 => (v / 1e3, "k"),
+        v => (v, ""),
+    };
+    format!("{:.2}{}", value, suffix)
+}
+
+/// A [`ProgressSink`] showing the progress on an [`indicatif`] bar, with the
+/// throughput, the elapsed time and the estimated remaining time as message.
+#[derive(Debug)]
+pub struct ProgressBarSink {
+    bar: ProgressBar,
+    eta: Mutex<EtaEstimator>,
+}
+
+impl ProgressBarSink {
+    pub fn new() -> Self {
+        let bar = ProgressBar::new(0);
+        if let Ok(style) = ProgressStyle::with_template("{wide_bar} {percent}% {msg}") {
+            bar.set_style(style);
+        }
+        Self {
+            bar,
+            eta: Mutex::new(EtaEstimator::default()),
+        }
+    }
+}
+
+impl Default for ProgressBarSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressSink for ProgressBarSink {
+    fn on_progress(&self, snapshot: ProgressSnapshot) {
+        let eta = self.eta.lock().unwrap().update(snapshot);
+        let eta = eta.map_or_else(|| "?".to_string(), |eta| format!("{}s", eta.as_secs()));
+
+        self.bar.set_length(snapshot.total);
+        self.bar.set_position(snapshot.done);
+        self.bar.set_message(format!(
+            "{} samples/s, {} rays/s, elapsed {}s, ETA {}",
+            si(snapshot.samples_per_second()),
+            si(snapshot.rays_per_second()),
+            snapshot.elapsed.as_secs(),
+            eta,
+        ));
+        if snapshot.done >= snapshot.total {
+            self.bar.finish();
+        }
+    }
+}
+
+/// Collects progress from the worker threads of a render and forwards it to a
+/// [`ProgressSink`].
+pub(crate) struct ProgressTracker<'a, P: ProgressSink> {
+    sink: &'a P,
+    start: Instant,
+    total: u64,
+    /// Samples done and rays traced
+    state: Mutex<(u64, u64)>,
+}
+
+impl<'a, P: ProgressSink> ProgressTracker<'a, P> {
+    pub(crate) fn new(sink: &'a P, total: u64) -> Self {
+        Self {
+            sink,
+            start: Instant::now(),
+            total,
+            state: Mutex::new((0, 0)),
+        }
+    }
+
+    /// Record that `samples` more samples are done, which took `rays` rays.
+    pub(crate) fn advance(&self, samples: u64, rays: u64) {
+        // hold the lock while reporting, so that snapshots arrive in order
+        let mut state = self.state.lock().unwrap();
+        state.0 += samples;
+        state.1 += rays;
+        self.sink.on_progress(ProgressSnapshot {
+            done: state.0,
+            total: self.total,
+            elapsed: self.start.elapsed(),
+            rays: state.1,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eta_decreases_for_constant_rate() {
+        let mut estimator = EtaEstimator::new(0.3);
+        let mut last_eta = Duration::MAX;
+
+        for second in 1..100 {
+            let snapshot = ProgressSnapshot {
+                done: second * 10,
+                total: 1000,
+                elapsed: Duration::from_secs(second),
+                rays: second * 50,
+            };
+            let eta = estimator.update(snapshot).unwrap();
+            assert!(eta < last_eta);
+            last_eta = eta;
+        }
+
+        assert_eq!(last_eta, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn eta_ignores_stale_snapshots() {
+        let mut estimator = EtaEstimator::default();
+        let snapshot = |done, elapsed| ProgressSnapshot {
+            done,
+            total: 100,
+            elapsed: Duration::from_secs(elapsed),
+            rays: 0,
+        };
+
+        estimator.update(snapshot(10, 1));
+        let eta = estimator.update(snapshot(20, 2));
+        assert_eq!(estimator.update(snapshot(15, 2)), eta);
+    }
+
+    #[test]
+    fn rates_start_at_zero() {
+        let snapshot = ProgressSnapshot {
+            done: 10,
+            total: 100,
+            elapsed: Duration::ZERO,
+            rays: 40,
+        };
+        assert_eq!(snapshot.samples_per_second(), 0.0);
+        assert_eq!(snapshot.rays_per_second(), 0.0);
+
+        let snapshot = ProgressSnapshot {
+            elapsed: Duration::from_millis(500),
+            ..snapshot
+        };
+        assert_eq!(snapshot.samples_per_second(), 20.0);
+        assert_eq!(snapshot.rays_per_second(), 80.0);
+    }
+}
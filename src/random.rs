@@ -0,0 +1,41 @@
+//! Random number generation shared by the ray tracer.
+//!
+//! Every thread owns a generator seeded from system entropy, like
+//! [`rand::thread_rng`]. Unlike the thread generator, it can be reseeded, which
+//! the [`RayTracer`](crate::RayTracer) does before each pixel when a seed is set
+//! so that a pixel's samples do not depend on how the work was scheduled.
+
+use std::cell::RefCell;
+
+use rand::{
+    distributions::{Distribution, Standard},
+    rngs::StdRng,
+    SeedableRng,
+};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Reseed the generator of the current thread.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Run `f` with the generator of the current thread.
+///
+/// # Panics
+///
+/// Panics if called again from inside `f`.
+pub fn with_rng<R, F: FnOnce(&mut StdRng) -> R>(f: F) -> R {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+/// Generate a random value using the [`Standard`] distribution, e.g. a float
+/// in `[0, 1)`.
+pub fn random<T>() -> T
+where
+    Standard: Distribution<T>,
+{
+    with_rng(|rng| Standard.sample(rng))
+}
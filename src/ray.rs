@@ -1,6 +1,7 @@
 use crate::{
     hit::{Hit, OutwardHitRecord},
     vec3::{Point3, Vec3},
+    Interval,
 };
 
 #[derive(Debug, Clone)]
@@ -8,12 +9,46 @@ pub struct Ray {
     origin: Point3,
     direction: Vec3<f64>,
     time: f64,
+    /// `1 / direction` for each component, infinite for a zero component
+    inverse_direction: Vec3<f64>,
+    /// 1 for an axis where the inverse direction is negative, including -0.0, otherwise 0
+    sign: [usize; 3],
+    /// Wavelength in nanometers, once a dispersive material has picked one
+    wavelength: Option<f64>,
 }
 
 impl Ray {
     pub fn new(origin: Point3, direction: Vec3<f64>, time: f64) -> Self {
         assert_ne!(direction.len_squared(), 0.0);
-        Self { origin, direction, time }
+        let inverse_direction = Vec3::new(
+            1.0 / direction.x(),
+            1.0 / direction.y(),
+            1.0 / direction.z(),
+        );
+        let sign = [0, 1, 2].map(|axis| usize::from(inverse_direction[axis] < 0.0));
+        Self {
+            origin,
+            direction,
+            time,
+            inverse_direction,
+            sign,
+            wavelength: None,
+        }
+    }
+
+    /// Same ray carrying light of a single `wavelength` in nanometers, see
+    /// [`crate::material::Dielectric::with_dispersion`].
+    pub fn with_wavelength(self, wavelength: f64) -> Self {
+        Self {
+            wavelength: Some(wavelength),
+            ..self
+        }
+    }
+
+    /// Wavelength of the light along the ray in nanometers, or `None` for
+    /// white light.
+    pub fn wavelength(&self) -> Option<f64> {
+        self.wavelength
     }
 
     pub fn origin(&self) -> Point3 {
@@ -28,6 +63,18 @@ impl Ray {
         self.time
     }
 
+    /// Inverse of each direction component, computed when the ray is created
+    /// for the slab test of bounding boxes.
+    pub fn inverse_direction(&self) -> Vec3<f64> {
+        self.inverse_direction
+    }
+
+    /// For each axis, 1 if the ray goes in the negative direction, including
+    /// a direction component of -0.0, and 0 otherwise.
+    pub fn sign(&self) -> [usize; 3] {
+        self.sign
+    }
+
     /// Return a point along the ray at `t`.
     /// Computed by (origin + t * direction)
     pub fn at(&self, t: f64) -> Point3 {
@@ -37,12 +84,15 @@ impl Ray {
     /// Move the ray origin by the given offset.
     /// This is useful for translating the ray into the object's local space.
     pub fn move_origin_by(self, offset: Vec3<f64>) -> Self {
-        Self::new(self.origin + offset, self.direction, self.time)
+        Self {
+            origin: self.origin + offset,
+            ..self
+        }
     }
 }
 
 impl Ray {
-    pub fn hit<T: Hit>(self, hittable: &T, t_min: f64, t_max: f64) -> Option<OutwardHitRecord> {
-        hittable.hit(self, t_min, t_max)
+    pub fn hit<T: Hit>(self, hittable: &T, t_range: Interval) -> Option<OutwardHitRecord> {
+        hittable.hit(self, t_range)
     }
 }
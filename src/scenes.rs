@@ -0,0 +1,904 @@
+//! The demo scenes of the book series, ready to render.
+//!
+//! Each scene comes with the camera placement, background and image settings it
+//! is meant to be rendered with, and [`Scene::build_tracer`] turns it into a
+//! [`RayTracer`].
+
+use std::{ops::Range, sync::Arc};
+
+use rand::Rng;
+
+use crate::{
+    background::{Background, EnvironmentMap},
+    camera::CameraBuilder,
+    hit::{rotation::RotateAnimated, ConstantMedium, NonUniformMedium, Transform, BVH},
+    material::{CheckerMaterial, Dielectric, DiffuseLight, Lambertian, Metal, NormalMap},
+    object::{rectangle::AxisAlignedRectangle, sphere::MovingSphere, Block, Cone, Quad, Triangle},
+    random,
+    texture::{Checker, CheckerPattern, Image, Noise, SolidColor, Texture},
+    Color, Hit, HitExt, Point3, RayTracer, RenderMode, Sphere, Vec3, WorkGranularity, World,
+};
+
+const SAMPLES_PER_PIXEL: u64 = 100;
+const SKY: Color = Color::new(0.7, 0.8, 1.0);
+const IMAGE_WIDTH: u64 = 400;
+const ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+/// A world with the camera and image settings to render it with.
+pub struct Scene {
+    pub world: World,
+    pub background: Arc<dyn Background>,
+    pub camera_builder: CameraBuilder,
+    pub samples_per_pixel: u64,
+    pub image_width: u64,
+    pub aspect_ratio: f64,
+    /// Objects to sample light from, see [`RayTracer::lights`]
+    pub lights: Option<Arc<dyn Hit>>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            world: Default::default(),
+            background: SKY.into(),
+            camera_builder: Default::default(),
+            samples_per_pixel: SAMPLES_PER_PIXEL,
+            image_width: IMAGE_WIDTH,
+            aspect_ratio: ASPECT_RATIO,
+            lights: None,
+        }
+    }
+}
+
+impl Scene {
+    /// Ray tracer for the scene, with the camera built for its aspect ratio and
+    /// the image height following from its width.
+    pub fn build_tracer(self, max_depth: i64) -> RayTracer<World> {
+        let image_height = (self.image_width as f64 / self.aspect_ratio) as u64;
+        let camera = self
+            .camera_builder
+            .view_up(0.0, 1.0, 0.0)
+            .aspect_ratio(self.aspect_ratio)
+            .focus_distance(10.0)
+            .build();
+
+        RayTracer {
+            world: self.world,
+            camera,
+            background: self.background,
+            max_depth,
+            samples_per_pixel: self.samples_per_pixel,
+            image_height,
+            render_mode: RenderMode::Shaded,
+            seed: None,
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: self.lights,
+        }
+    }
+}
+
+/// Random number in `range` from the generator of the current thread.
+fn gen_range(range: Range<f64>) -> f64 {
+    random::with_rng(|rng| rng.gen_range(range))
+}
+
+/// Small random spheres around three large ones, the cover of the first book,
+/// with two cones between the large spheres.
+///
+/// With a `seed`, the generator of the current thread is reseeded first, so the
+/// scene is the same every time.
+pub fn random_scene(seed: Option<u64>) -> Scene {
+    if let Some(seed) = seed {
+        random::seed(seed);
+    }
+    let mut world = World::new();
+
+    let ground_mat = Arc::new(Lambertian::new(Checker::new_solids(
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    )));
+    let ground_sphere = Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground_mat);
+
+    world.add(ground_sphere);
+
+    for a in -11..=11 {
+        for b in -11..=11 {
+            let choose_mat: f64 = random::random();
+            let center = Point3::new(
+                (a as f64) + gen_range(0.0..0.9),
+                0.2,
+                (b as f64) + gen_range(0.0..0.9),
+            );
+            let new_center = center + Vec3::new(0.0, gen_range(0.0..0.5), 0.0);
+
+            if choose_mat < 0.8 {
+                // Diffuse
+                let albedo = SolidColor::new(Color::random(0.0..1.0) * Color::random(0.0..1.0));
+                let sphere_mat = Arc::new(Lambertian::new(albedo));
+                let sphere = Sphere::new(center, 0.2, sphere_mat).into_moving(0.0..1.0, new_center);
+
+                world.add(sphere);
+            } else if choose_mat < 0.95 {
+                // Metal
+                let albedo = Color::random(0.4..1.0);
+                let fuzz = gen_range(0.0..0.5);
+                let sphere_mat = Arc::new(Metal::new(albedo, fuzz));
+                let sphere = Sphere::new(center, 0.2, sphere_mat);
+
+                world.add(sphere);
+            } else {
+                // Glass
+                let sphere_mat = Arc::new(Dielectric::new(1.5));
+                let sphere = Sphere::new(center, 0.2, sphere_mat);
+
+                world.add(sphere);
+            }
+        }
+    }
+
+    let mat1 = Arc::new(Dielectric::new(1.5));
+    let mat2 = Arc::new(Lambertian::new(SolidColor::new_rgb(0.4, 0.2, 0.1)));
+    let mat3 = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+
+    let sphere1 = Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, mat1);
+    let sphere2 = Sphere::new(Point3::new(-4.0, 1.0, 0.0), 1.0, mat2);
+    let sphere3 = Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, mat3);
+
+    world.add(sphere1);
+    world.add(sphere2);
+    world.add(sphere3);
+
+    // cones in the gaps between the large spheres
+    let cone_mat1 = Arc::new(Lambertian::new(Checker::new_solids(
+        Color::new(0.8, 0.1, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    )));
+    let cone_mat2 = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 0.1));
+    let cone1 = Cone::new(
+        Point3::new(-2.0, 0.0, 0.0),
+        Point3::new(-2.0, 1.6, 0.0),
+        0.6,
+        cone_mat1,
+    );
+    let cone2 = Cone::new(
+        Point3::new(2.0, 0.0, 0.0),
+        Point3::new(2.0, 1.6, 0.0),
+        0.6,
+        cone_mat2,
+    );
+
+    world.add(cone1);
+    world.add(cone2);
+
+    Scene {
+        world: world.into_accelerated(0.0..1.0),
+        camera_builder: CameraBuilder::default()
+            .look_from(13.0, 2.0, 3.0)
+            .look_at(0.0, 0.0, 0.0)
+            .vertical_field_of_view(20.0)
+            .aperture(0.1),
+        ..Default::default()
+    }
+}
+
+/// Two checkered spheres touching each other.
+pub fn two_spheres() -> Scene {
+    let mut world = World::new();
+
+    let checker = Checker::new_solids(Color::new(0.2, 0.3, 0.1), Color::new(0.9, 0.9, 0.9));
+    let material = Arc::new(Lambertian::new(checker));
+    world.add(Sphere::new(
+        Point3::new(0.0, -10.0, 0.0),
+        10.0,
+        material.clone(),
+    ));
+    world.add(Sphere::new(Point3::new(0.0, 10.0, 0.0), 10.0, material));
+
+    Scene {
+        world,
+        camera_builder: CameraBuilder::default()
+            .look_from(13.0, 2.0, 3.0)
+            .look_at(0.0, 0.0, 0.0)
+            .vertical_field_of_view(20.0),
+        ..Default::default()
+    }
+}
+
+/// Marble noise of `scale`, which is the same every time with a `seed`.
+fn marble(scale: f64, seed: Option<u64>) -> Noise {
+    match seed {
+        Some(seed) => Noise::with_seed(scale, seed),
+        None => Noise::new(scale),
+    }
+}
+
+/// A Perlin noise sphere on a Perlin noise ground.
+///
+/// With a `seed`, the noise is the same every time.
+pub fn two_perlin_spheres(seed: Option<u64>) -> Scene {
+    let mut world = World::new();
+
+    let perlin = marble(4.0, seed);
+    let material = Arc::new(Lambertian::new(perlin));
+    world.add(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        material.clone(),
+    ));
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, material));
+
+    Scene {
+        world,
+        camera_builder: CameraBuilder::default()
+            .look_from(13.0, 2.0, 3.0)
+            .look_at(0.0, 0.0, 0.0)
+            .vertical_field_of_view(20.0),
+        ..Default::default()
+    }
+}
+
+/// A block of smoke over the ground, whose density follows Perlin noise and
+/// thins out with height.
+///
+/// With a `seed`, the noise is the same every time.
+pub fn perlin_smoke(seed: Option<u64>) -> Scene {
+    const MAX_DENSITY: f64 = 2.0;
+    let mut world = World::new();
+
+    let ground = Arc::new(Lambertian::new_solid(Color::new(0.48, 0.83, 0.53)));
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground));
+
+    let white = Arc::new(Lambertian::new_solid(Color::WHITE));
+    let boundary = Block::new(
+        Point3::new(-2.0, 0.0, -2.0),
+        Point3::new(2.0, 3.0, 2.0),
+        white,
+    );
+    let noise = marble(2.0, seed);
+    let density = move |point: Point3| {
+        let noise = noise.color(point, 0.0, 0.0).x();
+        MAX_DENSITY * noise * (-point.y()).exp()
+    };
+    world.add(NonUniformMedium::new_solid(
+        boundary,
+        Color::constant(0.9),
+        density,
+        MAX_DENSITY,
+    ));
+
+    Scene {
+        world,
+        camera_builder: CameraBuilder::default()
+            .look_from(13.0, 2.0, 3.0)
+            .look_at(0.0, 1.0, 0.0)
+            .vertical_field_of_view(30.0),
+        ..Default::default()
+    }
+}
+
+/// A globe textured with `earth_texture`, such as `texture/earthmap.jpg`.
+pub fn earth(earth_texture: Image) -> Scene {
+    let earth_surface = Arc::new(Lambertian::new(earth_texture));
+    let globe = Sphere::new(Point3::zeros(), 2.0, earth_surface);
+
+    let world = World::from_vec(vec![Box::new(globe)]);
+
+    Scene {
+        world,
+        camera_builder: CameraBuilder::new()
+            .look_from(13.0, 2.0, 3.0)
+            .look_at(0.0, 0.0, 0.0)
+            .vertical_field_of_view(20.0),
+        ..Default::default()
+    }
+}
+
+/// The globe of [`earth`] under the equirectangular image `sky`, such as an
+/// HDR photo, which both lights it and shows behind it.
+pub fn earth_under_sky(earth_texture: Image, sky: Image) -> Scene {
+    Scene {
+        background: Arc::new(EnvironmentMap::new(sky)),
+        samples_per_pixel: 400,
+        ..earth(earth_texture)
+    }
+}
+
+/// The globe of [`earth`] on the ground, lit from the side by the
+/// rectangular light of [`simple_light`]. The luminance of `earth_texture`
+/// is used as a bump map, so the bright land stands out from the sea.
+pub fn bumpy_earth(earth_texture: Image) -> Scene {
+    let mut world = World::new();
+
+    let ground = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+    world.add(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground));
+    let surface = NormalMap::bump(Lambertian::new(earth_texture.clone()), earth_texture, 0.02);
+    world.add(Sphere::new(
+        Point3::new(0.0, 2.0, 0.0),
+        2.0,
+        Arc::new(surface),
+    ));
+
+    let diffuse_light = Arc::new(DiffuseLight::new_solid(Color::new(4.0, 4.0, 4.0)));
+    world.add(AxisAlignedRectangle::new_xy(
+        (3.0, 1.0),
+        (5.0, 3.0),
+        -2.0,
+        diffuse_light,
+    ));
+
+    Scene {
+        world,
+        background: Color::BLACK.into(),
+        camera_builder: CameraBuilder::new()
+            .look_from(26.0, 3.0, 6.0)
+            .look_at(0.0, 2.0, 0.0)
+            .vertical_field_of_view(20.0),
+        samples_per_pixel: 400,
+        ..Default::default()
+    }
+}
+
+/// The two Perlin noise spheres lit by a rectangular light.
+///
+/// With a `seed`, the noise is the same every time.
+pub fn simple_light(seed: Option<u64>) -> Scene {
+    let mut world = World::new();
+
+    let perlin = Arc::new(Lambertian::new(marble(4.0, seed)));
+    let sphere = Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, perlin.clone());
+    world.add(sphere);
+    world.add(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, perlin));
+
+    // light is brighter than `(1.0, 1.0, 1.0)` to bright enough to light up the scene
+    let diffuse_light = Arc::new(DiffuseLight::new_solid(Color::new(4.0, 4.0, 4.0)));
+    world.add(AxisAlignedRectangle::new_xy(
+        (3.0, 1.0),
+        (5.0, 3.0),
+        -2.0,
+        diffuse_light,
+    ));
+
+    Scene {
+        world,
+        background: Color::BLACK.into(),
+        camera_builder: CameraBuilder::new()
+            .look_from(26.0, 3.0, 6.0)
+            .look_at(0.0, 2.0, 0.0)
+            .vertical_field_of_view(20.0),
+        samples_per_pixel: 400,
+        ..Default::default()
+    }
+}
+
+/// The Cornell box with two rotated blocks.
+pub fn cornell_box() -> Scene {
+    const RED: Color = Color::new(0.65, 0.05, 0.05);
+    const WHITE: Color = Color::new(0.73, 0.73, 0.73);
+    const GREEN: Color = Color::new(0.12, 0.45, 0.15);
+    const LIGHT: Color = Color::new(15.0, 15.0, 15.0);
+
+    let red = Arc::new(Lambertian::new_solid(RED));
+    let white = Arc::new(Lambertian::new_solid(WHITE));
+    let green = Arc::new(Lambertian::new_solid(GREEN));
+    let light = Arc::new(DiffuseLight::new_solid(LIGHT).one_sided());
+    // facing down, so that it does not light the ceiling
+    let ceiling_light = Arc::new(
+        AxisAlignedRectangle::new_xz((213.0, 227.0), (343.0, 332.0), 554.0, light).flip_faces(),
+    );
+
+    let block_front = Transform::new(Block::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    ))
+    .rotate_y(15.0)
+    .translate(Vec3::new(265.0, 0.0, 295.0));
+
+    let block_back = Transform::new(Block::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        white.clone(),
+    ))
+    .rotate_y(-18.0)
+    .translate(Vec3::new(130.0, 0.0, 65.0));
+
+    let objects: Vec<Box<dyn Hit>> = vec![
+        Box::new(AxisAlignedRectangle::new_yz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            green,
+        )),
+        Box::new(AxisAlignedRectangle::new_yz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            0.0,
+            red,
+        )),
+        Box::new(ceiling_light.clone()),
+        Box::new(AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            0.0,
+            white.clone(),
+        )),
+        Box::new(AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            white.clone(),
+        )),
+        Box::new(AxisAlignedRectangle::new_xy(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            white,
+        )),
+        Box::new(block_front),
+        Box::new(block_back),
+    ];
+
+    Scene {
+        world: World::from_vec(objects),
+        background: Color::BLACK.into(),
+        camera_builder: CameraBuilder::new()
+            .look_from(278.0, 278.0, -800.0)
+            .look_at(278.0, 278.0, 0.0)
+            .vertical_field_of_view(40.0),
+        aspect_ratio: 1.0,
+        image_width: 600,
+        samples_per_pixel: 200,
+        lights: Some(ceiling_light),
+    }
+}
+
+/// The Cornell box with a two-bladed propeller spinning a quarter turn in
+/// front of the light during the shutter interval.
+pub fn cornell_propeller() -> Scene {
+    let mut scene = cornell_box();
+    let metal = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.2));
+
+    let blade = |degree: f64| {
+        Block::new(
+            Point3::new(-150.0, -12.0, -4.0),
+            Point3::new(150.0, 12.0, 4.0),
+            metal.clone(),
+        )
+        .rotate_z(degree)
+    };
+    let hub = World::from_vec(vec![blade(0.0).boxed(), blade(90.0).boxed()]);
+    let propeller =
+        RotateAnimated::new_z(hub, 0.0..90.0, 0.0..1.0).translate(Vec3::new(278.0, 420.0, 150.0));
+    scene.world.add(propeller);
+
+    scene
+}
+
+/// The Cornell box on a floor of mirror and white tiles, which reflect the
+/// walls and the blocks.
+pub fn cornell_checker_floor() -> Scene {
+    let mut scene = cornell_box();
+    let tiles = Arc::new(
+        CheckerMaterial::new(
+            Metal::new(Color::new(0.9, 0.9, 0.9), 0.0),
+            Lambertian::new_solid(Color::new(0.73, 0.73, 0.73)),
+        )
+        // ten tiles along the floor
+        .pattern(CheckerPattern::Parity {
+            scale: 10.0 / 555.0,
+        }),
+    );
+    // just above the white floor
+    scene.world.add(AxisAlignedRectangle::new_xz(
+        (0.0, 0.0),
+        (555.0, 555.0),
+        0.5,
+        tiles,
+    ));
+
+    scene
+}
+
+/// The Cornell box with blocks of black and white smoke.
+pub fn cornell_smoke() -> Scene {
+    const RED: Color = Color::new(0.65, 0.05, 0.05);
+    const WHITE: Color = Color::new(0.73, 0.73, 0.73);
+    const GREEN: Color = Color::new(0.12, 0.45, 0.15);
+    const LIGHT: Color = Color::new(7.0, 7.0, 7.0);
+
+    let red = Arc::new(Lambertian::new_solid(RED));
+    let white = Arc::new(Lambertian::new_solid(WHITE));
+    let green = Arc::new(Lambertian::new_solid(GREEN));
+    let light = Arc::new(DiffuseLight::new_solid(LIGHT));
+
+    let block_front = Transform::new(Block::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        red.clone(),
+    ))
+    .rotate_y(15.0)
+    .translate(Vec3::new(265.0, 0.0, 295.0));
+    let block_front = ConstantMedium::new_solid(block_front, Color::BLACK, 0.01);
+
+    let block_back = Transform::new(Block::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        white.clone(),
+    ))
+    .rotate_y(-18.0)
+    .translate(Vec3::new(130.0, 0.0, 65.0));
+    let block_back = ConstantMedium::new_solid(block_back, Color::WHITE, 0.01);
+
+    let objects: Vec<Box<dyn Hit>> = vec![
+        Box::new(AxisAlignedRectangle::new_yz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            green,
+        )),
+        Box::new(AxisAlignedRectangle::new_yz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            0.0,
+            red,
+        )),
+        Box::new(AxisAlignedRectangle::new_xz(
+            (113.0, 127.0),
+            (443.0, 432.0),
+            554.0,
+            light,
+        )),
+        Box::new(AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            0.0,
+            white.clone(),
+        )),
+        Box::new(AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            white.clone(),
+        )),
+        Box::new(AxisAlignedRectangle::new_xy(
+            (0.0, 0.0),
+            (555.0, 555.0),
+            555.0,
+            white,
+        )),
+        Box::new(block_front),
+        Box::new(block_back),
+    ];
+
+    Scene {
+        world: World::from_vec(objects),
+        background: Color::BLACK.into(),
+        camera_builder: CameraBuilder::new()
+            .look_from(278.0, 278.0, -800.0)
+            .look_at(278.0, 278.0, 0.0)
+            .vertical_field_of_view(40.0),
+        aspect_ratio: 1.0,
+        image_width: 600,
+        samples_per_pixel: 200,
+        lights: None,
+    }
+}
+
+/// Diffuse, hollow glass and metal spheres in a row, seen from up close.
+pub fn dielectric_scene() -> Scene {
+    let ground = Arc::new(Lambertian::new_solid(Color::new(0.8, 0.8, 0.0)));
+    let center = Arc::new(Lambertian::new_solid(Color::new(0.1, 0.2, 0.5)));
+    // green glass, which is darker where the shell is seen edge-on
+    let left = Arc::new(Dielectric::with_attenuation(
+        1.5,
+        Color::new(0.4, 0.9, 0.5),
+        4.0,
+    ));
+    let right = Arc::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.0));
+    let frosted = Arc::new(Dielectric::new(1.5).with_roughness(0.3));
+
+    let mut world = World::new();
+    world.add(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, ground));
+    world.add(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, center));
+    world.add(Sphere::new(Point3::new(-1.0, 0.0, -1.0), 0.5, left.clone()));
+    world.add(Sphere::new(Point3::new(-1.0, 0.0, -1.0), -0.4, left));
+    world.add(Sphere::new(Point3::new(1.0, 0.0, -1.0), 0.5, right));
+    // in front, between the glass and the middle sphere
+    world.add(Sphere::new(Point3::new(-0.5, -0.25, -0.4), 0.25, frosted));
+
+    Scene {
+        world,
+        samples_per_pixel: 100,
+        camera_builder: CameraBuilder::new()
+            .look_from(0.0, 0.0, 0.0)
+            .look_at(0.0, 0.0, -1.0),
+        ..Default::default()
+    }
+}
+
+/// A glass prism seen against a narrow white slit of light, which it splits
+/// into a rainbow.
+///
+/// The prism stands upright with a triangular cross section of dense flint
+/// glass. The slit is where the rays from the camera leave the prism at the
+/// angle of minimum deviation, so the camera sees it through the prism,
+/// spread out by the dispersion of the glass.
+pub fn prism() -> Scene {
+    // the minimum deviation of a 60 degree prism with an index of about 1.66
+    // is 52 degrees, half of it on the way in and half on the way out
+    const HALF_DEVIATION: f64 = 26.0;
+    const SIDE: f64 = 2.0;
+    const HEIGHT: f64 = 3.0;
+
+    let glass = Arc::new(Dielectric::with_dispersion(1.6, 0.02));
+    let light = Arc::new(DiffuseLight::new_solid(Color::constant(4.0)));
+
+    // the base of the prism is parallel to the rays inside, and the apex
+    // points away from the side the rays are bent to
+    let phi = HALF_DEVIATION.to_radians();
+    let apex = Vec3::new(phi.cos(), 0.0, -phi.sin());
+    let along_base = Vec3::new(-phi.sin(), 0.0, -phi.cos());
+    let height = SIDE * 3f64.sqrt() / 2.0;
+    let bottom = |offset: Vec3<f64>| offset - Vec3::new(0.0, HEIGHT / 2.0, 0.0);
+    let corners = [
+        bottom(apex * (2.0 * height / 3.0)),
+        bottom(-apex * (height / 3.0) + along_base * (SIDE / 2.0)),
+        bottom(-apex * (height / 3.0) - along_base * (SIDE / 2.0)),
+    ];
+    let up = Vec3::new(0.0, HEIGHT, 0.0);
+
+    let mut world = World::new();
+    for i in 0..3 {
+        let (from, to) = (corners[i], corners[(i + 1) % 3]);
+        world.add(Quad::new(from, to - from, up, glass.clone()));
+    }
+    let [a, b, c] = corners;
+    world.add(Triangle::new(a + up, b + up, c + up, glass.clone()));
+    world.add(Triangle::new(a, c, b, glass));
+
+    // the slit, facing the prism along the way out
+    let out = Vec3::new(-(2.0 * phi).sin(), 0.0, -(2.0 * phi).cos());
+    let across = Vec3::new((2.0 * phi).cos(), 0.0, -(2.0 * phi).sin());
+    let slit_width = 0.05;
+    let slit_corner = out * 6.0 - across * (slit_width / 2.0) - up / 2.0;
+    world.add(Quad::new(slit_corner, across * slit_width, up, light));
+
+    Scene {
+        world,
+        background: Color::BLACK.into(),
+        camera_builder: CameraBuilder::new()
+            .look_from(0.0, 0.0, 10.0)
+            .look_at(0.0, 0.0, 0.0)
+            .vertical_field_of_view(20.0),
+        samples_per_pixel: 400,
+        ..Default::default()
+    }
+}
+
+/// Everything of the second book together, with a globe textured with
+/// `earth_texture`.
+///
+/// With a `seed`, the generator of the current thread is reseeded first and the
+/// noise is seeded too, so the scene is the same every time.
+pub fn final_scene(earth_texture: Image, seed: Option<u64>) -> Scene {
+    if let Some(seed) = seed {
+        random::seed(seed);
+    }
+    let ground = Arc::new(Lambertian::new_solid(Color::new(0.48, 0.83, 0.53)));
+    let box_width = 100.0;
+    let time_range = 0.0..1.0;
+    let bottom_blocks = (0..20)
+        .flat_map(|i| {
+            let ground = ground.clone();
+            (0..20).map(move |j| {
+                let (i, j) = (i as f64, j as f64);
+                let min_point = Point3::new(-1000.0 + i * box_width, 0.0, -1000.0 + j * box_width);
+                let max_point = min_point + Vec3::new(box_width, gen_range(1.0..101.0), box_width);
+                Block::new(min_point, max_point, ground.clone())
+            })
+        })
+        .map(|b| -> Box<dyn Hit> { Box::new(b) })
+        .collect();
+    let bottom_blocks = BVH::new_sah(bottom_blocks, time_range.clone());
+
+    let light_material = Arc::new(DiffuseLight::new_solid(Color::new(7.0, 7.0, 7.0)));
+    let light = AxisAlignedRectangle::new_xz((123.0, 147.0), (423.0, 412.0), 554.0, light_material);
+
+    let center_from = Point3::new(400.0, 400.0, 200.0);
+    let center_to = center_from + Vec3::new(30.0, 0.0, 0.0);
+    let moving_sphere_material = Arc::new(Lambertian::new_solid(Color::new(0.7, 0.3, 0.1)));
+    let moving_sphere = MovingSphere::new(
+        time_range.clone(),
+        center_from,
+        center_to,
+        50.0,
+        moving_sphere_material,
+    );
+
+    let glass_material = Arc::new(Dielectric::new(1.5));
+    let glass_sphere = Sphere::new(
+        Point3::new(260.0, 150.0, 45.0),
+        50.0,
+        glass_material.clone(),
+    );
+
+    let metal_sphere = Sphere::new(
+        Point3::new(0.0, 150.0, 145.0),
+        50.0,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 1.0)),
+    );
+
+    let blue_sphere_boundary = Sphere::new(
+        Point3::new(360.0, 150.0, 145.0),
+        70.0,
+        glass_material.clone(),
+    );
+    let blue_sphere =
+        ConstantMedium::new_solid(blue_sphere_boundary.clone(), Color::new(0.2, 0.4, 0.9), 0.2);
+
+    let white_sphere_boundary = Sphere::new(Point3::zeros(), 5000.0, glass_material);
+    let white_sphere = ConstantMedium::new_solid(white_sphere_boundary, Color::WHITE, 0.0001);
+
+    let earth = Sphere::new(
+        Point3::new(400.0, 200.0, 400.0),
+        100.0,
+        Arc::new(Lambertian::new(earth_texture)),
+    );
+
+    let perlin_texture = marble(0.1, seed);
+    let perlin_sphere = Sphere::new(
+        Point3::new(220.0, 280.0, 300.0),
+        80.0,
+        Arc::new(Lambertian::new(perlin_texture)),
+    );
+
+    let white = Arc::new(Lambertian::new_solid(Color::constant(0.73)));
+    let sphere_blocks = (0..1000)
+        .map(|_| Sphere::new(Point3::random(0.0..165.0), 10.0, white.clone()))
+        .map(|b| b.boxed())
+        .collect();
+    let sphere_blocks = BVH::new_sah(sphere_blocks, time_range)
+        .rotate_y(15.0)
+        .translate(Vec3::new(-100.0, 270.0, 395.0));
+
+    let world = World::from_vec(vec![
+        Box::new(bottom_blocks),
+        Box::new(light),
+        Box::new(moving_sphere),
+        Box::new(glass_sphere),
+        Box::new(metal_sphere),
+        Box::new(blue_sphere),
+        Box::new(blue_sphere_boundary),
+        Box::new(white_sphere),
+        Box::new(earth),
+        Box::new(perlin_sphere),
+        Box::new(sphere_blocks),
+    ]);
+
+    Scene {
+        world,
+        background: Color::BLACK.into(),
+        aspect_ratio: 1.0,
+        image_width: 800,
+        samples_per_pixel: 10000,
+        camera_builder: CameraBuilder::new()
+            .look_from(478.0, 278.0, -600.0)
+            .look_at(278.0, 278.0, 0.0)
+            .vertical_field_of_view(40.0),
+        lights: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_scene_renders() {
+        let texture = || Image::new(image::RgbImage::from_pixel(4, 2, image::Rgb([40, 80, 160])));
+        let scenes = [
+            random_scene(Some(1)),
+            two_spheres(),
+            two_perlin_spheres(None),
+            perlin_smoke(None),
+            earth(texture()),
+            earth_under_sky(texture(), texture()),
+            bumpy_earth(texture()),
+            simple_light(None),
+            cornell_box(),
+            cornell_propeller(),
+            cornell_checker_floor(),
+            cornell_smoke(),
+            dielectric_scene(),
+            prism(),
+            final_scene(texture(), Some(1)),
+        ];
+
+        for scene in scenes {
+            let mut tracer = scene.build_tracer(4);
+            tracer.samples_per_pixel = 1;
+            tracer.seed = Some(1);
+            let (x, y) = (tracer.image_width() / 2, tracer.image_height / 2);
+            let pixel = tracer.render_region(x, y, x + 1, y + 1).get(0, 0);
+            assert!((0..3).all(|i| pixel[i].is_finite() && pixel[i] >= 0.0));
+        }
+    }
+
+    #[test]
+    fn seeded_noise_scenes_repeat() {
+        let texture = || Image::new(image::RgbImage::from_pixel(4, 2, image::Rgb([40, 80, 160])));
+        let render = |scene: Scene| {
+            let mut tracer = scene.build_tracer(4);
+            tracer.image_height = 12;
+            tracer.samples_per_pixel = 1;
+            tracer.seed = Some(1);
+            let width = tracer.image_width();
+            let image = tracer.render_region(0, 0, width, 12);
+            (0..12)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| image.get(x, y))
+                .collect::<Vec<_>>()
+        };
+        let scenes: [&dyn Fn() -> Scene; 4] = [
+            &|| two_perlin_spheres(Some(2)),
+            &|| perlin_smoke(Some(2)),
+            &|| simple_light(Some(2)),
+            &|| final_scene(texture(), Some(2)),
+        ];
+        for scene in scenes {
+            assert_eq!(render(scene()), render(scene()));
+        }
+    }
+
+    #[test]
+    fn sky_lights_the_globe() {
+        // a bright blue sky above a dim brown ground
+        let (sky, ground) = ([0.4, 0.6, 3.0], [0.5, 0.3, 0.1]);
+        let map =
+            image::Rgb32FImage::from_fn(4, 2, |_, y| image::Rgb(if y == 0 { sky } else { ground }));
+        let white = Image::new(image::RgbImage::from_pixel(1, 1, image::Rgb([255; 3])));
+        let mut tracer = earth_under_sky(white, Image::new_hdr(map)).build_tracer(8);
+        tracer.samples_per_pixel = 64;
+        tracer.seed = Some(5);
+        let (width, height) = (tracer.image_width(), tracer.image_height);
+
+        // the corner sees the map, and the globe is lit by the blue sky
+        let color = |[r, g, b]: [f32; 3]| Color::new(r as f64, g as f64, b as f64);
+        let corner = tracer.render_region(0, 0, 1, 1).get(0, 0);
+        assert!(corner == color(sky) || corner == color(ground), "{corner}");
+        let (x, y) = (width / 2, height / 2);
+        let globe = tracer.render_region(x, y, x + 1, y + 1).get(0, 0);
+        assert!(globe.b() > globe.r() && globe.b() > globe.g(), "{globe}");
+        assert!(globe.b() < 3.0, "{globe}");
+    }
+
+    #[test]
+    fn prism_splits_white_light() {
+        let mut tracer = prism().build_tracer(8);
+        tracer.image_height = 36;
+        tracer.samples_per_pixel = 64;
+        tracer.seed = Some(3);
+        let width = tracer.image_width();
+        let image = tracer.render_region(0, 0, width, 36);
+
+        // the slit seen through the prism, summed over the middle rows
+        let columns: Vec<Color> = (0..width)
+            .map(|x| (9..27).fold(Color::BLACK, |sum, y| sum + image.get(x, y)))
+            .collect();
+        let brightest = columns.iter().map(|c| c.luminance()).fold(0.0, f64::max);
+        let bright: Vec<_> = (0..columns.len())
+            .filter(|&x| columns[x].luminance() > 0.2 * brightest)
+            .collect();
+        let most = |channel: usize| {
+            let share = |x: &usize| columns[*x][channel] / columns[*x].iter().sum::<f64>();
+            *bright
+                .iter()
+                .max_by(|a, b| share(a).total_cmp(&share(b)))
+                .unwrap()
+        };
+        // blue is bent more than red, so the colors are spread out in order
+        assert!(bright.len() >= 3, "{bright:?}");
+        assert!(most(0) < most(1) && most(1) < most(2), "{bright:?}");
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    mpsc::Sender,
+    Arc, Mutex, RwLock,
+};
+
+use crate::{Color, RenderBuffer};
+
+/// Receiver of partial results while an image is being rendered.
+///
+/// The tile renderer calls [`PixelSink::on_tile`] from its worker threads as
+/// soon as a tile is done, so the order of the tiles is unspecified. Every
+/// pixel of the image is delivered exactly once.
+pub trait PixelSink: Sync {
+    /// Called when the tile with top-left corner `(x0, y0)` is done.
+    /// `pixels` holds `width * height` colors, row by row.
+    fn on_tile(&self, x0: u64, y0: u64, width: u64, height: u64, pixels: &[Color]);
+}
+
+impl PixelSink for () {
+    fn on_tile(&self, _: u64, _: u64, _: u64, _: u64, _: &[Color]) {}
+}
+
+/// A finished tile, as sent by [`ChannelSink`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    pub x0: u64,
+    pub y0: u64,
+    pub width: u64,
+    pub height: u64,
+    pub pixels: Vec<Color>,
+}
+
+/// A [`PixelSink`] forwarding every tile over a channel, so that another
+/// thread (e.g. a UI) can drain them.
+#[derive(Debug)]
+pub struct ChannelSink {
+    sender: Mutex<Sender<Tile>>,
+}
+
+impl ChannelSink {
+    pub fn new(sender: Sender<Tile>) -> Self {
+        Self {
+            sender: Mutex::new(sender),
+        }
+    }
+}
+
+impl PixelSink for ChannelSink {
+    fn on_tile(&self, x0: u64, y0: u64, width: u64, height: u64, pixels: &[Color]) {
+        let tile = Tile {
+            x0,
+            y0,
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        };
+        // the receiver may have hung up, which only means nobody is listening
+        let _ = self.sender.lock().unwrap().send(tile);
+    }
+}
+
+/// A render buffer shared with other threads, which can poll the image while
+/// it is being rendered, e.g. by [`crate::RayTracer::render_into_shared`].
+///
+/// Clones share the same buffer. Tiles are written under a write lock, one
+/// tile at a time.
+#[derive(Debug, Clone)]
+pub struct SharedFramebuffer {
+    buffer: Arc<RwLock<RenderBuffer>>,
+    tiles: Arc<AtomicU64>,
+}
+
+impl SharedFramebuffer {
+    /// Create a black framebuffer of the given size.
+    pub fn new(width: u64, height: u64) -> Self {
+        Self {
+            buffer: Arc::new(RwLock::new(RenderBuffer::new(width, height))),
+            tiles: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn width(&self) -> u64 {
+        self.buffer.read().unwrap().width()
+    }
+
+    pub fn height(&self) -> u64 {
+        self.buffer.read().unwrap().height()
+    }
+
+    /// Copy of the image as rendered so far.
+    pub fn snapshot(&self) -> RenderBuffer {
+        self.buffer.read().unwrap().clone()
+    }
+
+    /// Number of tiles written so far. It only increases, and a tile is
+    /// counted once it is visible in [`SharedFramebuffer::snapshot`].
+    pub fn tile_count(&self) -> u64 {
+        self.tiles.load(Ordering::Acquire)
+    }
+}
+
+impl PixelSink for SharedFramebuffer {
+    fn on_tile(&self, x0: u64, y0: u64, width: u64, _height: u64, pixels: &[Color]) {
+        let mut buffer = self.buffer.write().unwrap();
+        for (index, &color) in pixels.iter().enumerate() {
+            let index = index as u64;
+            buffer.set(x0 + index % width, y0 + index / width, color);
+        }
+        self.tiles.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// Flag to stop a render early from another thread.
+///
+/// Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,235 @@
+//! Progressive rendering until a noise target or a budget is reached.
+
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::{random, Color, Hit, RayTracer, RenderBuffer, RenderMode};
+
+/// When to stop adding samples to an image, see [`RayTracer::render_to_target`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderTarget {
+    /// Exactly this many samples per pixel
+    SamplesPerPixel(u64),
+    /// Until the noise of almost every pixel is below `threshold`, see
+    /// [`RenderTarget::NOISY_PIXEL_CUTOFF`], or until `max_spp` samples per
+    /// pixel or `max_time` are spent.
+    ///
+    /// The noise of a pixel is the estimated standard error of the mean of
+    /// its luminance.
+    Noise {
+        threshold: f64,
+        max_spp: u64,
+        max_time: Option<Duration>,
+    },
+}
+
+impl RenderTarget {
+    /// A noise target is reached when at most this fraction of the pixels are
+    /// above the threshold.
+    pub const NOISY_PIXEL_CUTOFF: f64 = 0.01;
+
+    /// Noise is not estimated from fewer samples than this, so that a few
+    /// samples that happen to agree do not stop the render.
+    const MIN_NOISE_SAMPLES: u64 = 4;
+}
+
+/// Why [`RayTracer::render_to_target`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The requested samples per pixel are taken
+    SamplesTaken,
+    /// The noise is below the threshold
+    NoiseReached,
+    /// The noise target is not reached within `max_spp`
+    MaxSamples,
+    /// The noise target is not reached within `max_time`
+    TimeBudget,
+}
+
+/// Summary of a [`RayTracer::render_to_target`] run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderStats {
+    /// Number of passes, each adding one sample to every pixel
+    pub samples_per_pixel: u64,
+    /// Fraction of the pixels whose noise is above the threshold at the end,
+    /// zero for [`RenderTarget::SamplesPerPixel`]
+    pub noisy_fraction: f64,
+    pub elapsed: Duration,
+    pub stop_reason: StopReason,
+}
+
+/// Running mean of the color of a pixel, and of the variance of its luminance.
+#[derive(Debug, Clone, Default)]
+struct PixelEstimate {
+    samples: u64,
+    mean: Color,
+    luminance_mean: f64,
+    /// Sum of squared differences from the luminance mean (Welford)
+    luminance_m2: f64,
+}
+
+impl PixelEstimate {
+    fn add(&mut self, color: Color) {
+        self.samples += 1;
+        let n = self.samples as f64;
+        self.mean += (color - self.mean) / n;
+
+        let luminance = color.luminance();
+        let delta = luminance - self.luminance_mean;
+        self.luminance_mean += delta / n;
+        self.luminance_m2 += delta * (luminance - self.luminance_mean);
+    }
+
+    /// Standard error of the luminance mean.
+    fn noise(&self) -> f64 {
+        let n = self.samples as f64;
+        (self.luminance_m2 / (n - 1.0) / n).sqrt()
+    }
+}
+
+impl<H: Hit> RayTracer<H> {
+    /// Render by adding one jittered sample to every pixel per pass until
+    /// `target` is reached, ignoring [`RayTracer::samples_per_pixel`].
+    ///
+    /// With a [`RayTracer::seed`], every sample is reseeded from the seed, the
+    /// pixel and the pass, so a render stopping after the same pass gives the
+    /// same image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the render mode is not [`RenderMode::Shaded`].
+    pub fn render_to_target(&self, target: RenderTarget) -> (RenderBuffer, RenderStats) {
+        assert_eq!(
+            self.render_mode,
+            RenderMode::Shaded,
+            "only shaded renders can be progressive"
+        );
+        let start = Instant::now();
+        let (image_width, image_height) = (self.image_width(), self.image_height);
+        let mut estimates = vec![PixelEstimate::default(); (image_width * image_height) as usize];
+
+        let mut pass = 0;
+        let (stop_reason, noisy_fraction) = loop {
+            estimates
+                .par_iter_mut()
+                .enumerate()
+                .for_each(|(index, estimate)| {
+                    let index = index as u64;
+                    let (i, j) = (index % image_width, index / image_width);
+                    if let Some(seed) = self.seed {
+                        let sample = pass * image_width * image_height + index;
+                        random::seed(seed ^ sample.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                    }
+                    estimate.add(self.sample_jittered(i, j, image_width, image_height));
+                });
+            pass += 1;
+
+            match target {
+                RenderTarget::SamplesPerPixel(samples) => {
+                    if pass >= samples {
+                        break (StopReason::SamplesTaken, 0.0);
+                    }
+                }
+                RenderTarget::Noise {
+                    threshold,
+                    max_spp,
+                    max_time,
+                } => {
+                    if pass < RenderTarget::MIN_NOISE_SAMPLES.min(max_spp) {
+                        continue;
+                    }
+                    let noisy = estimates
+                        .par_iter()
+                        .filter(|estimate| estimate.samples < 2 || estimate.noise() > threshold)
+                        .count();
+                    let noisy_fraction = noisy as f64 / estimates.len() as f64;
+
+                    if noisy_fraction <= RenderTarget::NOISY_PIXEL_CUTOFF {
+                        break (StopReason::NoiseReached, noisy_fraction);
+                    } else if pass >= max_spp {
+                        break (StopReason::MaxSamples, noisy_fraction);
+                    } else if max_time.is_some_and(|max_time| start.elapsed() >= max_time) {
+                        break (StopReason::TimeBudget, noisy_fraction);
+                    }
+                }
+            }
+        };
+
+        let pixels = estimates
+            .into_iter()
+            .map(|estimate| estimate.mean)
+            .collect();
+        let buffer = RenderBuffer::from_pixels(image_width, image_height, pixels);
+        let stats = RenderStats {
+            samples_per_pixel: pass,
+            noisy_fraction,
+            elapsed: start.elapsed(),
+            stop_reason,
+        };
+        (buffer, stats)
+    }
+
+    /// Color of one primary ray through a random point of pixel `(i, j)`,
+    /// counting rows from the top.
+    fn sample_jittered(&self, i: u64, j: u64, image_width: u64, image_height: u64) -> Color {
+        let (width, height) = (image_width as f64, image_height as f64);
+        let (i, j) = (i as f64, height - j as f64 - 1.0);
+        let u = (i + random::random::<f64>()) / (width - 1.0);
+        let v = (j + random::random::<f64>()) / (height - 1.0);
+
+        let ray = self.camera.cast(u, v);
+        self.ray_color(ray, crate::T_RANGE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{material::Lambertian, Camera, Point3, Sphere, WorkGranularity, World};
+
+    fn tracer<H: Hit>(world: H) -> RayTracer<H> {
+        RayTracer {
+            world,
+            camera: Camera::builder().aspect_ratio(1.0).build(),
+            background: Color::new(0.7, 0.8, 1.0).into(),
+            max_depth: 8,
+            samples_per_pixel: 1,
+            image_height: 16,
+            render_mode: RenderMode::Shaded,
+            seed: Some(6),
+            work_granularity: WorkGranularity::Auto,
+            material_override: None,
+            lights: None,
+        }
+    }
+
+    #[test]
+    fn smooth_scene_stops_early() {
+        let target = RenderTarget::Noise {
+            threshold: 1e-3,
+            max_spp: 1000,
+            max_time: None,
+        };
+        let (buffer, stats) = tracer(World::new()).render_to_target(target);
+        assert_eq!(stats.stop_reason, StopReason::NoiseReached);
+        assert_eq!(stats.samples_per_pixel, RenderTarget::MIN_NOISE_SAMPLES);
+        assert_eq!(buffer.get(3, 5), Color::new(0.7, 0.8, 1.0));
+
+        let material = Arc::new(Lambertian::new_solid(Color::constant(0.5)));
+        let sphere = tracer(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material));
+        let target = RenderTarget::Noise {
+            threshold: 1e-6,
+            max_spp: 6,
+            max_time: None,
+        };
+        let (buffer, stats) = sphere.render_to_target(target);
+        assert_eq!(stats.stop_reason, StopReason::MaxSamples);
+        assert!(stats.noisy_fraction > RenderTarget::NOISY_PIXEL_CUTOFF);
+
+        let (same, _) = sphere.render_to_target(RenderTarget::SamplesPerPixel(6));
+        assert_eq!(same, buffer);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{Color, Point3};
+
+use super::Texture;
+
+/// Two textures blended by a mask, such as dirt over an image or a smooth
+/// transition between two noise textures.
+///
+/// The weight of `second` is the luminance of the mask at the point, clamped
+/// to `[0, 1]`, so black is only `first` and white only `second`.
+///
+/// 8-bit [`Image`](super::Image) masks should be [`linear`](super::Image::linear):
+/// they are decoded from sRGB otherwise, and a 50% gray weighs about 0.22
+/// instead of a half.
+#[derive(Debug, Clone)]
+pub struct Blend<A: Texture, B: Texture, M: Texture> {
+    first: A,
+    second: B,
+    mask: M,
+}
+
+impl<A: Texture, B: Texture, M: Texture> Blend<A, B, M> {
+    pub fn new(first: A, second: B, mask: M) -> Self {
+        Self {
+            first,
+            second,
+            mask,
+        }
+    }
+}
+
+fn blend(first: Color, second: Color, mask: Color) -> Color {
+    first.lerp(second, mask.luminance().clamp(0.0, 1.0))
+}
+
+impl<A: Texture, B: Texture, M: Texture> Texture for Blend<A, B, M> {
+    fn color(&self, point: Point3, u: f64, v: f64) -> Color {
+        blend(
+            self.first.color(point, u, v),
+            self.second.color(point, u, v),
+            self.mask.color(point, u, v),
+        )
+    }
+
+    fn color_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        blend(
+            self.first.color_at_time(point, u, v, time),
+            self.second.color_at_time(point, u, v, time),
+            self.mask.color_at_time(point, u, v, time),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::{FnTexture, Image, SolidColor};
+
+    #[test]
+    fn snow_caps_on_the_earth() {
+        let earth = Image::new(image::RgbImage::from_pixel(2, 2, image::Rgb([0, 0, 255])));
+        let snow = SolidColor::new(Color::WHITE);
+        // snow from a latitude of 60 degrees, fading in over 10 degrees, with
+        // values out of range near the poles
+        let caps = FnTexture::from_uv(|_, v| {
+            let latitude = (v - 0.5).abs() * 180.0;
+            Color::constant((latitude - 60.0) / 10.0)
+        });
+        let blended = Blend::new(earth, snow, caps);
+        let color = |v| blended.color(Point3::zeros(), 0.3, v);
+
+        let blue = Color::new(0.0, 0.0, 1.0);
+        assert_eq!(color(0.5), blue);
+        assert_eq!(color(0.3), blue);
+        assert!((color(0.5 + 65.0 / 180.0) - Color::new(0.5, 0.5, 1.0)).norm() < 1e-9);
+        assert_eq!(color(0.0), Color::WHITE);
+        assert_eq!(color(1.0), Color::WHITE);
+    }
+
+    #[test]
+    fn gray_image_mask_blends_halfway() {
+        let gray = image::RgbImage::from_pixel(1, 1, image::Rgb([128; 3]));
+        let blend = |mask| {
+            Blend::new(
+                SolidColor::new(Color::BLACK),
+                SolidColor::new(Color::WHITE),
+                mask,
+            )
+            .color(Point3::zeros(), 0.5, 0.5)
+        };
+
+        let linear = blend(Image::new(gray.clone()).linear());
+        assert!((linear - Color::constant(128.0 / 255.0)).norm() < 1e-9);
+        // decoded from sRGB, the same gray is much darker
+        let srgb = blend(Image::new(gray));
+        assert!(srgb.r() < 0.25, "{srgb:?}");
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::{Color, Point3};
+
+use super::Texture;
+
+/// Texture whose color is a closure of the point and the texture
+/// coordinates, for trying out a pattern without writing a type for it.
+///
+/// For example, the same as a [`super::SolidColor`]:
+///
+/// ```
+/// use rtweekend::{
+///     texture::{FnTexture, SolidColor, Texture},
+///     Color, Point3,
+/// };
+///
+/// let orange = Color::new(0.9, 0.5, 0.1);
+/// let texture = FnTexture::new(move |_point, _u, _v| orange);
+/// let solid = SolidColor::new(orange);
+/// let point = Point3::new(1.0, 2.0, 3.0);
+/// assert_eq!(
+///     texture.color(point, 0.3, 0.7),
+///     Texture::color(&solid, point, 0.3, 0.7)
+/// );
+/// ```
+#[derive(Clone)]
+pub struct FnTexture<F> {
+    color: F,
+}
+
+impl<F> FnTexture<F>
+where
+    F: Fn(Point3, f64, f64) -> Color + Sync + Send,
+{
+    pub fn new(color: F) -> Self {
+        Self { color }
+    }
+}
+
+impl FnTexture<()> {
+    /// Texture of the texture coordinates only, such as polka dots on a
+    /// sphere.
+    pub fn from_uv<G>(color: G) -> FnTexture<impl Fn(Point3, f64, f64) -> Color + Sync + Send>
+    where
+        G: Fn(f64, f64) -> Color + Sync + Send,
+    {
+        FnTexture::new(move |_, u, v| color(u, v))
+    }
+
+    /// Texture of the point in world space only, such as contour lines of
+    /// the height.
+    pub fn from_point<G>(color: G) -> FnTexture<impl Fn(Point3, f64, f64) -> Color + Sync + Send>
+    where
+        G: Fn(Point3) -> Color + Sync + Send,
+    {
+        FnTexture::new(move |point, _, _| color(point))
+    }
+}
+
+// closures are not `Debug`
+impl<F> fmt::Debug for FnTexture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnTexture").finish_non_exhaustive()
+    }
+}
+
+impl<F> Texture for FnTexture<F>
+where
+    F: Fn(Point3, f64, f64) -> Color + Sync + Send,
+{
+    fn color(&self, point: Point3, u: f64, v: f64) -> Color {
+        (self.color)(point, u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::Lambertian, object::rectangle::AxisAlignedRectangle, texture::Checker, Hit,
+        Interval, Ray, Vec3,
+    };
+
+    #[test]
+    fn composes_in_checker_and_lambertian() {
+        // dots of radius 0.2 in the middle of each tenth of the texture
+        let dots = FnTexture::from_uv(|u, v| {
+            let (du, dv) = ((u * 10.0).fract() - 0.5, (v * 10.0).fract() - 0.5);
+            if du * du + dv * dv < 0.04 {
+                Color::new(1.0, 0.0, 0.0)
+            } else {
+                Color::WHITE
+            }
+        });
+        let height = FnTexture::from_point(|point| Color::constant(point.y()));
+        let checker = Checker::new(dots, height);
+        let ground = AxisAlignedRectangle::new_xz(
+            (0.0, 0.0),
+            (1.0, 1.0),
+            0.5,
+            Arc::new(Lambertian::new(checker)),
+        );
+        let albedo = |x: f64, z: f64| {
+            let ray = Ray::new(Point3::new(x, 1.0, z), -Vec3::unit_y(), 0.0);
+            let hit = ground
+                .hit(ray.clone(), Interval::from_min(0.0))
+                .unwrap()
+                .into_against_ray();
+            hit.material.scatter(&ray, &hit).unwrap().1
+        };
+
+        // odd cells of the checker have dots, and even ones the height
+        let step = std::f64::consts::PI / 10.0;
+        assert_eq!(albedo(0.5 * step, 0.5 * step), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(albedo(0.5 * step + 0.03, 0.5 * step), Color::WHITE);
+        assert_eq!(albedo(0.5 * step, 1.5 * step), Color::constant(0.5));
+        assert_eq!(
+            format!("{:?}", FnTexture::new(|_, _, _| Color::BLACK)),
+            "FnTexture { .. }"
+        );
+    }
+}
@@ -1,41 +1,189 @@
-use image::io::Reader;
+use std::{
+    io::{BufRead, Cursor, Seek},
+    path::Path,
+};
+
+use image::{
+    codecs::hdr::HdrDecoder, io::Reader, DynamicImage, GrayImage, ImageFormat, Rgb32FImage,
+    RgbImage,
+};
 
 use crate::Color;
 
 use super::Texture;
 
-type PixelFormat = u8;
+/// Pixels of an image, in the format they were loaded in.
+#[derive(Debug, Clone)]
+enum Pixels {
+    Rgb8(RgbImage),
+    /// Linear colors of high dynamic range images, such as HDR or EXR files
+    Rgb32F(Rgb32FImage),
+}
 
 /// Image texture
+///
+/// The stored image is first turned by `rotate_quarter_turns`, then mirrored
+/// by `flip_u` and `flip_v`. Texture coordinates outside the image are clamped
+/// to its edges, and the nearest pixel is used.
+///
+/// 8-bit images are decoded from sRGB to linear colors, unless `srgb` is
+/// turned off, e.g. for masks or data stored linearly. Floating point images
+/// are linear already, and their colors are used as they are.
 #[derive(Debug, Clone)]
 pub struct Image {
-    image: image::ImageBuffer<image::Rgb<PixelFormat>, Vec<PixelFormat>>,
+    pixels: Pixels,
+    /// Decode the channels of 8-bit images with the sRGB transfer curve
+    pub srgb: bool,
+    /// Mirror the image horizontally
+    pub flip_u: bool,
+    /// Mirror the image vertically
+    pub flip_v: bool,
+    /// Number of times the image is turned counterclockwise by 90 degrees
+    pub rotate_quarter_turns: u8,
 }
 
 impl Image {
-    pub fn new(image: image::ImageBuffer<image::Rgb<PixelFormat>, Vec<PixelFormat>>) -> Self {
-        Self { image }
+    /// Image of sRGB encoded 8-bit pixels.
+    pub fn new(image: RgbImage) -> Self {
+        Self {
+            pixels: Pixels::Rgb8(image),
+            srgb: true,
+            flip_u: false,
+            flip_v: false,
+            rotate_quarter_turns: 0,
+        }
+    }
+
+    /// Image of sRGB encoded 8-bit pixels, same as [`Image::new`].
+    pub fn from_buffer(image: RgbImage) -> Self {
+        Self::new(image)
+    }
+
+    /// Image of linear floating point pixels, kept at full precision.
+    pub fn new_hdr(image: Rgb32FImage) -> Self {
+        Self {
+            pixels: Pixels::Rgb32F(image),
+            ..Self::new(RgbImage::new(0, 0))
+        }
+    }
+
+    /// Same image, with 8-bit pixels taken as linear colors.
+    pub fn linear(mut self) -> Self {
+        self.srgb = false;
+        self
+    }
+
+    /// Same image, mirrored horizontally.
+    pub fn flipped_u(mut self) -> Self {
+        self.flip_u = !self.flip_u;
+        self
     }
-    pub fn open(path: &str) -> Result<Self, image::ImageError> {
-        let image = Reader::open(path)?.decode()?;
-        Ok(Self::new(image.to_rgb8()))
+
+    /// Same image, mirrored vertically.
+    pub fn flipped_v(mut self) -> Self {
+        self.flip_v = !self.flip_v;
+        self
+    }
+
+    /// Same image, turned counterclockwise by `quarter_turns` times 90 degrees.
+    pub fn rotated(mut self, quarter_turns: u8) -> Self {
+        self.rotate_quarter_turns = (self.rotate_quarter_turns + quarter_turns % 4) % 4;
+        self
+    }
+
+    /// Texture coordinates in the stored image for `(u, v)` on the surface.
+    fn orient(&self, u: f64, v: f64) -> (f64, f64) {
+        let u = if self.flip_u { 1.0 - u } else { u };
+        let v = if self.flip_v { 1.0 - v } else { v };
+        match self.rotate_quarter_turns % 4 {
+            0 => (u, v),
+            1 => (v, 1.0 - u),
+            2 => (1.0 - u, 1.0 - v),
+            _ => (1.0 - v, u),
+        }
+    }
+
+    /// Image from the file at `path`. Floating point images, such as HDR and
+    /// EXR files, keep their full precision, and the others are converted to
+    /// 8-bit sRGB.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, image::ImageError> {
+        Self::decode(Reader::open(path)?)
+    }
+
+    /// Image from an encoded file in memory, e.g. one embedded with
+    /// `include_bytes!`. The format is guessed from the content, and the
+    /// pixels are kept as in [`Image::open`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, image::ImageError> {
+        Self::decode(Reader::new(Cursor::new(bytes)).with_guessed_format()?)
+    }
+
+    fn decode<R: BufRead + Seek>(reader: Reader<R>) -> Result<Self, image::ImageError> {
+        if reader.format() == Some(ImageFormat::Hdr) {
+            // `decode` quantizes HDR files to 8 bits, so read the floats directly
+            let decoder = HdrDecoder::new(reader.into_inner())?;
+            let metadata = decoder.metadata();
+            let pixels = decoder.read_image_hdr()?;
+            let image = Rgb32FImage::from_vec(
+                metadata.width,
+                metadata.height,
+                pixels.into_iter().flat_map(|pixel| pixel.0).collect(),
+            )
+            .expect("HDR decoder returns a pixel for each of width times height");
+            return Ok(Self::new_hdr(image));
+        }
+
+        let image = reader.decode()?;
+        Ok(match image {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                Self::new_hdr(image.into_rgb32f())
+            }
+            _ => Self::new(image.to_rgb8()),
+        })
+    }
+
+    /// Luminance of the stored pixels as 8-bit gray, before they are turned
+    /// or decoded from sRGB.
+    pub(crate) fn to_luma8(&self) -> GrayImage {
+        match &self.pixels {
+            Pixels::Rgb8(image) => DynamicImage::ImageRgb8(image.clone()).to_luma8(),
+            Pixels::Rgb32F(image) => DynamicImage::ImageRgb32F(image.clone()).to_luma8(),
+        }
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        match &self.pixels {
+            Pixels::Rgb8(image) => image.dimensions(),
+            Pixels::Rgb32F(image) => image.dimensions(),
+        }
     }
 }
 
 impl Texture for Image {
     fn color(&self, _point: crate::Point3, u: f64, v: f64) -> Color {
+        let (u, v) = self.orient(u, v);
         // - epsilons are used to prevent out-of-bounds errors
         let u = u.clamp(0.0, 1.0 - f64::EPSILON);
         // Flip v to image coordinates
-        let v = 1.0 - v.clamp(0.0, 1.0 - f64::EPSILON);
-
-        let width = self.image.width() as f64;
-        let height = self.image.height() as f64;
+        let v = (1.0 - v).clamp(0.0, 1.0 - f64::EPSILON);
 
-        let x = (width * u) as u32;
-        let y = (height * v) as u32;
+        let (width, height) = self.dimensions();
+        let x = (width as f64 * u) as u32;
+        let y = (height as f64 * v) as u32;
 
-        self.image.get_pixel(x, y).0.into()
+        match &self.pixels {
+            Pixels::Rgb8(image) => {
+                let [r, g, b] = image.get_pixel(x, y).0;
+                if self.srgb {
+                    Color::from_srgb8(r, g, b)
+                } else {
+                    Color::from_rgb8(r, g, b)
+                }
+            }
+            Pixels::Rgb32F(image) => {
+                let [r, g, b] = image.get_pixel(x, y).0;
+                Color::new(r as f64, g as f64, b as f64)
+            }
+        }
     }
 }
 
@@ -45,11 +193,82 @@ mod tests {
 
     #[test]
     fn read_image() {
-        let image = Reader::open("texture/earthmap.jpg")
-            .unwrap()
-            .decode()
-            .unwrap()
-            .into_rgba32f();
-        assert_eq!(image.get_pixel(0, 0).0, [1.0, 1.0, 1.0, 1.0]);
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/white_red.png");
+        let image = Image::open(std::path::PathBuf::from(path)).unwrap();
+        assert_eq!(
+            image.color(crate::Point3::zeros(), 0.25, 0.5),
+            Color::constant(1.0)
+        );
+        assert_eq!(
+            image.color(crate::Point3::zeros(), 0.75, 0.5),
+            Color::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn read_image_from_bytes() {
+        let bytes = include_bytes!("../../tests/fixtures/white_red.png");
+        let image = Image::from_bytes(bytes).unwrap();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(
+            image.color(crate::Point3::zeros(), 0.75, 0.5),
+            Color::new(1.0, 0.0, 0.0)
+        );
+
+        let hdr = include_bytes!("../../tests/fixtures/two_pixels.hdr");
+        let image = Image::from_bytes(hdr).unwrap();
+        assert!((image.color(crate::Point3::zeros(), 0.25, 0.5).r() - 2.0).abs() < 1e-2);
+
+        assert!(Image::from_bytes(b"not an image").is_err());
+    }
+
+    #[test]
+    fn decodes_srgb() {
+        let image = Image::new(RgbImage::from_pixel(1, 1, image::Rgb([128, 255, 0])));
+        let color = image.color(crate::Point3::zeros(), 0.5, 0.5);
+        assert_eq!(color, Color::from_srgb8(128, 255, 0));
+        assert!((color.r() - 0.21586).abs() < 1e-5);
+        let linear = image.linear().color(crate::Point3::zeros(), 0.5, 0.5);
+        assert_eq!(linear, Color::new(128.0 / 255.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn hdr_keeps_bright_pixels() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/two_pixels.hdr");
+        let image = Image::open(path).unwrap();
+        let color = |u| image.color(crate::Point3::zeros(), u, 0.5);
+        // brighter than white, and not decoded as sRGB
+        assert!((color(0.25) - Color::new(2.0, 0.5, 0.25)).norm() < 1e-2);
+        assert!((color(0.75) - Color::constant(0.25)).norm() < 1e-2);
+    }
+
+    #[test]
+    fn orientation() {
+        // top left, top right, bottom left, bottom right of the stored image
+        let texels = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        let image = Image::new(image::ImageBuffer::from_fn(2, 2, |x, y| {
+            image::Rgb(texels[(2 * y + x) as usize])
+        }));
+        let corners = |image: &Image| {
+            [(0.25, 0.75), (0.75, 0.75), (0.25, 0.25), (0.75, 0.25)]
+                .map(|(u, v)| image.color(crate::Point3::zeros(), u, v))
+        };
+        let expected = |order: [usize; 4]| order.map(|i| Color::from(texels[i]));
+
+        assert_eq!(corners(&image), expected([0, 1, 2, 3]));
+        assert_eq!(corners(&image.clone().flipped_u()), expected([1, 0, 3, 2]));
+        assert_eq!(corners(&image.clone().flipped_v()), expected([2, 3, 0, 1]));
+        assert_eq!(corners(&image.clone().rotated(1)), expected([1, 3, 0, 2]));
+        assert_eq!(corners(&image.clone().rotated(2)), expected([3, 2, 1, 0]));
+        assert_eq!(corners(&image.clone().rotated(3)), expected([2, 0, 3, 1]));
+        assert_eq!(
+            corners(&image.clone().rotated(5)),
+            corners(&image.clone().rotated(1))
+        );
+        // turning comes before mirroring, whatever the builder order
+        assert_eq!(
+            corners(&image.clone().flipped_u().rotated(1)),
+            expected([3, 1, 2, 0])
+        );
     }
 }
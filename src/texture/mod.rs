@@ -1,13 +1,23 @@
 mod noise;
 mod perlin;
 mod image;
+mod uv_grid;
+mod uv_pattern;
+mod uv_transform;
+mod closure;
+mod blend;
 
 use std::fmt::Debug;
 
 use crate::{Color, Point3};
 
-pub use noise::Noise;
+pub use noise::{Axis, Noise, NoiseBuilder, NoiseStyle};
 pub use self::image::Image;
+pub use uv_grid::UvGrid;
+pub use uv_pattern::{Stripes, UvChecker, UvDirection};
+pub use uv_transform::{UvTransform, UvWrap};
+pub use closure::FnTexture;
+pub use blend::Blend;
 
 /// A texture usually means a function that makes the colors on a surface procedural.
 /// This procedure can be synthesis code, or it could be an image lookup, or a
@@ -20,8 +30,37 @@ pub trait Texture: Sync + Send + Debug {
     /// * `point` - The point on the surface of the object.
     /// * `u`, `v` - The texture coordinates corresponding to the point.
     fn color(&self, point: Point3, u: f64, v: f64) -> Color;
+
+    /// The color of the texture at a given point, seen by a ray at `time`.
+    ///
+    /// Only animated textures depend on the time, the default ignores it.
+    #[allow(unused_variables)] // This is a default implementation, so the arguments may not be used.
+    fn color_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        self.color(point, u, v)
+    }
 }
 
+/// Chainable wrappers for any [`Texture`], e.g.
+/// `Image::open(path)?.tiled(4.0, 2.0)`.
+pub trait TextureExt: Texture + Sized {
+    /// Repeat the texture `u` times along `u` and `v` times along `v`.
+    fn tiled(self, u: f64, v: f64) -> UvTransform<Self> {
+        UvTransform::new(self).with_scale(u, v)
+    }
+
+    /// Move the texture by `(u, v)` in texture space, wrapping it around.
+    fn uv_offset(self, u: f64, v: f64) -> UvTransform<Self> {
+        UvTransform::new(self).with_offset(u, v)
+    }
+
+    /// Turn the texture by `degrees` counterclockwise in texture space.
+    fn uv_rotated(self, degrees: f64) -> UvTransform<Self> {
+        UvTransform::new(self).with_rotation_degrees(degrees)
+    }
+}
+
+impl<T: Texture> TextureExt for T {}
+
 /// A solid color texture.
 #[derive(Debug, Clone)]
 pub struct SolidColor {
@@ -48,6 +87,50 @@ impl Texture for SolidColor {
     }
 }
 
+/// How space is split into the alternating cells of a 3D checker pattern.
+///
+/// Shared by [`Checker`] and [`crate::material::CheckerMaterial`], so that
+/// their patterns line up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckerPattern {
+    /// Cubes `1 / scale` wide, which are odd when the sum of the indices of
+    /// the cube along each axis is odd.
+    Parity { scale: f64 },
+    /// The sign of `sin(f x) sin(f y) sin(f z)` with `frequency` f, as in the
+    /// book.
+    ///
+    /// The product is zero on the planes where one of the sines is, such as
+    /// `y = 0`, so surfaces lying in one of them get stripes or noise instead
+    /// of squares.
+    Sine { frequency: f64 },
+}
+
+impl CheckerPattern {
+    /// Whether `point` is in an odd cell of the pattern.
+    pub fn is_odd(&self, point: Point3) -> bool {
+        match *self {
+            CheckerPattern::Parity { scale } => {
+                let cells = (scale * point).apply(f64::floor);
+                (cells.x() + cells.y() + cells.z()).rem_euclid(2.0) == 1.0
+            }
+            CheckerPattern::Sine { frequency } => {
+                let sines = (frequency * point).apply(f64::sin);
+                sines.x() * sines.y() * sines.z() < 0.0
+            }
+        }
+    }
+}
+
+impl Default for CheckerPattern {
+    /// Cubes `PI / 10` wide, which are the squares of the sine pattern of
+    /// frequency 10 of the book.
+    fn default() -> Self {
+        CheckerPattern::Parity {
+            scale: 10.0 / std::f64::consts::PI,
+        }
+    }
+}
+
 /// A checker texture that forms a 3D checker pattern
 #[derive(Debug, Clone)]
 pub struct Checker<O, E>
@@ -57,6 +140,7 @@ where
 {
     odd: O,
     even: E,
+    pattern: CheckerPattern,
 }
 
 impl<O, E> Checker<O, E>
@@ -65,34 +149,83 @@ where
     E: Texture,
 {
     pub fn new(odd: O, even: E) -> Self {
-        Self { odd, even }
+        Self {
+            odd,
+            even,
+            pattern: CheckerPattern::default(),
+        }
+    }
+
+    /// The same checker with the cells of `pattern`, e.g. the sine pattern of
+    /// the book.
+    pub fn pattern(self, pattern: CheckerPattern) -> Self {
+        Self { pattern, ..self }
     }
 }
 
 impl Checker<SolidColor, SolidColor> {
     pub fn new_solids(odd: Color, even: Color) -> Self {
-        Self {
-            odd: SolidColor::new(odd),
-            even: SolidColor::new(even),
-        }
+        Self::new(SolidColor::new(odd), SolidColor::new(even))
     }
 }
 
-fn sines(point: &Point3) -> f64 {
-    let sines = (10.0 * point).apply(|x| x.sin());
-    sines.x() * sines.y() * sines.z()
-}
-
 impl<O, E> Texture for Checker<O, E>
 where
     O: Texture,
     E: Texture,
 {
     fn color(&self, point: Point3, u: f64, v: f64) -> Color {
-        if sines(&point).is_sign_negative() {
+        if self.pattern.is_odd(point) {
             self.odd.color(point, u, v)
         } else {
             self.even.color(point, u, v)
         }
     }
+
+    fn color_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        if self.pattern.is_odd(point) {
+            self.odd.color_at_time(point, u, v, time)
+        } else {
+            self.even.color_at_time(point, u, v, time)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_on_their_boundaries() {
+        let pattern = CheckerPattern::Parity { scale: 1.0 };
+        // a boundary belongs to the cell on its positive side
+        assert!(!pattern.is_odd(Point3::zeros()));
+        assert!(!pattern.is_odd(Point3::new(-0.0, -0.0, -0.0)));
+        assert!(pattern.is_odd(Point3::new(1.0, 0.0, 0.0)));
+        assert!(pattern.is_odd(Point3::new(0.0, 0.0, -1e-12)));
+        assert!(!pattern.is_odd(Point3::new(0.999, 0.0, 0.0)));
+        assert!(!pattern.is_odd(Point3::new(1.0, -1.0, 2.0)));
+        assert!(pattern.is_odd(Point3::new(-3.0, 4.0, 2.5)));
+    }
+
+    #[test]
+    fn ground_plane_has_squares() {
+        let checker = Checker::new_solids(Color::BLACK, Color::WHITE);
+        let sine = checker.clone().pattern(CheckerPattern::Sine { frequency: 10.0 });
+        let step = std::f64::consts::PI / 10.0;
+
+        // the centers of the squares alternate, like the sine pattern off the plane
+        for i in -5..5 {
+            for k in -5..5 {
+                let center = |y| Point3::new((i as f64 + 0.5) * step, y, (k as f64 + 0.5) * step);
+                let odd = (i + k) % 2 != 0;
+                let expected = if odd { Color::BLACK } else { Color::WHITE };
+                assert_eq!(checker.color(center(0.0), 0.0, 0.0), expected);
+                assert_eq!(checker.color(center(0.5 * step), 0.0, 0.0), expected);
+                assert_eq!(sine.color(center(0.5 * step), 0.0, 0.0), expected);
+                // on the plane, the sine pattern is the same everywhere
+                assert_eq!(sine.color(center(0.0), 0.0, 0.0), Color::WHITE);
+            }
+        }
+    }
 }
@@ -1,30 +1,287 @@
-use crate::Color;
+use crate::{Color, Point3};
 
 use super::{perlin::Perlin, Texture};
 
+/// A coordinate axis, along which the stripes of [`NoiseStyle::Marble`] go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// How [`Noise`] turns Perlin noise into a value between 0 and 1, which
+/// picks the color between its two colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseStyle {
+    /// Stripes along an axis, whose phase is disturbed by the turbulence at
+    /// the point
+    Marble,
+    /// The turbulence of the scaled point, like clouds, clamped to 1
+    Turbulence,
+    /// The sum of the octaves of noise at the scaled point, from `[-1, 1]`
+    /// to `[0, 1]`, like smoke
+    Raw,
+}
+
 /// A noise texture which uses Perlin noise to generate a color.
+///
+/// [`Noise::new`] is the gray marble of the book, and [`Noise::builder`]
+/// gives the other styles and colors.
 #[derive(Debug, Clone)]
 pub struct Noise {
     perlin: Perlin,
     /// The scale of the noise
     scale: f64,
+    /// How fast the pattern changes over time, zero for a static pattern
+    speed: f64,
+    /// Number of octaves summed into the turbulence
+    octaves: usize,
+    /// How much the turbulence moves the stripes of marble
+    phase: f64,
+    /// Axis along which the stripes of marble go
+    axis: Axis,
+    /// Colors at the values 0 and 1
+    colors: (Color, Color),
+    style: NoiseStyle,
 }
 
 impl Noise {
     pub fn new(scale: f64) -> Self {
-        Self {
-            perlin: Perlin::new(),
-            scale,
+        Self::animated(scale, 0.0)
+    }
+
+    /// Noise whose pattern evolves with the time of the rays, `speed` times
+    /// faster than the time.
+    pub fn animated(scale: f64, speed: f64) -> Self {
+        Self::builder().scale(scale).speed(speed).build()
+    }
+
+    /// The marble of [`Noise::new`], the same on every run for the same
+    /// `seed`.
+    pub fn with_seed(scale: f64, seed: u64) -> Self {
+        Self::builder().scale(scale).seed(seed).build()
+    }
+
+    /// Builder starting from the marble of [`Noise::new`] with a scale of 1.
+    pub fn builder() -> NoiseBuilder {
+        NoiseBuilder::new()
+    }
+
+    /// Value of the noise at a point and time `t`, between 0 and 1.
+    fn value(&self, point: Point3, t: f64) -> f64 {
+        match self.style {
+            NoiseStyle::Marble => {
+                let turbulence = self.perlin.turbulence4(&point, t, self.octaves);
+                let coord = match self.axis {
+                    Axis::X => point.x(),
+                    Axis::Y => point.y(),
+                    Axis::Z => point.z(),
+                };
+                0.5 * (1.0 + (self.scale * coord + self.phase * turbulence).sin())
+            }
+            NoiseStyle::Turbulence => {
+                let point = self.scale * point;
+                self.perlin.turbulence4(&point, t, self.octaves).min(1.0)
+            }
+            NoiseStyle::Raw => {
+                let point = self.scale * point;
+                let octaves = self.perlin.octaves4(&point, t, self.octaves);
+                (0.5 * (1.0 + octaves)).clamp(0.0, 1.0)
+            }
         }
     }
+
+    fn color_of(&self, value: f64) -> Color {
+        self.colors.0.lerp(self.colors.1, value)
+    }
 }
 
 impl Texture for Noise {
-    fn color(&self, point: crate::Point3, _u: f64, _v: f64) -> Color {
-        // marble-like
-        let phase = 10.0 * self.perlin.turbulence(&point, 7);
-        let value = (self.scale * point.z() + phase).sin();
-        let color = 0.5 * (1.0 + value);
-        Color::constant(color)
+    fn color(&self, point: Point3, _u: f64, _v: f64) -> Color {
+        self.color_of(self.value(point, 0.0))
+    }
+
+    fn color_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        if self.speed == 0.0 {
+            return self.color(point, u, v);
+        }
+        self.color_of(self.value(point, self.speed * time))
+    }
+}
+
+/// Builder of a [`Noise`] texture, e.g.
+/// `Noise::builder().scale(4.0).octaves(5).style(NoiseStyle::Turbulence).build()`.
+#[derive(Debug, Clone)]
+pub struct NoiseBuilder {
+    scale: f64,
+    speed: f64,
+    octaves: usize,
+    phase: f64,
+    axis: Axis,
+    colors: (Color, Color),
+    style: NoiseStyle,
+    seed: Option<u64>,
+}
+
+impl NoiseBuilder {
+    pub fn new() -> Self {
+        Self {
+            scale: 1.0,
+            speed: 0.0,
+            octaves: 7,
+            phase: 10.0,
+            axis: Axis::Z,
+            colors: (Color::BLACK, Color::WHITE),
+            style: NoiseStyle::Marble,
+            seed: None,
+        }
+    }
+
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// See [`Noise::animated`].
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `octaves` is zero.
+    pub fn octaves(mut self, octaves: usize) -> Self {
+        assert!(octaves > 0, "noise needs at least one octave");
+        self.octaves = octaves;
+        self
+    }
+
+    /// How much the turbulence moves the stripes of marble, 10 by default.
+    pub fn phase(mut self, phase: f64) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    pub fn axis(mut self, axis: Axis) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    /// Colors at the noise values 0 and 1, black and white by default.
+    pub fn colors(mut self, low: Color, high: Color) -> Self {
+        self.colors = (low, high);
+        self
+    }
+
+    pub fn style(mut self, style: NoiseStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Seed of the noise, which is random otherwise, see [`Noise::with_seed`].
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Noise {
+        Noise {
+            perlin: self.seed.map_or_else(Perlin::new, Perlin::with_seed),
+            scale: self.scale,
+            speed: self.speed,
+            octaves: self.octaves,
+            phase: self.phase,
+            axis: self.axis,
+            colors: self.colors,
+            style: self.style,
+        }
+    }
+}
+
+impl Default for NoiseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animated_noise_changes_over_time() {
+        let point = Point3::new(1.3, -0.4, 2.7);
+
+        let animated = Noise::animated(4.0, 1.0);
+        let (before, after) = (
+            animated.color_at_time(point, 0.0, 0.0, 0.0),
+            animated.color_at_time(point, 0.0, 0.0, 1.0),
+        );
+        assert_ne!(before, after);
+        assert_eq!(before, animated.color(point, 0.0, 0.0));
+
+        let frozen = Noise::new(4.0);
+        for time in [0.0, 0.3, 1.0] {
+            let color = frozen.color_at_time(point, 0.0, 0.0, time);
+            assert_eq!(color, frozen.color(point, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn seeded_noise_repeats() {
+        let point = Point3::new(1.3, -0.4, 2.7);
+        let color = |noise: Noise| noise.color(point, 0.0, 0.0);
+        assert_eq!(
+            color(Noise::with_seed(4.0, 1)),
+            color(Noise::with_seed(4.0, 1))
+        );
+        assert_ne!(
+            color(Noise::with_seed(4.0, 1)),
+            color(Noise::with_seed(4.0, 2))
+        );
+    }
+
+    #[test]
+    fn styles_and_colors() {
+        let points: Vec<_> = (0..500)
+            .map(|i| Point3::new(0.37 * i as f64, 1.1 - 0.013 * i as f64, 0.21 * i as f64))
+            .collect();
+
+        // a single octave of raw noise is the noise itself, mapped to [0, 1]
+        let raw = Noise::builder()
+            .scale(3.0)
+            .octaves(1)
+            .style(NoiseStyle::Raw)
+            .build();
+        for &point in &points {
+            let value = raw.color(point, 0.0, 0.0);
+            let noise = raw.perlin.noise(&(3.0 * point));
+            assert!((value.r() - 0.5 * (1.0 + noise)).abs() < 1e-12);
+            assert!((0.0..=1.0).contains(&value.r()));
+        }
+
+        // the same marble as `Noise::new`, with the colors in between
+        let (low, high) = (Color::new(0.1, 0.0, 0.3), Color::new(0.9, 0.8, 0.5));
+        let gray = Noise::new(4.0);
+        let colored = Noise {
+            colors: (low, high),
+            ..gray.clone()
+        };
+        for &point in &points {
+            let value = gray.color(point, 0.0, 0.0).r();
+            let color = colored.color(point, 0.0, 0.0);
+            assert!((color - low.lerp(high, value)).norm() < 1e-12);
+        }
+
+        let clouds = Noise::builder()
+            .scale(2.0)
+            .style(NoiseStyle::Turbulence)
+            .build();
+        assert!(points.iter().all(|&point| {
+            let value = clouds.color(point, 0.0, 0.0).r();
+            (0.0..=1.0).contains(&value)
+        }));
     }
 }
@@ -1,4 +1,4 @@
-use rand::seq::SliceRandom;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
 use crate::{Point3, Vec3};
 
@@ -20,10 +20,8 @@ pub struct Perlin {
 }
 
 fn corner_iterator() -> impl Iterator<Item = (usize, usize, usize)> {
-    (0..2_usize).into_iter().flat_map(move |i| {
-        (0..2_usize)
-            .into_iter()
-            .flat_map(move |j| (0..2_usize).into_iter().map(move |k| (i, j, k)))
+    (0..2_usize).flat_map(move |i| {
+        (0..2_usize).flat_map(move |j| (0..2_usize).map(move |k| (i, j, k)))
     })
 }
 
@@ -31,16 +29,27 @@ impl Perlin {
     const POINT_COUNT: usize = 256;
 
     pub fn new() -> Self {
-        let mut rng = rand::thread_rng();
+        Self::from_rng(&mut rand::thread_rng())
+    }
+
+    /// Noise which is the same for the same `seed`, on every run.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: &mut impl Rng) -> Self {
         let range = 0..Self::POINT_COUNT;
         let random_vectors = range
             .clone()
-            .map(|_| Vec3::random(-1.0..1.0).normalized())
+            .map(|_| {
+                let mut coordinate = || rng.gen_range(-1.0..1.0);
+                Vec3::new(coordinate(), coordinate(), coordinate()).normalized()
+            })
             .collect();
 
         let mut perm = || {
             let mut vec = range.clone().collect::<Vec<_>>();
-            vec.shuffle(&mut rng);
+            vec.shuffle(rng);
             vec
         };
 
@@ -86,19 +95,65 @@ impl Perlin {
         self.perlin_interpolation(point, intermediate)
     }
 
-    /// Get the turbulence value at a point, which a composite noise that has multiple
-    /// summed frenquencies
-    pub fn turbulence(&self, point: &Point3, depth: usize) -> f64 {
-        let result = (0..depth)
-            .fold((0.0, *point, 1.0), |(result, point, weight), _| {
+    /// Get the noise value at a point and time `t`.
+    ///
+    /// Every integer `t` is a slice of the 3D noise far away from the others,
+    /// and times in between blend the two slices around them smoothly. At
+    /// integer times this is [`Perlin::noise`] of a shifted point, and at
+    /// `t = 0` exactly [`Perlin::noise`].
+    pub fn noise4(&self, point: &Point3, t: f64) -> f64 {
+        // far enough that neighboring slices look unrelated
+        const SLICE_OFFSET: Vec3<f64> = Vec3::new(37.7, 59.3, 71.1);
+
+        let floor = t.floor();
+        let fraction = t - floor;
+        let smoothed = fraction * fraction * (3.0 - 2.0 * fraction);
+        let slice = |w: f64| self.noise(&(*point + w * SLICE_OFFSET));
+
+        let below = slice(floor);
+        if smoothed == 0.0 {
+            return below;
+        }
+        (1.0 - smoothed) * below + smoothed * slice(floor + 1.0)
+    }
+
+    /// Get the turbulence value at a point and time `t`, which a composite
+    /// noise that has multiple summed frenquencies, see [`Perlin::noise4`].
+    pub fn turbulence4(&self, point: &Point3, t: f64, depth: usize) -> f64 {
+        self.octaves4(point, t, depth).abs()
+    }
+
+    /// Sum of `depth` octaves of noise at a point and time `t`, each at twice
+    /// the frequency and half the weight of the one before, which is the
+    /// turbulence before taking its absolute value.
+    pub fn octaves4(&self, point: &Point3, t: f64, depth: usize) -> f64 {
+        (0..depth)
+            .fold((0.0, *point, t, 1.0), |(result, point, t, weight), _| {
                 (
-                    result + weight * self.noise(&point),
+                    result + weight * self.noise4(&point, t),
                     point * 2.0,
+                    t * 2.0,
                     weight * 0.5,
                 )
             })
-            .0;
+            .0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_noise() {
+        let points: Vec<_> = (0..10)
+            .flat_map(|i| (0..10).map(move |k| Point3::new(0.3 * i as f64, 0.7, 0.45 * k as f64)))
+            .collect();
+        let noise = |perlin: &Perlin| points.iter().map(|p| perlin.noise(p)).collect::<Vec<_>>();
 
-        result.abs()
+        let first = noise(&Perlin::with_seed(7));
+        assert_eq!(first, noise(&Perlin::with_seed(7)));
+        assert_ne!(first, noise(&Perlin::with_seed(8)));
+        assert_ne!(first, noise(&Perlin::new()));
     }
 }
@@ -0,0 +1,86 @@
+use crate::{Color, Point3};
+
+use super::Texture;
+
+/// Debug texture that shows the texture coordinates of a surface.
+///
+/// Inside the unit square, the red channel is `u` and the green channel is
+/// `v`, so the orientation of the mapping is visible at a glance. Grid lines
+/// are drawn with full blue on top of that, and cells have no blue. Texture
+/// coordinates outside the unit square are [`UvGrid::OUT_OF_RANGE`], and NaN
+/// ones are [`UvGrid::NAN`].
+#[derive(Debug, Clone)]
+pub struct UvGrid {
+    /// Number of grid cells along each of `u` and `v`
+    pub frequency: f64,
+    /// Width of the grid lines, as a fraction of a cell
+    pub thickness: f64,
+}
+
+impl UvGrid {
+    /// Color of texture coordinates outside `[0, 1]`.
+    pub const OUT_OF_RANGE: Color = Color::new(0.5, 0.5, 0.5);
+    /// Color of NaN texture coordinates.
+    pub const NAN: Color = Color::new(1.0, 0.0, 0.5);
+
+    /// # Panics
+    ///
+    /// Panics if `frequency` is not positive, or if `thickness` is not in `[0, 1]`.
+    pub fn new(frequency: f64, thickness: f64) -> Self {
+        assert!(frequency > 0.0, "grid frequency must be positive");
+        assert!(
+            (0.0..=1.0).contains(&thickness),
+            "grid line thickness must be between 0 and 1"
+        );
+        Self {
+            frequency,
+            thickness,
+        }
+    }
+
+    /// Whether `coord` is on a grid line.
+    fn on_line(&self, coord: f64) -> bool {
+        let offset = (coord * self.frequency).fract();
+        let half = self.thickness / 2.0;
+        offset < half || offset > 1.0 - half
+    }
+}
+
+impl Default for UvGrid {
+    /// Ten cells along each direction, with lines a tenth of a cell wide.
+    fn default() -> Self {
+        Self::new(10.0, 0.1)
+    }
+}
+
+impl Texture for UvGrid {
+    fn color(&self, _point: Point3, u: f64, v: f64) -> Color {
+        if u.is_nan() || v.is_nan() {
+            return Self::NAN;
+        }
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return Self::OUT_OF_RANGE;
+        }
+
+        let line = self.on_line(u) || self.on_line(v);
+        Color::new(u, v, if line { 1.0 } else { 0.0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documented_colors() {
+        let grid = UvGrid::default();
+        let color = |u, v| grid.color(Point3::zeros(), u, v);
+
+        assert_eq!(color(0.0, 0.0), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(color(1.0, 1.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(color(0.5, 0.5), Color::new(0.5, 0.5, 1.0));
+        assert_eq!(color(0.25, 0.75), Color::new(0.25, 0.75, 0.0));
+        assert_eq!(color(1.5, 0.5), UvGrid::OUT_OF_RANGE);
+        assert_eq!(color(f64::NAN, 0.5), UvGrid::NAN);
+    }
+}
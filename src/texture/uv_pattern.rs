@@ -0,0 +1,197 @@
+use crate::{Color, Point3};
+
+use super::{SolidColor, Texture};
+
+/// Checker pattern in texture space, whose squares follow the texture
+/// coordinates of the surface rather than the world axes, e.g. the lines of
+/// longitude and latitude on a sphere.
+///
+/// There are `u_scale` squares along `u` and `v_scale` along `v` in the unit
+/// square. A square is odd when the sum of its indices along `u` and `v` is
+/// odd.
+#[derive(Debug, Clone)]
+pub struct UvChecker<O: Texture, E: Texture> {
+    odd: O,
+    even: E,
+    u_scale: f64,
+    v_scale: f64,
+}
+
+impl<O: Texture, E: Texture> UvChecker<O, E> {
+    /// # Panics
+    ///
+    /// Panics if `u_scale` or `v_scale` is not positive.
+    pub fn new(odd: O, even: E, u_scale: f64, v_scale: f64) -> Self {
+        assert!(
+            u_scale > 0.0 && v_scale > 0.0,
+            "checker scales must be positive"
+        );
+        Self {
+            odd,
+            even,
+            u_scale,
+            v_scale,
+        }
+    }
+
+    fn is_odd(&self, u: f64, v: f64) -> bool {
+        let cells = (u * self.u_scale).floor() + (v * self.v_scale).floor();
+        cells.rem_euclid(2.0) == 1.0
+    }
+}
+
+impl UvChecker<SolidColor, SolidColor> {
+    pub fn new_solids(odd: Color, even: Color, u_scale: f64, v_scale: f64) -> Self {
+        Self::new(
+            SolidColor::new(odd),
+            SolidColor::new(even),
+            u_scale,
+            v_scale,
+        )
+    }
+}
+
+impl<O: Texture, E: Texture> Texture for UvChecker<O, E> {
+    fn color(&self, point: Point3, u: f64, v: f64) -> Color {
+        if self.is_odd(u, v) {
+            self.odd.color(point, u, v)
+        } else {
+            self.even.color(point, u, v)
+        }
+    }
+
+    fn color_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        if self.is_odd(u, v) {
+            self.odd.color_at_time(point, u, v, time)
+        } else {
+            self.even.color_at_time(point, u, v, time)
+        }
+    }
+}
+
+/// Texture coordinate along which [`Stripes`] alternate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UvDirection {
+    U,
+    V,
+}
+
+/// Stripes of two textures alternating along `u` or `v`, such as the bands
+/// of a beach ball or a planet.
+///
+/// There are `count` stripes in the unit square, starting with the first
+/// texture at 0.
+#[derive(Debug, Clone)]
+pub struct Stripes<A: Texture, B: Texture> {
+    first: A,
+    second: B,
+    direction: UvDirection,
+    count: f64,
+}
+
+impl<A: Texture, B: Texture> Stripes<A, B> {
+    /// # Panics
+    ///
+    /// Panics if `count` is not positive.
+    pub fn new(first: A, second: B, direction: UvDirection, count: f64) -> Self {
+        assert!(count > 0.0, "stripe count must be positive");
+        Self {
+            first,
+            second,
+            direction,
+            count,
+        }
+    }
+
+    fn is_second(&self, u: f64, v: f64) -> bool {
+        let coord = match self.direction {
+            UvDirection::U => u,
+            UvDirection::V => v,
+        };
+        (coord * self.count).floor().rem_euclid(2.0) == 1.0
+    }
+}
+
+impl Stripes<SolidColor, SolidColor> {
+    pub fn new_solids(first: Color, second: Color, direction: UvDirection, count: f64) -> Self {
+        Self::new(
+            SolidColor::new(first),
+            SolidColor::new(second),
+            direction,
+            count,
+        )
+    }
+}
+
+impl<A: Texture, B: Texture> Texture for Stripes<A, B> {
+    fn color(&self, point: Point3, u: f64, v: f64) -> Color {
+        if self.is_second(u, v) {
+            self.second.color(point, u, v)
+        } else {
+            self.first.color(point, u, v)
+        }
+    }
+
+    fn color_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        if self.is_second(u, v) {
+            self.second.color_at_time(point, u, v, time)
+        } else {
+            self.first.color_at_time(point, u, v, time)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use super::*;
+    use crate::{material::DiffuseLight, texture::Image, Hit, Interval, Ray, Sphere, Vec3};
+
+    #[test]
+    fn checker_follows_longitude_and_latitude() {
+        let (black, white) = (Color::BLACK, Color::WHITE);
+        let checker = UvChecker::new_solids(black, white, 8.0, 4.0);
+        let sphere = Sphere::new(Point3::zeros(), 1.0, Arc::new(DiffuseLight::new(checker)));
+        // the color seen from the direction at `u` and `v` on the sphere
+        let emitted_at = |u: f64, v: f64| {
+            let (phi, theta) = (2.0 * PI * u - PI, PI * v);
+            let direction = Vec3::new(
+                theta.sin() * phi.cos(),
+                -theta.cos(),
+                -theta.sin() * phi.sin(),
+            );
+            let ray = Ray::new(2.0 * direction, -direction, 0.0);
+            sphere.hit(ray, Interval::from_min(0.0)).unwrap().emitted
+        };
+
+        for i in 0..8 {
+            for k in 0..4 {
+                let expected = if (i + k) % 2 == 1 { black } else { white };
+                // anywhere inside the square between the meridians and parallels
+                for (du, dv) in [(0.5, 0.5), (0.1, 0.2), (0.9, 0.8)] {
+                    let (u, v) = ((i as f64 + du) / 8.0, (k as f64 + dv) / 4.0);
+                    assert_eq!(emitted_at(u, v), expected, "{u} {v}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stripes_of_images() {
+        let image = |rgb| Image::new(image::RgbImage::from_pixel(1, 1, image::Rgb(rgb)));
+        let stripes = Stripes::new(image([255, 0, 0]), image([0, 0, 255]), UvDirection::V, 4.0);
+        let color = |u, v| stripes.color(Point3::zeros(), u, v);
+        let (red, blue) = (Color::new(1.0, 0.0, 0.0), Color::new(0.0, 0.0, 1.0));
+
+        assert_eq!(color(0.1, 0.1), red);
+        assert_eq!(color(0.9, 0.1), red);
+        assert_eq!(color(0.1, 0.3), blue);
+        assert_eq!(color(0.5, 0.5), red);
+        assert_eq!(color(0.5, 0.99), blue);
+
+        let across = Stripes::new_solids(red, blue, UvDirection::U, 2.0);
+        assert_eq!(across.color(Point3::zeros(), 0.25, 0.9), red);
+        assert_eq!(across.color(Point3::zeros(), 0.75, 0.1), blue);
+    }
+}
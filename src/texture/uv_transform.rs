@@ -0,0 +1,173 @@
+use crate::{Color, Point3};
+
+use super::Texture;
+
+/// How [`UvTransform`] brings texture coordinates outside the unit square
+/// back into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UvWrap {
+    /// Tile the texture, so that 1.25 is 0.25
+    #[default]
+    Repeat,
+    /// Tile the texture mirrored every other time, so that 1.25 is 0.75
+    Mirror,
+    /// Stretch the edges of the texture, so that 1.25 is 1
+    Clamp,
+}
+
+impl UvWrap {
+    fn wrap(self, coord: f64) -> f64 {
+        match self {
+            UvWrap::Repeat => coord.rem_euclid(1.0),
+            UvWrap::Mirror => {
+                let coord = coord.rem_euclid(2.0);
+                if coord > 1.0 {
+                    2.0 - coord
+                } else {
+                    coord
+                }
+            }
+            UvWrap::Clamp => coord.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// A texture whose texture coordinates are scaled, rotated and moved, e.g. to
+/// tile an image over a surface.
+///
+/// The coordinates `(u, v)` of the surface are first rotated counterclockwise
+/// about the center of the unit square, then scaled, then offset, and finally
+/// wrapped back into the unit square before looking up the inner texture. The
+/// point on the surface is passed through as it is.
+#[derive(Debug, Clone)]
+pub struct UvTransform<T: Texture> {
+    texture: T,
+    scale: (f64, f64),
+    offset: (f64, f64),
+    rotation_degrees: f64,
+    wrap: UvWrap,
+}
+
+impl<T: Texture> UvTransform<T> {
+    /// The same texture, with no transform and repeating.
+    pub fn new(texture: T) -> Self {
+        Self {
+            texture,
+            scale: (1.0, 1.0),
+            offset: (0.0, 0.0),
+            rotation_degrees: 0.0,
+            wrap: UvWrap::default(),
+        }
+    }
+
+    /// Repeat the texture `u` times along `u` and `v` times along `v`.
+    pub fn with_scale(mut self, u: f64, v: f64) -> Self {
+        self.scale = (u, v);
+        self
+    }
+
+    pub fn with_offset(mut self, u: f64, v: f64) -> Self {
+        self.offset = (u, v);
+        self
+    }
+
+    pub fn with_rotation_degrees(mut self, rotation_degrees: f64) -> Self {
+        self.rotation_degrees = rotation_degrees;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: UvWrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Coordinates in the inner texture for `(u, v)` on the surface.
+    fn remap(&self, u: f64, v: f64) -> (f64, f64) {
+        let (sin, cos) = self.rotation_degrees.to_radians().sin_cos();
+        let (du, dv) = (u - 0.5, v - 0.5);
+        let (u, v) = (cos * du - sin * dv + 0.5, sin * du + cos * dv + 0.5);
+        (
+            self.wrap.wrap(u * self.scale.0 + self.offset.0),
+            self.wrap.wrap(v * self.scale.1 + self.offset.1),
+        )
+    }
+}
+
+impl<T: Texture> Texture for UvTransform<T> {
+    fn color(&self, point: Point3, u: f64, v: f64) -> Color {
+        let (u, v) = self.remap(u, v);
+        self.texture.color(point, u, v)
+    }
+
+    fn color_at_time(&self, point: Point3, u: f64, v: f64, time: f64) -> Color {
+        let (u, v) = self.remap(u, v);
+        self.texture.color_at_time(point, u, v, time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::DiffuseLight,
+        object::Quad,
+        texture::{Image, TextureExt},
+        Hit, Interval, Ray, Vec3,
+    };
+
+    #[test]
+    fn tiled_checker_has_more_columns() {
+        let checker = image::RgbImage::from_fn(2, 2, |x, y| {
+            image::Rgb([if (x + y) % 2 == 0 { 255 } else { 0 }; 3])
+        });
+        let texture = Image::new(checker).tiled(4.0, 1.0);
+        let quad = Quad::new_xy(
+            (0.0, 0.0),
+            (1.0, 1.0),
+            0.0,
+            Arc::new(DiffuseLight::new(texture)),
+        );
+        let emitted = |x: f64| {
+            let ray = Ray::new(Point3::new(x, 0.25, 1.0), -Vec3::unit_z(), 0.0);
+            quad.hit(ray, Interval::from_min(0.0)).unwrap().emitted
+        };
+
+        // columns of alternating color along a row
+        let colors: Vec<_> = (0..80).map(|i| emitted((i as f64 + 0.5) / 80.0)).collect();
+        let columns = 1 + colors.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        assert_eq!(columns, 8);
+    }
+
+    #[test]
+    fn wraps_and_rotates() {
+        #[derive(Debug, Clone)]
+        struct Coordinates;
+        impl Texture for Coordinates {
+            fn color(&self, _point: Point3, u: f64, v: f64) -> Color {
+                Color::new(u, v, 0.0)
+            }
+        }
+        let uv = |texture: &UvTransform<Coordinates>, u, v| {
+            let color = texture.color(Point3::zeros(), u, v);
+            (
+                (color.r() * 1e9).round() / 1e9,
+                (color.g() * 1e9).round() / 1e9,
+            )
+        };
+
+        let shifted = UvTransform::new(Coordinates).with_offset(0.5, -0.25);
+        assert_eq!(uv(&shifted, 0.75, 0.5), (0.25, 0.25));
+        let mirrored = shifted.clone().with_wrap(UvWrap::Mirror);
+        assert_eq!(uv(&mirrored, 0.75, 0.5), (0.75, 0.25));
+        assert_eq!(uv(&mirrored, 0.75, 0.0), (0.75, 0.25));
+        let clamped = shifted.with_wrap(UvWrap::Clamp);
+        assert_eq!(uv(&clamped, 0.75, 0.0), (1.0, 0.0));
+
+        // a quarter turn counterclockwise about the center
+        let turned = UvTransform::new(Coordinates).with_rotation_degrees(90.0);
+        assert_eq!(uv(&turned, 0.75, 0.5), (0.5, 0.75));
+        assert_eq!(uv(&turned, 0.75, 0.25), (0.75, 0.75));
+    }
+}
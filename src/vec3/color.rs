@@ -1,6 +1,40 @@
+use std::{ops::Range, sync::OnceLock};
+
+use log::warn;
+
 pub type Color = super::Vec3<f64>;
 
 pub const COLOR_MAX: f64 = 255.0;
+
+/// Wavelengths of visible light in nanometers, see [`Color::from_wavelength`].
+pub const VISIBLE_WAVELENGTHS: Range<f64> = 380.0..780.0;
+
+/// Piecewise Gaussian with a different width on each side of `mean`.
+fn lobe(x: f64, mean: f64, width_below: f64, width_above: f64) -> f64 {
+    let width = if x < mean { width_below } else { width_above };
+    (-0.5 * ((x - mean) / width).powi(2)).exp()
+}
+
+/// Linear sRGB of light of a single `wavelength` in nanometers, with the
+/// colors outside the gamut clipped to it.
+///
+/// The CIE 1931 color matching functions are the multi-lobe fit of Wyman,
+/// Sloan and Shirley, "Simple Analytic Approximations to the CIE XYZ Color
+/// Matching Functions" (2013).
+fn spectral_rgb(wavelength: f64) -> Color {
+    let w = wavelength;
+    let x = 1.056 * lobe(w, 599.8, 37.9, 31.0) + 0.362 * lobe(w, 442.0, 16.0, 26.7)
+        - 0.065 * lobe(w, 501.1, 20.4, 26.2);
+    let y = 0.821 * lobe(w, 568.8, 46.9, 40.5) + 0.286 * lobe(w, 530.9, 16.3, 31.1);
+    let z = 1.217 * lobe(w, 437.0, 11.8, 36.0) + 0.681 * lobe(w, 459.0, 26.0, 13.8);
+
+    let rgb = Color::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    );
+    rgb.max(&Color::BLACK)
+}
 impl Color {
     pub const BLACK: Self = Self::new(0.0, 0.0, 0.0);
     pub const WHITE: Self = Self::new(1.0, 1.0, 1.0);
@@ -15,14 +49,15 @@ impl Color {
     /// - The PPM color string is of the form "R G B".
     /// - Colors are "gamma corrected" by raising them to the power of 1/2.
     pub fn format_color(&self) -> String {
-        let color = self;
-        let color = color.sqrt().clamp(0.0, 0.999);
-        let color = (COLOR_MAX * color).round();
+        let [r, g, b] = self.to_rgb8();
+        format!("{} {} {}", r, g, b)
+    }
 
-        format!(
-            "{} {} {}",
-            color[0] as u64, color[1] as u64, color[2] as u64
-        )
+    /// Gamma corrected 8-bit channels of the color, as written to images.
+    pub fn to_rgb8(&self) -> [u8; 3] {
+        let color = self.sqrt().clamp(0.0, 0.999);
+        let color = (COLOR_MAX * color).round();
+        [color[0] as u8, color[1] as u8, color[2] as u8]
     }
 
     pub fn is_valid_color(&self) -> bool {
@@ -30,9 +65,105 @@ impl Color {
             .all(|x| x.is_finite() && (0.0..=1.0).contains(x))
     }
 
+    /// Map `value` onto a heat ramp going from blue (0.0) over green (0.5)
+    /// to red (1.0). Values outside `[0, 1]` are clamped.
+    pub fn heat(value: f64) -> Self {
+        let value = value.clamp(0.0, 1.0);
+        if value < 0.5 {
+            Self::BLUE.lerp(Self::GREEN, value * 2.0)
+        } else {
+            Self::GREEN.lerp(Self::RED, (value - 0.5) * 2.0)
+        }
+    }
+
+    /// Relative luminance of a linear color, with the Rec. 709 weights.
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.r() + 0.7152 * self.g() + 0.0722 * self.b()
+    }
+
     pub fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
         const COLOR_MAX: f64 = u8::MAX as f64;
-        Self::new(r as f64 / COLOR_MAX, g as f64 / COLOR_MAX, b as f64 / COLOR_MAX)
+        Self::new(
+            r as f64 / COLOR_MAX,
+            g as f64 / COLOR_MAX,
+            b as f64 / COLOR_MAX,
+        )
+    }
+
+    /// Linear color of 8-bit channels encoded with the sRGB transfer curve,
+    /// as in most JPEG and PNG images.
+    pub fn from_srgb8(r: u8, g: u8, b: u8) -> Self {
+        static LINEAR: OnceLock<[f64; 256]> = OnceLock::new();
+        let linear = LINEAR.get_or_init(|| {
+            std::array::from_fn(|i| {
+                let encoded = i as f64 / COLOR_MAX;
+                if encoded <= 0.04045 {
+                    encoded / 12.92
+                } else {
+                    ((encoded + 0.055) / 1.055).powf(2.4)
+                }
+            })
+        });
+        Self::new(linear[r as usize], linear[g as usize], linear[b as usize])
+    }
+
+    /// Approximate color of a blackbody at `temperature` Kelvin, scaled so
+    /// that the largest channel is 1.0.
+    ///
+    /// Uses Tanner Helland's fit, which is valid from 1000K to 40000K. Only
+    /// 1000K to 12000K is accepted here, other temperatures are clamped.
+    pub fn from_kelvin(temperature: f64) -> Self {
+        const KELVIN_RANGE: (f64, f64) = (1000.0, 12000.0);
+        if !(KELVIN_RANGE.0..=KELVIN_RANGE.1).contains(&temperature) {
+            warn!("color temperature {temperature}K clamped to {KELVIN_RANGE:?}");
+        }
+
+        let t = temperature.clamp(KELVIN_RANGE.0, KELVIN_RANGE.1) / 100.0;
+        let r = if t <= 66.0 {
+            255.0
+        } else {
+            329.698727446 * (t - 60.0).powf(-0.1332047592)
+        };
+        let g = if t <= 66.0 {
+            99.4708025861 * t.ln() - 161.1195681661
+        } else {
+            288.1221695283 * (t - 60.0).powf(-0.0755148492)
+        };
+        let b = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.5177312231 * (t - 10.0).ln() - 305.0447927307
+        };
+
+        let color = Self::new(r, g, b).clamp(0.0, 255.0);
+        color / color.max_component()
+    }
+
+    /// Color of light of a single `wavelength` in nanometers, as a weight
+    /// for one wavelength of white light.
+    ///
+    /// The colors are scaled so that they average to white over wavelengths
+    /// picked uniformly from [`VISIBLE_WAVELENGTHS`], which lets a renderer
+    /// trace one wavelength per ray and still get white light on average.
+    /// Wavelengths outside the visible range are black.
+    pub fn from_wavelength(wavelength: f64) -> Self {
+        static AVERAGE: OnceLock<Color> = OnceLock::new();
+        let average = AVERAGE.get_or_init(|| {
+            const STEPS: usize = 4000;
+            let step = (VISIBLE_WAVELENGTHS.end - VISIBLE_WAVELENGTHS.start) / STEPS as f64;
+            let sum = (0..STEPS)
+                .map(|i| spectral_rgb(VISIBLE_WAVELENGTHS.start + (i as f64 + 0.5) * step))
+                .fold(Color::BLACK, |sum, color| sum + color);
+            sum / STEPS as f64
+        });
+
+        if VISIBLE_WAVELENGTHS.contains(&wavelength) {
+            spectral_rgb(wavelength) / *average
+        } else {
+            Self::BLACK
+        }
     }
 }
 
@@ -41,3 +172,60 @@ impl From<[u8; 3]> for Color {
         Self::from_rgb8(pixel[0], pixel[1], pixel[2])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kelvin() {
+        let white = Color::from_kelvin(6600.0);
+        assert!(white.iter().all(|&c| c > 0.95), "6600K is {white}");
+
+        let warm = Color::from_kelvin(2700.0);
+        assert!(
+            warm.r() > warm.g() && warm.g() > warm.b(),
+            "2700K is {warm}"
+        );
+
+        let cool = Color::from_kelvin(10000.0);
+        assert!(
+            cool.b() > cool.g() && cool.g() > cool.r(),
+            "10000K is {cool}"
+        );
+        assert_eq!(cool.b(), 1.0);
+
+        assert_eq!(Color::from_kelvin(100.0), Color::from_kelvin(1000.0));
+    }
+
+    #[test]
+    fn srgb_decoding() {
+        assert_eq!(Color::from_srgb8(0, 255, 0), Color::new(0.0, 1.0, 0.0));
+        // mid gray on screen is about a fifth of white light
+        let gray = Color::from_srgb8(128, 188, 10);
+        assert!((gray.r() - 0.21586).abs() < 1e-5, "{gray}");
+        assert!((gray.g() - 0.50289).abs() < 1e-5, "{gray}");
+        // on the linear segment near black
+        assert!((gray.b() - 10.0 / 255.0 / 12.92).abs() < 1e-12, "{gray}");
+    }
+
+    #[test]
+    fn spectrum_averages_to_white() {
+        let steps = 1000;
+        let step = (VISIBLE_WAVELENGTHS.end - VISIBLE_WAVELENGTHS.start) / steps as f64;
+        let average = (0..steps)
+            .map(|i| Color::from_wavelength(VISIBLE_WAVELENGTHS.start + (i as f64 + 0.5) * step))
+            .fold(Color::BLACK, |sum, color| sum + color)
+            / steps as f64;
+        assert!((average - Color::WHITE).norm() < 1e-3, "{average}");
+
+        let red = Color::from_wavelength(650.0);
+        let green = Color::from_wavelength(530.0);
+        let blue = Color::from_wavelength(450.0);
+        assert!(red.r() > red.g() && red.r() > red.b(), "{red}");
+        assert!(green.g() > green.r() && green.g() > green.b(), "{green}");
+        assert!(blue.b() > blue.r() && blue.b() > blue.g(), "{blue}");
+        assert!(red.iter().chain(green.iter()).all(|&c| c >= 0.0));
+        assert_eq!(Color::from_wavelength(900.0), Color::BLACK);
+    }
+}
@@ -0,0 +1,215 @@
+use std::ops::Mul;
+
+use super::{Quat, Vec3};
+
+/// 3x3 matrix, for linear maps such as rotation and scaling.
+///
+/// `a * b` is the map `b` followed by `a`, and `m * v` maps the vector `v`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    rows: [Vec3<f64>; 3],
+}
+
+impl Mat3 {
+    pub const IDENTITY: Self = Self::from_rows([
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+    ]);
+
+    pub const fn from_rows(rows: [Vec3<f64>; 3]) -> Self {
+        Self { rows }
+    }
+
+    pub fn from_cols(cols: [Vec3<f64>; 3]) -> Self {
+        Self::from_rows(cols).transpose()
+    }
+
+    /// Scaling by `factors` along the x, y and z axes.
+    pub fn from_diagonal(factors: Vec3<f64>) -> Self {
+        Self::from_rows([
+            Vec3::new(factors.x(), 0.0, 0.0),
+            Vec3::new(0.0, factors.y(), 0.0),
+            Vec3::new(0.0, 0.0, factors.z()),
+        ])
+    }
+
+    /// Rotation by the unit quaternion `rotation`.
+    pub fn from_quat(rotation: Quat) -> Self {
+        Self::from_cols([
+            rotation.rotate_vec(Vec3::unit_x()),
+            rotation.rotate_vec(Vec3::unit_y()),
+            rotation.rotate_vec(Vec3::unit_z()),
+        ])
+    }
+
+    /// Rotation by `degree` around `axis`, see [`Quat::from_axis_angle`].
+    pub fn from_axis_angle(axis: Vec3<f64>, degree: f64) -> Self {
+        Self::from_quat(Quat::from_axis_angle(axis, degree))
+    }
+
+    pub fn row(&self, index: usize) -> Vec3<f64> {
+        self.rows[index]
+    }
+
+    pub fn col(&self, index: usize) -> Vec3<f64> {
+        Vec3::new(
+            self.rows[0][index],
+            self.rows[1][index],
+            self.rows[2][index],
+        )
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_rows([self.col(0), self.col(1), self.col(2)])
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.rows[0].dot(self.rows[1].cross(self.rows[2]))
+    }
+
+    /// The inverse, or `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        // the columns of the inverse are the cross products of the rows
+        let [a, b, c] = self.rows;
+        let adjugate = Self::from_cols([b.cross(c), c.cross(a), a.cross(b)]);
+        Some(adjugate * (1.0 / determinant))
+    }
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let cols = [rhs.col(0), rhs.col(1), rhs.col(2)];
+        Self::from_rows(
+            self.rows
+                .map(|row| Vec3::new(row.dot(cols[0]), row.dot(cols[1]), row.dot(cols[2]))),
+        )
+    }
+}
+
+impl Mul<Vec3<f64>> for Mat3 {
+    type Output = Vec3<f64>;
+
+    fn mul(self, rhs: Vec3<f64>) -> Vec3<f64> {
+        Vec3::new(
+            self.rows[0].dot(rhs),
+            self.rows[1].dot(rhs),
+            self.rows[2].dot(rhs),
+        )
+    }
+}
+
+impl Mul<f64> for Mat3 {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        Self::from_rows(self.rows.map(|row| row * rhs))
+    }
+}
+
+/// Affine map `x -> linear * x + translation`, a 3x4 matrix.
+///
+/// Like [`Mat3`], `a * b` is the map `b` followed by `a`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    pub linear: Mat3,
+    pub translation: Vec3<f64>,
+}
+
+impl Affine {
+    pub const IDENTITY: Self = Self::new(Mat3::IDENTITY, Vec3::new(0.0, 0.0, 0.0));
+
+    pub const fn new(linear: Mat3, translation: Vec3<f64>) -> Self {
+        Self {
+            linear,
+            translation,
+        }
+    }
+
+    pub fn from_linear(linear: Mat3) -> Self {
+        Self::new(linear, Vec3::zeros())
+    }
+
+    pub fn from_translation(translation: Vec3<f64>) -> Self {
+        Self::new(Mat3::IDENTITY, translation)
+    }
+
+    pub fn transform_point(&self, point: Vec3<f64>) -> Vec3<f64> {
+        self.linear * point + self.translation
+    }
+
+    /// Map a direction, which is not moved by the translation.
+    pub fn transform_vector(&self, vector: Vec3<f64>) -> Vec3<f64> {
+        self.linear * vector
+    }
+
+    /// The inverse, or `None` if the linear part is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let linear = self.linear.inverse()?;
+        Some(Self::new(linear, -(linear * self.translation)))
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Affine {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.linear * rhs.linear,
+            self.transform_point(rhs.translation),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_undoes_the_map() {
+        let map = Affine::from_translation(Vec3::new(1.0, -2.0, 3.0))
+            * Affine::from_linear(Mat3::from_axis_angle(Vec3::new(1.0, 2.0, 3.0), 40.0))
+            * Affine::from_linear(Mat3::from_diagonal(Vec3::new(2.0, 0.5, -1.0)));
+        let inverse = map.inverse().unwrap();
+
+        let point = Vec3::new(0.3, -4.0, 2.5);
+        assert!((inverse.transform_point(map.transform_point(point)) - point).norm() < 1e-12);
+        let identity = map * inverse;
+        for i in 0..3 {
+            assert!((identity.linear.row(i) - Mat3::IDENTITY.row(i)).norm() < 1e-12);
+        }
+        assert!(identity.translation.norm() < 1e-12);
+
+        assert_eq!(
+            Mat3::from_diagonal(Vec3::new(1.0, 0.0, 1.0)).inverse(),
+            None
+        );
+    }
+
+    #[test]
+    fn rotation_matches_quaternion() {
+        let rotation = Quat::from_euler(10.0, 20.0, 30.0);
+        let vector = Vec3::new(1.0, 2.0, 3.0);
+        let rotated = Mat3::from_quat(rotation) * vector;
+        assert!((rotated - rotation.rotate_vec(vector)).norm() < 1e-12);
+        assert!((Mat3::from_quat(rotation).determinant() - 1.0).abs() < 1e-12);
+    }
+}
@@ -1,8 +1,14 @@
 mod color;
 mod point3;
+mod quat;
+mod mat;
+mod onb;
 
-pub use color::Color;
+pub use color::{Color, VISIBLE_WAVELENGTHS};
 pub use point3::Point3;
+pub use quat::Quat;
+pub use mat::{Affine, Mat3};
+pub use onb::Onb;
 
 use std::{
     fmt::Display,
@@ -258,13 +264,13 @@ where
 {
     /// Generate a random vector with components in the `range`.
     pub fn random(range: Range<T>) -> Self {
-        let mut rng = rand::thread_rng();
-
-        Self([
-            rng.gen_range(range.clone()),
-            rng.gen_range(range.clone()),
-            rng.gen_range(range),
-        ])
+        crate::random::with_rng(|rng| {
+            Self([
+                rng.gen_range(range.clone()),
+                rng.gen_range(range.clone()),
+                rng.gen_range(range),
+            ])
+        })
     }
 }
 
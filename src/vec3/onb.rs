@@ -0,0 +1,83 @@
+use super::Vec3;
+
+/// Orthonormal basis, a right-handed frame of unit vectors `u`, `v` and `w`.
+///
+/// Directions sampled around +z, such as cosine-weighted ones, are turned
+/// into directions around a surface normal by a basis whose `w` is the normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Onb {
+    u: Vec3<f64>,
+    v: Vec3<f64>,
+    w: Vec3<f64>,
+}
+
+impl Onb {
+    /// Basis whose `w` is along `w`, with the other two axes picked
+    /// arbitrarily.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `w` is zero.
+    pub fn from_w(w: Vec3<f64>) -> Self {
+        assert!(!w.is_near_zero(), "basis axis must not be zero");
+        let w = w.normalized();
+        // any direction not parallel to w gives the other axes
+        let helper = if w.x().abs() < 0.9 {
+            Vec3::unit_x()
+        } else {
+            Vec3::unit_y()
+        };
+        let v = w.cross(helper).normalized();
+        let u = v.cross(w);
+        Self { u, v, w }
+    }
+
+    pub fn u(&self) -> Vec3<f64> {
+        self.u
+    }
+
+    pub fn v(&self) -> Vec3<f64> {
+        self.v
+    }
+
+    pub fn w(&self) -> Vec3<f64> {
+        self.w
+    }
+
+    /// The vector with coordinates `a` in this basis.
+    pub fn local(&self, a: Vec3<f64>) -> Vec3<f64> {
+        self.u * a.x() + self.v * a.y() + self.w * a.z()
+    }
+
+    /// The coordinates of `a` in this basis, the inverse of [`Onb::local`].
+    pub fn to_local(&self, a: Vec3<f64>) -> Vec3<f64> {
+        Vec3::new(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basis_is_orthonormal_and_right_handed() {
+        for w in [
+            Vec3::unit_x(),
+            -Vec3::unit_y(),
+            Vec3::new(0.3, -2.0, 5.0),
+            Vec3::new(-1.0, 1e-3, 0.0),
+        ] {
+            let onb = Onb::from_w(w);
+            assert!((onb.w() - w.normalized()).norm() < 1e-12);
+            for axis in [onb.u(), onb.v(), onb.w()] {
+                assert!((axis.norm() - 1.0).abs() < 1e-12);
+            }
+            assert!(onb.u().dot(onb.v()).abs() < 1e-12);
+            assert!(onb.v().dot(onb.w()).abs() < 1e-12);
+            assert!((onb.u().cross(onb.v()) - onb.w()).norm() < 1e-12);
+
+            let a = Vec3::new(0.5, -1.5, 2.0);
+            assert!((onb.to_local(onb.local(a)) - a).norm() < 1e-12);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::{Float, Vec3};
+use crate::random;
 use rand::Rng;
 
 pub type Point3 = super::Vec3<f64>;
@@ -18,6 +19,21 @@ impl Point3 {
         }
     }
 
+    /// Generate a random unit vector, uniformly distributed over the
+    /// directions.
+    ///
+    /// A point in the unit sphere is normalized, rejecting points so close
+    /// to the center that normalizing them would lose precision.
+    pub fn random_unit_vector() -> Self {
+        loop {
+            let v = Self::random_in_unit_sphere();
+            let len_squared = v.len_squared();
+            if len_squared > 1e-160 {
+                return v / len_squared.sqrt();
+            }
+        }
+    }
+
     /// Generate a random point inside unit hemisphere of the given normal,
     /// centered at the origin.
     pub fn random_in_unit_hemisphere(normal: Vec3<f64>) -> Point3 {
@@ -33,14 +49,12 @@ impl Point3 {
     /// Generate a random point inside unit disk on the XY plane,
     /// centered at the origin.
     pub fn random_in_unit_disk() -> Self {
-        let mut rng = rand::thread_rng();
-
-        loop {
+        random::with_rng(|rng| loop {
             let v = Self::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0);
             if v.norm() < 1.0 {
                 return v;
             }
-        }
+        })
     }
 
     /// Generate a random point in a disk of `radius` centered at the origin.
@@ -49,10 +63,9 @@ impl Point3 {
             return Self::zeros();
         }
 
-        let mut rng = rand::thread_rng();
         let range = -radius..radius;
 
-        loop {
+        random::with_rng(|rng| loop {
             let v = Self::new(
                 rng.gen_range(range.clone()),
                 rng.gen_range(range.clone()),
@@ -61,10 +74,61 @@ impl Point3 {
             if v.norm() < 1.0 {
                 return v * radius;
             }
-        }
+        })
+    }
+
+    /// Generate a random unit direction around +z, whose density is
+    /// proportional to the cosine of its angle with +z.
+    pub fn random_cosine_direction() -> Self {
+        let (r1, r2): (f64, f64) = (random::random(), random::random());
+        let phi = 2.0 * std::f64::consts::PI * r1;
+        let r = r2.sqrt();
+        Self::new(r * phi.cos(), r * phi.sin(), (1.0 - r2).sqrt())
     }
 
     pub fn is_valid_point(&self) -> bool {
         self.iter().all(|x| x.is_finite())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 100_000;
+
+    /// Mean of `f` over the directions from `generate`, which must be unit
+    /// vectors.
+    fn mean(generate: fn() -> Point3, f: impl Fn(Vec3<f64>) -> f64) -> f64 {
+        let sum: f64 = (0..SAMPLES)
+            .map(|_| {
+                let direction = generate();
+                assert!((direction.norm() - 1.0).abs() < 1e-12);
+                f(direction)
+            })
+            .sum();
+        sum / SAMPLES as f64
+    }
+
+    #[test]
+    fn unit_vectors_are_uniform() {
+        random::seed(71);
+        for axis in 0..3 {
+            let component = mean(Point3::random_unit_vector, |d| d[axis]);
+            let squared = mean(Point3::random_unit_vector, |d| d[axis] * d[axis]);
+            assert!(component.abs() < 0.01, "{component}");
+            assert!((squared - 1.0 / 3.0).abs() < 0.01, "{squared}");
+        }
+    }
+
+    #[test]
+    fn cosine_directions_have_mean_cosine_two_thirds() {
+        random::seed(72);
+        let cosine = mean(Point3::random_cosine_direction, |d| d.z());
+        assert!((cosine - 2.0 / 3.0).abs() < 0.005, "{cosine}");
+        // the density is cos / pi, so cos^2 has mean 1 / 2
+        let squared = mean(Point3::random_cosine_direction, |d| d.z() * d.z());
+        assert!((squared - 0.5).abs() < 0.005, "{squared}");
+        assert!(mean(Point3::random_cosine_direction, |d| d.x()).abs() < 0.01);
+    }
+}
@@ -0,0 +1,119 @@
+use std::ops::Mul;
+
+use super::Vec3;
+
+/// Quaternion `w + xi + yj + zk`, used for rotations.
+///
+/// Unit quaternions represent rotations. `a * b` is the rotation `b` followed
+/// by `a`, like composing rotation matrices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quat {
+    /// The rotation by zero degrees.
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 0.0);
+
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Rotation by `degree` around `axis`, counterclockwise when looking
+    /// from the tip of `axis` towards the origin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` is zero.
+    pub fn from_axis_angle(axis: Vec3<f64>, degree: f64) -> Self {
+        assert!(axis.norm() > 0.0, "rotation axis must not be zero");
+        let (sin, cos) = (degree.to_radians() / 2.0).sin_cos();
+        let axis = axis.normalized() * sin;
+        Self::new(cos, axis.x(), axis.y(), axis.z())
+    }
+
+    /// Rotation by `x` degrees around the X axis, then `y` around the Y axis,
+    /// then `z` around the Z axis.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Self {
+        Self::from_axis_angle(Vec3::unit_z(), z)
+            * Self::from_axis_angle(Vec3::unit_y(), y)
+            * Self::from_axis_angle(Vec3::unit_x(), x)
+    }
+
+    fn vector(&self) -> Vec3<f64> {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    pub fn norm(&self) -> f64 {
+        (self.w * self.w + self.vector().len_squared()).sqrt()
+    }
+
+    pub fn normalized(self) -> Self {
+        let norm = self.norm();
+        Self::new(self.w / norm, self.x / norm, self.y / norm, self.z / norm)
+    }
+
+    /// The conjugate, which is the inverse rotation for unit quaternions.
+    pub fn conjugate(self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Rotate `vec` by the rotation of this unit quaternion.
+    pub fn rotate_vec(&self, vec: Vec3<f64>) -> Vec3<f64> {
+        // q v q*, expanded so that no quaternion products are needed
+        let axis = self.vector();
+        let t = 2.0 * axis.cross(vec);
+        vec + self.w * t + axis.cross(t)
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Quat {
+    type Output = Self;
+
+    /// Hamilton product.
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self.vector(), rhs.vector());
+        let w = self.w * rhs.w - a.dot(b);
+        let vector = self.w * b + rhs.w * a + a.cross(b);
+        Self::new(w, vector.x(), vector.y(), vector.z())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(lhs: Vec3<f64>, rhs: Vec3<f64>) {
+        assert!((lhs - rhs).norm() < 1e-12, "{lhs} != {rhs}");
+    }
+
+    #[test]
+    fn composition_matches_sequential_rotations() {
+        let first = Quat::from_axis_angle(Vec3::new(1.0, 2.0, 3.0), 40.0);
+        let second = Quat::from_axis_angle(Vec3::new(-2.0, 0.5, 1.0), 110.0);
+        let vec = Vec3::new(0.3, -1.2, 2.5);
+
+        assert_near(
+            (second * first).rotate_vec(vec),
+            second.rotate_vec(first.rotate_vec(vec)),
+        );
+        assert_near((first.conjugate() * first).rotate_vec(vec), vec);
+        assert_near(
+            Quat::from_euler(90.0, 90.0, 0.0).rotate_vec(Vec3::unit_y()),
+            Vec3::unit_x(),
+        );
+        assert_near(
+            Quat::from_axis_angle(Vec3::unit_z(), 90.0).rotate_vec(Vec3::unit_x()),
+            Vec3::unit_y(),
+        );
+    }
+}